@@ -21,7 +21,9 @@ pub struct BootstrapBehaviour {
 }
 
 impl BootstrapBehaviour {
-    pub fn new(keypair: &Keypair) -> Self {
+    /// `protocol_version` 必须与客户端的 `NodeConfig::protocol_version` 一致，
+    /// `agent_version` 仅用于展示。
+    pub fn new(keypair: &Keypair, protocol_version: String, agent_version: String) -> Self {
         let peer_id = keypair.public().to_peer_id();
 
         // ===== Ping =====
@@ -32,11 +34,11 @@ impl BootstrapBehaviour {
         );
 
         // ===== Identify =====
-        // protocol_version 必须与客户端一致（"/swarmdrop/1.0.0"），
+        // protocol_version 必须与客户端一致，
         // 客户端 event_loop 只在 protocol_version 匹配时才将对方加入 Kad 路由表。
         let identify = identify::Behaviour::new(
-            identify::Config::new("/swarmdrop/1.0.0".to_string(), keypair.public())
-                .with_agent_version(format!("swarm-bootstrap/{}", env!("CARGO_PKG_VERSION")))
+            identify::Config::new(protocol_version, keypair.public())
+                .with_agent_version(agent_version)
                 .with_push_listen_addr_updates(true)
                 .with_cache_size(1000),
         );
@@ -12,10 +12,13 @@ use behaviour::BootstrapBehaviourEvent;
 /// 启动引导+中继节点
 ///
 /// 构建 Swarm 并运行事件循环，直到收到关闭信号。
+/// `protocol_version` / `agent_version` 用于 identify，前者必须与客户端一致。
+/// TCP 与 QUIC 传输始终构建（AutoNAT 回拨需要），只在 `listen_addrs` 给出的地址上监听。
 pub async fn run(
     keypair: Keypair,
-    tcp_addr: Multiaddr,
-    quic_addr: Multiaddr,
+    protocol_version: String,
+    agent_version: String,
+    listen_addrs: Vec<Multiaddr>,
     idle_timeout: Duration,
     external_addrs: Vec<Multiaddr>,
 ) -> Result<()> {
@@ -26,12 +29,15 @@ pub async fn run(
         .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)?
         .with_quic()
         .with_dns()?
-        .with_behaviour(behaviour::BootstrapBehaviour::new)?
+        .with_behaviour(|key| {
+            behaviour::BootstrapBehaviour::new(key, protocol_version, agent_version)
+        })?
         .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(idle_timeout))
         .build();
 
-    swarm.listen_on(tcp_addr)?;
-    swarm.listen_on(quic_addr)?;
+    for addr in listen_addrs {
+        swarm.listen_on(addr)?;
+    }
 
     // 注册公网地址，relay reservation 响应会携带这些地址给 client
     for addr in &external_addrs {
@@ -67,19 +73,15 @@ fn handle_event(event: SwarmEvent<BootstrapBehaviourEvent>) {
             peer_id,
             num_established,
             ..
-        } => {
-            if num_established.get() == 1 {
-                info!("Peer connected: {}", peer_id);
-            }
+        } if num_established.get() == 1 => {
+            info!("Peer connected: {}", peer_id);
         }
         SwarmEvent::ConnectionClosed {
             peer_id,
-            num_established,
+            num_established: 0,
             ..
         } => {
-            if num_established == 0 {
-                info!("Peer disconnected: {}", peer_id);
-            }
+            info!("Peer disconnected: {}", peer_id);
         }
         SwarmEvent::Behaviour(BootstrapBehaviourEvent::Identify(
             libp2p::identify::Event::Received { peer_id, info, .. },
@@ -1,8 +1,8 @@
 use std::path::PathBuf;
 use std::time::Duration;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand, ValueEnum};
 use libp2p::Multiaddr;
 use tracing::info;
 
@@ -15,17 +15,45 @@ struct Cli {
     command: Command,
 }
 
+/// 监听的传输协议
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Transport {
+    /// 仅 TCP
+    Tcp,
+    /// 仅 QUIC
+    Quic,
+    /// TCP + QUIC
+    Both,
+}
+
+impl Transport {
+    fn tcp(self) -> bool {
+        self != Transport::Quic
+    }
+
+    fn quic(self) -> bool {
+        self != Transport::Tcp
+    }
+}
+
+/// 未指定端口时的默认值（TCP 与 QUIC 分属 TCP / UDP，可共用同一端口号）
+const DEFAULT_PORT: u16 = 4001;
+
 #[derive(Subcommand, Debug)]
 enum Command {
     /// 启动引导+中继节点
     Run {
-        /// TCP 监听端口
-        #[arg(long, default_value = "4001")]
-        tcp_port: u16,
+        /// 监听的传输协议
+        #[arg(long, value_enum, default_value_t = Transport::Both)]
+        transport: Transport,
 
-        /// QUIC 监听端口
-        #[arg(long, default_value = "4001")]
-        quic_port: u16,
+        /// TCP 监听端口（默认 4001，--transport quic 时不可用）
+        #[arg(long)]
+        tcp_port: Option<u16>,
+
+        /// QUIC 监听端口（默认 4001，--transport tcp 时不可用）
+        #[arg(long)]
+        quic_port: Option<u16>,
 
         /// 密钥文件路径（默认从二进制所在目录查找 identity.key）
         #[arg(long)]
@@ -42,6 +70,14 @@ enum Command {
         /// 公网 IP 地址（relay server 必须设置，否则 reservation 响应不含地址）
         #[arg(long)]
         external_ip: Option<String>,
+
+        /// identify 协议版本，必须与客户端 `NodeConfig::protocol_version` 一致
+        #[arg(long, default_value = "/swarmdrop/1.0.0")]
+        protocol_version: String,
+
+        /// identify agent 版本
+        #[arg(long, default_value = concat!("swarm-bootstrap/", env!("CARGO_PKG_VERSION")))]
+        agent_version: String,
     },
 
     /// 打印节点 PeerId 后退出
@@ -65,6 +101,25 @@ fn resolve_key_file(key_file: Option<PathBuf>) -> PathBuf {
     })
 }
 
+/// 按所选传输确定端口，给未启用的传输指定端口时报错（避免误以为已在该端口监听）
+fn resolve_ports(
+    transport: Transport,
+    tcp_port: Option<u16>,
+    quic_port: Option<u16>,
+) -> Result<(Option<u16>, Option<u16>)> {
+    if !transport.tcp() && tcp_port.is_some() {
+        bail!("--tcp-port cannot be used with --transport quic");
+    }
+    if !transport.quic() && quic_port.is_some() {
+        bail!("--quic-port cannot be used with --transport tcp");
+    }
+    let port = |enabled: bool, port: Option<u16>| enabled.then(|| port.unwrap_or(DEFAULT_PORT));
+    Ok((
+        port(transport.tcp(), tcp_port),
+        port(transport.quic(), quic_port),
+    ))
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -76,12 +131,15 @@ fn main() -> Result<()> {
         }
 
         Command::Run {
+            transport,
             tcp_port,
             quic_port,
             key_file,
             listen_addr,
             idle_timeout,
             external_ip,
+            protocol_version,
+            agent_version,
         } => {
             tracing_subscriber::fmt()
                 .with_env_filter(
@@ -90,26 +148,32 @@ fn main() -> Result<()> {
                 )
                 .init();
 
+            let (tcp_port, quic_port) = resolve_ports(transport, tcp_port, quic_port)?;
             let key_file = resolve_key_file(key_file);
             let keypair = swarm_bootstrap::util::load_or_generate_keypair(&key_file)?;
             let peer_id = keypair.public().to_peer_id();
             info!("Node PeerId: {}", peer_id);
-
-            let tcp_addr: Multiaddr =
-                format!("/ip4/{}/tcp/{}", listen_addr, tcp_port).parse()?;
-            let quic_addr: Multiaddr =
-                format!("/ip4/{}/udp/{}/quic-v1", listen_addr, quic_port).parse()?;
-
-            info!("TCP listen address: {}", tcp_addr);
-            info!("QUIC listen address: {}", quic_addr);
-
-            let external_addrs: Vec<Multiaddr> = if let Some(ref ip) = external_ip {
-                vec![
-                    format!("/ip4/{}/tcp/{}", ip, tcp_port).parse()?,
-                    format!("/ip4/{}/udp/{}/quic-v1", ip, quic_port).parse()?,
-                ]
-            } else {
-                vec![]
+            info!("Protocol version: {}", protocol_version);
+
+            // 同一组端口既用于监听地址，也用于对外公布的地址
+            let addrs = |ip: &str| -> Result<Vec<Multiaddr>> {
+                let mut addrs = Vec::new();
+                if let Some(port) = tcp_port {
+                    addrs.push(format!("/ip4/{}/tcp/{}", ip, port).parse()?);
+                }
+                if let Some(port) = quic_port {
+                    addrs.push(format!("/ip4/{}/udp/{}/quic-v1", ip, port).parse()?);
+                }
+                Ok(addrs)
+            };
+            let listen_addrs = addrs(&listen_addr)?;
+            for addr in &listen_addrs {
+                info!("Listen address: {}", addr);
+            }
+
+            let external_addrs: Vec<Multiaddr> = match external_ip {
+                Some(ref ip) => addrs(ip)?,
+                None => vec![],
             };
 
             tokio::runtime::Builder::new_multi_thread()
@@ -117,8 +181,9 @@ fn main() -> Result<()> {
                 .build()?
                 .block_on(swarm_bootstrap::run(
                     keypair,
-                    tcp_addr,
-                    quic_addr,
+                    protocol_version,
+                    agent_version,
+                    listen_addrs,
                     Duration::from_secs(idle_timeout),
                     external_addrs,
                 ))?;
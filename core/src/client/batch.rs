@@ -0,0 +1,85 @@
+use libp2p::{Multiaddr, PeerId};
+
+use crate::Result;
+use crate::command::{BatchCommand, BatchOp, BatchOutcome};
+use crate::runtime::CborMessage;
+
+use super::NetClient;
+
+impl<Req, Resp> NetClient<Req, Resp>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    /// 创建批量命令，收集多项操作后经一次命令往返提交
+    ///
+    /// ```ignore
+    /// let outcomes = client
+    ///     .batch()
+    ///     .add_peer_addrs(peer_a, addrs_a)
+    ///     .block_peer(peer_b)
+    ///     .submit()
+    ///     .await?;
+    /// ```
+    pub fn batch(&self) -> CommandBatch<'_, Req, Resp> {
+        CommandBatch {
+            client: self,
+            ops: Vec::new(),
+        }
+    }
+}
+
+/// 批量命令构建器，由 [`NetClient::batch`] 创建
+///
+/// 所有操作在同一轮事件循环中按添加顺序执行，不会与其他命令交错。
+#[must_use = "批量命令需调用 submit 才会执行"]
+pub struct CommandBatch<'a, Req, Resp>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    client: &'a NetClient<Req, Resp>,
+    ops: Vec<BatchOp>,
+}
+
+impl<Req, Resp> CommandBatch<'_, Req, Resp>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    /// 同 [`NetClient::add_peer_addrs`]
+    pub fn add_peer_addrs(mut self, peer_id: PeerId, addrs: Vec<Multiaddr>) -> Self {
+        self.ops.push(BatchOp::AddPeerAddrs { peer_id, addrs });
+        self
+    }
+
+    /// 同 [`NetClient::add_external_address`]
+    pub fn add_external_address(mut self, addr: Multiaddr) -> Self {
+        self.ops.push(BatchOp::AddExternalAddress { addr });
+        self
+    }
+
+    /// 同 [`NetClient::block_peer`]
+    pub fn block_peer(mut self, peer_id: PeerId) -> Self {
+        self.ops.push(BatchOp::BlockPeer { peer_id });
+        self
+    }
+
+    /// 已添加的操作数
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// 提交并等待执行完成，结果与添加顺序一一对应；没有操作时不经过事件循环
+    pub async fn submit(self) -> Result<Vec<BatchOutcome>> {
+        if self.ops.is_empty() {
+            return Ok(Vec::new());
+        }
+        let cmd = BatchCommand::new(self.ops);
+        self.client.command(cmd).await
+    }
+}
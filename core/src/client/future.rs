@@ -1,10 +1,23 @@
+use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
+
+use libp2p::kad::QueryId;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::oneshot;
+use tokio::time::Sleep;
 
 use crate::Result;
-use crate::command::{Command, CommandHandler, CommandTask, ResultHandle};
+use crate::command::{
+    CancelQueryCommand, Command, CommandHandler, CommandTask, ResultHandle, TrackedQueries,
+};
+use crate::error::Error;
 use crate::runtime::CborMessage;
 
 /// 命令 Future，使任意 CommandHandler 可被 await
+///
+/// 带超时创建时，到期仍未完成则以 `Error::CommandTimeout` 结束；
+/// 事件循环按同一超时移除该命令（尚未执行的命令直接丢弃）。
 pub struct CommandFuture<T, Req, Resp>
 where
     T: CommandHandler<Req, Resp> + Send + 'static,
@@ -14,6 +27,9 @@ where
     handler: Option<T>,
     handle: ResultHandle<T::Result>,
     sender: tokio::sync::mpsc::Sender<Command<Req, Resp>>,
+    timeout: Option<Duration>,
+    /// 首次 poll 时开始计时
+    deadline: Option<Pin<Box<Sleep>>>,
 }
 
 impl<T, Req, Resp> CommandFuture<T, Req, Resp>
@@ -28,6 +44,20 @@ where
             handler: Some(handler),
             handle: ResultHandle::new(),
             sender,
+            timeout: None,
+            deadline: None,
+        }
+    }
+
+    /// 创建带超时的命令 Future，`timeout` 代替命令自身的超时时间
+    pub fn new_with_timeout(
+        handler: T,
+        sender: tokio::sync::mpsc::Sender<Command<Req, Resp>>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..Self::new(handler, sender)
         }
     }
 }
@@ -46,17 +76,173 @@ where
 
         // 首次 poll 时发送命令
         if let Some(handler) = this.handler.take() {
-            let task = CommandTask::new(handler, this.handle.clone());
-            if this.sender.try_send(Box::new(task)).is_err() {
-                return Poll::Ready(Err(crate::error::Error::Behaviour(
-                    "command channel closed".into(),
-                )));
+            let mut task = CommandTask::new(handler, this.handle.clone());
+            if let Some(timeout) = this.timeout {
+                task = task.with_timeout(timeout);
+                this.deadline = Some(Box::pin(tokio::time::sleep(timeout)));
+            }
+            match this.sender.try_send(Box::new(task)) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    return Poll::Ready(Err(Error::CommandChannelFull));
+                }
+                Err(TrySendError::Closed(_)) => {
+                    return Poll::Ready(Err(Error::CommandChannelClosed));
+                }
             }
         }
 
         // 注册 waker 并检查结果
         // 必须在首次 poll 时也注册 waker，否则同步完成的命令（如 stop_provide）
         // 会在 handle.finish() 时找不到 waker，导致 Future 永远不会被唤醒
-        this.handle.poll(cx)
+        if let Poll::Ready(result) = this.handle.poll(cx) {
+            return Poll::Ready(result);
+        }
+        // 标记为已完成，事件循环据此丢弃尚未执行的命令
+        if let Some(deadline) = this.deadline.as_mut()
+            && deadline.as_mut().poll(cx).is_ready()
+        {
+            this.deadline = None;
+            this.handle.finish(Err(Error::CommandTimeout));
+            return this.handle.poll(cx);
+        }
+        Poll::Pending
+    }
+}
+
+/// 发出命令但不等待其结果，命令进入通道即返回
+pub(crate) fn send_detached<T, Req, Resp>(
+    handler: T,
+    sender: &tokio::sync::mpsc::Sender<Command<Req, Resp>>,
+) -> Result<()>
+where
+    T: CommandHandler<Req, Resp>,
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    let task = CommandTask::new(handler, ResultHandle::new());
+    sender.try_send(Box::new(task)).map_err(|e| match e {
+        TrySendError::Full(_) => Error::CommandChannelFull,
+        TrySendError::Closed(_) => Error::CommandChannelClosed,
+    })
+}
+
+/// 已发出的 Kad 查询
+///
+/// 可直接 `.await` 取得结果；需要中途放弃时，通过 `id()` 取得 `QueryId`
+/// 并调用 `NetClient::cancel_query`，结果随之变为 `Error::Cancelled`。
+/// 命令在创建时即发出；结果未出来前丢弃 `KadQuery` 会取消查询。
+pub struct KadQuery<T> {
+    handle: ResultHandle<T>,
+    started: Option<oneshot::Receiver<QueryId>>,
+    id: Option<QueryId>,
+    /// 丢弃时取消已发出的查询
+    cancel: Option<Box<dyn FnOnce(QueryId) + Send>>,
+}
+
+impl<T: Send + 'static> KadQuery<T> {
+    /// `timeout` 为 `Some` 时覆盖命令自身的超时，到期以 `Error::CommandTimeout` 结束
+    pub(crate) fn send<C, Req, Resp>(
+        handler: C,
+        started: oneshot::Receiver<QueryId>,
+        sender: &tokio::sync::mpsc::Sender<Command<Req, Resp>>,
+        queries: TrackedQueries,
+        timeout: Option<Duration>,
+    ) -> Self
+    where
+        C: CommandHandler<Req, Resp, Result = T>,
+        Req: CborMessage,
+        Resp: CborMessage,
+    {
+        let handle = ResultHandle::new();
+        let mut task = CommandTask::new(handler, handle.clone());
+        if let Some(timeout) = timeout {
+            task = task.with_timeout(timeout);
+        }
+        match sender.try_send(Box::new(task)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => handle.finish(Err(Error::CommandChannelFull)),
+            Err(TrySendError::Closed(_)) => handle.finish(Err(Error::CommandChannelClosed)),
+        }
+        let sender = sender.clone();
+        let cancel = move |id| {
+            let _ = send_detached(CancelQueryCommand::new(id, queries), &sender);
+        };
+        Self {
+            handle,
+            started: Some(started),
+            id: None,
+            cancel: Some(Box::new(cancel)),
+        }
+    }
+
+    /// 查询的 `QueryId`，在事件循环发起查询后可用
+    ///
+    /// 命令未能发出（或事件循环已退出）时返回 `None`，失败原因由 `.await` 结果给出。
+    pub async fn id(&mut self) -> Option<QueryId> {
+        if self.id.is_none()
+            && let Some(started) = self.started.take()
+        {
+            self.id = started.await.ok();
+        }
+        self.id
+    }
+}
+
+impl<T> Drop for KadQuery<T> {
+    fn drop(&mut self) {
+        if self.handle.is_finished() {
+            return;
+        }
+        // 先关闭接收端：此后命令上报 `QueryId` 会失败，由命令自行结束查询；
+        // 关闭前已上报的则在这里取出，另发命令取消
+        let id = self.id.or_else(|| {
+            let started = self.started.as_mut()?;
+            started.close();
+            started.try_recv().ok()
+        });
+        if let (Some(id), Some(cancel)) = (id, self.cancel.take()) {
+            cancel(id);
+        }
+    }
+}
+
+impl<T> std::future::Future for KadQuery<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.handle.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::command::CoreSwarm;
+
+    /// 永远不会完成的命令
+    struct Pending;
+
+    #[async_trait]
+    impl CommandHandler<(), ()> for Pending {
+        type Result = ();
+
+        async fn run(&mut self, _swarm: &mut CoreSwarm<(), ()>, _handle: &ResultHandle<()>) {}
+    }
+
+    #[tokio::test]
+    async fn times_out_and_marks_command_finished() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let timeout = Duration::from_millis(20);
+        let result = CommandFuture::new_with_timeout(Pending, tx, timeout).await;
+        assert!(matches!(result, Err(Error::CommandTimeout)), "{result:?}");
+
+        // 事件循环按同一超时计时，并可据 is_finished 丢弃未执行的命令
+        let cmd = rx.try_recv().expect("command should have been sent");
+        assert_eq!(cmd.timeout(), Some(timeout));
+        assert!(cmd.is_finished());
     }
 }
@@ -0,0 +1,34 @@
+use crate::Result;
+use crate::command::{PublishCommand, SubscribeCommand, UnsubscribeCommand};
+use crate::runtime::CborMessage;
+
+use super::NetClient;
+
+impl<Req, Resp> NetClient<Req, Resp>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    /// 订阅 Gossipsub 主题，返回 `false` 表示已订阅过
+    ///
+    /// 收到的消息通过 `NodeEvent::GossipMessage` 上报。需开启 `enable_gossipsub`。
+    pub async fn subscribe(&self, topic: impl Into<String>) -> Result<bool> {
+        let cmd = SubscribeCommand::new(topic);
+        self.command(cmd).await
+    }
+
+    /// 取消订阅 Gossipsub 主题，返回 `false` 表示原本未订阅
+    pub async fn unsubscribe(&self, topic: impl Into<String>) -> Result<bool> {
+        let cmd = UnsubscribeCommand::new(topic);
+        self.command(cmd).await
+    }
+
+    /// 向 Gossipsub 主题发布消息
+    ///
+    /// 本节点无需订阅该主题；若当前没有订阅该主题的已连接 peer，
+    /// 返回 `Error::Gossipsub`（InsufficientPeers）。
+    pub async fn publish(&self, topic: impl Into<String>, data: impl Into<Vec<u8>>) -> Result<()> {
+        let cmd = PublishCommand::new(topic, data.into());
+        self.command(cmd).await
+    }
+}
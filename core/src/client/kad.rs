@@ -1,12 +1,24 @@
-use libp2p::kad::{Record, RecordKey};
+use std::future::Future;
+use std::time::Duration;
 
+use futures::Stream;
+use futures::channel::mpsc;
+use libp2p::kad::{QueryId, Quorum, Record, RecordKey};
+use libp2p::{Multiaddr, PeerId};
+
+use tokio::sync::oneshot;
+
+use super::future::{KadQuery, send_detached};
 use crate::Result;
 use crate::command::{
-    BootstrapCommand, BootstrapResult, GetClosestPeersCommand, GetClosestPeersResult,
-    GetProvidersCommand, GetProvidersResult, GetRecordCommand, GetRecordResult, PutRecordCommand,
-    RemoveRecordCommand, StartProvideCommand, StopProvideCommand,
+    BootstrapCommand, BootstrapResult, CancelQueryCommand, CommandHandler,
+    FindAndConnectProviderCommand, FindPeerCommand, GetClosestPeersCommand, GetClosestPeersResult,
+    GetProvidersCommand, GetProvidersResult, GetRecordAllCommand, GetRecordAllResult,
+    GetRecordCommand, GetRecordOpts, GetRecordResult, ImportPeersCommand, KadBucketInfo,
+    PublishRecordCommand, PublishRecordResult, PutProgress, PutRecordCommand,
+    PutRecordLocalCommand, QueryTracker, RemoveRecordCommand, RepublishProviderCommand,
+    RoutingTableCommand, StartProvideCommand, StopProvideCommand,
 };
-use super::future::CommandFuture;
 use crate::runtime::CborMessage;
 use crate::util::QueryStatsInfo;
 
@@ -18,50 +30,238 @@ where
     Resp: CborMessage,
 {
     /// Bootstrap - 加入 DHT 网络，填充路由表
+    ///
+    /// 路由表为空时先重新连接配置的引导节点，等其完成 Identify 后再发起查询；
+    /// 引导节点均不可达时返回 `Error::KadBootstrapUnreachable`，未配置引导节点时返回 `KadNoPeers`。
     pub async fn bootstrap(&self) -> Result<BootstrapResult> {
-        let cmd = BootstrapCommand::new();
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        let cmd = BootstrapCommand::new()
+            .with_bootstrap_peers(self.bootstrap_peers.clone(), self.kad_query_timeout);
+        self.command(cmd).await
+    }
+
+    /// 发出可取消的 Kad 查询，`build` 负责把 `QueryTracker` 交给命令
+    ///
+    /// 与 `command` 一样，命令未自带超时时使用全局的 `command_timeout`。
+    fn kad_query<C>(&self, build: impl FnOnce(QueryTracker) -> C) -> KadQuery<C::Result>
+    where
+        C: CommandHandler<Req, Resp>,
+    {
+        let (started_tx, started_rx) = oneshot::channel();
+        let cmd = build(QueryTracker::new(started_tx, self.tracked_queries.clone()));
+        let timeout = self.command_timeout.filter(|_| cmd.timeout().is_none());
+        KadQuery::send(
+            cmd,
+            started_rx,
+            &self.command_tx,
+            self.tracked_queries.clone(),
+            timeout,
+        )
+    }
+
+    /// 取消进行中的 Kad 查询，对应的 `KadQuery` 以 `Error::Cancelled` 结束
+    ///
+    /// 查询已结束或不存在时返回 `false`。
+    pub async fn cancel_query(&self, query_id: QueryId) -> Result<bool> {
+        let cmd = CancelQueryCommand::new(query_id, self.tracked_queries.clone());
+        self.command(cmd).await
+    }
+
+    /// 从 DHT 获取记录（可取消，见 `KadQuery`）
+    pub fn get_record(&self, key: RecordKey) -> KadQuery<GetRecordResult> {
+        self.kad_query(|tracker| GetRecordCommand::new(key).with_tracker(tracker))
+    }
+
+    /// 从 DHT 获取记录，要求 `opts.quorum` 个节点返回相同取值（可取消，见 `KadQuery`）
+    ///
+    /// 各节点取值不一致时以支持者最多的为准，不足 quorum 返回 `Error::KadRecordQuorumFailed`。
+    /// `opts.cache` 开启时把结果回写到查询路径上离 key 最近、但没有该记录的节点。
+    /// `get_record` 相当于 `Quorum::One` 且不回写。
+    pub fn get_record_with_opts(
+        &self,
+        key: RecordKey,
+        opts: GetRecordOpts,
+    ) -> KadQuery<GetRecordResult> {
+        self.kad_query(|tracker| {
+            GetRecordCommand::new(key)
+                .with_opts(opts)
+                .with_tracker(tracker)
+        })
     }
 
-    /// 从 DHT 获取记录
-    pub async fn get_record(&self, key: RecordKey) -> Result<GetRecordResult> {
-        let cmd = GetRecordCommand::new(key);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+    /// 从 DHT 获取某个 key 的全部记录（含各自的来源 peer），直到查询结束
+    ///
+    /// 与 `get_record` 不同，不会在找到第一条后停止，可用于检测不同节点上的取值冲突。
+    pub fn get_record_all(&self, key: RecordKey) -> KadQuery<GetRecordAllResult> {
+        self.kad_query(|tracker| GetRecordAllCommand::new(key).with_tracker(tracker))
     }
 
-    /// 将记录存入 DHT
+    /// 将记录存入 DHT（`Quorum::One`）
     pub async fn put_record(&self, record: Record) -> Result<QueryStatsInfo> {
-        let cmd = PutRecordCommand::new(record);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.put_record_with_quorum(record, Quorum::One).await
+    }
+
+    /// 将记录存入 DHT，要求至少 `quorum` 个远端节点确认存储
+    ///
+    /// 确认数不足时返回 `Error::KadQuorumFailed`，其中 `stored` 为实际确认的节点数
+    /// （为 0 通常表示没有可达的 DHT 节点）。
+    pub async fn put_record_with_quorum(
+        &self,
+        record: Record,
+        quorum: Quorum,
+    ) -> Result<QueryStatsInfo> {
+        let cmd = PutRecordCommand::with_quorum(record, quorum);
+        self.command(cmd).await
+    }
+
+    /// 将记录存入 DHT，并上报复制进度
+    ///
+    /// 返回 (进度 Stream, 结果 Future)。命令在 Future 首次 poll 时才会发出，
+    /// 需要与 Stream 并发驱动（如 `tokio::join!` 或 spawn）；Future 完成后 Stream 结束。
+    pub fn put_record_with_progress(
+        &self,
+        record: Record,
+        quorum: Quorum,
+    ) -> (
+        impl Stream<Item = PutProgress> + Send + 'static,
+        impl Future<Output = Result<QueryStatsInfo>> + Send + 'static,
+    ) {
+        let (tx, rx) = mpsc::unbounded();
+        let cmd = PutRecordCommand::with_progress(record, quorum, tx);
+        (rx, self.command(cmd))
+    }
+
+    /// 从 DHT 获取 Provider 列表（可取消，见 `KadQuery`）
+    pub fn get_providers(&self, key: RecordKey) -> KadQuery<GetProvidersResult> {
+        self.kad_query(|tracker| GetProvidersCommand::new(key).with_tracker(tracker))
+    }
+
+    /// 从 DHT 查找 Provider，每找到一个立即产出，查询结束时 Stream 结束
+    ///
+    /// 适合“连接第一个可用 Provider”这类看重延迟的场景。查询在调用时即发出；
+    /// 丢弃 Stream 后剩余查询会在下一步结果到达时结束。
+    pub fn get_providers_stream(
+        &self,
+        key: RecordKey,
+    ) -> impl Stream<Item = PeerId> + Send + 'static {
+        let (tx, rx) = mpsc::unbounded();
+        // 结果经 Stream 交付，不经 KadQuery（丢弃它会取消查询）；
+        // 命令未能发出时 tx 随之丢弃，Stream 立即结束
+        let _ = send_detached(
+            GetProvidersCommand::new(key).with_stream(tx),
+            &self.command_tx,
+        );
+        rx
     }
 
-    /// 从 DHT 获取 Provider 列表
-    pub async fn get_providers(&self, key: RecordKey) -> Result<GetProvidersResult> {
-        let cmd = GetProvidersCommand::new(key);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+    /// 从 DHT 获取 Provider 列表，找到 `min_count` 个即提前返回
+    ///
+    /// 达到数量后会结束剩余查询；超过 `timeout` 则返回已找到的部分结果。
+    /// `timeout` 长于 `kad_query_timeout` 时 Kad 查询先超时，同样返回已找到的部分结果，
+    /// 一个都没找到时返回 `Error::KadTimeout`。
+    pub fn get_providers_min(
+        &self,
+        key: RecordKey,
+        min_count: usize,
+        timeout: Duration,
+    ) -> KadQuery<GetProvidersResult> {
+        self.kad_query(|tracker| {
+            GetProvidersCommand::with_min_count(key, min_count, timeout).with_tracker(tracker)
+        })
     }
 
-    /// 查找最近的 Peers
-    pub async fn get_closest_peers(&self, key: RecordKey) -> Result<GetClosestPeersResult> {
-        let cmd = GetClosestPeersCommand::new(key);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+    /// 查找 key 的 Provider 并连接第一个可达的节点
+    ///
+    /// 某个 Provider 不可达时自动尝试下一个，返回已连接的 `PeerId`，
+    /// 可直接用于后续 `send_request`。
+    pub async fn find_and_connect_provider(&self, key: RecordKey) -> Result<PeerId> {
+        let cmd = FindAndConnectProviderCommand::new(key)
+            .with_address_order(self.dial_address_order)
+            .with_pending_dials(self.pending_dials.clone());
+        self.command(cmd).await
+    }
+
+    /// 查找最近的 Peers（可取消，见 `KadQuery`）
+    pub fn get_closest_peers(&self, key: RecordKey) -> KadQuery<GetClosestPeersResult> {
+        self.kad_query(|tracker| GetClosestPeersCommand::new(key).with_tracker(tracker))
+    }
+
+    /// 经 Kad 查找指定 peer 的地址（如只有分享码中的 PeerId 时）
+    ///
+    /// 路由表中已有时直接返回，否则以其 PeerId 发起最近节点查询。
+    /// 找到的地址会登记到地址簿，之后可直接 `dial(peer_id)`；
+    /// 查询结束仍未找到返回 `Error::KadNotFound`。
+    pub async fn find_peer(&self, peer_id: PeerId) -> Result<Vec<Multiaddr>> {
+        let cmd = FindPeerCommand::new(peer_id);
+        self.command(cmd).await
     }
 
     /// 开始提供资源
     pub async fn start_provide(&self, key: RecordKey) -> Result<QueryStatsInfo> {
         let cmd = StartProvideCommand::new(key);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.command(cmd).await
+    }
+
+    /// 存储记录并宣告本节点为该 key 的 Provider，返回两个查询各自的统计
+    ///
+    /// 相当于同时执行 `put_record` 与 `start_provide`；存储失败时撤销宣告（`stop_provide`）并返回存储的错误。
+    pub async fn publish_record(&self, record: Record) -> Result<PublishRecordResult> {
+        let cmd = PublishRecordCommand::new(record);
+        self.command(cmd).await
+    }
+
+    /// 立即重新宣告已提供的 key，用于地址变化（如打洞升级为直连）后让其他节点尽快拿到新地址
+    ///
+    /// 本节点未提供该 key 时返回 `Error::Kad`。
+    pub async fn republish_provider(&self, key: RecordKey) -> Result<QueryStatsInfo> {
+        let cmd = RepublishProviderCommand::new(key);
+        self.command(cmd).await
+    }
+
+    /// 立即重新宣告本节点提供的所有 key，返回合并的查询统计；没有提供任何 key 时直接返回
+    pub async fn republish_all_providers(&self) -> Result<QueryStatsInfo> {
+        self.command(RepublishProviderCommand::all()).await
     }
 
     /// 停止提供资源
     pub async fn stop_provide(&self, key: RecordKey) -> Result<()> {
         let cmd = StopProvideCommand::new(key);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.command(cmd).await
+    }
+
+    /// 只把记录写入本地存储，不向 DHT 复制，同步完成
+    ///
+    /// 适合先在本地备好自己托管的内容，再配合 `start_provide` 对外提供；
+    /// 其他节点查询时本节点可直接返回该记录。存储拒绝写入（容量已满或记录过大）
+    /// 时返回 `Error::KadStore`。Kad 的周期性复制任务之后仍可能把它复制给最近的节点。
+    pub async fn put_record_local(&self, record: Record) -> Result<()> {
+        let cmd = PutRecordLocalCommand::new(record);
+        self.command(cmd).await
     }
 
     /// 从本地存储中删除记录
     pub async fn remove_record(&self, key: RecordKey) -> Result<()> {
         let cmd = RemoveRecordCommand::new(key);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.command(cmd).await
+    }
+
+    /// 读取本地 Kad 路由表（只含非空 bucket），用于排查 DHT 连通性
+    pub async fn kad_routing_table(&self) -> Result<Vec<KadBucketInfo>> {
+        self.command(RoutingTableCommand).await
+    }
+
+    /// 导出路由表中的所有节点及地址，可在关闭前保存，下次启动通过
+    /// `NodeConfig::with_seed_peers` 或 `import_peers` 恢复
+    pub async fn export_routing_table(&self) -> Result<Vec<(PeerId, Vec<Multiaddr>)>> {
+        let buckets = self.kad_routing_table().await?;
+        Ok(buckets
+            .into_iter()
+            .flat_map(|bucket| bucket.peers)
+            .collect())
+    }
+
+    /// 将节点地址导入 Kad 路由表（只登记地址，不主动拨号）
+    pub async fn import_peers(&self, peers: Vec<(PeerId, Vec<Multiaddr>)>) -> Result<()> {
+        let cmd = ImportPeersCommand::new(peers);
+        self.command(cmd).await
     }
 }
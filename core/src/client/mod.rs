@@ -1,19 +1,45 @@
+mod batch;
 mod future;
+mod gossipsub;
 mod kad;
 mod req_resp;
 
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use libp2p::core::transport::ListenerId;
+use libp2p::identify;
+use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
 use libp2p::{Multiaddr, PeerId};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
+use tracing::warn;
 
 use crate::Result;
 use crate::command::{
-    AddPeerAddrsCommand, Command, DialCommand, DisconnectCommand, GetListenAddrsCommand,
-    IsConnectedCommand,
+    AddExternalAddressCommand, AddPeerAddrsCommand, BlockPeerCommand, CancelRelayCommand, Command,
+    CommandHandler, ConnectedPeersCommand, ConnectionCountCommand, ConnectionInfoCommand,
+    DialAddrCommand, DialCommand, DialManyCommand, DisconnectCommand, GetListenAddrsCommand,
+    HandshakeCommand, IsConnectedCommand, ListenCommand, NatStatusCommand, NodeStatus,
+    RelayReservationsCommand, RemoveExternalAddressCommand, RemoveListenerCommand,
+    ReserveRelayCommand, SetAgentVersionCommand, ShutdownCommand, StatusCommand, StreamSlot,
+    TrackedQueries, UnblockPeerCommand, UpgradeConnectionCommand, relay_dial_addr,
 };
-use crate::event::NodeEvent;
+use crate::config::{AddressOrder, HandshakeInfo, NodeConfig};
+use crate::error::Error;
+use crate::event::{NatStatus, NodeEvent};
 use crate::pending_map::PendingMap;
-use crate::runtime::CborMessage;
+use crate::runtime::{
+    BandwidthStats, CborMessage, ConnectionInfo, InboundRoutes, KeepAlivePeers, NatTracker,
+    PeerBandwidth, PeerConnections, PeerHandshakes, PeerRoutes, PendingDials, RelayListeners,
+    RelayReservationInfo, SharedState,
+};
+pub use batch::CommandBatch;
 use future::CommandFuture;
+pub use future::KadQuery;
+
+/// 未配置 `dial_timeout` 时 `dial_many` 的整体超时
+const DIAL_MANY_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// 网络客户端，用于发送命令
 pub struct NetClient<Req, Resp>
@@ -21,8 +47,37 @@ where
     Req: CborMessage,
     Resp: CborMessage,
 {
+    local_peer_id: PeerId,
     command_tx: mpsc::Sender<Command<Req, Resp>>,
-    pending_channels: PendingMap<u64, libp2p::request_response::ResponseChannel<Resp>>,
+    /// `dial` / `dial_many` 的候选地址顺序
+    dial_address_order: AddressOrder,
+    /// `dial` / `dial_with_opts` 的截止时间
+    dial_timeout: Option<Duration>,
+    /// 未自带超时的命令使用的超时时间
+    command_timeout: Option<Duration>,
+    /// 配置的引导节点，`status` 据此判断是否连着引导节点，`bootstrap` 在路由表为空时拨号它们
+    bootstrap_peers: Arc<[(PeerId, Multiaddr)]>,
+    /// Kad 查询超时，用于计算 `bootstrap` 恢复路由表时的总时限
+    kad_query_timeout: Duration,
+    /// 启动时的 identify 配置，`set_agent_version` 以此为模板重建 identify behaviour
+    identify_config: identify::Config,
+    /// 事件广播的弱引用，事件循环持有强引用，退出后广播通道随之关闭
+    broadcast_tx: broadcast::WeakSender<NodeEvent<Req>>,
+    /// inbound request 订阅表的弱引用，事件循环持有强引用
+    inbound_routes: Weak<InboundRoutes<Req>>,
+    pending_channels: PendingMap<u64, (PeerId, libp2p::request_response::ResponseChannel<Resp>)>,
+    stream_slots: PendingMap<u64, (PeerId, StreamSlot<Resp>)>,
+    keep_alive_peers: KeepAlivePeers,
+    peer_handshakes: PeerHandshakes,
+    nat: NatTracker,
+    relay_listeners: RelayListeners,
+    connections: PeerConnections,
+    pending_dials: PendingDials,
+    dropped_events: Arc<AtomicU64>,
+    bandwidth: PeerBandwidth,
+    tracked_queries: TrackedQueries,
+    #[cfg(feature = "metrics")]
+    metrics: crate::runtime::NodeMetrics,
 }
 
 impl<Req, Resp> Clone for NetClient<Req, Resp>
@@ -32,8 +87,29 @@ where
 {
     fn clone(&self) -> Self {
         Self {
+            local_peer_id: self.local_peer_id,
             command_tx: self.command_tx.clone(),
+            dial_address_order: self.dial_address_order,
+            dial_timeout: self.dial_timeout,
+            command_timeout: self.command_timeout,
+            bootstrap_peers: self.bootstrap_peers.clone(),
+            kad_query_timeout: self.kad_query_timeout,
+            identify_config: self.identify_config.clone(),
+            broadcast_tx: self.broadcast_tx.clone(),
+            inbound_routes: self.inbound_routes.clone(),
             pending_channels: self.pending_channels.clone(),
+            stream_slots: self.stream_slots.clone(),
+            keep_alive_peers: self.keep_alive_peers.clone(),
+            peer_handshakes: self.peer_handshakes.clone(),
+            nat: self.nat.clone(),
+            relay_listeners: self.relay_listeners.clone(),
+            connections: self.connections.clone(),
+            pending_dials: self.pending_dials.clone(),
+            dropped_events: self.dropped_events.clone(),
+            bandwidth: self.bandwidth.clone(),
+            tracked_queries: self.tracked_queries.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics.clone(),
         }
     }
 }
@@ -44,62 +120,474 @@ where
     Resp: CborMessage,
 {
     pub(crate) fn new(
+        local_peer_id: PeerId,
         command_tx: mpsc::Sender<Command<Req, Resp>>,
-        pending_channels: PendingMap<u64, libp2p::request_response::ResponseChannel<Resp>>,
+        broadcast_tx: broadcast::WeakSender<NodeEvent<Req>>,
+        inbound_routes: Weak<InboundRoutes<Req>>,
+        shared: SharedState<Resp>,
+        config: &NodeConfig,
+        identify_config: identify::Config,
     ) -> Self {
         Self {
+            local_peer_id,
             command_tx,
-            pending_channels,
+            dial_address_order: config.dial_address_order,
+            dial_timeout: config.dial_timeout,
+            command_timeout: config.command_timeout,
+            bootstrap_peers: config.bootstrap_peers.clone().into(),
+            kad_query_timeout: config.kad_query_timeout,
+            identify_config,
+            broadcast_tx,
+            inbound_routes,
+            pending_channels: shared.pending_channels,
+            stream_slots: shared.stream_slots,
+            keep_alive_peers: shared.keep_alive_peers,
+            peer_handshakes: shared.peer_handshakes,
+            nat: shared.nat,
+            relay_listeners: shared.relay_listeners,
+            connections: shared.connections,
+            pending_dials: shared.pending_dials,
+            dropped_events: shared.dropped_events,
+            bandwidth: shared.bandwidth,
+            tracked_queries: TrackedQueries::default(),
+            #[cfg(feature = "metrics")]
+            metrics: shared.metrics,
+        }
+    }
+
+    /// 本节点的 PeerId
+    pub fn local_peer_id(&self) -> PeerId {
+        self.local_peer_id
+    }
+
+    /// 发出命令，命令未自带超时时使用全局的 `command_timeout`
+    fn command<T>(&self, cmd: T) -> CommandFuture<T, Req, Resp>
+    where
+        T: CommandHandler<Req, Resp> + Send + 'static,
+    {
+        let sender = self.command_tx.clone();
+        match self.command_timeout {
+            Some(timeout) if cmd.timeout().is_none() => {
+                CommandFuture::new_with_timeout(cmd, sender, timeout)
+            }
+            _ => CommandFuture::new(cmd, sender),
         }
     }
 
-    /// 连接到指定 peer
+    /// 连接到指定 peer，超过 `dial_timeout` 返回 `Error::DialTimeout`
     pub async fn dial(&self, peer_id: PeerId) -> Result<()> {
-        let cmd = DialCommand::new(peer_id);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        let cmd = DialCommand::new(peer_id)
+            .with_address_order(self.dial_address_order)
+            .with_timeout(self.dial_timeout)
+            .with_pending_dials(self.pending_dials.clone());
+        self.command(cmd).await
+    }
+
+    /// 按地址拨号（如二维码 / 分享链接中的地址），返回对端 PeerId
+    ///
+    /// 地址可带 `/p2p/<peer_id>` 后缀，不带时握手完成后才知道对端身份。
+    /// 连接成功后地址会登记到地址簿和 Kad 路由表，之后可直接按 PeerId 拨号。
+    pub async fn dial_addr(&self, addr: Multiaddr) -> Result<PeerId> {
+        let cmd = DialAddrCommand::new(addr).with_pending_dials(self.pending_dials.clone());
+        self.command(cmd).await
+    }
+
+    /// 使用自定义 `DialOpts` 拨号，等待本次拨号的连接建立或失败
+    pub async fn dial_with_opts(&self, opts: DialOpts) -> Result<()> {
+        let cmd = DialCommand::from(opts)
+            .with_timeout(self.dial_timeout)
+            .with_pending_dials(self.pending_dials.clone());
+        self.command(cmd).await
+    }
+
+    /// 按指定拨号条件连接 peer
+    ///
+    /// 如 `PeerCondition::Always` 可在已连接时强制建立新连接。
+    pub async fn dial_with_condition(
+        &self,
+        peer_id: PeerId,
+        condition: PeerCondition,
+    ) -> Result<()> {
+        self.dial_with_opts(DialOpts::peer_id(peer_id).condition(condition).build())
+            .await
+    }
+
+    /// 经中继连接 `target`：拨号 `<relay_addr>/p2p-circuit/p2p/<target>`，中继连接建立后返回
+    ///
+    /// 适用于从带外渠道得知对端中继地址的场景，地址无需事先登记到地址簿。
+    /// `relay_addr` 须以 `/p2p/<relay>` 结尾，否则返回 `Error::Dial`；已连接时直接返回。
+    /// 连接建立后（开启 `enable_dcutr` 时）由 DCUtR 自动尝试打洞，结果通过
+    /// `HolePunchSucceeded` / `HolePunchFailed` 事件上报，也可用 `upgrade_connection` 等待。
+    pub async fn dial_via_relay(&self, relay_addr: Multiaddr, target: PeerId) -> Result<()> {
+        let circuit = relay_dial_addr(&relay_addr, target).ok_or_else(|| {
+            Error::Dial(format!(
+                "relay address must end with /p2p/<relay>: {relay_addr}"
+            ))
+        })?;
+        let cmd = DialCommand::via_relay(target, circuit)
+            .with_timeout(self.dial_timeout)
+            .with_pending_dials(self.pending_dials.clone());
+        self.command(cmd).await
+    }
+
+    /// 同时拨号多个 peer，返回每个 peer 的拨号结果（按输入顺序，重复的 peer 只拨一次）
+    ///
+    /// 各 peer 结果相互独立；整体超时取 `dial_timeout`（未配置时为 30 秒），且不超过
+    /// `command_timeout`，到期仍未连上的 peer 记为超时失败。
+    /// 外层 `Err` 仅表示命令本身无法执行（如事件循环已关闭）。
+    pub async fn dial_many(&self, peers: Vec<PeerId>) -> Result<Vec<(PeerId, Result<()>)>> {
+        let timeout = self.dial_timeout.unwrap_or(DIAL_MANY_TIMEOUT);
+        let timeout = self
+            .command_timeout
+            .map_or(timeout, |limit| timeout.min(limit));
+        self.dial_many_with_timeout(peers, timeout).await
+    }
+
+    /// 同时拨号多个 peer，使用单独的整体超时，其余行为与 `dial_many` 相同
+    pub async fn dial_many_with_timeout(
+        &self,
+        peers: Vec<PeerId>,
+        timeout: Duration,
+    ) -> Result<Vec<(PeerId, Result<()>)>> {
+        let cmd = DialManyCommand::new(peers, timeout)
+            .with_address_order(self.dial_address_order)
+            .with_pending_dials(self.pending_dials.clone());
+        self.command(cmd).await
     }
 
     /// 检查是否已连接到指定 peer
     pub async fn is_connected(&self, peer_id: PeerId) -> Result<bool> {
         let cmd = IsConnectedCommand::new(peer_id);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.command(cmd).await
+    }
+
+    /// 获取当前已连接的所有 peer
+    pub async fn connected_peers(&self) -> Result<Vec<PeerId>> {
+        self.command(ConnectedPeersCommand).await
+    }
+
+    /// 获取当前已建立的连接总数（同一 peer 可能有多个连接）
+    pub async fn connection_count(&self) -> Result<usize> {
+        self.command(ConnectionCountCommand).await
+    }
+
+    /// 与指定 peer 的每条活跃连接：对端地址、方向、是否经中继及最近一次 Ping 延迟，
+    /// 未连接时返回空列表
+    pub async fn connection_info(&self, peer_id: PeerId) -> Result<Vec<ConnectionInfo>> {
+        let cmd = ConnectionInfoCommand::new(peer_id, self.connections.clone());
+        self.command(cmd).await
+    }
+
+    /// 节点状态快照（连接数、Kad 模式、外部地址、是否连着引导节点），
+    /// 各字段在同一次事件循环中读取，适合做健康检查
+    pub async fn status(&self) -> Result<NodeStatus> {
+        self.command(StatusCommand::new(self.bootstrap_peers.clone()))
+            .await
+    }
+
+    /// 当前 NAT 状态
+    ///
+    /// 尚无探测完成时为 `Unknown`；地址被 AutoNAT 确认可达后为 `Public`；
+    /// 多个服务器均探测失败后为 `Private`（见 `autonat_failure_threshold`）。
+    pub async fn nat_status(&self) -> Result<NatStatus> {
+        let cmd = NatStatusCommand::new(self.nat.clone());
+        self.command(cmd).await
     }
 
     /// 断开与指定 peer 的所有连接
     pub async fn disconnect(&self, peer_id: PeerId) -> Result<()> {
         let cmd = DisconnectCommand::new(peer_id);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.command(cmd).await
     }
 
-    /// 获取本节点的所有可达地址（监听地址 + 外部地址）
+    /// 屏蔽 peer：立即断开并拒绝其后续所有连接，返回 `false` 表示已屏蔽过
+    ///
+    /// 黑名单只保存在内存中，重启后清空。
+    pub async fn block_peer(&self, peer_id: PeerId) -> Result<bool> {
+        let cmd = BlockPeerCommand::new(peer_id);
+        self.command(cmd).await
+    }
+
+    /// 解除屏蔽，返回 `false` 表示原本未屏蔽
+    pub async fn unblock_peer(&self, peer_id: PeerId) -> Result<bool> {
+        let cmd = UnblockPeerCommand::new(peer_id);
+        self.command(cmd).await
+    }
+
+    /// 获取本节点的所有可达地址（外部地址在前，其后是监听地址）
+    ///
+    /// `0.0.0.0` / `::` 监听地址会展开为各网卡的具体地址，结果已去重。
     pub async fn get_addrs(&self) -> Result<Vec<Multiaddr>> {
-        let cmd = GetListenAddrsCommand::new();
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.get_addrs_with_loopback(true).await
+    }
+
+    /// 同 [`get_addrs`](Self::get_addrs)，`include_loopback = false` 时过滤回环地址，
+    /// 适合生成分享链接 / 二维码
+    pub async fn get_addrs_with_loopback(&self, include_loopback: bool) -> Result<Vec<Multiaddr>> {
+        let cmd = GetListenAddrsCommand::new().with_loopback(include_loopback);
+        self.command(cmd).await
+    }
+
+    /// 运行时新增监听地址，返回监听器 id
+    ///
+    /// 等待该监听器的第一个地址就绪后返回（同时会发出 `NodeEvent::Listening`）。
+    pub async fn listen_on(&self, addr: Multiaddr) -> Result<ListenerId> {
+        let cmd = ListenCommand::new(addr);
+        self.command(cmd).await
+    }
+
+    /// 关闭指定监听器，返回 `false` 表示监听器不存在
+    pub async fn remove_listener(&self, listener_id: ListenerId) -> Result<bool> {
+        let cmd = RemoveListenerCommand::new(listener_id);
+        self.command(cmd).await
+    }
+
+    /// 手动登记本节点的外部地址（如端口映射后的公网地址）
+    ///
+    /// 随后出现在 `get_addrs()` 中，并通过下一次 Identify push 通告给已连接的 peer。
+    pub async fn add_external_address(&self, addr: Multiaddr) -> Result<()> {
+        let cmd = AddExternalAddressCommand::new(addr);
+        self.command(cmd).await
+    }
+
+    /// 移除本节点的外部地址（不存在时忽略）
+    pub async fn remove_external_address(&self, addr: Multiaddr) -> Result<()> {
+        let cmd = RemoveExternalAddressCommand::new(addr);
+        self.command(cmd).await
+    }
+
+    /// 经指定中继申请 relay 预约，中继接受后返回
+    ///
+    /// 预约成功后本节点可通过 `<relay_addr>/p2p/<relay>/p2p-circuit/p2p/<本节点>` 被连接，
+    /// 并由 relay client 自动续约；中继不可达或拒绝预约时返回 `Error::Relay`。
+    pub async fn reserve_relay(&self, relay_peer_id: PeerId, relay_addr: Multiaddr) -> Result<()> {
+        let cmd = ReserveRelayCommand::new(relay_peer_id, relay_addr, self.relay_listeners.clone());
+        self.command(cmd).await
+    }
+
+    /// 列出经各中继的预约：电路监听地址、最近一次接受 / 续约时间与电路限制
+    ///
+    /// `accepted_at` 为 `None` 的条目仍在申请中；配合 `cancel_relay` 管理预约。
+    pub async fn relay_reservations(&self) -> Result<Vec<RelayReservationInfo>> {
+        let cmd = RelayReservationsCommand::new(self.relay_listeners.clone());
+        self.command(cmd).await
+    }
+
+    /// 放弃经指定中继的预约，返回 `false` 表示没有该中继的预约
+    pub async fn cancel_relay(&self, relay_peer_id: PeerId) -> Result<bool> {
+        let cmd = CancelRelayCommand::new(relay_peer_id, self.relay_listeners.clone());
+        self.command(cmd).await
+    }
+
+    /// 对经中继连接的 peer 重新发起 DCUtR 打洞（如收到 `HolePunchFailed` 后重试）
+    ///
+    /// 经原中继再建一条电路触发新一轮打洞，打洞成功或出现直连后返回；已有直连时立即返回。
+    /// 未连接时返回 `Error::PeerDisconnected`，打洞失败返回 `Error::HolePunch`。
+    pub async fn upgrade_connection(&self, peer_id: PeerId) -> Result<()> {
+        let cmd = UpgradeConnectionCommand::new(peer_id, self.connections.clone());
+        self.command(cmd).await
     }
 
     /// 将指定 peer 的地址注册到 Swarm 地址簿
     pub async fn add_peer_addrs(&self, peer_id: PeerId, addrs: Vec<Multiaddr>) -> Result<()> {
         let cmd = AddPeerAddrsCommand::new(peer_id, addrs);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.command(cmd).await
+    }
+
+    /// 批量注册多个 peer 的地址（如启动时恢复持久化的地址簿），只需一次命令往返
+    pub async fn add_peers_addrs(&self, entries: Vec<(PeerId, Vec<Multiaddr>)>) -> Result<()> {
+        let cmd = AddPeerAddrsCommand::batch(entries);
+        self.command(cmd).await
+    }
+
+    /// 在 `duration` 内保持与 `peer_id` 的连接不因空闲而关闭
+    ///
+    /// 空闲超时是 swarm 全局的（`idle_connection_timeout`），保活通过周期性
+    /// Identify push 制造连接活动实现，对端因此会收到 `IdentifyReceived` 事件。
+    /// 重复调用以最后一次为准，`Duration::ZERO` 取消保活。只对已连接的 peer 生效，
+    /// 不会主动重连。
+    pub fn keep_alive(&self, peer_id: PeerId, duration: Duration) {
+        self.keep_alive_peers.set(peer_id, duration);
+    }
+
+    /// 固定 `peer_id`：无限期保持连接，断开后自动重拨；`keep = false` 取消
+    ///
+    /// 与 `keep_alive` 相同，通过周期性 Identify push（间隔为 `idle_connection_timeout`
+    /// 的一半）使连接不会因空闲而关闭，不受全局空闲超时影响。不同的是连接关闭时
+    /// （对端断开、网络中断等）会立即重拨，失败后在每个保活周期重试，直到取消固定；
+    /// 固定时尚未连接的 peer 也会在下个保活周期拨号。拨号使用已知地址
+    /// （Kad 路由表、`add_peer_addrs` 等）。
+    /// 与 `keep_alive` 共用同一登记表，后调用的覆盖先调用的。
+    /// 固定的 peer 不会被 `target_connection_count` 裁剪。
+    pub fn set_keep_alive(&self, peer_id: PeerId, keep: bool) {
+        self.keep_alive_peers.pin(peer_id, keep);
+    }
+
+    /// 与已连接的 peer 交换应用层握手信息，返回对端的握手信息
+    ///
+    /// 连接建立时已自动握手，通常直接用 `peer_info` 读取即可；
+    /// 本方法用于重新协商（如本地能力变化后）。需配置 `handshake`，否则返回错误。
+    pub async fn version_handshake(&self, peer_id: PeerId) -> Result<HandshakeInfo> {
+        let local = self
+            .peer_handshakes
+            .local()
+            .cloned()
+            .ok_or_else(|| Error::Behaviour("Handshake is not enabled".into()))?;
+        let cmd = HandshakeCommand::new(peer_id, local);
+        self.command(cmd).await
+    }
+
+    /// 修改 identify 上报的 agent_version（如插件加载后更新版本号）
+    ///
+    /// 实现上是整体替换 identify behaviour，有两点限制：
+    /// - 只影响之后建立的连接。已建立连接的 handler 持有旧配置，重连前对端看到的仍是旧值，
+    ///   包括 identify push（如 `keep_alive` / `set_keep_alive` 的周期性 push）。
+    /// - identify 缓存的对端地址被清空，且无法迁移到新 behaviour。按 PeerId 拨号
+    ///   （`dial`、`dial_many`、`find_peer` 等）在对端重新 identify 前只能使用 Kad 路由表、
+    ///   mDNS 与 `add_peer_addrs` 登记的地址。
+    ///
+    /// `protocol_version` 不能运行时修改，对端以它判断网络是否兼容，需重启节点。
+    pub async fn set_agent_version(&self, version: impl Into<String>) -> Result<()> {
+        let config = self
+            .identify_config
+            .clone()
+            .with_agent_version(version.into());
+        let cmd = SetAgentVersionCommand::new(config, self.connections.clone());
+        self.command(cmd).await
+    }
+
+    /// 对端的应用层握手信息（尚未完成握手或已断开时为 `None`）
+    pub fn peer_info(&self, peer_id: &PeerId) -> Option<HandshakeInfo> {
+        self.peer_handshakes.get(peer_id)
+    }
+
+    /// 订阅节点事件，可多次调用以创建多个相互独立的消费者
+    ///
+    /// 与 `start` 返回的 `EventReceiver` 并存：每个订阅者只收到订阅之后发出的事件，
+    /// 缓冲区容量为 `event_channel_size`，消费过慢时最旧的事件会被丢弃
+    /// （见 `BroadcastEventReceiver::missed`），不会阻塞事件循环。
+    /// 事件循环退出后 `recv` 返回 `None`。
+    pub fn subscribe_events(&self) -> BroadcastEventReceiver<Req> {
+        let event_rx = match self.broadcast_tx.upgrade() {
+            Some(tx) => tx.subscribe(),
+            // 事件循环已退出：返回一个已关闭的接收端
+            None => broadcast::channel(1).1,
+        };
+        BroadcastEventReceiver::new(event_rx)
+    }
+
+    /// 因事件通道满被丢弃的事件总数（`event_overflow` 为 `DropOldest` / `DropNewest` 时）
+    ///
+    /// 只统计 `EventReceiver` 的丢弃；`subscribe_events` 订阅者各自通过 `missed` 统计。
+    pub fn dropped_event_count(&self) -> u64 {
+        self.dropped_events.load(Ordering::Relaxed)
+    }
+
+    /// 与 `peer_id` 之间的累计流量（断开后保留），未连接过时为 0
+    ///
+    /// 直接读取 transport 的原子计数，不经过事件循环。
+    pub fn bandwidth_for(&self, peer_id: &PeerId) -> BandwidthStats {
+        self.bandwidth.peer(peer_id)
+    }
+
+    /// 所有连接的累计流量
+    pub fn bandwidth_totals(&self) -> BandwidthStats {
+        self.bandwidth.totals()
+    }
+
+    /// Prometheus 指标注册表（需开启 `metrics` feature）
+    ///
+    /// 包含连接、带宽、Ping RTT、Kad 查询及 request-response 请求计数，
+    /// 可用 `prometheus_client::encoding::text::encode` 导出后由 HTTP 端点提供。
+    #[cfg(feature = "metrics")]
+    pub fn metrics_registry(&self) -> Arc<prometheus_client::registry::Registry> {
+        self.metrics.registry()
     }
 
     pub fn shutdown(self) {
         drop(self.command_tx);
     }
+
+    /// 优雅关闭节点
+    ///
+    /// 事件循环停止接收新命令，等待进行中的命令完成（最多 `timeout`，
+    /// 到期未完成的命令以 `Error::CommandTimeout` 结束），关闭所有监听后退出，
+    /// 并发出最后一个事件 `NodeEvent::Shutdown`。
+    pub async fn shutdown_graceful(self, timeout: Duration) -> Result<()> {
+        let cmd = ShutdownCommand::new(timeout);
+        self.command(cmd).await
+    }
 }
 
 /// 事件接收器
 pub struct EventReceiver<Req = ()> {
     event_rx: mpsc::Receiver<NodeEvent<Req>>,
+    /// 事件发送端持有强引用，事件循环退出后无法再登记
+    peer_routes: Weak<PeerRoutes<Req>>,
 }
 
 impl<Req> EventReceiver<Req> {
-    pub(crate) fn new(event_rx: mpsc::Receiver<NodeEvent<Req>>) -> Self {
-        Self { event_rx }
+    pub(crate) fn new(
+        event_rx: mpsc::Receiver<NodeEvent<Req>>,
+        peer_routes: Weak<PeerRoutes<Req>>,
+    ) -> Self {
+        Self {
+            event_rx,
+            peer_routes,
+        }
     }
 
     /// 接收下一个事件
     pub async fn recv(&mut self) -> Option<NodeEvent<Req>> {
         self.event_rx.recv().await
     }
+
+    /// 创建只接收与 `peer_id` 相关事件（见 `NodeEvent::peer_id`）的接收器
+    ///
+    /// 与当前接收器互不影响，`start` 返回的接收器仍收到全部事件；不涉及单个 peer 的事件
+    /// （`Listening`、`PeersDiscovered`、`NatStatusChanged` 等）只投递给它。
+    /// 只收到创建之后发出的事件，缓冲区容量同 `event_channel_size`，消费过慢时新事件被丢弃
+    /// 并计入 `dropped_event_count`。事件循环退出后 `recv` 返回 `None`。
+    pub fn filter_peer(&self, peer_id: PeerId) -> EventReceiver<Req> {
+        let (event_tx, event_rx) = mpsc::channel(self.event_rx.max_capacity());
+        // 事件循环已退出时 event_tx 随即被丢弃，返回的接收器直接结束
+        if let Some(routes) = self.peer_routes.upgrade() {
+            routes.lock().entry(peer_id).or_default().push(event_tx);
+        }
+        EventReceiver::new(event_rx, self.peer_routes.clone())
+    }
+}
+
+/// 广播事件接收器，由 `NetClient::subscribe_events` 创建
+pub struct BroadcastEventReceiver<Req = ()> {
+    event_rx: broadcast::Receiver<NodeEvent<Req>>,
+    missed: u64,
+}
+
+impl<Req: Clone> BroadcastEventReceiver<Req> {
+    fn new(event_rx: broadcast::Receiver<NodeEvent<Req>>) -> Self {
+        Self {
+            event_rx,
+            missed: 0,
+        }
+    }
+
+    /// 接收下一个事件，消费过慢而被丢弃的事件会被跳过
+    pub async fn recv(&mut self) -> Option<NodeEvent<Req>> {
+        loop {
+            match self.event_rx.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("Event subscriber lagged, {} events dropped", n);
+                    self.missed += n;
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// 因消费过慢累计丢弃的事件数
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
 }
@@ -1,11 +1,20 @@
+use std::time::Duration;
+
+use futures::Stream;
 use libp2p::PeerId;
+use libp2p::request_response::ResponseChannel;
+use tokio::sync::{mpsc, oneshot};
 
-use super::future::CommandFuture;
 use crate::Result;
-use crate::command::{SendRequestCommand, SendResponseCommand};
+use crate::command::{
+    NotifyCommand, SendRequestCommand, SendRequestStreamingCommand, SendResponseCommand,
+    SendResponseManyCommand, SendStreamFrameCommand, StreamFrame, StreamRequest, StreamSlot,
+};
+use crate::error::Error;
 use crate::runtime::CborMessage;
 
 use super::NetClient;
+use super::future::send_detached;
 
 impl<Req, Resp> NetClient<Req, Resp>
 where
@@ -18,7 +27,38 @@ where
         Req: Unpin,
     {
         let cmd = SendRequestCommand::new(peer_id, request);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+        self.command(cmd).await
+    }
+
+    /// 发送请求并等待响应，使用单独的超时时间
+    ///
+    /// 超时后返回 `Error::RequestTimeout`。全局的 `req_resp_timeout` 仍作用于底层协议，
+    /// 因此 `timeout` 大于它时以 `req_resp_timeout` 为准（返回 `RequestResponse` 错误）。
+    pub async fn send_request_with_timeout(
+        &self,
+        peer_id: PeerId,
+        request: Req,
+        timeout: Duration,
+    ) -> Result<Resp>
+    where
+        Req: Unpin,
+    {
+        let cmd = SendRequestCommand::with_timeout(peer_id, request, timeout);
+        self.command(cmd).await
+    }
+
+    /// 发送一条无需回复的请求（在线状态、遥测等），请求交给 swarm 即返回
+    ///
+    /// 不等待响应；对端的响应（若有）与之后的发送失败（如拨号失败、对端断开）都会被丢弃。
+    /// 与 `send_request` 一样受 `max_concurrent_requests_per_peer` 限制：请求在收到响应
+    /// 或失败前一直占用名额，超出的通知在事件循环中排队，轮到它发出时才返回。
+    /// 对端仍收到普通的 `InboundRequest`，不回复时其待回复条目会在 `req_resp_timeout`
+    /// 后以 `InboundRequestFailed` 清理。
+    pub async fn notify(&self, peer_id: PeerId, request: Req) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        send_detached(NotifyCommand::new(peer_id, request, tx), &self.command_tx)?;
+        // 命令未执行就被丢弃（如事件循环关闭）时 tx 随之丢弃
+        rx.await.map_err(|_| Error::CommandChannelClosed)
     }
 
     /// 回复一个 inbound request
@@ -29,13 +69,210 @@ where
     where
         Resp: Unpin,
     {
-        let channel = self.pending_channels.take(&pending_id).ok_or_else(|| {
-            crate::error::Error::RequestResponse(format!(
+        let (peer_id, channel) = self.take_pending_channel(pending_id)?;
+        let cmd = SendResponseCommand::new(peer_id, channel, response);
+        self.command(cmd).await
+    }
+
+    fn take_pending_channel(&self, pending_id: u64) -> Result<(PeerId, ResponseChannel<Resp>)> {
+        self.pending_channels.take(&pending_id).ok_or_else(|| {
+            Error::RequestResponse(format!(
                 "No pending channel for pending_id={} (expired or already responded)",
                 pending_id
             ))
+        })
+    }
+
+    /// 只包含 inbound request 的事件流，产出 `(pending_id, peer_id, request)`
+    ///
+    /// 由事件循环直接投递的独立通道，不经过事件广播：不会从 `start` 返回的 `EventReceiver`
+    /// 中取走任何事件，两者可同时消费（`EventReceiver` 仍会收到 `NodeEvent::InboundRequest`），
+    /// 同一请求只需在其中一处回复。只产出调用之后到达的请求；通道无界，消费过慢时请求
+    /// 在流中累积而不会被跳过，但其 `ResponseChannel` 仍按 `req_resp_timeout` 过期。
+    /// 丢弃流即取消订阅，事件循环退出后流结束。
+    pub fn inbound_requests(&self) -> impl Stream<Item = (u64, PeerId, Req)> + Send + 'static {
+        let (tx, rx) = mpsc::unbounded_channel();
+        // 事件循环已退出时 tx 随即被丢弃，流直接结束
+        if let Some(routes) = self.inbound_routes.upgrade() {
+            routes.lock().push(tx);
+        }
+        futures::stream::unfold(rx, |mut rx| async move {
+            let request = rx.recv().await?;
+            Some((request, rx))
+        })
+    }
+
+    /// 用同一个响应回复多个 inbound request，返回每个 `pending_id` 的回复结果（按输入顺序）
+    ///
+    /// 先一次性取出全部 `ResponseChannel`，再以单个命令统一回复。各 id 结果相互独立，
+    /// 已过期或已回复的 id 不影响其他 id。外层 `Err` 仅表示命令本身无法执行（如事件循环已关闭）。
+    pub async fn send_response_many(
+        &self,
+        pending_ids: Vec<u64>,
+        response: Resp,
+    ) -> Result<Vec<(u64, Result<()>)>>
+    where
+        Resp: Unpin,
+    {
+        // 先检查再取出 ResponseChannel，避免事件循环已关闭时白白丢弃它们
+        if self.command_tx.is_closed() {
+            return Err(Error::CommandChannelClosed);
+        }
+        let mut channels = Vec::new();
+        let mut results = Vec::with_capacity(pending_ids.len());
+        for pending_id in pending_ids {
+            let result = self
+                .take_pending_channel(pending_id)
+                .map(|entry| channels.push(entry));
+            results.push((pending_id, result));
+        }
+        if channels.is_empty() {
+            return Ok(results);
+        }
+
+        // 命令按取出顺序返回结果，依次填回取到 channel 的各 id
+        let cmd = SendResponseManyCommand::new(channels, response);
+        let mut sent = self.command(cmd).await?.into_iter();
+        for (_, result) in results.iter_mut().filter(|(_, r)| r.is_ok()) {
+            if let Some(outcome) = sent.next() {
+                *result = outcome;
+            }
+        }
+        Ok(results)
+    }
+
+    /// 发送流式请求，返回逐块产出响应的 Stream
+    ///
+    /// 基于拉取模型：每次 poll 才向对端拉取下一个分块，同一时刻至多一个分块在途。
+    /// 消费者处理较慢时不会有数据堆积，对端的 `send_stream_chunk` 会相应地等待；
+    /// 但两次 poll 间隔超过 `req_resp_timeout` 时，对端会将该流视为放弃。
+    ///
+    /// 对端调用 `end_stream` 后 Stream 正常结束；拉取失败或对端调用
+    /// `fail_stream` 时产出一个最终的 `Err` 后结束。
+    pub fn send_request_streaming(
+        &self,
+        peer_id: PeerId,
+        request: Req,
+    ) -> impl Stream<Item = Result<Resp>> + Send + 'static
+    where
+        Req: Unpin,
+    {
+        enum State<Req> {
+            Open(Req),
+            Next(u64),
+            Done,
+        }
+
+        let client = self.clone();
+        futures::stream::unfold(State::Open(request), move |state| {
+            let client = client.clone();
+            async move {
+                let request = match state {
+                    State::Open(request) => StreamRequest::Open(request),
+                    State::Next(stream_id) => StreamRequest::Next { stream_id },
+                    State::Done => return None,
+                };
+                let cmd = SendRequestStreamingCommand::new(peer_id, request);
+                match client.command(cmd).await {
+                    Ok(StreamFrame::Chunk { stream_id, data }) => {
+                        Some((Ok(data), State::Next(stream_id)))
+                    }
+                    Ok(StreamFrame::End) => None,
+                    Ok(StreamFrame::Error(e)) => {
+                        Some((Err(Error::RequestResponse(e)), State::Done))
+                    }
+                    Err(e) => Some((Err(e), State::Done)),
+                }
+            }
+        })
+    }
+
+    /// 向流式请求回复一个分块
+    ///
+    /// `pending_id` 来自 `NodeEvent::InboundRequestStream`。
+    /// 分块被对端拉取后才返回，因此生产速度自动受限于消费速度；
+    /// 上一个分块尚未被拉取时再次调用会返回错误。
+    pub async fn send_stream_chunk(&self, pending_id: u64, chunk: Resp) -> Result<()>
+    where
+        Resp: Unpin,
+    {
+        let frame = StreamFrame::Chunk {
+            stream_id: pending_id,
+            data: chunk,
+        };
+        self.send_stream_frame(pending_id, frame).await
+    }
+
+    /// 正常结束流式响应
+    pub async fn end_stream(&self, pending_id: u64) -> Result<()>
+    where
+        Resp: Unpin,
+    {
+        self.send_stream_frame(pending_id, StreamFrame::End).await
+    }
+
+    /// 以错误终止流式响应，请求方会收到一个最终的 `Err`
+    pub async fn fail_stream(&self, pending_id: u64, reason: impl Into<String>) -> Result<()>
+    where
+        Resp: Unpin,
+    {
+        self.send_stream_frame(pending_id, StreamFrame::Error(reason.into()))
+            .await
+    }
+
+    async fn send_stream_frame(&self, pending_id: u64, frame: StreamFrame<Resp>) -> Result<()>
+    where
+        Resp: Unpin,
+    {
+        enum Action<Resp> {
+            /// 对端已在等待，立即发送
+            Send(
+                libp2p::request_response::ResponseChannel<StreamFrame<Resp>>,
+                StreamFrame<Resp>,
+            ),
+            /// 分块已入队，等待对端拉取
+            Wait(oneshot::Receiver<Result<()>>),
+        }
+
+        let action = self.stream_slots.update(pending_id, |slot| match slot {
+            Some((peer, StreamSlot::Waiting(channel))) => {
+                let next = (!frame.is_terminal()).then_some((peer, StreamSlot::Idle));
+                (next, Ok(Action::Send(channel, frame)))
+            }
+            Some((peer, StreamSlot::Idle)) => {
+                let (tx, rx) = oneshot::channel();
+                (
+                    Some((peer, StreamSlot::Queued(frame, tx))),
+                    Ok(Action::Wait(rx)),
+                )
+            }
+            Some(queued @ (_, StreamSlot::Queued(..))) => (
+                Some(queued),
+                Err(Error::RequestResponse(format!(
+                    "Stream {} has an undelivered chunk",
+                    pending_id
+                ))),
+            ),
+            None => (
+                None,
+                Err(Error::RequestResponse(format!(
+                    "No active stream for pending_id={} (expired or already ended)",
+                    pending_id
+                ))),
+            ),
         })?;
-        let cmd = SendResponseCommand::new(channel, response);
-        CommandFuture::new(cmd, self.command_tx.clone()).await
+
+        match action {
+            Action::Send(channel, frame) => {
+                let cmd = SendStreamFrameCommand::new(channel, frame);
+                self.command(cmd).await
+            }
+            Action::Wait(rx) => rx.await.unwrap_or_else(|_| {
+                Err(Error::RequestResponse(format!(
+                    "Stream {} expired before the chunk was pulled",
+                    pending_id
+                )))
+            }),
+        }
     }
 }
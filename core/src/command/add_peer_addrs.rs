@@ -5,15 +5,19 @@ use crate::runtime::CborMessage;
 
 use super::{CommandHandler, CoreSwarm, ResultHandle};
 
-/// AddPeerAddrs 命令 - 将指定 peer 的地址注册到 Swarm 地址簿
+/// AddPeerAddrs 命令 - 将一个或多个 peer 的地址注册到 Swarm 地址簿
 pub struct AddPeerAddrsCommand {
-    peer_id: PeerId,
-    addrs: Vec<Multiaddr>,
+    entries: Vec<(PeerId, Vec<Multiaddr>)>,
 }
 
 impl AddPeerAddrsCommand {
     pub fn new(peer_id: PeerId, addrs: Vec<Multiaddr>) -> Self {
-        Self { peer_id, addrs }
+        Self::batch(vec![(peer_id, addrs)])
+    }
+
+    /// 批量注册多个 peer 的地址，一次命令完成
+    pub fn batch(entries: Vec<(PeerId, Vec<Multiaddr>)>) -> Self {
+        Self { entries }
     }
 }
 
@@ -22,8 +26,10 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for AddPeerA
     type Result = ();
 
     async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
-        for addr in &self.addrs {
-            swarm.add_peer_address(self.peer_id, addr.clone());
+        for (peer_id, addrs) in self.entries.drain(..) {
+            for addr in addrs {
+                swarm.add_peer_address(peer_id, addr);
+            }
         }
         handle.finish(Ok(()));
     }
@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use libp2p::{Multiaddr, PeerId};
+
+use crate::runtime::CborMessage;
+
+use super::block_peer::block_peer;
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// 批量命令中的一项操作
+#[derive(Debug, Clone)]
+pub enum BatchOp {
+    /// 同 `add_peer_addrs`
+    AddPeerAddrs {
+        peer_id: PeerId,
+        addrs: Vec<Multiaddr>,
+    },
+    /// 同 `add_external_address`
+    AddExternalAddress { addr: Multiaddr },
+    /// 同 `block_peer`
+    BlockPeer { peer_id: PeerId },
+}
+
+/// 批量命令中每项操作的结果，与 `BatchOp` 一一对应
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchOutcome {
+    AddPeerAddrs,
+    AddExternalAddress,
+    /// `false` 表示已在黑名单中
+    BlockPeer(bool),
+}
+
+/// Batch 命令 - 在同一轮事件循环中按顺序执行多项操作
+///
+/// 执行期间不处理其他命令和 swarm 事件，其他命令看到的要么是全部操作之前、
+/// 要么是全部操作之后的状态。结果按操作顺序返回。
+pub struct BatchCommand {
+    ops: Vec<BatchOp>,
+}
+
+impl BatchCommand {
+    pub fn new(ops: Vec<BatchOp>) -> Self {
+        Self { ops }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for BatchCommand {
+    type Result = Vec<BatchOutcome>;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let outcomes = self
+            .ops
+            .drain(..)
+            .map(|op| match op {
+                BatchOp::AddPeerAddrs { peer_id, addrs } => {
+                    for addr in addrs {
+                        swarm.add_peer_address(peer_id, addr);
+                    }
+                    BatchOutcome::AddPeerAddrs
+                }
+                BatchOp::AddExternalAddress { addr } => {
+                    swarm.add_external_address(addr);
+                    BatchOutcome::AddExternalAddress
+                }
+                BatchOp::BlockPeer { peer_id } => {
+                    BatchOutcome::BlockPeer(block_peer(swarm, peer_id))
+                }
+            })
+            .collect();
+        handle.finish(Ok(outcomes));
+    }
+}
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use libp2p::PeerId;
+
+use crate::runtime::CborMessage;
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// BlockPeer 命令 - 屏蔽 peer，返回 `false` 表示已在黑名单中
+///
+/// 立即关闭与该 peer 的所有连接，之后的入站/出站连接都会被拒绝；
+/// 同时从 Kad 路由表移除，避免查询时再向它发起拨号。黑名单只保存在内存中。
+pub struct BlockPeerCommand {
+    peer_id: PeerId,
+}
+
+impl BlockPeerCommand {
+    pub fn new(peer_id: PeerId) -> Self {
+        Self { peer_id }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for BlockPeerCommand {
+    type Result = bool;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        handle.finish(Ok(block_peer(swarm, self.peer_id)));
+    }
+}
+
+/// 加入黑名单并移出 Kad 路由表，返回 `false` 表示已在黑名单中
+pub(crate) fn block_peer<Req: CborMessage, Resp: CborMessage>(
+    swarm: &mut CoreSwarm<Req, Resp>,
+    peer_id: PeerId,
+) -> bool {
+    let behaviour = swarm.behaviour_mut();
+    let inserted = behaviour.blocked_peers.block_peer(peer_id);
+    if let Some(kad) = behaviour.kad.as_mut() {
+        kad.remove_peer(&peer_id);
+    }
+    inserted
+}
+
+/// UnblockPeer 命令 - 解除屏蔽，返回 `false` 表示原本不在黑名单中
+pub struct UnblockPeerCommand {
+    peer_id: PeerId,
+}
+
+impl UnblockPeerCommand {
+    pub fn new(peer_id: PeerId) -> Self {
+        Self { peer_id }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for UnblockPeerCommand {
+    type Result = bool;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let removed = swarm
+            .behaviour_mut()
+            .blocked_peers
+            .unblock_peer(self.peer_id);
+        handle.finish(Ok(removed));
+    }
+}
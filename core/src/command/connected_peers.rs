@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use libp2p::PeerId;
+
+use crate::runtime::CborMessage;
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// ConnectedPeers 命令 - 获取当前已连接的所有 peer
+pub struct ConnectedPeersCommand;
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for ConnectedPeersCommand {
+    type Result = Vec<PeerId>;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        handle.finish(Ok(swarm.connected_peers().cloned().collect()));
+    }
+}
+
+/// ConnectionCount 命令 - 获取当前已建立的连接数（同一 peer 可能有多个连接）
+pub struct ConnectionCountCommand;
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for ConnectionCountCommand {
+    type Result = usize;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let count = swarm.network_info().connection_counters().num_established();
+        handle.finish(Ok(count as usize));
+    }
+}
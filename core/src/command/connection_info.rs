@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use libp2p::PeerId;
+
+use crate::runtime::{CborMessage, ConnectionInfo, PeerConnections};
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// ConnectionInfo 命令 - 读取与某个 peer 的每条连接（地址、方向、是否中继、延迟）
+///
+/// 未连接时返回空列表。
+pub struct ConnectionInfoCommand {
+    peer_id: PeerId,
+    connections: PeerConnections,
+}
+
+impl ConnectionInfoCommand {
+    pub fn new(peer_id: PeerId, connections: PeerConnections) -> Self {
+        Self {
+            peer_id,
+            connections,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for ConnectionInfoCommand {
+    type Result = Vec<ConnectionInfo>;
+
+    async fn run(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        handle.finish(Ok(self.connections.info(&self.peer_id)));
+    }
+}
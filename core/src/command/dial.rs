@@ -1,19 +1,134 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
 use async_trait::async_trait;
-use libp2p::{PeerId, swarm::SwarmEvent};
+use libp2p::core::Endpoint;
+use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
+use libp2p::swarm::{ConnectionId, DialError, NetworkBehaviour, SwarmEvent};
+use libp2p::{Multiaddr, PeerId};
+use tracing::warn;
 
+use crate::config::AddressOrder;
 use crate::error::Error;
-use crate::runtime::{CborMessage, CoreBehaviourEvent};
+use crate::runtime::{CborMessage, CoreBehaviourEvent, PendingDials};
 
 use super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
 
 /// Dial 命令 - 连接到指定 peer
+///
+/// 通过 `new(peer_id)` 使用默认拨号条件（已连接则直接返回，
+/// 已有拨号进行中则加入该拨号，与 `dial_many` 相同）；
+/// 通过 `From<DialOpts>` 可自定义拨号条件、内联地址等，
+/// 此时按 `ConnectionId` 匹配本次拨号产生的连接事件。
+///
+/// 设置 `with_timeout` 后，到期仍未连上时返回 `Error::DialTimeout`。
+/// 本命令的拨号是发往该 peer 的唯一拨号时一并中止它；加入了其他拨号、
+/// 或还有其他拨号（如 `dial_many`、自动重连）在进行时只结束本命令。
 pub struct DialCommand {
-    peer_id: PeerId,
+    peer_id: Option<PeerId>,
+    opts: Option<DialOpts>,
+    connection_id: ConnectionId,
+    /// 默认拨号：已连接则直接返回，该 peer 的任一连接建立即视为成功
+    by_peer: bool,
+    /// 已连接（含拨号条件因已连接而不成立）时直接视为成功
+    connected_ok: bool,
+    /// 默认拨号时候选地址的顺序
+    address_order: AddressOrder,
+    /// 已有其他拨号在进行，复用其结果：其连接建立即视为成功；
+    /// 其失败时若再无拨号进行中，改由本命令自行拨号
+    joined: bool,
+    /// 拨号截止时间
+    timeout: Option<Duration>,
+    /// 登记本次拨号，由 EventLoop 上报 `DialStarted`
+    pending_dials: Option<PendingDials>,
 }
 
 impl DialCommand {
     pub fn new(peer_id: PeerId) -> Self {
-        Self { peer_id }
+        Self {
+            by_peer: true,
+            ..Self::from(DialOpts::peer_id(peer_id).build())
+        }
+    }
+
+    /// 经中继电路地址 `circuit` 拨号 `target`，已连接时直接返回
+    ///
+    /// 连接检查与拨号在同一次事件循环中完成，期间对端经其他途径连上也不会报错。
+    pub fn via_relay(target: PeerId, circuit: Multiaddr) -> Self {
+        let opts = DialOpts::peer_id(target)
+            .condition(PeerCondition::Disconnected)
+            .addresses(vec![circuit])
+            .build();
+        Self {
+            connected_ok: true,
+            ..Self::from(opts)
+        }
+    }
+
+    /// 默认拨号时按 `order` 排列候选地址（对 `From<DialOpts>` 无效）
+    pub fn with_address_order(mut self, order: AddressOrder) -> Self {
+        self.address_order = order;
+        self
+    }
+
+    /// 设置拨号截止时间，`None` 时只受全局 `command_timeout` 约束
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 拨号开始时登记到 `pending_dials`，由 EventLoop 上报 `DialStarted`
+    pub fn with_pending_dials(mut self, pending_dials: PendingDials) -> Self {
+        self.pending_dials = Some(pending_dials);
+        self
+    }
+
+    /// 发起拨号；该 peer 已有拨号进行中时加入该拨号。返回是否仍需等待结果
+    fn start_dial<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        opts: DialOpts,
+        addrs: Vec<Multiaddr>,
+        handle: &ResultHandle<()>,
+    ) -> bool {
+        self.connection_id = opts.connection_id();
+        self.joined = false;
+        match swarm.dial(opts) {
+            Ok(()) => {
+                if let Some(pending_dials) = &self.pending_dials {
+                    pending_dials.start(self.peer_id, self.connection_id, addrs);
+                }
+                true
+            }
+            Err(DialError::DialPeerConditionFalse(_)) if self.by_peer => {
+                self.joined = true;
+                true
+            }
+            Err(DialError::DialPeerConditionFalse(_)) if self.connected_ok => {
+                handle.finish(Ok(()));
+                false
+            }
+            Err(e) => {
+                handle.finish(Err(Error::Dial(e.to_string())));
+                false
+            }
+        }
+    }
+}
+
+impl From<DialOpts> for DialCommand {
+    fn from(opts: DialOpts) -> Self {
+        Self {
+            peer_id: opts.get_peer_id(),
+            connection_id: opts.connection_id(),
+            opts: Some(opts),
+            by_peer: false,
+            connected_ok: false,
+            address_order: AddressOrder::Default,
+            joined: false,
+            timeout: None,
+            pending_dials: None,
+        }
     }
 }
 
@@ -22,34 +137,183 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for DialComm
     type Result = ();
 
     async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
-        if swarm.is_connected(&self.peer_id) {
+        if (self.by_peer || self.connected_ok)
+            && let Some(peer_id) = self.peer_id
+            && swarm.is_connected(&peer_id)
+        {
             handle.finish(Ok(()));
             return;
         }
-        if let Err(e) = swarm.dial(self.peer_id) {
-            handle.finish(Err(Error::Dial(e.to_string())));
+        let Some(mut opts) = self.opts.take() else {
+            handle.finish(Err(Error::Dial("Dial: run called twice".into())));
+            return;
+        };
+        let mut addrs = Vec::new();
+        if self.by_peer
+            && let Some(peer_id) = self.peer_id
+        {
+            (opts, addrs) = ordered_dial_opts(swarm, peer_id, self.address_order);
         }
+        self.start_dial(swarm, opts, addrs, handle);
     }
 
     async fn on_event(
         &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
         match &event {
-            SwarmEvent::ConnectionEstablished { peer_id, .. } if *peer_id == self.peer_id => {
+            SwarmEvent::ConnectionEstablished {
+                peer_id,
+                connection_id,
+                ..
+            } if *connection_id == self.connection_id
+                || (self.by_peer && Some(*peer_id) == self.peer_id) =>
+            {
                 handle.finish(Ok(()));
                 (false, Some(event)) // 不消费，前端需要 PeerConnected
             }
             SwarmEvent::OutgoingConnectionError {
-                peer_id: Some(peer_id),
+                connection_id,
                 error,
                 ..
-            } if *peer_id == self.peer_id => {
+            } if *connection_id == self.connection_id => {
                 handle.finish(Err(Error::Dial(error.to_string())));
                 (false, Some(event)) // 不消费
             }
+            // 加入的拨号（如 mDNS 对过期地址的拨号）失败不代表 peer 不可达：
+            // 仍有其他拨号进行中则继续加入，否则以当前候选地址自行拨号
+            SwarmEvent::OutgoingConnectionError {
+                peer_id: Some(peer_id),
+                ..
+            } if self.joined && Some(*peer_id) == self.peer_id => {
+                let peer_id = *peer_id;
+                if swarm.is_connected(&peer_id) {
+                    handle.finish(Ok(()));
+                    return (false, Some(event));
+                }
+                let (opts, addrs) = ordered_dial_opts(swarm, peer_id, self.address_order);
+                (self.start_dial(swarm, opts, addrs, handle), Some(event))
+            }
             _ => (true, Some(event)), // 继续等待
         }
     }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    async fn on_timeout(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        // disconnect_peer_id 会中止发往该 peer 的所有拨号，只在本命令独占时调用
+        if let Some(peer_id) = self.peer_id
+            && !self.joined
+            && !swarm.is_connected(&peer_id)
+            && let Some(pending_dials) = &self.pending_dials
+            && !pending_dials.has_other_dials(&peer_id, &self.connection_id)
+        {
+            warn!("Dial to {} timed out, aborting pending dial", peer_id);
+            let _ = swarm.disconnect_peer_id(peer_id);
+        } else {
+            warn!("Dial to {:?} timed out", self.peer_id);
+        }
+        handle.finish(Err(Error::DialTimeout));
+    }
+}
+
+/// 按 `order` 排好候选地址的拨号参数及候选地址
+///
+/// `AddressOrder::Default` 时直接交给 swarm 自行收集地址，返回的候选地址为空。
+/// 其他顺序下先收集候选地址（见 `known_addrs`），排序后作为显式地址拨号，不再由 behaviour 追加。
+pub(crate) fn ordered_dial_opts<Req, Resp>(
+    swarm: &mut CoreSwarm<Req, Resp>,
+    peer_id: PeerId,
+    order: AddressOrder,
+) -> (DialOpts, Vec<Multiaddr>)
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    ordered_dial_opts_with(swarm, peer_id, order, Vec::new())
+}
+
+/// 同 `ordered_dial_opts`，另带调用方已知的地址 `known`（如 Kad 查询中对端返回的地址，
+/// 查询结束后 behaviour 不再提供）
+///
+/// `AddressOrder::Default` 时 `known` 排在 behaviour 收集的地址之前；
+/// 其他顺序下与 behaviour 收集的地址一起排序。
+pub(crate) fn ordered_dial_opts_with<Req, Resp>(
+    swarm: &mut CoreSwarm<Req, Resp>,
+    peer_id: PeerId,
+    order: AddressOrder,
+    known: Vec<Multiaddr>,
+) -> (DialOpts, Vec<Multiaddr>)
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    if order == AddressOrder::Default {
+        if known.is_empty() {
+            return (DialOpts::peer_id(peer_id).build(), known);
+        }
+        let opts = DialOpts::peer_id(peer_id)
+            .addresses(known.clone())
+            .extend_addresses_through_behaviour()
+            .build();
+        return (opts, known);
+    }
+    let mut addrs = known;
+    addrs.extend(known_addrs(swarm, peer_id));
+    if addrs.is_empty() {
+        // 没有已知地址时交给 swarm 自行收集，仍找不到时由其报告错误
+        return (DialOpts::peer_id(peer_id).build(), addrs);
+    }
+    let mut seen = HashSet::new();
+    addrs.retain(|addr| seen.insert(addr.clone()));
+    order.sort(&mut addrs);
+    let opts = DialOpts::peer_id(peer_id).addresses(addrs.clone()).build();
+    (opts, addrs)
+}
+
+/// peer 的已知地址：Kad 路由表与进行中查询里的地址、identify 缓存，
+/// 以及经 `Swarm::add_peer_address` 注册（`add_peer_addrs`、mDNS 等）、由 `req_resp` 保存的地址
+///
+/// 只向 `kad`、`identify`、`req_resp` 询问 `handle_pending_outbound_connection`：这三者的实现
+/// 只读取各自的地址簿，不记录传入的 `ConnectionId`，因此可以用一个不交给 swarm 的 id 查询。
+/// 其他 behaviour（如屏蔽列表、按 id 登记待建立连接的 `connection_limits`）不能这样调用，
+/// 由 swarm 在真正拨号时询问。
+fn known_addrs<Req, Resp>(swarm: &mut CoreSwarm<Req, Resp>, peer_id: PeerId) -> Vec<Multiaddr>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    let connection_id = ConnectionId::new_unchecked(0);
+    let behaviour = swarm.behaviour_mut();
+    [
+        behaviour.kad.handle_pending_outbound_connection(
+            connection_id,
+            Some(peer_id),
+            &[],
+            Endpoint::Dialer,
+        ),
+        behaviour.identify.handle_pending_outbound_connection(
+            connection_id,
+            Some(peer_id),
+            &[],
+            Endpoint::Dialer,
+        ),
+        behaviour.req_resp.handle_pending_outbound_connection(
+            connection_id,
+            Some(peer_id),
+            &[],
+            Endpoint::Dialer,
+        ),
+    ]
+    .into_iter()
+    .flat_map(Result::unwrap_or_default)
+    .collect()
 }
@@ -0,0 +1,113 @@
+use async_trait::async_trait;
+use libp2p::multiaddr::Protocol;
+use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
+use libp2p::swarm::{ConnectionId, SwarmEvent};
+use libp2p::{Multiaddr, PeerId};
+use tracing::info;
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent, PendingDials};
+
+use super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+
+/// DialAddr 命令 - 按地址拨号（如二维码 / 分享链接中的地址），返回对端 PeerId
+///
+/// 地址以 `/p2p/<peer_id>` 结尾时按该 peer 拨号（已连接则直接返回），
+/// 否则以未知 peer 拨号，握手完成后得到 PeerId。
+/// 连接建立后地址登记到 Swarm 地址簿和 Kad 路由表。
+pub struct DialAddrCommand {
+    /// 去掉 `/p2p` 后缀的地址
+    addr: Multiaddr,
+    peer_id: Option<PeerId>,
+    connection_id: Option<ConnectionId>,
+    /// 登记本次拨号，由 EventLoop 上报 `DialStarted`
+    pending_dials: Option<PendingDials>,
+}
+
+impl DialAddrCommand {
+    pub fn new(mut addr: Multiaddr) -> Self {
+        let peer_id = match addr.iter().last() {
+            Some(Protocol::P2p(peer_id)) => {
+                addr.pop();
+                Some(peer_id)
+            }
+            _ => None,
+        };
+        Self {
+            addr,
+            peer_id,
+            connection_id: None,
+            pending_dials: None,
+        }
+    }
+
+    /// 拨号开始时登记到 `pending_dials`，由 EventLoop 上报 `DialStarted`
+    pub fn with_pending_dials(mut self, pending_dials: PendingDials) -> Self {
+        self.pending_dials = Some(pending_dials);
+        self
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for DialAddrCommand {
+    type Result = PeerId;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let opts = match self.peer_id {
+            Some(peer_id) if swarm.is_connected(&peer_id) => {
+                handle.finish(Ok(peer_id));
+                return;
+            }
+            // 允许与其他拨号（如 mDNS 自动拨号）并行，以本次地址的结果为准
+            Some(peer_id) => DialOpts::peer_id(peer_id)
+                .addresses(vec![self.addr.clone()])
+                .condition(PeerCondition::Disconnected)
+                .build(),
+            None => DialOpts::unknown_peer_id()
+                .address(self.addr.clone())
+                .build(),
+        };
+        let connection_id = opts.connection_id();
+        self.connection_id = Some(connection_id);
+        match swarm.dial(opts) {
+            Ok(()) => {
+                if let Some(pending_dials) = &self.pending_dials {
+                    pending_dials.start(self.peer_id, connection_id, vec![self.addr.clone()]);
+                }
+            }
+            Err(e) => handle.finish(Err(Error::Dial(e.to_string()))),
+        }
+    }
+
+    async fn on_event(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match &event {
+            SwarmEvent::ConnectionEstablished {
+                peer_id,
+                connection_id,
+                ..
+            } if Some(*connection_id) == self.connection_id => {
+                info!("Dialed {} at {}, registering address", peer_id, self.addr);
+                swarm.add_peer_address(*peer_id, self.addr.clone());
+                if let Some(kad) = swarm.behaviour_mut().kad.as_mut() {
+                    kad.add_address(peer_id, self.addr.clone());
+                }
+                handle.finish(Ok(*peer_id));
+                (false, Some(event)) // 不消费，前端需要 PeerConnected
+            }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                error,
+                ..
+            } if Some(*connection_id) == self.connection_id => {
+                handle.finish(Err(Error::Dial(error.to_string())));
+                (false, Some(event)) // 不消费
+            }
+            _ => (true, Some(event)), // 继续等待
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use libp2p::PeerId;
+use libp2p::swarm::{ConnectionId, DialError, SwarmEvent};
+
+use crate::config::AddressOrder;
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent, PendingDials};
+
+use super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle, ordered_dial_opts};
+
+/// DialMany 命令 - 同时拨号多个 peer，汇总各自的结果
+///
+/// 每个 peer 的结果相互独立：某个 peer 不可达不影响其余 peer。
+/// 整体超时后仍未有结果的 peer 记为拨号超时。
+pub struct DialManyCommand {
+    peers: Vec<PeerId>,
+    timeout: Duration,
+    /// 进行中的拨号：目标 peer → 本次拨号的 ConnectionId
+    ///
+    /// 为 `None` 时表示该 peer 已有其他拨号在进行（如 Kad 自动拨号），
+    /// 复用其结果：该 peer 的任一出站失败都视为本次失败。
+    pending: HashMap<PeerId, Option<ConnectionId>>,
+    results: HashMap<PeerId, crate::Result<()>>,
+    address_order: AddressOrder,
+    /// 登记发起的拨号，由 EventLoop 上报 `DialStarted`
+    pending_dials: Option<PendingDials>,
+}
+
+impl DialManyCommand {
+    pub fn new(peers: Vec<PeerId>, timeout: Duration) -> Self {
+        let mut unique = Vec::with_capacity(peers.len());
+        for peer_id in peers {
+            if !unique.contains(&peer_id) {
+                unique.push(peer_id);
+            }
+        }
+        Self {
+            peers: unique,
+            timeout,
+            pending: HashMap::new(),
+            results: HashMap::new(),
+            address_order: AddressOrder::Default,
+            pending_dials: None,
+        }
+    }
+
+    /// 按 `order` 排列每个 peer 的候选地址
+    pub fn with_address_order(mut self, order: AddressOrder) -> Self {
+        self.address_order = order;
+        self
+    }
+
+    /// 拨号开始时登记到 `pending_dials`，由 EventLoop 上报 `DialStarted`
+    pub fn with_pending_dials(mut self, pending_dials: PendingDials) -> Self {
+        self.pending_dials = Some(pending_dials);
+        self
+    }
+
+    /// 所有 peer 都有结果时按输入顺序返回
+    fn try_finish(&mut self, handle: &ResultHandle<Vec<(PeerId, crate::Result<()>)>>) -> bool {
+        if !self.pending.is_empty() {
+            return false;
+        }
+        let results = self
+            .peers
+            .iter()
+            .map(|peer_id| {
+                let result = self
+                    .results
+                    .remove(peer_id)
+                    .unwrap_or_else(|| Err(Error::Dial("Dial timed out".into())));
+                (*peer_id, result)
+            })
+            .collect();
+        handle.finish(Ok(results));
+        true
+    }
+
+    fn resolve(&mut self, peer_id: PeerId, result: crate::Result<()>) {
+        self.pending.remove(&peer_id);
+        self.results.insert(peer_id, result);
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for DialManyCommand {
+    type Result = Vec<(PeerId, crate::Result<()>)>;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        for peer_id in self.peers.clone() {
+            if swarm.is_connected(&peer_id) {
+                self.results.insert(peer_id, Ok(()));
+                continue;
+            }
+            let (opts, addrs) = ordered_dial_opts(swarm, peer_id, self.address_order);
+            let connection_id = opts.connection_id();
+            match swarm.dial(opts) {
+                Ok(()) => {
+                    if let Some(pending_dials) = &self.pending_dials {
+                        pending_dials.start(Some(peer_id), connection_id, addrs);
+                    }
+                    self.pending.insert(peer_id, Some(connection_id));
+                }
+                Err(DialError::DialPeerConditionFalse(_)) => {
+                    self.pending.insert(peer_id, None);
+                }
+                Err(e) => {
+                    self.results
+                        .insert(peer_id, Err(Error::Dial(e.to_string())));
+                }
+            }
+        }
+        self.try_finish(handle);
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match &event {
+            // 目标 peer 的任一连接建立即视为成功
+            SwarmEvent::ConnectionEstablished { peer_id, .. }
+                if self.pending.contains_key(peer_id) =>
+            {
+                self.resolve(*peer_id, Ok(()));
+            }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                peer_id: Some(peer_id),
+                error,
+            } if self
+                .pending
+                .get(peer_id)
+                .is_some_and(|id| id.is_none_or(|id| id == *connection_id)) =>
+            {
+                self.resolve(*peer_id, Err(Error::Dial(error.to_string())));
+            }
+            _ => {}
+        }
+        // 不消费事件，前端仍需要 PeerConnected / ConnectionFailed
+        (!self.try_finish(handle), Some(event))
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        Some(self.timeout)
+    }
+
+    async fn on_timeout(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        self.pending.clear();
+        self.try_finish(handle);
+    }
+}
@@ -34,6 +34,7 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for Disconne
 
     async fn on_event(
         &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use libp2p::Multiaddr;
+
+use crate::runtime::CborMessage;
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// AddExternalAddress 命令 - 手动登记外部地址（如路由器端口映射后的公网地址）
+///
+/// 地址视为已确认：会出现在 `get_addrs()` 中，并随下一次 Identify push 通告给对端。
+pub struct AddExternalAddressCommand {
+    addr: Multiaddr,
+}
+
+impl AddExternalAddressCommand {
+    pub fn new(addr: Multiaddr) -> Self {
+        Self { addr }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for AddExternalAddressCommand {
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        swarm.add_external_address(self.addr.clone());
+        handle.finish(Ok(()));
+    }
+}
+
+/// RemoveExternalAddress 命令 - 移除外部地址（不存在时忽略）
+pub struct RemoveExternalAddressCommand {
+    addr: Multiaddr,
+}
+
+impl RemoveExternalAddressCommand {
+    pub fn new(addr: Multiaddr) -> Self {
+        Self { addr }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp>
+    for RemoveExternalAddressCommand
+{
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        swarm.remove_external_address(&self.addr);
+        handle.finish(Ok(()));
+    }
+}
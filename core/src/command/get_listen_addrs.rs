@@ -1,12 +1,21 @@
+use std::net::IpAddr;
+
 use async_trait::async_trait;
 use libp2p::Multiaddr;
+use libp2p::multiaddr::Protocol;
+use tracing::warn;
 
 use crate::runtime::CborMessage;
 
 use super::{CommandHandler, CoreSwarm, ResultHandle};
 
-/// GetListenAddrs 命令 - 获取本节点的所有可达地址（监听地址 + 外部地址）
-pub struct GetListenAddrsCommand;
+/// GetListenAddrs 命令 - 获取本节点的所有可达地址（外部地址 + 监听地址）
+///
+/// 已确认的外部地址排在前面；`0.0.0.0` / `::` 这类未指定地址展开为各网卡的具体地址。
+/// 两组分别排序，整体去重。
+pub struct GetListenAddrsCommand {
+    include_loopback: bool,
+}
 
 impl Default for GetListenAddrsCommand {
     fn default() -> Self {
@@ -16,7 +25,15 @@ impl Default for GetListenAddrsCommand {
 
 impl GetListenAddrsCommand {
     pub fn new() -> Self {
-        Self
+        Self {
+            include_loopback: true,
+        }
+    }
+
+    /// 是否保留回环地址（默认保留），生成分享链接时可关闭
+    pub fn with_loopback(mut self, include_loopback: bool) -> Self {
+        self.include_loopback = include_loopback;
+        self
     }
 }
 
@@ -25,10 +42,118 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetListe
     type Result = Vec<Multiaddr>;
 
     async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
-        let mut addrs: Vec<Multiaddr> = swarm.listeners().cloned().collect();
-        addrs.extend(swarm.external_addresses().cloned());
+        let listeners: Vec<Multiaddr> = swarm.listeners().cloned().collect();
+        let interface_ips = if listeners.iter().any(is_unspecified) {
+            interface_ips()
+        } else {
+            Vec::new()
+        };
+
+        let mut addrs: Vec<Multiaddr> = swarm.external_addresses().cloned().collect();
         addrs.sort();
+        let mut local = expand_unspecified(listeners, &interface_ips);
+        local.sort();
+        for addr in local {
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
         addrs.dedup();
+        if !self.include_loopback {
+            addrs.retain(|addr| !is_loopback(addr));
+        }
         handle.finish(Ok(addrs));
     }
 }
+
+/// 本机各网卡的 IP；读取失败时返回空（未指定地址随之被丢弃）
+fn interface_ips() -> Vec<IpAddr> {
+    match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces.into_iter().map(|i| i.ip()).collect(),
+        Err(e) => {
+            warn!("Failed to list network interfaces: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 把以未指定 IP 开头的地址替换为同协议族的每个网卡地址，其余地址原样保留
+fn expand_unspecified(addrs: Vec<Multiaddr>, interface_ips: &[IpAddr]) -> Vec<Multiaddr> {
+    let mut expanded = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        let mut iter = addr.iter();
+        let (ipv4, rest) = match iter.next() {
+            Some(Protocol::Ip4(ip)) if ip.is_unspecified() => (true, iter),
+            Some(Protocol::Ip6(ip)) if ip.is_unspecified() => (false, iter),
+            _ => {
+                expanded.push(addr);
+                continue;
+            }
+        };
+        let rest: Vec<Protocol> = rest.collect();
+        for ip in interface_ips.iter().filter(|ip| ip.is_ipv4() == ipv4) {
+            let concrete = Multiaddr::from(*ip);
+            expanded.push(concrete.into_iter().chain(rest.iter().cloned()).collect());
+        }
+    }
+    expanded
+}
+
+fn is_unspecified(addr: &Multiaddr) -> bool {
+    match addr.iter().next() {
+        Some(Protocol::Ip4(ip)) => ip.is_unspecified(),
+        Some(Protocol::Ip6(ip)) => ip.is_unspecified(),
+        _ => false,
+    }
+}
+
+fn is_loopback(addr: &Multiaddr) -> bool {
+    match addr.iter().next() {
+        Some(Protocol::Ip4(ip)) => ip.is_loopback(),
+        Some(Protocol::Ip6(ip)) => ip.is_loopback(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> Multiaddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn unspecified_addrs_expand_per_family() {
+        let ips: Vec<IpAddr> = vec![
+            "127.0.0.1".parse().unwrap(),
+            "192.168.1.5".parse().unwrap(),
+            "::1".parse().unwrap(),
+        ];
+        let expanded = expand_unspecified(
+            vec![
+                addr("/ip4/0.0.0.0/udp/4001/quic-v1"),
+                addr("/ip6/::/tcp/4001"),
+                addr("/ip4/10.0.0.2/tcp/4001"),
+            ],
+            &ips,
+        );
+        assert_eq!(
+            expanded,
+            vec![
+                addr("/ip4/127.0.0.1/udp/4001/quic-v1"),
+                addr("/ip4/192.168.1.5/udp/4001/quic-v1"),
+                addr("/ip6/::1/tcp/4001"),
+                addr("/ip4/10.0.0.2/tcp/4001"),
+            ]
+        );
+    }
+
+    #[test]
+    fn loopback_and_unspecified_detection() {
+        assert!(is_unspecified(&addr("/ip4/0.0.0.0/tcp/1")));
+        assert!(!is_unspecified(&addr("/ip4/127.0.0.1/tcp/1")));
+        assert!(is_loopback(&addr("/ip6/::1/tcp/1")));
+        assert!(!is_loopback(&addr("/dns4/localhost/tcp/1")));
+    }
+}
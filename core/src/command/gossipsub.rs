@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use libp2p::gossipsub::{self, IdentTopic};
+
+use crate::error::Error;
+use crate::runtime::CborMessage;
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// 取出已启用的 Gossipsub 行为，未启用时返回错误
+fn gossipsub_mut<Req: CborMessage, Resp: CborMessage>(
+    swarm: &mut CoreSwarm<Req, Resp>,
+) -> Result<&mut gossipsub::Behaviour, Error> {
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .as_mut()
+        .ok_or_else(|| Error::Behaviour("Gossipsub is not enabled".into()))
+}
+
+/// Subscribe 命令 - 订阅主题，返回 `false` 表示已订阅过
+pub struct SubscribeCommand {
+    topic: IdentTopic,
+}
+
+impl SubscribeCommand {
+    pub fn new(topic: impl Into<String>) -> Self {
+        Self {
+            topic: IdentTopic::new(topic),
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for SubscribeCommand {
+    type Result = bool;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let result = gossipsub_mut(swarm).and_then(|gossipsub| {
+            gossipsub
+                .subscribe(&self.topic)
+                .map_err(|e| Error::Gossipsub(format!("Subscribe {}: {}", self.topic, e)))
+        });
+        handle.finish(result);
+    }
+}
+
+/// Unsubscribe 命令 - 取消订阅主题，返回 `false` 表示原本未订阅
+pub struct UnsubscribeCommand {
+    topic: IdentTopic,
+}
+
+impl UnsubscribeCommand {
+    pub fn new(topic: impl Into<String>) -> Self {
+        Self {
+            topic: IdentTopic::new(topic),
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for UnsubscribeCommand {
+    type Result = bool;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let result = gossipsub_mut(swarm).map(|gossipsub| gossipsub.unsubscribe(&self.topic));
+        handle.finish(result);
+    }
+}
+
+/// Publish 命令 - 向主题发布消息
+pub struct PublishCommand {
+    topic: IdentTopic,
+    data: Vec<u8>,
+}
+
+impl PublishCommand {
+    pub fn new(topic: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            topic: IdentTopic::new(topic),
+            data,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for PublishCommand {
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let result = gossipsub_mut(swarm).and_then(|gossipsub| {
+            gossipsub
+                .publish(self.topic.clone(), std::mem::take(&mut self.data))
+                .map(|_| ())
+                .map_err(|e| Error::Gossipsub(format!("Publish {}: {}", self.topic, e)))
+        });
+        handle.finish(result);
+    }
+}
@@ -1,13 +1,15 @@
 use std::marker::PhantomData;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use parking_lot::Mutex;
 use std::sync::Arc;
 use std::task::{Context, Poll, Waker};
 
-use libp2p::Swarm;
 use libp2p::swarm::SwarmEvent;
+use libp2p::{PeerId, Swarm};
 
+use crate::error::Error;
 use crate::runtime::{CborMessage, CoreBehaviour, CoreBehaviourEvent};
 
 /// Swarm 类型别名
@@ -19,8 +21,26 @@ pub type CoreSwarm<Req, Resp> = Swarm<CoreBehaviour<Req, Resp>>;
 /// - `remaining_event`:
 ///   - `None` — 事件已被该命令消费，不再传递
 ///   - `Some(event)` — 事件未消费，传递给下一个命令或 convert_to_node_event
+///
+/// 只有按本命令独有的 id（`QueryId`、`OutboundRequestId` 等）匹配的事件才可消费。
+/// 连接生命周期事件（见 `is_shared_event`）可能同时被多个命令等待
+/// （如对同一 peer 的 `dial` 与 `dial_many`），必须传递。
 pub type OnEventResult<Req, Resp> = (bool, Option<SwarmEvent<CoreBehaviourEvent<Req, Resp>>>);
 
+/// 是否为所有命令共享、不可消费的连接生命周期事件
+pub(crate) fn is_shared_event<Req: CborMessage, Resp: CborMessage>(
+    event: &SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+) -> bool {
+    matches!(
+        event,
+        SwarmEvent::ConnectionEstablished { .. }
+            | SwarmEvent::ConnectionClosed { .. }
+            | SwarmEvent::OutgoingConnectionError { .. }
+            | SwarmEvent::IncomingConnectionError { .. }
+            | SwarmEvent::Dialing { .. }
+    )
+}
+
 /// 命令结果句柄，用于命令完成时返回结果
 #[derive(Debug)]
 pub struct ResultHandle<T>(Arc<Mutex<ResultState<T>>>);
@@ -29,6 +49,8 @@ pub struct ResultHandle<T>(Arc<Mutex<ResultState<T>>>);
 struct ResultState<T> {
     result: Option<crate::Result<T>>,
     waker: Option<Waker>,
+    /// 是否已调用过 `finish`（结果被取走后仍保持 true）
+    finished: bool,
 }
 
 impl<T> Default for ResultState<T> {
@@ -36,6 +58,7 @@ impl<T> Default for ResultState<T> {
         Self {
             result: None,
             waker: None,
+            finished: false,
         }
     }
 }
@@ -71,10 +94,16 @@ impl<T> ResultHandle<T> {
     pub fn finish(&self, result: crate::Result<T>) {
         let mut state = self.0.lock();
         state.result = Some(result);
+        state.finished = true;
         if let Some(waker) = state.waker.take() {
             waker.wake();
         }
     }
+
+    /// 命令是否已完成
+    pub fn is_finished(&self) -> bool {
+        self.0.lock().finished
+    }
 }
 
 /// 命令处理器 trait
@@ -98,13 +127,48 @@ where
     /// 返回 `(keep_active, remaining_event)`：
     /// - `keep_active`: true 继续等待后续事件，false 命令完成
     /// - `remaining_event`: None 表示已消费，Some 表示传递给下一个处理者
+    ///
+    /// 传入 `swarm` 便于命令在收到中间结果后继续推进（如提前结束 Kad 查询）。
     async fn on_event(
         &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         _handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
         (false, Some(event))
     }
+
+    /// 命令超时时间（从 `run` 开始计时），默认不超时
+    ///
+    /// 返回 `Some` 时，EventLoop 会在到期后调用 `on_timeout` 并移除该命令。
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// 超时回调，默认以 `Error::CommandTimeout` 结束命令
+    async fn on_timeout(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        handle.finish(Err(Error::CommandTimeout));
+    }
+
+    /// 占用出站请求并发名额的 peer，默认 `None`
+    ///
+    /// 配置了 `max_concurrent_requests_per_peer` 时，EventLoop 据此限制同一 peer
+    /// 同时进行的命令数，超出的命令排队，等名额空出后才调用 `run`。
+    fn request_peer(&self) -> Option<PeerId> {
+        None
+    }
+
+    /// 是否为关闭命令，默认 false
+    ///
+    /// EventLoop 收到关闭命令后停止接收新命令，等其余命令完成（或 `timeout` 到期）
+    /// 后调用其 `on_timeout` 收尾，然后退出事件循环。
+    fn is_shutdown(&self) -> bool {
+        false
+    }
 }
 
 /// 命令 trait object 包装
@@ -120,8 +184,16 @@ where
     async fn run_boxed(&mut self, swarm: &mut CoreSwarm<Req, Resp>);
     async fn on_event_boxed(
         &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
     ) -> OnEventResult<Req, Resp>;
+    fn timeout(&self) -> Option<Duration>;
+    async fn on_timeout_boxed(&mut self, swarm: &mut CoreSwarm<Req, Resp>);
+    fn request_peer(&self) -> Option<PeerId>;
+    fn is_shutdown(&self) -> bool;
+    fn is_finished(&self) -> bool;
+    /// 命令类型名（不含模块路径和泛型参数），用于日志 span
+    fn name(&self) -> &'static str;
 }
 
 /// 命令任务，包装 CommandHandler + ResultHandle
@@ -133,6 +205,8 @@ where
 {
     handler: T,
     handle: ResultHandle<T::Result>,
+    /// 覆盖 `handler.timeout()` 的超时时间
+    timeout: Option<Duration>,
     _phantom: PhantomData<(Req, Resp)>,
 }
 
@@ -146,9 +220,16 @@ where
         Self {
             handler,
             handle,
+            timeout: None,
             _phantom: PhantomData,
         }
     }
+
+    /// 以 `timeout` 代替命令自身的超时时间
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 }
 
 #[async_trait]
@@ -165,8 +246,57 @@ where
 
     async fn on_event_boxed(
         &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
     ) -> OnEventResult<Req, Resp> {
-        self.handler.on_event(event, &self.handle).await
+        self.handler.on_event(swarm, event, &self.handle).await
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout.or_else(|| self.handler.timeout())
+    }
+
+    async fn on_timeout_boxed(&mut self, swarm: &mut CoreSwarm<Req, Resp>) {
+        self.handler.on_timeout(swarm, &self.handle).await;
+    }
+
+    fn request_peer(&self) -> Option<PeerId> {
+        self.handler.request_peer()
+    }
+
+    fn is_shutdown(&self) -> bool {
+        self.handler.is_shutdown()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    fn name(&self) -> &'static str {
+        short_type_name::<T>()
+    }
+}
+
+/// 去掉模块路径和泛型参数的类型名，如 `GetRecordCommand`
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Plain;
+    struct Generic<T>(PhantomData<T>);
+
+    #[test]
+    fn short_type_name_strips_path_and_generics() {
+        assert_eq!(short_type_name::<Plain>(), "Plain");
+        assert_eq!(
+            short_type_name::<Generic<std::collections::HashMap<u8, u8>>>(),
+            "Generic"
+        );
     }
 }
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use libp2p::PeerId;
+use libp2p::request_response::{Event, Message, OutboundRequestId};
+use libp2p::swarm::SwarmEvent;
+use tracing::info;
+
+use crate::config::HandshakeInfo;
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent};
+
+use super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+
+/// Handshake 命令 - 主动与 peer 交换应用层握手信息
+///
+/// 响应事件不被消费，继续交给 EventLoop 更新 peer 信息并发出 `HandshakeCompleted`。
+pub struct HandshakeCommand {
+    peer_id: PeerId,
+    local: HandshakeInfo,
+    request_id: Option<OutboundRequestId>,
+}
+
+impl HandshakeCommand {
+    pub fn new(peer_id: PeerId, local: HandshakeInfo) -> Self {
+        Self {
+            peer_id,
+            local,
+            request_id: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req, Resp> CommandHandler<Req, Resp> for HandshakeCommand
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    type Result = HandshakeInfo;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let Some(handshake) = swarm.behaviour_mut().handshake.as_mut() else {
+            handle.finish(Err(Error::Behaviour("Handshake is not enabled".into())));
+            return;
+        };
+        let request_id = handshake.send_request(&self.peer_id, self.local.clone());
+        self.request_id = Some(request_id);
+        info!("Sent handshake to {}", self.peer_id);
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match &event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Handshake(Event::Message {
+                message:
+                    Message::Response {
+                        request_id,
+                        response,
+                    },
+                ..
+            })) if self.request_id.as_ref() == Some(request_id) => {
+                handle.finish(Ok(response.clone()));
+                (false, Some(event))
+            }
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Handshake(Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+                ..
+            })) if self.request_id.as_ref() == Some(request_id) => {
+                handle.finish(Err(Error::RequestResponse(format!(
+                    "Handshake with {} failed: {:?}",
+                    peer, error
+                ))));
+                (false, Some(event))
+            }
+            _ => (true, Some(event)),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use libp2p::core::transport::ListenerId;
+use libp2p::identify;
+use libp2p::swarm::behaviour::{ConnectionEstablished, ExternalAddrConfirmed};
+use libp2p::swarm::{FromSwarm, NetworkBehaviour, NewListenAddr};
+
+use crate::runtime::{CborMessage, PeerConnections};
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// SetAgentVersion 命令 - 以新的 agent_version 重建 identify behaviour
+///
+/// identify 的配置构建后不可修改，且每条连接的 handler 各持一份副本，
+/// 只能整体替换 behaviour：把当前的监听地址、外部地址和连接补发给新 behaviour，
+/// 之后建立的连接上报新值，已建立的连接在重连前仍上报旧值。
+/// identify 缓存的对端地址随旧 behaviour 一起丢弃（identify 未提供写入缓存的接口，无法迁移）。
+pub struct SetAgentVersionCommand {
+    config: identify::Config,
+    connections: PeerConnections,
+}
+
+impl SetAgentVersionCommand {
+    pub fn new(config: identify::Config, connections: PeerConnections) -> Self {
+        Self {
+            config,
+            connections,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for SetAgentVersionCommand {
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let mut identify = identify::Behaviour::new(self.config.clone());
+
+        for addr in swarm.listeners() {
+            identify.on_swarm_event(FromSwarm::NewListenAddr(NewListenAddr {
+                listener_id: ListenerId::next(),
+                addr,
+            }));
+        }
+        for addr in swarm.external_addresses() {
+            identify.on_swarm_event(FromSwarm::ExternalAddrConfirmed(ExternalAddrConfirmed {
+                addr,
+            }));
+        }
+
+        // identify 按连接记录对端的观察地址，补发时按建立顺序累计 other_established
+        let mut established: HashMap<_, usize> = HashMap::new();
+        for (peer_id, connection_id, endpoint) in self.connections.established() {
+            let other_established = established.entry(peer_id).or_default();
+            identify.on_swarm_event(FromSwarm::ConnectionEstablished(ConnectionEstablished {
+                peer_id,
+                connection_id,
+                endpoint: &endpoint,
+                failed_addresses: &[],
+                other_established: *other_established,
+            }));
+            *other_established += 1;
+        }
+
+        swarm.behaviour_mut().identify = identify;
+        handle.finish(Ok(()));
+    }
+}
@@ -1,13 +1,20 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
 use async_trait::async_trait;
 use libp2p::kad::{self, QueryId};
-use libp2p::swarm::SwarmEvent;
-use tracing::{error, info};
+use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
+use libp2p::swarm::{ConnectionId, SwarmEvent};
+use libp2p::{Multiaddr, PeerId, identify};
+use tracing::{error, info, warn};
 
 use crate::error::Error;
 use crate::runtime::{CborMessage, CoreBehaviourEvent};
 use crate::util::QueryStatsInfo;
 
 use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::kad_mut;
 
 /// Bootstrap 命令结果
 #[derive(Debug, Clone)]
@@ -18,10 +25,25 @@ pub struct BootstrapResult {
     pub stats: QueryStatsInfo,
 }
 
+/// 路由表为空时预留给引导节点连接与 Identify 的时长，命令总时限为它加上查询超时
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Bootstrap 命令 - 加入 DHT 网络，填充路由表
+///
+/// 路由表为空且配置了引导节点时，先拨号引导节点，等其完成 Identify 后再发起查询；
+/// 所有引导节点都拨号失败，或限定时间内没有引导节点完成 Identify 时返回
+/// `Error::KadBootstrapUnreachable`。
 pub struct BootstrapCommand {
     query_id: Option<QueryId>,
     stats: Option<kad::QueryStats>,
+    bootstrap_peers: Arc<[(PeerId, Multiaddr)]>,
+    query_timeout: Duration,
+    /// 路由表为空、正在等待引导节点
+    recovering: bool,
+    /// 恢复阶段发起的拨号：ConnectionId → 目标引导节点
+    dials: HashMap<ConnectionId, PeerId>,
+    /// 恢复阶段已连上、等待 Identify 的引导节点数
+    connected: usize,
 }
 
 impl BootstrapCommand {
@@ -29,6 +51,143 @@ impl BootstrapCommand {
         Self {
             query_id: None,
             stats: None,
+            bootstrap_peers: Arc::from([]),
+            query_timeout: Duration::ZERO,
+            recovering: false,
+            dials: HashMap::new(),
+            connected: 0,
+        }
+    }
+
+    /// 路由表为空时改为先连接 `peers`，`query_timeout` 为随后 bootstrap 查询的超时
+    pub fn with_bootstrap_peers(
+        mut self,
+        peers: Arc<[(PeerId, Multiaddr)]>,
+        query_timeout: Duration,
+    ) -> Self {
+        self.bootstrap_peers = peers;
+        self.query_timeout = query_timeout;
+        self
+    }
+
+    fn start<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<BootstrapResult>,
+    ) {
+        let kad = match kad_mut(swarm) {
+            Ok(kad) => kad,
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        match kad.bootstrap() {
+            Ok(query_id) => {
+                self.query_id = Some(query_id);
+                info!("Bootstrap started, query_id: {:?}", query_id);
+            }
+            Err(e) if self.recovering => {
+                error!("Bootstrap failed to start after recovery: {:?}", e);
+                handle.finish(Err(Error::KadBootstrapUnreachable));
+            }
+            Err(e) if !self.bootstrap_peers.is_empty() => {
+                info!("Routing table empty ({:?}), connecting bootstrap peers", e);
+                self.recover(swarm, handle);
+            }
+            Err(e) => {
+                error!("Bootstrap failed to start: {:?}", e);
+                handle.finish(Err(Error::KadNoPeers));
+            }
+        }
+    }
+
+    /// 把引导节点地址写回路由表；已连上的直接开始查询，否则拨号等待 Identify
+    fn recover<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<BootstrapResult>,
+    ) {
+        self.recovering = true;
+        let mut ready = false;
+        let mut addrs: HashMap<PeerId, Vec<Multiaddr>> = HashMap::new();
+        for (peer_id, addr) in self.bootstrap_peers.iter() {
+            addrs.entry(*peer_id).or_default().push(addr.clone());
+        }
+        for (peer_id, addrs) in addrs {
+            if swarm.is_connected(&peer_id) {
+                self.add_addresses(swarm, &peer_id);
+                ready = true;
+                continue;
+            }
+            let opts = DialOpts::peer_id(peer_id)
+                .addresses(addrs)
+                .condition(PeerCondition::Always)
+                .build();
+            let connection_id = opts.connection_id();
+            match swarm.dial(opts) {
+                Ok(()) => {
+                    info!("Dialing bootstrap peer {} for recovery", peer_id);
+                    self.dials.insert(connection_id, peer_id);
+                }
+                Err(e) => warn!("Failed to dial bootstrap peer {}: {}", peer_id, e),
+            }
+        }
+        if ready {
+            self.start(swarm, handle);
+        } else if self.dials.is_empty() {
+            handle.finish(Err(Error::KadBootstrapUnreachable));
+        }
+    }
+
+    fn add_addresses<Req: CborMessage, Resp: CborMessage>(
+        &self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        peer_id: &PeerId,
+    ) {
+        let Some(kad) = swarm.behaviour_mut().kad.as_mut() else {
+            return;
+        };
+        for (_, addr) in self.bootstrap_peers.iter().filter(|(p, _)| p == peer_id) {
+            kad.add_address(peer_id, addr.clone());
+        }
+    }
+
+    /// 恢复阶段等待引导节点时处理连接与 Identify 事件，事件本身不消费
+    fn on_recovery_event<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        event: &SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<BootstrapResult>,
+    ) {
+        match event {
+            SwarmEvent::ConnectionEstablished { connection_id, .. }
+                if self.dials.remove(connection_id).is_some() =>
+            {
+                self.connected += 1;
+            }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                error,
+                ..
+            } => {
+                let Some(peer_id) = self.dials.remove(connection_id) else {
+                    return;
+                };
+                warn!("Bootstrap peer {} unreachable: {}", peer_id, error);
+                if self.dials.is_empty() && self.connected == 0 {
+                    handle.finish(Err(Error::KadBootstrapUnreachable));
+                }
+            }
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Identify(identify::Event::Received {
+                peer_id,
+                ..
+            })) if self.bootstrap_peers.iter().any(|(p, _)| p == peer_id) => {
+                info!("Bootstrap peer {} identified, starting bootstrap", peer_id);
+                self.add_addresses(swarm, peer_id);
+                self.start(swarm, handle);
+            }
+            _ => {}
         }
     }
 }
@@ -44,23 +203,19 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for Bootstra
     type Result = BootstrapResult;
 
     async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
-        match swarm.behaviour_mut().kad.bootstrap() {
-            Ok(query_id) => {
-                self.query_id = Some(query_id);
-                info!("Bootstrap started, query_id: {:?}", query_id);
-            }
-            Err(e) => {
-                error!("Bootstrap failed to start: {:?}", e);
-                handle.finish(Err(Error::Kad("Bootstrap failed: no known peers".to_string())));
-            }
-        }
+        self.start(swarm, handle);
     }
 
     async fn on_event(
         &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
+        if self.query_id.is_none() {
+            self.on_recovery_event(swarm, &event, handle);
+            return (!handle.is_finished(), Some(event));
+        }
         match event {
             SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
                 kad::Event::OutboundQueryProgressed {
@@ -86,10 +241,8 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for Bootstra
                     }
                     Err(e) => {
                         error!("Bootstrap error: {:?}", e);
-                        handle.finish(Err(Error::Kad(format!(
-                            "Bootstrap: {:?}",
-                            e
-                        ))));
+                        // BootstrapError 只有 Timeout 一种
+                        handle.finish(Err(Error::KadTimeout));
                         return (false, None); // 消费，完成
                     }
                 }
@@ -119,4 +272,23 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for Bootstra
             other => (true, Some(other)), // 继续等待
         }
     }
+
+    /// 只有需要等待引导节点时才限定时长，正常情况仍由 `kad_query_timeout` 兜底
+    fn timeout(&self) -> Option<Duration> {
+        self.recovering
+            .then(|| RECOVERY_TIMEOUT + self.query_timeout)
+    }
+
+    async fn on_timeout(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        if self.query_id.is_none() {
+            warn!("No bootstrap peer identified before timeout");
+            handle.finish(Err(Error::KadBootstrapUnreachable));
+        } else {
+            handle.finish(Err(Error::KadTimeout));
+        }
+    }
 }
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use libp2p::kad::QueryId;
+use tracing::info;
+
+use crate::runtime::CborMessage;
+
+use super::super::{CommandHandler, CoreSwarm, ResultHandle};
+use super::TrackedQueries;
+
+/// CancelQuery 命令 - 提前结束进行中的 Kad 查询
+///
+/// 发起查询的命令随后以 `Error::Cancelled` 结束；查询已结束、不存在或不属于进行中的命令
+/// （如命令已超时）时返回 `false`。
+pub struct CancelQueryCommand {
+    query_id: QueryId,
+    queries: TrackedQueries,
+}
+
+impl CancelQueryCommand {
+    pub fn new(query_id: QueryId, queries: TrackedQueries) -> Self {
+        Self { query_id, queries }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for CancelQueryCommand {
+    type Result = bool;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let Some(mut query) = swarm
+            .behaviour_mut()
+            .kad
+            .as_mut()
+            .and_then(|kad| kad.query_mut(&self.query_id))
+        else {
+            handle.finish(Ok(false));
+            return;
+        };
+        if !self.queries.cancel(self.query_id) {
+            handle.finish(Ok(false));
+            return;
+        }
+        info!("Cancelling Kad query {:?}", self.query_id);
+        query.finish();
+        handle.finish(Ok(true));
+    }
+}
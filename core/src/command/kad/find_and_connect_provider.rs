@@ -0,0 +1,272 @@
+use std::collections::{HashSet, VecDeque};
+
+use async_trait::async_trait;
+use libp2p::kad::{self, RecordKey};
+use libp2p::swarm::{ConnectionId, DialError, SwarmEvent};
+use libp2p::{Multiaddr, PeerId};
+use tracing::{info, warn};
+
+use crate::config::AddressOrder;
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent, PendingDials};
+
+use super::super::{
+    CommandHandler, CoreSwarm, OnEventResult, ResultHandle, ordered_dial_opts_with,
+};
+use super::get_providers::provider_addrs;
+use super::kad_mut;
+
+/// FindAndConnectProvider 命令 - 查找 key 的 Provider 并连接第一个可达的节点
+///
+/// Provider 一经发现就按顺序逐个拨号，某个 Provider 不可达时自动尝试下一个；
+/// 连接成功后结束剩余查询。Provider 记录与路由表中的地址在发现时记下，
+/// 查询结束后拨号后面的 Provider 仍可使用。
+pub struct FindAndConnectProviderCommand {
+    key: RecordKey,
+    query_id: Option<kad::QueryId>,
+    query_done: bool,
+    /// 待拨号的 Provider 及发现时记下的地址
+    candidates: VecDeque<(PeerId, Vec<Multiaddr>)>,
+    /// 已见过的 Provider（去重）
+    seen: HashSet<PeerId>,
+    /// 正在拨号的 Provider
+    dialing: Option<ProviderDial>,
+    last_error: Option<String>,
+    /// 候选地址的顺序
+    address_order: AddressOrder,
+    /// 登记发起的拨号，由 EventLoop 上报 `DialStarted`
+    pending_dials: Option<PendingDials>,
+}
+
+/// 正在拨号的 Provider
+struct ProviderDial {
+    peer_id: PeerId,
+    addrs: Vec<Multiaddr>,
+    connection_id: ConnectionId,
+    /// 已有其他拨号（如 mDNS 发起的）在进行，复用其结果；
+    /// 其失败时改由本命令以记下的地址自行拨号
+    joined: bool,
+}
+
+impl FindAndConnectProviderCommand {
+    pub fn new(key: RecordKey) -> Self {
+        Self {
+            key,
+            query_id: None,
+            query_done: false,
+            candidates: VecDeque::new(),
+            seen: HashSet::new(),
+            dialing: None,
+            last_error: None,
+            address_order: AddressOrder::Default,
+            pending_dials: None,
+        }
+    }
+
+    /// 按 `order` 排列每个 Provider 的候选地址
+    pub fn with_address_order(mut self, order: AddressOrder) -> Self {
+        self.address_order = order;
+        self
+    }
+
+    /// 拨号开始时登记到 `pending_dials`，由 EventLoop 上报 `DialStarted`
+    pub fn with_pending_dials(mut self, pending_dials: PendingDials) -> Self {
+        self.pending_dials = Some(pending_dials);
+        self
+    }
+
+    /// 结束剩余的 Kad 查询
+    fn finish_query<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+    ) {
+        if self.query_done {
+            return;
+        }
+        if let Some(mut query) = self
+            .query_id
+            .and_then(|id| swarm.behaviour_mut().kad.as_mut()?.query_mut(&id))
+        {
+            query.finish();
+        }
+    }
+
+    /// 若当前没有进行中的拨号，拨下一个候选 Provider
+    ///
+    /// 返回 `true` 表示命令已结束（已连上，或所有候选都失败且查询已完成）。
+    fn advance<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<PeerId>,
+    ) -> bool {
+        if self.dialing.is_some() {
+            return false;
+        }
+
+        while let Some((peer_id, known)) = self.candidates.pop_front() {
+            let (opts, addrs) =
+                ordered_dial_opts_with(swarm, peer_id, self.address_order, known.clone());
+            let connection_id = opts.connection_id();
+            let joined = match swarm.dial(opts) {
+                Ok(()) => {
+                    info!("FindAndConnectProvider: dialing provider {}", peer_id);
+                    if let Some(pending_dials) = &self.pending_dials {
+                        pending_dials.start(Some(peer_id), connection_id, addrs);
+                    }
+                    false
+                }
+                // 拨号条件不成立：已连接，或已有拨号在进行
+                Err(DialError::DialPeerConditionFalse(_)) => {
+                    if swarm.is_connected(&peer_id) {
+                        info!("FindAndConnectProvider: already connected to {}", peer_id);
+                        self.finish_query(swarm);
+                        handle.finish(Ok(peer_id));
+                        return true;
+                    }
+                    info!(
+                        "FindAndConnectProvider: joining pending dial to {}",
+                        peer_id
+                    );
+                    true
+                }
+                Err(e) => {
+                    warn!("FindAndConnectProvider: dial {} failed: {}", peer_id, e);
+                    self.last_error = Some(e.to_string());
+                    continue;
+                }
+            };
+            self.dialing = Some(ProviderDial {
+                peer_id,
+                addrs: known,
+                connection_id,
+                joined,
+            });
+            return false;
+        }
+
+        if !self.query_done {
+            return false;
+        }
+
+        match self.last_error.take() {
+            Some(e) => handle.finish(Err(Error::Dial(format!(
+                "No reachable provider for key: {}",
+                e
+            )))),
+            None => handle.finish(Err(Error::KadNotFound)),
+        }
+        true
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp>
+    for FindAndConnectProviderCommand
+{
+    type Result = PeerId;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        // 自己不算候选 Provider
+        self.seen.insert(*swarm.local_peer_id());
+        let query_id = match kad_mut(swarm) {
+            Ok(kad) => kad.get_providers(self.key.clone()),
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        self.query_id = Some(query_id);
+    }
+
+    async fn on_event(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
+                kad::Event::OutboundQueryProgressed {
+                    id,
+                    result: kad::QueryResult::GetProviders(res),
+                    step,
+                    ..
+                },
+            )) if self.query_id == Some(id) => {
+                self.query_done = step.last;
+
+                match res {
+                    Ok(kad::GetProvidersOk::FoundProviders { providers, .. }) => {
+                        for peer_id in providers {
+                            if !self.seen.insert(peer_id) {
+                                continue;
+                            }
+                            // 已连接的 Provider 直接返回
+                            if swarm.is_connected(&peer_id) {
+                                info!("FindAndConnectProvider: already connected to {}", peer_id);
+                                self.finish_query(swarm);
+                                handle.finish(Ok(peer_id));
+                                return (false, None); // 消费，完成
+                            }
+                            // 发现时记下已知地址，供查询结束后拨号
+                            let addrs = swarm
+                                .behaviour_mut()
+                                .kad
+                                .as_mut()
+                                .map(|kad| provider_addrs(kad, &self.key, peer_id))
+                                .unwrap_or_default();
+                            self.candidates.push_back((peer_id, addrs));
+                        }
+                    }
+                    Ok(kad::GetProvidersOk::FinishedWithNoAdditionalRecord { .. }) => {}
+                    Err(e) => {
+                        warn!("FindAndConnectProvider query error: {:?}", e);
+                    }
+                }
+
+                let finished = self.advance(swarm, handle);
+                (!finished, None) // 消费
+            }
+            SwarmEvent::ConnectionEstablished { peer_id, .. }
+                if self.dialing.as_ref().is_some_and(|d| d.peer_id == peer_id) =>
+            {
+                info!("FindAndConnectProvider: connected to provider {}", peer_id);
+                self.finish_query(swarm);
+                handle.finish(Ok(peer_id));
+                (false, Some(event)) // 不消费，前端需要 PeerConnected
+            }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                ref error,
+                ..
+            } if self
+                .dialing
+                .as_ref()
+                .is_some_and(|d| !d.joined && d.connection_id == connection_id) =>
+            {
+                warn!("FindAndConnectProvider: provider unreachable: {}", error);
+                self.last_error = Some(error.to_string());
+                self.dialing = None;
+                let finished = self.advance(swarm, handle);
+                (!finished, Some(event)) // 不消费
+            }
+            // 加入的拨号失败不代表 Provider 不可达：以记下的地址重新拨号，
+            // 仍有其他拨号进行中时会再次加入
+            SwarmEvent::OutgoingConnectionError {
+                peer_id: Some(peer_id),
+                ..
+            } if self
+                .dialing
+                .as_ref()
+                .is_some_and(|d| d.joined && d.peer_id == peer_id) =>
+            {
+                if let Some(dial) = self.dialing.take() {
+                    self.candidates.push_front((dial.peer_id, dial.addrs));
+                }
+                let finished = self.advance(swarm, handle);
+                (!finished, Some(event)) // 不消费
+            }
+            other => (true, Some(other)), // 继续等待
+        }
+    }
+}
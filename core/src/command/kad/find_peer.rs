@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use libp2p::kad;
+use libp2p::swarm::SwarmEvent;
+use libp2p::{Multiaddr, PeerId};
+use tracing::{info, warn};
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent};
+
+use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::{kad_mut, routing_table_addrs};
+
+/// FindPeer 命令 - 经 Kad 查找指定 peer 的地址
+///
+/// 路由表中已有该 peer 时直接返回其地址；否则以其 PeerId 为 key 发起
+/// `get_closest_peers`，结果中出现目标（或路由表因此更新）即提前结束查询。
+/// 找到的地址会登记到 swarm 地址簿，之后可直接按 PeerId 拨号。
+pub struct FindPeerCommand {
+    peer_id: PeerId,
+    query_id: Option<kad::QueryId>,
+    stats: Option<kad::QueryStats>,
+}
+
+impl FindPeerCommand {
+    pub fn new(peer_id: PeerId) -> Self {
+        Self {
+            peer_id,
+            query_id: None,
+            stats: None,
+        }
+    }
+
+    /// 结果中的目标 peer 的地址，未出现或没有地址时返回 `None`
+    fn addrs_in(&self, peers: &[kad::PeerInfo]) -> Option<Vec<Multiaddr>> {
+        peers
+            .iter()
+            .find(|p| p.peer_id == self.peer_id && !p.addrs.is_empty())
+            .map(|p| p.addrs.clone())
+    }
+
+    /// 登记地址、结束查询并返回结果
+    fn found<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        addrs: Vec<Multiaddr>,
+        handle: &ResultHandle<Vec<Multiaddr>>,
+    ) {
+        info!("FindPeer found {} addrs for {}", addrs.len(), self.peer_id);
+        for addr in &addrs {
+            swarm.add_peer_address(self.peer_id, addr.clone());
+        }
+        if let Some(mut query) = self
+            .query_id
+            .take()
+            .and_then(|id| swarm.behaviour_mut().kad.as_mut()?.query_mut(&id))
+        {
+            query.finish();
+        }
+        handle.finish(Ok(addrs));
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for FindPeerCommand {
+    type Result = Vec<Multiaddr>;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let kad = match kad_mut(swarm) {
+            Ok(kad) => kad,
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        let known = routing_table_addrs(kad, self.peer_id);
+        if !known.is_empty() {
+            handle.finish(Ok(known));
+            return;
+        }
+        let query_id = kad.get_closest_peers(self.peer_id);
+        self.query_id = Some(query_id);
+    }
+
+    async fn on_event(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
+                kad::Event::OutboundQueryProgressed {
+                    id,
+                    result: kad::QueryResult::GetClosestPeers(res),
+                    stats,
+                    step,
+                },
+            )) if self.query_id == Some(id) => {
+                super::merge_stats(&mut self.stats, stats);
+                let peers = match &res {
+                    Ok(ok) => &ok.peers,
+                    Err(kad::GetClosestPeersError::Timeout { peers, .. }) => peers,
+                };
+                if let Some(addrs) = self.addrs_in(peers) {
+                    self.found(swarm, addrs, handle);
+                    return (false, None); // 消费，完成
+                }
+                if let Err(e) = res {
+                    warn!("FindPeer {} error: {:?}", self.peer_id, e);
+                    handle.finish(Err(Error::KadTimeout));
+                    return (false, None); // 消费，完成
+                }
+                if !step.last {
+                    return (true, None); // 消费，继续等待
+                }
+                handle.finish(Err(super::not_found(&self.stats)));
+                (false, None) // 消费，完成
+            }
+            // 查询途中与目标建立连接时路由表先于查询结果更新
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(kad::Event::RoutingUpdated {
+                peer,
+                ref addresses,
+                ..
+            })) if self.query_id.is_some() && peer == self.peer_id => {
+                let addrs = addresses.iter().cloned().collect();
+                self.found(swarm, addrs, handle);
+                (false, Some(event)) // 不消费，EventLoop 仍需同步地址簿
+            }
+            other => (true, Some(other)), // 继续等待
+        }
+    }
+}
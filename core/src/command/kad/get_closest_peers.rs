@@ -9,6 +9,7 @@ use crate::runtime::{CborMessage, CoreBehaviourEvent};
 use crate::util::QueryStatsInfo;
 
 use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::{QueryTracker, kad_mut};
 
 /// GetClosestPeers 命令结果
 #[derive(Debug, Clone)]
@@ -24,6 +25,7 @@ pub struct GetClosestPeersCommand {
     query_id: Option<kad::QueryId>,
     peers: Vec<PeerId>,
     stats: Option<kad::QueryStats>,
+    tracker: QueryTracker,
 }
 
 impl GetClosestPeersCommand {
@@ -33,24 +35,36 @@ impl GetClosestPeersCommand {
             query_id: None,
             peers: Vec::new(),
             stats: None,
+            tracker: QueryTracker::default(),
         }
     }
+
+    /// 上报 `QueryId` 并支持通过 `cancel_query` 取消
+    pub fn with_tracker(mut self, tracker: QueryTracker) -> Self {
+        self.tracker = tracker;
+        self
+    }
 }
 
 #[async_trait]
 impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetClosestPeersCommand {
     type Result = GetClosestPeersResult;
 
-    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, _handle: &ResultHandle<Self::Result>) {
-        let query_id = swarm
-            .behaviour_mut()
-            .kad
-            .get_closest_peers(self.key.to_vec());
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let query_id = match kad_mut(swarm) {
+            Ok(kad) => kad.get_closest_peers(self.key.to_vec()),
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
         self.query_id = Some(query_id);
+        self.tracker.started(swarm, query_id, handle);
     }
 
     async fn on_event(
         &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
@@ -66,6 +80,12 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetClose
                 // 累积统计
                 super::merge_stats(&mut self.stats, stats);
 
+                if step.last && self.tracker.finished() {
+                    info!("GetClosestPeers cancelled");
+                    handle.finish(Err(Error::Cancelled));
+                    return (false, None); // 消费，完成
+                }
+
                 // 处理结果
                 match res {
                     Ok(ok) => {
@@ -78,7 +98,8 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetClose
                     }
                     Err(e) => {
                         error!("GetClosestPeers error: {:?}", e);
-                        handle.finish(Err(Error::Kad(format!("GetClosestPeers: {:?}", e))));
+                        // GetClosestPeersError 只有 Timeout 一种
+                        handle.finish(Err(Error::KadTimeout));
                         return (false, None); // 消费，完成
                     }
                 }
@@ -88,6 +109,12 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetClose
                     return (true, None); // 消费，继续等待
                 }
 
+                // 一个节点都没问到，空结果是因为路由表为空
+                if self.peers.is_empty() && super::no_peers_queried(&self.stats) {
+                    handle.finish(Err(Error::KadNoPeers));
+                    return (false, None); // 消费，完成
+                }
+
                 // 查询完成
                 let stats_info = QueryStatsInfo::from(self.stats.as_ref().unwrap());
                 info!(
@@ -106,4 +133,14 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetClose
             other => (true, Some(other)), // 继续等待
         }
     }
+
+    async fn on_timeout(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        info!("GetClosestPeers timed out");
+        self.tracker.abort(swarm);
+        handle.finish(Err(Error::CommandTimeout));
+    }
 }
@@ -1,29 +1,77 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
-use libp2p::PeerId;
+use futures::channel::mpsc::UnboundedSender;
+use libp2p::kad::store::RecordStore;
 use libp2p::kad::{self, RecordKey};
 use libp2p::swarm::SwarmEvent;
+use libp2p::{Multiaddr, PeerId};
 use tracing::{error, info};
 
 use crate::error::Error;
 use crate::runtime::{CborMessage, CoreBehaviourEvent};
+use crate::store::BoxedStore;
 use crate::util::QueryStatsInfo;
 
 use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::{QueryTracker, kad_mut, routing_table_addrs};
 
 /// GetProviders 命令结果
 #[derive(Debug, Clone)]
 pub struct GetProvidersResult {
-    /// 找到的 Provider PeerId 列表
-    pub providers: Vec<PeerId>,
+    /// 找到的 Provider 及其已知地址（本地存储的 Provider 记录与 Kad 路由表中的地址）
+    ///
+    /// 地址为空时需先 `find_peer` 才能拨号。
+    pub provider_addrs: Vec<(PeerId, Vec<Multiaddr>)>,
     /// 查询统计信息
     pub stats: QueryStatsInfo,
 }
 
+impl GetProvidersResult {
+    /// 找到的 Provider PeerId 列表
+    pub fn providers(&self) -> Vec<PeerId> {
+        self.provider_addrs
+            .iter()
+            .map(|(peer_id, _)| *peer_id)
+            .collect()
+    }
+}
+
+/// Provider 的已知地址：本地存储的 Provider 记录，以及 Provider 在 Kad 路由表中的地址
+pub(super) fn provider_addrs(
+    kad: &mut kad::Behaviour<BoxedStore>,
+    key: &RecordKey,
+    provider: PeerId,
+) -> Vec<Multiaddr> {
+    let mut addrs: Vec<Multiaddr> = kad
+        .store_mut()
+        .providers(key)
+        .into_iter()
+        .filter(|record| record.provider == provider)
+        .flat_map(|record| record.addresses)
+        .collect();
+    addrs.extend(routing_table_addrs(kad, provider));
+    let mut unique = Vec::with_capacity(addrs.len());
+    for addr in addrs {
+        if !unique.contains(&addr) {
+            unique.push(addr);
+        }
+    }
+    unique
+}
+
 pub struct GetProvidersCommand {
     key: RecordKey,
     query_id: Option<kad::QueryId>,
-    providers: Vec<PeerId>,
+    providers: Vec<(PeerId, Vec<Multiaddr>)>,
     stats: Option<kad::QueryStats>,
+    /// 找到至少这么多 Provider 后提前返回
+    min_count: Option<usize>,
+    /// 超时后返回已找到的 Provider
+    timeout: Option<Duration>,
+    /// 逐个推送新找到的 Provider
+    stream: Option<UnboundedSender<PeerId>>,
+    tracker: QueryTracker,
 }
 
 impl GetProvidersCommand {
@@ -33,6 +81,79 @@ impl GetProvidersCommand {
             query_id: None,
             providers: Vec::new(),
             stats: None,
+            min_count: None,
+            timeout: None,
+            stream: None,
+            tracker: QueryTracker::default(),
+        }
+    }
+
+    /// 上报 `QueryId` 并支持通过 `cancel_query` 取消
+    pub fn with_tracker(mut self, tracker: QueryTracker) -> Self {
+        self.tracker = tracker;
+        self
+    }
+
+    /// 每找到一个新的 Provider 立即发送到 `stream`，命令结束时 `stream` 随之关闭
+    ///
+    /// 接收端被丢弃后提前结束查询。
+    pub fn with_stream(mut self, stream: UnboundedSender<PeerId>) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// 找到 `min_count` 个 Provider 即提前返回并结束剩余查询，
+    /// 超过 `timeout` 或 Kad 查询超时则返回已找到的部分结果
+    pub fn with_min_count(key: RecordKey, min_count: usize, timeout: Duration) -> Self {
+        Self {
+            min_count: Some(min_count),
+            timeout: Some(timeout),
+            ..Self::new(key)
+        }
+    }
+
+    /// 结束 Kad 查询并返回已收集的 Provider
+    fn finish_early<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<GetProvidersResult>,
+    ) {
+        self.tracker.abort(swarm);
+
+        let stats_info = self
+            .stats
+            .as_ref()
+            .map(QueryStatsInfo::from)
+            .unwrap_or_default();
+        handle.finish(Ok(GetProvidersResult {
+            provider_addrs: std::mem::take(&mut self.providers),
+            stats: stats_info,
+        }));
+    }
+
+    /// 记录新找到的 Provider（已记录的只合并地址），新出现的立即推送
+    fn collect<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        providers: impl IntoIterator<Item = PeerId>,
+    ) {
+        let Some(kad) = swarm.behaviour_mut().kad.as_mut() else {
+            return;
+        };
+        for provider in providers {
+            let addrs = provider_addrs(kad, &self.key, provider);
+            if let Some((_, known)) = self.providers.iter_mut().find(|(p, _)| *p == provider) {
+                for addr in addrs {
+                    if !known.contains(&addr) {
+                        known.push(addr);
+                    }
+                }
+                continue;
+            }
+            if let Some(tx) = &self.stream {
+                let _ = tx.unbounded_send(provider);
+            }
+            self.providers.push((provider, addrs));
         }
     }
 }
@@ -41,13 +162,21 @@ impl GetProvidersCommand {
 impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetProvidersCommand {
     type Result = GetProvidersResult;
 
-    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, _handle: &ResultHandle<Self::Result>) {
-        let query_id = swarm.behaviour_mut().kad.get_providers(self.key.clone());
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let query_id = match kad_mut(swarm) {
+            Ok(kad) => kad.get_providers(self.key.clone()),
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
         self.query_id = Some(query_id);
+        self.tracker.started(swarm, query_id, handle);
     }
 
     async fn on_event(
         &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
@@ -63,11 +192,17 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetProvi
                 // 累积统计
                 super::merge_stats(&mut self.stats, stats);
 
+                if step.last && self.tracker.finished() {
+                    info!("GetProviders cancelled");
+                    handle.finish(Err(Error::Cancelled));
+                    return (false, None); // 消费，完成
+                }
+
                 // 处理结果
                 match res {
                     Ok(kad::GetProvidersOk::FoundProviders { providers, .. }) => {
-                        // 收集 providers
-                        self.providers.extend(providers);
+                        // 收集 providers 及其地址，新出现的立即推送
+                        self.collect(swarm, providers);
                         info!(
                             "GetProviders progress: found {} providers so far",
                             self.providers.len()
@@ -82,11 +217,38 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetProvi
                     }
                     Err(e) => {
                         error!("GetProviders error: {:?}", e);
-                        handle.finish(Err(Error::Kad(format!("GetProviders: {:?}", e))));
+                        // GetProvidersError 只有 Timeout 一种。
+                        // min_count 模式下 Kad 查询先于 `timeout` 超时时，与 `on_timeout` 一样返回已找到的部分结果
+                        if self.min_count.is_some() && !self.providers.is_empty() {
+                            self.finish_early(swarm, handle);
+                        } else {
+                            handle.finish(Err(Error::KadTimeout));
+                        }
                         return (false, None); // 消费，完成
                     }
                 }
 
+                // 流的接收端已丢弃，不再需要后续结果
+                if !step.last && self.stream.as_ref().is_some_and(|tx| tx.is_closed()) {
+                    info!("GetProviders stream dropped, finishing query");
+                    self.finish_early(swarm, handle);
+                    return (false, None); // 消费，完成
+                }
+
+                // 已达到最少数量，提前结束查询
+                if !step.last
+                    && self
+                        .min_count
+                        .is_some_and(|min| self.providers.len() >= min)
+                {
+                    info!(
+                        "GetProviders reached min count: {} providers",
+                        self.providers.len()
+                    );
+                    self.finish_early(swarm, handle);
+                    return (false, None); // 消费，完成
+                }
+
                 // 非最后一步，继续等待
                 if !step.last {
                     return (true, None); // 消费，继续等待
@@ -101,7 +263,7 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetProvi
                 );
 
                 handle.finish(Ok(GetProvidersResult {
-                    providers: std::mem::take(&mut self.providers),
+                    provider_addrs: std::mem::take(&mut self.providers),
                     stats: stats_info,
                 }));
 
@@ -110,4 +272,20 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetProvi
             other => (true, Some(other)), // 继续等待
         }
     }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    async fn on_timeout(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        info!(
+            "GetProviders timed out with {} providers",
+            self.providers.len()
+        );
+        self.finish_early(swarm, handle);
+    }
 }
@@ -1,13 +1,15 @@
 use async_trait::async_trait;
-use libp2p::kad::{self, Record, RecordKey};
+use libp2p::PeerId;
+use libp2p::kad::{self, PeerRecord, Record, RecordKey};
 use libp2p::swarm::SwarmEvent;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::error::Error;
-use crate::runtime::{CborMessage, CoreBehaviourEvent};
+use crate::runtime::{CborMessage, CoreBehaviourEvent, KAD_REPLICATION_FACTOR};
 use crate::util::QueryStatsInfo;
 
 use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::{QueryTracker, kad_mut};
 
 /// GetRecord 命令结果
 #[derive(Debug, Clone)]
@@ -18,35 +20,124 @@ pub struct GetRecordResult {
     pub stats: QueryStatsInfo,
 }
 
+/// `get_record_with_opts` 的查询选项
+#[derive(Debug, Clone, Copy)]
+pub struct GetRecordOpts {
+    /// 需要返回相同取值的节点数（本地存储也算一个），以 Kad 复制因子为总数计算
+    pub quorum: kad::Quorum,
+    /// 是否把结果回写到查询路径上离 key 最近、但没有该记录的节点（Kad 读时缓存）
+    pub cache: bool,
+}
+
+impl Default for GetRecordOpts {
+    fn default() -> Self {
+        Self {
+            quorum: kad::Quorum::One,
+            cache: true,
+        }
+    }
+}
+
+/// GetRecord 命令 - 找到 `quorum` 个取值相同的副本后结束查询
+///
+/// 取值不一致时按 `value` 分组，支持者最多的一组胜出（数量相同取先找到的）；
+/// 查询结束时胜出的一组仍不足 `quorum`，以 `Error::KadRecordQuorumFailed` 结束。
 pub struct GetRecordCommand {
     key: RecordKey,
+    opts: GetRecordOpts,
     query_id: Option<kad::QueryId>,
-    record: Option<Record>,
+    records: Vec<PeerRecord>,
+    cache_candidates: Vec<PeerId>,
     stats: Option<kad::QueryStats>,
+    tracker: QueryTracker,
 }
 
 impl GetRecordCommand {
+    /// 找到第一条记录即返回，不回写缓存
     pub fn new(key: RecordKey) -> Self {
         Self {
             key,
+            opts: GetRecordOpts {
+                quorum: kad::Quorum::One,
+                cache: false,
+            },
             query_id: None,
-            record: None,
+            records: Vec::new(),
+            cache_candidates: Vec::new(),
             stats: None,
+            tracker: QueryTracker::default(),
         }
     }
+
+    /// 按指定的 quorum 与缓存选项查询
+    pub fn with_opts(mut self, opts: GetRecordOpts) -> Self {
+        self.opts = opts;
+        self
+    }
+
+    /// 上报 `QueryId` 并支持通过 `cancel_query` 取消
+    pub fn with_tracker(mut self, tracker: QueryTracker) -> Self {
+        self.tracker = tracker;
+        self
+    }
+
+    fn quorum_reached(&self) -> bool {
+        reconcile(&self.records).is_some_and(|(_, agreed)| agreed >= quorum_size(self.opts.quorum))
+    }
+}
+
+/// quorum 对应的节点数，与 libp2p 存储记录时一样以复制因子为总数
+fn quorum_size(quorum: kad::Quorum) -> usize {
+    let total = KAD_REPLICATION_FACTOR.get();
+    match quorum {
+        kad::Quorum::One => 1,
+        kad::Quorum::Majority => total / 2 + 1,
+        kad::Quorum::All => total,
+        kad::Quorum::N(n) => n.get().min(total),
+    }
+}
+
+/// 按取值分组，返回支持者最多的记录及支持数（数量相同时取先找到的）
+fn reconcile(records: &[PeerRecord]) -> Option<(&Record, usize)> {
+    let mut best: Option<(&Record, usize)> = None;
+    for (i, candidate) in records.iter().enumerate() {
+        // 只在每组第一次出现时计数
+        if records[..i]
+            .iter()
+            .any(|r| r.record.value == candidate.record.value)
+        {
+            continue;
+        }
+        let agreed = records[i..]
+            .iter()
+            .filter(|r| r.record.value == candidate.record.value)
+            .count();
+        if best.is_none_or(|(_, n)| agreed > n) {
+            best = Some((&candidate.record, agreed));
+        }
+    }
+    best
 }
 
 #[async_trait]
 impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetRecordCommand {
     type Result = GetRecordResult;
 
-    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, _handle: &ResultHandle<Self::Result>) {
-        let query_id = swarm.behaviour_mut().kad.get_record(self.key.clone());
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let query_id = match kad_mut(swarm) {
+            Ok(kad) => kad.get_record(self.key.clone()),
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
         self.query_id = Some(query_id);
+        self.tracker.started(swarm, query_id, handle);
     }
 
     async fn on_event(
         &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
@@ -62,22 +153,38 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetRecor
                 // 累积统计
                 super::merge_stats(&mut self.stats, stats);
 
+                if step.last && self.tracker.finished() {
+                    info!("GetRecord cancelled");
+                    handle.finish(Err(Error::Cancelled));
+                    return (false, None); // 消费，完成
+                }
+
                 // 处理结果
                 match res {
-                    Ok(ok) => {
-                        // 保存找到的记录（取第一个）
-                        if self.record.is_none()
-                            && let kad::GetRecordOk::FoundRecord(peer_record) = ok {
-                                self.record = Some(peer_record.record);
-                                info!("GetRecord: found record");
-                            }
+                    Ok(kad::GetRecordOk::FoundRecord(peer_record)) => {
+                        info!("GetRecord: found record from {:?}", peer_record.peer);
+                        self.records.push(peer_record);
+                        // 达到 quorum 后提前结束查询，剩余结果在最后一步汇总
+                        if !step.last
+                            && self.quorum_reached()
+                            && let Some(mut query) = swarm
+                                .behaviour_mut()
+                                .kad
+                                .as_mut()
+                                .and_then(|kad| kad.query_mut(&id))
+                        {
+                            query.finish();
+                        }
+                    }
+                    Ok(kad::GetRecordOk::FinishedWithNoAdditionalRecord { cache_candidates }) => {
+                        self.cache_candidates = cache_candidates.into_values().collect();
                     }
                     Err(e) => {
                         // 如果已经找到记录，忽略后续错误
-                        if self.record.is_none() {
+                        if self.records.is_empty() {
                             error!("GetRecord error: {:?}", e);
                             if step.last {
-                                handle.finish(Err(Error::Kad(format!("GetRecord: {:?}", e))));
+                                handle.finish(Err(super::get_record_error(&e, &self.stats)));
                                 return (false, None); // 消费，完成
                             }
                         }
@@ -91,19 +198,36 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetRecor
 
                 // 查询完成
                 let stats_info = QueryStatsInfo::from(self.stats.as_ref().unwrap());
+                let quorum = quorum_size(self.opts.quorum);
 
-                match self.record.take() {
-                    Some(record) => {
+                match reconcile(&self.records) {
+                    Some((record, agreed)) if agreed >= quorum => {
                         info!("GetRecord completed: {:?}", stats_info);
+                        let record = record.clone();
+                        if self.opts.cache
+                            && !self.cache_candidates.is_empty()
+                            && let Ok(kad) = kad_mut(swarm)
+                        {
+                            kad.put_record_to(
+                                record.clone(),
+                                self.cache_candidates.drain(..),
+                                kad::Quorum::One,
+                            );
+                        }
                         handle.finish(Ok(GetRecordResult {
                             record,
                             stats: stats_info,
                         }));
                     }
+                    Some((_, agreed)) => {
+                        warn!(
+                            "GetRecord: {} of {} required peers agree on the value",
+                            agreed, quorum
+                        );
+                        handle.finish(Err(Error::KadRecordQuorumFailed { agreed, quorum }));
+                    }
                     None => {
-                        handle.finish(Err(Error::Kad(
-                            "Record not found".to_string(),
-                        )));
+                        handle.finish(Err(super::not_found(&self.stats)));
                     }
                 }
 
@@ -112,4 +236,55 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetRecor
             other => (true, Some(other)), // 继续等待
         }
     }
+
+    async fn on_timeout(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        info!("GetRecord timed out");
+        self.tracker.abort(swarm);
+        handle.finish(Err(Error::CommandTimeout));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+
+    fn found(value: &str) -> PeerRecord {
+        PeerRecord {
+            peer: Some(PeerId::random()),
+            record: Record::new(RecordKey::new(&"key"), value.as_bytes().to_vec()),
+        }
+    }
+
+    #[test]
+    fn quorum_size_is_relative_to_replication_factor() {
+        assert_eq!(quorum_size(kad::Quorum::One), 1);
+        assert_eq!(quorum_size(kad::Quorum::Majority), 2);
+        assert_eq!(quorum_size(kad::Quorum::All), 3);
+        assert_eq!(
+            quorum_size(kad::Quorum::N(NonZeroUsize::new(10).unwrap())),
+            3
+        );
+    }
+
+    #[test]
+    fn reconcile_picks_most_supported_value() {
+        assert!(reconcile(&[]).is_none());
+
+        let records = [found("old"), found("new"), found("new")];
+        let (record, agreed) = reconcile(&records).unwrap();
+        assert_eq!(record.value, b"new");
+        assert_eq!(agreed, 2);
+
+        // 数量相同时取先找到的
+        let records = [found("a"), found("b")];
+        let (record, agreed) = reconcile(&records).unwrap();
+        assert_eq!(record.value, b"a");
+        assert_eq!(agreed, 1);
+    }
 }
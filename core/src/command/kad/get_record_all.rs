@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use libp2p::kad::{self, PeerRecord, RecordKey};
+use libp2p::swarm::SwarmEvent;
+use tracing::{error, info};
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent};
+use crate::util::QueryStatsInfo;
+
+use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::{QueryTracker, kad_mut};
+
+/// GetRecordAll 命令结果
+#[derive(Debug, Clone)]
+pub struct GetRecordAllResult {
+    /// 找到的全部记录及其来源（`peer` 为 `None` 表示来自本地存储）
+    pub records: Vec<PeerRecord>,
+    /// 查询统计信息
+    pub stats: QueryStatsInfo,
+}
+
+/// GetRecordAll 命令 - 收集查询过程中找到的所有记录，直到查询结束
+///
+/// 用于检测 DHT 中同一 key 的不同取值（冲突检测）。
+pub struct GetRecordAllCommand {
+    key: RecordKey,
+    query_id: Option<kad::QueryId>,
+    records: Vec<PeerRecord>,
+    stats: Option<kad::QueryStats>,
+    tracker: QueryTracker,
+}
+
+impl GetRecordAllCommand {
+    pub fn new(key: RecordKey) -> Self {
+        Self {
+            key,
+            query_id: None,
+            records: Vec::new(),
+            stats: None,
+            tracker: QueryTracker::default(),
+        }
+    }
+
+    /// 上报 `QueryId` 并支持通过 `cancel_query` 取消
+    pub fn with_tracker(mut self, tracker: QueryTracker) -> Self {
+        self.tracker = tracker;
+        self
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for GetRecordAllCommand {
+    type Result = GetRecordAllResult;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let query_id = match kad_mut(swarm) {
+            Ok(kad) => kad.get_record(self.key.clone()),
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        self.query_id = Some(query_id);
+        self.tracker.started(swarm, query_id, handle);
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
+                kad::Event::OutboundQueryProgressed {
+                    id,
+                    result: kad::QueryResult::GetRecord(res),
+                    stats,
+                    step,
+                },
+            )) if self.query_id == Some(id) => {
+                super::merge_stats(&mut self.stats, stats);
+
+                if step.last && self.tracker.finished() {
+                    info!("GetRecordAll cancelled");
+                    handle.finish(Err(Error::Cancelled));
+                    return (false, None); // 消费，完成
+                }
+
+                match res {
+                    Ok(kad::GetRecordOk::FoundRecord(peer_record)) => {
+                        info!("GetRecordAll: found record from {:?}", peer_record.peer);
+                        self.records.push(peer_record);
+                    }
+                    Ok(kad::GetRecordOk::FinishedWithNoAdditionalRecord { .. }) => {}
+                    Err(e) => {
+                        // 已找到记录时，结束阶段的错误不影响结果
+                        if self.records.is_empty() {
+                            error!("GetRecordAll error: {:?}", e);
+                            if step.last {
+                                handle.finish(Err(super::get_record_error(&e, &self.stats)));
+                                return (false, None);
+                            }
+                        }
+                    }
+                }
+
+                if !step.last {
+                    return (true, None); // 消费，继续收集
+                }
+
+                let stats = QueryStatsInfo::from(self.stats.as_ref().unwrap());
+                if self.records.is_empty() {
+                    handle.finish(Err(super::not_found(&self.stats)));
+                } else {
+                    info!(
+                        "GetRecordAll completed with {} records: {:?}",
+                        self.records.len(),
+                        stats
+                    );
+                    handle.finish(Ok(GetRecordAllResult {
+                        records: std::mem::take(&mut self.records),
+                        stats,
+                    }));
+                }
+                (false, None)
+            }
+            other => (true, Some(other)),
+        }
+    }
+
+    async fn on_timeout(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        info!("GetRecordAll timed out");
+        self.tracker.abort(swarm);
+        handle.finish(Err(Error::CommandTimeout));
+    }
+}
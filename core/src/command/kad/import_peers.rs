@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use libp2p::{Multiaddr, PeerId};
+
+use crate::runtime::CborMessage;
+
+use super::super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// 将 peer 地址写入 Kad 路由表和 Swarm 地址簿
+///
+/// 只登记地址，不主动拨号；路由表满时 Kad 会按自身策略丢弃。未启用 Kad 时只写入地址簿。
+pub(crate) fn import_peers<Req: CborMessage, Resp: CborMessage>(
+    swarm: &mut CoreSwarm<Req, Resp>,
+    peers: &[(PeerId, Vec<Multiaddr>)],
+) {
+    for (peer_id, addrs) in peers {
+        for addr in addrs {
+            if let Some(kad) = swarm.behaviour_mut().kad.as_mut() {
+                kad.add_address(peer_id, addr.clone());
+            }
+            swarm.add_peer_address(*peer_id, addr.clone());
+        }
+    }
+}
+
+/// ImportPeers 命令 - 导入路由表快照（如 `export_routing_table` 的结果）
+pub struct ImportPeersCommand {
+    peers: Vec<(PeerId, Vec<Multiaddr>)>,
+}
+
+impl ImportPeersCommand {
+    pub fn new(peers: Vec<(PeerId, Vec<Multiaddr>)>) -> Self {
+        Self { peers }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for ImportPeersCommand {
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        import_peers(swarm, &self.peers);
+        handle.finish(Ok(()));
+    }
+}
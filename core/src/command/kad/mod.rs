@@ -1,22 +1,74 @@
 mod bootstrap;
+mod cancel_query;
+mod find_and_connect_provider;
+mod find_peer;
 mod get_closest_peers;
 mod get_providers;
 mod get_record;
+mod get_record_all;
+mod import_peers;
+mod publish_record;
 mod put_record;
+mod put_record_local;
 mod remove_record;
+mod republish_provider;
+mod routing_table;
 mod start_provide;
 mod stop_provide;
 
 pub use bootstrap::*;
+pub use cancel_query::*;
+pub use find_and_connect_provider::*;
+pub use find_peer::*;
 pub use get_closest_peers::*;
 pub use get_providers::*;
 pub use get_record::*;
+pub use get_record_all::*;
+pub use import_peers::ImportPeersCommand;
+pub(crate) use import_peers::import_peers;
+pub use publish_record::*;
 pub use put_record::*;
+pub use put_record_local::*;
 pub use remove_record::*;
+pub use republish_provider::*;
+pub use routing_table::*;
 pub use start_provide::*;
 pub use stop_provide::*;
 
-use libp2p::kad;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use libp2p::{Multiaddr, PeerId, kad};
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+
+use super::{CoreSwarm, ResultHandle};
+use crate::error::Error;
+use crate::runtime::CborMessage;
+use crate::store::BoxedStore;
+
+/// 取出已启用的 Kad 行为，`enable_kad` 关闭时返回错误
+fn kad_mut<Req: CborMessage, Resp: CborMessage>(
+    swarm: &mut CoreSwarm<Req, Resp>,
+) -> Result<&mut kad::Behaviour<BoxedStore>, Error> {
+    swarm
+        .behaviour_mut()
+        .kad
+        .as_mut()
+        .ok_or_else(|| Error::Config("kad disabled".into()))
+}
+
+/// peer 在 Kad 路由表中的地址，不在路由表中时为空
+fn routing_table_addrs(kad: &mut kad::Behaviour<BoxedStore>, peer_id: PeerId) -> Vec<Multiaddr> {
+    kad.kbucket(peer_id)
+        .and_then(|bucket| {
+            bucket
+                .iter()
+                .find(|entry| *entry.node.key.preimage() == peer_id)
+                .map(|entry| entry.node.value.iter().cloned().collect())
+        })
+        .unwrap_or_default()
+}
 
 /// 累积 Kad 查询统计（多步查询中每步都会产生新的 stats）
 fn merge_stats(existing: &mut Option<kad::QueryStats>, incoming: kad::QueryStats) {
@@ -25,3 +77,118 @@ fn merge_stats(existing: &mut Option<kad::QueryStats>, incoming: kad::QueryStats
         None => incoming,
     });
 }
+
+/// 查询没有向任何节点发出请求，说明路由表为空
+fn no_peers_queried(stats: &Option<kad::QueryStats>) -> bool {
+    stats.as_ref().is_some_and(|s| s.num_requests() == 0)
+}
+
+/// 查询结束仍未找到结果：路由表为空时为 `KadNoPeers`，否则为 `KadNotFound`
+fn not_found(stats: &Option<kad::QueryStats>) -> Error {
+    if no_peers_queried(stats) {
+        Error::KadNoPeers
+    } else {
+        Error::KadNotFound
+    }
+}
+
+/// GetRecord 失败分类，超时与未找到分开以便调用方决定是否重试
+fn get_record_error(e: &kad::GetRecordError, stats: &Option<kad::QueryStats>) -> Error {
+    match e {
+        kad::GetRecordError::Timeout { .. } => Error::KadTimeout,
+        kad::GetRecordError::NotFound { .. } => not_found(stats),
+        kad::GetRecordError::QuorumFailed { .. } => Error::Kad(format!("GetRecord: {:?}", e)),
+    }
+}
+
+/// 由 `QueryTracker` 跟踪的进行中查询，以及其中被 `cancel_query` 提前结束的查询
+///
+/// 查询发出时登记，发起查询的命令结束（含超时）时注销；
+/// `CancelQueryCommand` 只取消仍登记在册的查询，发起查询的命令以 `Error::Cancelled` 结束。
+#[derive(Clone, Default)]
+pub struct TrackedQueries(Arc<Mutex<TrackedState>>);
+
+#[derive(Default)]
+struct TrackedState {
+    live: HashSet<kad::QueryId>,
+    cancelled: HashSet<kad::QueryId>,
+}
+
+impl TrackedQueries {
+    fn register(&self, id: kad::QueryId) {
+        self.0.lock().live.insert(id);
+    }
+
+    /// 标记为已取消，查询未登记（不属于任何命令或已结束）时返回 `false`
+    fn cancel(&self, id: kad::QueryId) -> bool {
+        let mut state = self.0.lock();
+        if !state.live.contains(&id) {
+            return false;
+        }
+        state.cancelled.insert(id);
+        true
+    }
+
+    /// 注销查询，返回其是否已被取消
+    fn remove(&self, id: &kad::QueryId) -> bool {
+        let mut state = self.0.lock();
+        state.live.remove(id);
+        state.cancelled.remove(id)
+    }
+}
+
+/// 可取消查询的跟踪信息：查询发出后上报 `QueryId` 并登记，命令结束时注销
+#[derive(Default)]
+pub struct QueryTracker {
+    started: Option<oneshot::Sender<kad::QueryId>>,
+    queries: TrackedQueries,
+    id: Option<kad::QueryId>,
+}
+
+impl QueryTracker {
+    pub fn new(started: oneshot::Sender<kad::QueryId>, queries: TrackedQueries) -> Self {
+        Self {
+            started: Some(started),
+            queries,
+            id: None,
+        }
+    }
+
+    /// 登记已发出的查询并上报 `QueryId`；对应的 `KadQuery` 已被丢弃时立即结束查询
+    fn started<Req: CborMessage, Resp: CborMessage, T>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        id: kad::QueryId,
+        handle: &ResultHandle<T>,
+    ) {
+        self.queries.register(id);
+        self.id = Some(id);
+        if let Some(tx) = self.started.take()
+            && tx.send(id).is_err()
+        {
+            self.abort(swarm);
+            handle.finish(Err(Error::Cancelled));
+        }
+    }
+
+    /// 查询已结束：注销并返回是否被取消（只在最后一步调用）
+    fn finished(&mut self) -> bool {
+        self.id.take().is_some_and(|id| self.queries.remove(&id))
+    }
+
+    /// 命令提前结束（达到目标数量、超时等）：结束仍在进行的 Kad 查询并注销
+    fn abort<Req: CborMessage, Resp: CborMessage>(&mut self, swarm: &mut CoreSwarm<Req, Resp>) {
+        let Some(id) = self.id.take() else {
+            return;
+        };
+        if let Some(mut query) = swarm
+            .behaviour_mut()
+            .kad
+            .as_mut()
+            .and_then(|kad| kad.query_mut(&id))
+        {
+            query.finish();
+        }
+        self.queries.remove(&id);
+    }
+}
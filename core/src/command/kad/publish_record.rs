@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use libp2p::kad::store::RecordStore;
+use libp2p::kad::{self, Record};
+use libp2p::swarm::SwarmEvent;
+use tracing::{error, info, warn};
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent};
+use crate::store::BoxedStore;
+use crate::util::QueryStatsInfo;
+
+use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::kad_mut;
+
+/// PublishRecord 命令结果
+#[derive(Debug, Clone)]
+pub struct PublishRecordResult {
+    /// 存储记录的查询统计
+    pub put: QueryStatsInfo,
+    /// 宣告 Provider 的查询统计
+    pub provide: QueryStatsInfo,
+}
+
+/// PublishRecord 命令 - 存储记录并宣告本节点为该 key 的 Provider
+///
+/// 两个查询同时发起，都成功才返回；存储失败时结束进行中的宣告并停止提供该 key，
+/// 已发出的 Provider 记录随远端过期失效。调用前已经通过 `start_provide` 提供的 key
+/// 不会被停止提供。
+pub struct PublishRecordCommand {
+    record: Record,
+    /// 发起前本节点是否已在提供该 key，是则失败时保留原有宣告
+    already_providing: bool,
+    put: Phase,
+    provide: Phase,
+}
+
+/// 单个查询的进度
+#[derive(Default)]
+struct Phase {
+    query_id: Option<kad::QueryId>,
+    stats: Option<kad::QueryStats>,
+    done: Option<QueryStatsInfo>,
+}
+
+impl Phase {
+    fn is(&self, id: kad::QueryId) -> bool {
+        self.query_id == Some(id)
+    }
+}
+
+impl PublishRecordCommand {
+    pub fn new(record: Record) -> Self {
+        Self {
+            record,
+            already_providing: false,
+            put: Phase::default(),
+            provide: Phase::default(),
+        }
+    }
+
+    /// 撤销宣告：结束进行中的查询，并从本地移除本命令新增的 Provider 记录
+    fn rollback<Req: CborMessage, Resp: CborMessage>(&self, swarm: &mut CoreSwarm<Req, Resp>) {
+        let Some(kad) = swarm.behaviour_mut().kad.as_mut() else {
+            return;
+        };
+        if self.provide.done.is_none()
+            && let Some(mut query) = self.provide.query_id.and_then(|id| kad.query_mut(&id))
+        {
+            query.finish();
+        }
+        self.stop_providing(kad);
+        warn!(
+            "PublishRecord rolled back provide for {:?}",
+            self.record.key
+        );
+    }
+
+    /// 停止提供该 key，调用前已在提供时保留
+    fn stop_providing(&self, kad: &mut kad::Behaviour<BoxedStore>) {
+        if !self.already_providing {
+            kad.stop_providing(&self.record.key);
+        }
+    }
+
+    /// 两个查询都成功时返回结果
+    fn try_finish(&mut self, handle: &ResultHandle<PublishRecordResult>) -> bool {
+        let (Some(put), Some(provide)) = (&self.put.done, &self.provide.done) else {
+            return false;
+        };
+        info!(
+            "PublishRecord success: put {:?}, provide {:?}",
+            put, provide
+        );
+        handle.finish(Ok(PublishRecordResult {
+            put: put.clone(),
+            provide: provide.clone(),
+        }));
+        true
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for PublishRecordCommand {
+    type Result = PublishRecordResult;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let kad = match kad_mut(swarm) {
+            Ok(kad) => kad,
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        self.already_providing = kad
+            .store_mut()
+            .provided()
+            .any(|provider| provider.key == self.record.key);
+        match kad.start_providing(self.record.key.clone()) {
+            Ok(query_id) => self.provide.query_id = Some(query_id),
+            Err(e) => {
+                handle.finish(Err(Error::KadStore(e)));
+                return;
+            }
+        }
+        match kad.put_record(self.record.clone(), kad::Quorum::One) {
+            Ok(query_id) => self.put.query_id = Some(query_id),
+            Err(e) => {
+                self.rollback(swarm);
+                handle.finish(Err(Error::KadStore(e)));
+            }
+        }
+    }
+
+    async fn on_event(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
+                kad::Event::OutboundQueryProgressed {
+                    id,
+                    result: kad::QueryResult::PutRecord(res),
+                    stats,
+                    step,
+                },
+            )) if self.put.is(id) => {
+                super::merge_stats(&mut self.put.stats, stats);
+                if !step.last {
+                    return (true, None);
+                }
+                match res {
+                    Ok(_) => {
+                        self.put.done =
+                            Some(QueryStatsInfo::from(self.put.stats.as_ref().unwrap()));
+                        (!self.try_finish(handle), None)
+                    }
+                    Err(e) => {
+                        error!("PublishRecord put error: {:?}", e);
+                        self.rollback(swarm);
+                        let err = match e {
+                            kad::PutRecordError::QuorumFailed {
+                                success, quorum, ..
+                            } => Error::KadQuorumFailed {
+                                stored: success.len(),
+                                quorum: quorum.get(),
+                            },
+                            e => Error::Kad(format!("PutRecord: {:?}", e)),
+                        };
+                        handle.finish(Err(err));
+                        (false, None)
+                    }
+                }
+            }
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
+                kad::Event::OutboundQueryProgressed {
+                    id,
+                    result: kad::QueryResult::StartProviding(res),
+                    stats,
+                    step,
+                },
+            )) if self.provide.is(id) => {
+                super::merge_stats(&mut self.provide.stats, stats);
+                if !step.last {
+                    return (true, None);
+                }
+                match res {
+                    Ok(_) => {
+                        self.provide.done =
+                            Some(QueryStatsInfo::from(self.provide.stats.as_ref().unwrap()));
+                        (!self.try_finish(handle), None)
+                    }
+                    Err(e) => {
+                        // 记录可能已写入远端，无法撤回；停止提供，避免与失败结果不一致
+                        error!("PublishRecord provide error: {:?}", e);
+                        if let Some(kad) = swarm.behaviour_mut().kad.as_mut() {
+                            self.stop_providing(kad);
+                        }
+                        handle.finish(Err(Error::Kad(format!("Provide: {:?}", e))));
+                        (false, None)
+                    }
+                }
+            }
+            other => (true, Some(other)),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use futures::channel::mpsc::UnboundedSender;
 use libp2p::kad::{self, Record};
 use libp2p::swarm::SwarmEvent;
 use tracing::{error, info};
@@ -8,21 +9,104 @@ use crate::runtime::{CborMessage, CoreBehaviourEvent};
 use crate::util::QueryStatsInfo;
 
 use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::kad_mut;
+
+/// PutRecord 复制进度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PutProgress {
+    /// 已成功存储记录的远端节点数
+    pub stored: usize,
+    /// 达成 quorum 所需的节点数
+    pub quorum: usize,
+}
 
 pub struct PutRecordCommand {
     record: Record,
+    quorum: kad::Quorum,
     query_id: Option<kad::QueryId>,
     stats: Option<kad::QueryStats>,
+    /// 进度接收端，`None` 表示不上报进度
+    progress: Option<UnboundedSender<PutProgress>>,
+    last_progress: Option<PutProgress>,
 }
 
 impl PutRecordCommand {
     pub fn new(record: Record) -> Self {
         Self {
             record,
+            quorum: kad::Quorum::One,
             query_id: None,
             stats: None,
+            progress: None,
+            last_progress: None,
+        }
+    }
+
+    /// 按指定 quorum 存储
+    pub fn with_quorum(record: Record, quorum: kad::Quorum) -> Self {
+        Self {
+            quorum,
+            ..Self::new(record)
         }
     }
+
+    /// 按指定 quorum 存储，并把复制进度发送到 `progress`
+    pub fn with_progress(
+        record: Record,
+        quorum: kad::Quorum,
+        progress: UnboundedSender<PutProgress>,
+    ) -> Self {
+        Self {
+            progress: Some(progress),
+            ..Self::with_quorum(record, quorum)
+        }
+    }
+
+    /// 进度有变化时发送（接收端已丢弃则忽略）
+    fn report(&mut self, progress: PutProgress) {
+        if self.last_progress.as_ref() == Some(&progress) {
+            return;
+        }
+        if let Some(tx) = &self.progress {
+            let _ = tx.unbounded_send(progress.clone());
+        }
+        self.last_progress = Some(progress);
+    }
+
+    /// 从进行中的查询采样已复制的节点数
+    ///
+    /// Kad 不会为每个节点的存储确认产生事件，只能在事件循环
+    /// 处理其他 swarm 事件时读取查询状态。
+    fn sample_progress<Req: CborMessage, Resp: CborMessage>(
+        &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
+    ) {
+        if self.progress.is_none() {
+            return;
+        }
+        let Some(query) = self
+            .query_id
+            .and_then(|id| swarm.behaviour().kad.as_ref()?.query(&id))
+        else {
+            return;
+        };
+        let progress = match query.info() {
+            kad::QueryInfo::PutRecord {
+                phase: kad::PutRecordPhase::PutRecord { success, .. },
+                quorum,
+                ..
+            } => PutProgress {
+                stored: success.len(),
+                quorum: quorum.get(),
+            },
+            kad::QueryInfo::PutRecord { quorum, .. } => PutProgress {
+                stored: 0,
+                quorum: quorum.get(),
+            },
+            _ => return,
+        };
+        self.report(progress);
+    }
 }
 
 #[async_trait]
@@ -30,22 +114,27 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for PutRecor
     type Result = QueryStatsInfo;
 
     async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
-        match swarm
-            .behaviour_mut()
-            .kad
-            .put_record(self.record.clone(), kad::Quorum::One)
-        {
+        let kad = match kad_mut(swarm) {
+            Ok(kad) => kad,
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        match kad.put_record(self.record.clone(), self.quorum) {
             Ok(query_id) => {
                 self.query_id = Some(query_id);
+                self.sample_progress(swarm);
             }
             Err(e) => {
-                handle.finish(Err(Error::Kad(format!("PutRecord store: {}", e))));
+                handle.finish(Err(Error::KadStore(e)));
             }
         }
     }
 
     async fn on_event(
         &mut self,
+        swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
@@ -71,17 +160,42 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for PutRecor
                 match res {
                     Ok(_) => {
                         info!("PutRecord success: {:?}", stats_info);
+                        // 达成 quorum 的那次确认不一定被采样到，补发最终进度
+                        if let Some(last) = self.last_progress.clone() {
+                            self.report(PutProgress {
+                                stored: last.stored.max(last.quorum),
+                                quorum: last.quorum,
+                            });
+                        }
                         handle.finish(Ok(stats_info));
                     }
                     Err(e) => {
                         error!("PutRecord error: {:?}", e);
-                        handle.finish(Err(Error::Kad(format!("PutRecord: {:?}", e))));
+                        let err = match &e {
+                            kad::PutRecordError::QuorumFailed {
+                                success, quorum, ..
+                            } => {
+                                self.report(PutProgress {
+                                    stored: success.len(),
+                                    quorum: quorum.get(),
+                                });
+                                Error::KadQuorumFailed {
+                                    stored: success.len(),
+                                    quorum: quorum.get(),
+                                }
+                            }
+                            _ => Error::Kad(format!("PutRecord: {:?}", e)),
+                        };
+                        handle.finish(Err(err));
                     }
                 }
 
                 (false, None) // 消费，完成
             }
-            other => (true, Some(other)), // 继续等待
+            other => {
+                self.sample_progress(swarm);
+                (true, Some(other)) // 继续等待
+            }
         }
     }
 }
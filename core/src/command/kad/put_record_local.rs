@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use libp2p::kad::Record;
+use libp2p::kad::store::RecordStore;
+
+use crate::error::Error;
+use crate::runtime::CborMessage;
+
+use super::super::{CommandHandler, CoreSwarm, ResultHandle};
+use super::kad_mut;
+
+/// PutRecordLocal 命令 - 只写入本地存储，不发起 DHT 复制
+pub struct PutRecordLocalCommand {
+    record: Option<Record>,
+}
+
+impl PutRecordLocalCommand {
+    pub fn new(record: Record) -> Self {
+        Self {
+            record: Some(record),
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for PutRecordLocalCommand {
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let Some(record) = self.record.take() else {
+            handle.finish(Err(Error::Kad("PutRecordLocal: run called twice".into())));
+            return;
+        };
+        let result =
+            kad_mut(swarm).and_then(|kad| kad.store_mut().put(record).map_err(Error::from));
+        handle.finish(result);
+    }
+}
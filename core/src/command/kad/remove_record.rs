@@ -4,6 +4,7 @@ use libp2p::kad::RecordKey;
 use crate::runtime::CborMessage;
 
 use super::super::{CommandHandler, CoreSwarm, ResultHandle};
+use super::kad_mut;
 
 /// RemoveRecord 命令 - 从本地存储中删除记录
 pub struct RemoveRecordCommand {
@@ -21,7 +22,6 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for RemoveRe
     type Result = ();
 
     async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
-        swarm.behaviour_mut().kad.remove_record(&self.key);
-        handle.finish(Ok(()));
+        handle.finish(kad_mut(swarm).map(|kad| kad.remove_record(&self.key)));
     }
 }
@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use libp2p::kad::store::RecordStore;
+use libp2p::kad::{self, RecordKey};
+use libp2p::swarm::SwarmEvent;
+use tracing::{info, warn};
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent};
+use crate::util::QueryStatsInfo;
+
+use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::kad_mut;
+
+/// RepublishProvider 命令 - 立即重新宣告本节点已提供的 key
+///
+/// 对每个 key 重新执行 `start_providing`，让 provider 记录带上当前地址（如打洞升级后的直连地址），
+/// 不必等待 Kad 的周期性重新发布。所有查询结束后返回合并的统计；
+/// 任一查询失败时返回其错误。
+pub struct RepublishProviderCommand {
+    /// `None` 表示本地存储中的所有 key
+    key: Option<RecordKey>,
+    pending: HashSet<kad::QueryId>,
+    stats: Option<kad::QueryStats>,
+    error: Option<Error>,
+}
+
+impl RepublishProviderCommand {
+    /// 重新宣告单个 key，本节点未提供该 key 时返回 `Error::Kad`
+    pub fn new(key: RecordKey) -> Self {
+        Self::with_key(Some(key))
+    }
+
+    /// 重新宣告本地存储中本节点提供的所有 key
+    pub fn all() -> Self {
+        Self::with_key(None)
+    }
+
+    fn with_key(key: Option<RecordKey>) -> Self {
+        Self {
+            key,
+            pending: HashSet::new(),
+            stats: None,
+            error: None,
+        }
+    }
+
+    /// 所有查询结束：有失败时返回首个错误，否则返回合并的统计
+    fn finish(&mut self, handle: &ResultHandle<QueryStatsInfo>) {
+        match self.error.take() {
+            Some(e) => handle.finish(Err(e)),
+            None => handle.finish(Ok(self
+                .stats
+                .as_ref()
+                .map(QueryStatsInfo::from)
+                .unwrap_or_default())),
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for RepublishProviderCommand {
+    type Result = QueryStatsInfo;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let kad = match kad_mut(swarm) {
+            Ok(kad) => kad,
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        let provided: Vec<RecordKey> = kad
+            .store_mut()
+            .provided()
+            .map(|record| record.key.clone())
+            .filter(|key| self.key.as_ref().is_none_or(|k| k == key))
+            .collect();
+
+        if let Some(key) = &self.key
+            && provided.is_empty()
+        {
+            handle.finish(Err(Error::Kad(format!("Not providing key {:?}", key))));
+            return;
+        }
+
+        for key in provided {
+            match kad.start_providing(key) {
+                Ok(query_id) => {
+                    self.pending.insert(query_id);
+                }
+                Err(e) => {
+                    self.error.get_or_insert(Error::KadStore(e));
+                }
+            }
+        }
+        info!("Republishing {} provider record(s)", self.pending.len());
+        if self.pending.is_empty() {
+            self.finish(handle);
+        }
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
+                kad::Event::OutboundQueryProgressed {
+                    id,
+                    result: kad::QueryResult::StartProviding(res),
+                    stats,
+                    step,
+                },
+            )) if self.pending.contains(&id) => {
+                super::merge_stats(&mut self.stats, stats);
+                if !step.last {
+                    return (true, None);
+                }
+
+                self.pending.remove(&id);
+                if let Err(e) = res {
+                    warn!("Republish provider error: {:?}", e);
+                    self.error
+                        .get_or_insert(Error::Kad(format!("Provide: {:?}", e)));
+                }
+                if self.pending.is_empty() {
+                    self.finish(handle);
+                    return (false, None);
+                }
+                (true, None)
+            }
+            other => (true, Some(other)),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use libp2p::{Multiaddr, PeerId};
+
+use crate::runtime::CborMessage;
+
+use super::super::{CommandHandler, CoreSwarm, ResultHandle};
+use super::kad_mut;
+
+/// 单个 k-bucket 的快照
+#[derive(Debug, Clone)]
+pub struct KadBucketInfo {
+    /// bucket 序号，即与本节点 XOR 距离的 log2（0..256）
+    pub index: u32,
+    /// bucket 内的节点及其已知地址
+    pub peers: Vec<(PeerId, Vec<Multiaddr>)>,
+}
+
+/// RoutingTable 命令 - 读取本地 Kad 路由表，只返回非空 bucket，不发起网络查询
+pub struct RoutingTableCommand;
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for RoutingTableCommand {
+    type Result = Vec<KadBucketInfo>;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let kad = match kad_mut(swarm) {
+            Ok(kad) => kad,
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        let buckets = kad
+            .kbuckets()
+            .map(|bucket| KadBucketInfo {
+                // bucket i 覆盖距离区间 [2^i, 2^(i+1))，下界非零
+                index: bucket.range().0.ilog2().unwrap_or_default(),
+                peers: bucket
+                    .iter()
+                    .map(|entry| {
+                        (
+                            *entry.node.key.preimage(),
+                            entry.node.value.iter().cloned().collect(),
+                        )
+                    })
+                    .collect(),
+            })
+            .collect();
+        handle.finish(Ok(buckets));
+    }
+}
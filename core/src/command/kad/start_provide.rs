@@ -8,6 +8,7 @@ use crate::runtime::{CborMessage, CoreBehaviourEvent};
 use crate::util::QueryStatsInfo;
 
 use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::kad_mut;
 
 pub struct StartProvideCommand {
     key: RecordKey,
@@ -30,22 +31,26 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for StartPro
     type Result = QueryStatsInfo;
 
     async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
-        match swarm
-            .behaviour_mut()
-            .kad
-            .start_providing(self.key.clone())
-        {
+        let kad = match kad_mut(swarm) {
+            Ok(kad) => kad,
+            Err(e) => {
+                handle.finish(Err(e));
+                return;
+            }
+        };
+        match kad.start_providing(self.key.clone()) {
             Ok(query_id) => {
                 self.query_id = Some(query_id);
             }
             Err(e) => {
-                handle.finish(Err(Error::Kad(format!("StartProviding store: {}", e))));
+                handle.finish(Err(Error::KadStore(e)));
             }
         }
     }
 
     async fn on_event(
         &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
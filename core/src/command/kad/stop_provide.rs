@@ -4,6 +4,7 @@ use libp2p::kad::RecordKey;
 use crate::runtime::CborMessage;
 
 use super::super::{CommandHandler, CoreSwarm, ResultHandle};
+use super::kad_mut;
 
 pub struct StopProvideCommand {
     key: RecordKey,
@@ -20,7 +21,6 @@ impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for StopProv
     type Result = ();
 
     async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
-        swarm.behaviour_mut().kad.stop_providing(&self.key);
-        handle.finish(Ok(()));
+        handle.finish(kad_mut(swarm).map(|kad| kad.stop_providing(&self.key)));
     }
 }
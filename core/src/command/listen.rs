@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use libp2p::Multiaddr;
+use libp2p::core::transport::ListenerId;
+use libp2p::swarm::SwarmEvent;
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent};
+
+use super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+
+/// Listen 命令 - 运行时新增监听地址
+///
+/// 等待该监听器产生第一个 `NewListenAddr` 后返回其 `ListenerId`。
+pub struct ListenCommand {
+    addr: Multiaddr,
+    listener_id: Option<ListenerId>,
+}
+
+impl ListenCommand {
+    pub fn new(addr: Multiaddr) -> Self {
+        Self {
+            addr,
+            listener_id: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for ListenCommand {
+    type Result = ListenerId;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        match swarm.listen_on(self.addr.clone()) {
+            Ok(listener_id) => self.listener_id = Some(listener_id),
+            Err(e) => handle.finish(Err(Error::Listen(e.to_string()))),
+        }
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        let Some(id) = self.listener_id else {
+            return (false, Some(event));
+        };
+        match &event {
+            SwarmEvent::NewListenAddr { listener_id, .. } if *listener_id == id => {
+                handle.finish(Ok(id));
+                (false, Some(event)) // 不消费，前端需要 Listening
+            }
+            SwarmEvent::ListenerError { listener_id, error } if *listener_id == id => {
+                handle.finish(Err(Error::Listen(error.to_string())));
+                (false, Some(event)) // 不消费
+            }
+            SwarmEvent::ListenerClosed {
+                listener_id,
+                reason,
+                ..
+            } if *listener_id == id => {
+                let reason = match reason {
+                    Ok(()) => "listener closed".to_string(),
+                    Err(e) => e.to_string(),
+                };
+                handle.finish(Err(Error::Listen(reason)));
+                (false, Some(event)) // 不消费
+            }
+            _ => (true, Some(event)), // 继续等待
+        }
+    }
+}
@@ -1,17 +1,51 @@
 mod add_peer_addrs;
+mod batch;
+mod block_peer;
+mod connected_peers;
+mod connection_info;
 mod dial;
+mod dial_addr;
+mod dial_many;
 mod disconnect;
+mod external_address;
 mod get_listen_addrs;
+mod gossipsub;
 mod handler;
+mod handshake;
+mod identify;
 mod is_connected;
 mod kad;
+mod listen;
+mod nat_status;
+mod relay;
+mod remove_listener;
 mod req_resp;
+mod shutdown;
+mod status;
+mod upgrade_connection;
 
 pub use add_peer_addrs::*;
+pub use batch::*;
+pub use block_peer::*;
+pub use connected_peers::*;
+pub use connection_info::*;
 pub use dial::*;
+pub use dial_addr::*;
+pub use dial_many::*;
 pub use disconnect::*;
+pub use external_address::*;
 pub use get_listen_addrs::*;
+pub use gossipsub::*;
 pub use handler::*;
+pub use handshake::*;
+pub use identify::*;
 pub use is_connected::*;
 pub use kad::*;
+pub use listen::*;
+pub use nat_status::*;
+pub use relay::*;
+pub use remove_listener::*;
 pub use req_resp::*;
+pub use shutdown::*;
+pub use status::*;
+pub use upgrade_connection::*;
@@ -0,0 +1,32 @@
+use async_trait::async_trait;
+
+use crate::event::NatStatus;
+use crate::runtime::{CborMessage, NatTracker};
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// NatStatus 命令 - 读取当前 NAT 状态
+///
+/// 在事件循环中执行，结果反映执行前已处理的所有 AutoNAT 探测。
+pub struct NatStatusCommand {
+    tracker: NatTracker,
+}
+
+impl NatStatusCommand {
+    pub fn new(tracker: NatTracker) -> Self {
+        Self { tracker }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for NatStatusCommand {
+    type Result = NatStatus;
+
+    async fn run(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        handle.finish(Ok(self.tracker.status()));
+    }
+}
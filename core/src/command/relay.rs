@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use libp2p::core::transport::ListenerId;
+use libp2p::multiaddr::Protocol;
+use libp2p::swarm::SwarmEvent;
+use libp2p::{Multiaddr, PeerId};
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent, RelayListeners, RelayReservationInfo};
+
+use super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+
+/// 中继节点地址对应的电路监听地址（`<relay_addr>/p2p/<relay>/p2p-circuit`）
+///
+/// `relay_addr` 已包含 `/p2p/<relay>` 时不再重复追加。
+pub(crate) fn relay_circuit_addr(relay_peer_id: PeerId, relay_addr: &Multiaddr) -> Multiaddr {
+    let base = if relay_addr.iter().any(|p| matches!(p, Protocol::P2p(_))) {
+        relay_addr.clone()
+    } else {
+        relay_addr.clone().with(Protocol::P2p(relay_peer_id))
+    };
+    base.with(Protocol::P2pCircuit)
+}
+
+/// 经中继拨号 `target` 的电路地址（`<relay_addr>/p2p-circuit/p2p/<target>`）
+///
+/// `relay_addr` 须以 `/p2p/<relay>` 结尾，也接受已带 `/p2p-circuit` 或
+/// `/p2p-circuit/p2p/<target>` 后缀的地址；无法确定中继节点时返回 `None`。
+pub(crate) fn relay_dial_addr(relay_addr: &Multiaddr, target: PeerId) -> Option<Multiaddr> {
+    let mut base = relay_addr.clone();
+    if base.iter().last() == Some(Protocol::P2p(target)) {
+        base.pop();
+    }
+    if base.iter().last() == Some(Protocol::P2pCircuit) {
+        base.pop();
+    }
+    match base.iter().last() {
+        Some(Protocol::P2p(relay)) if relay != target => {
+            Some(base.with(Protocol::P2pCircuit).with(Protocol::P2p(target)))
+        }
+        _ => None,
+    }
+}
+
+/// ReserveRelay 命令 - 经指定中继申请预约
+///
+/// 在电路地址上监听，等到中继接受预约（`ReservationReqAccepted`）后返回；
+/// 监听器在此之前关闭（中继不可达或拒绝预约）则返回 `Error::Relay`。
+/// 预约成功后由 relay client 自动续约，直到 `cancel_relay`。
+pub struct ReserveRelayCommand {
+    relay_peer_id: PeerId,
+    relay_addr: Multiaddr,
+    relay_listeners: RelayListeners,
+    listener_id: Option<ListenerId>,
+}
+
+impl ReserveRelayCommand {
+    pub fn new(
+        relay_peer_id: PeerId,
+        relay_addr: Multiaddr,
+        relay_listeners: RelayListeners,
+    ) -> Self {
+        Self {
+            relay_peer_id,
+            relay_addr,
+            relay_listeners,
+            listener_id: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for ReserveRelayCommand {
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let addr = relay_circuit_addr(self.relay_peer_id, &self.relay_addr);
+        match swarm.listen_on(addr.clone()) {
+            Ok(listener_id) => {
+                self.relay_listeners
+                    .insert(listener_id, self.relay_peer_id, addr);
+                self.listener_id = Some(listener_id);
+            }
+            Err(e) => handle.finish(Err(Error::Relay(e.to_string()))),
+        }
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        let Some(id) = self.listener_id else {
+            return (false, Some(event));
+        };
+        match &event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::RelayClient(
+                libp2p::relay::client::Event::ReservationReqAccepted { relay_peer_id, .. },
+            )) if *relay_peer_id == self.relay_peer_id => {
+                handle.finish(Ok(()));
+                (false, Some(event)) // 不消费，前端需要 RelayReservationAccepted
+            }
+            SwarmEvent::ListenerClosed {
+                listener_id,
+                reason,
+                ..
+            } if *listener_id == id => {
+                let reason = match reason {
+                    Ok(()) => "relay listener closed before reservation was accepted".to_string(),
+                    Err(e) => e.to_string(),
+                };
+                handle.finish(Err(Error::Relay(reason)));
+                (false, Some(event)) // 不消费，前端需要 RelayReservationFailed
+            }
+            _ => (true, Some(event)), // 继续等待
+        }
+    }
+}
+
+/// CancelRelay 命令 - 关闭经指定中继的电路监听器，放弃预约
+///
+/// 返回 `false` 表示没有经该中继的预约。
+pub struct CancelRelayCommand {
+    relay_peer_id: PeerId,
+    relay_listeners: RelayListeners,
+}
+
+impl CancelRelayCommand {
+    pub fn new(relay_peer_id: PeerId, relay_listeners: RelayListeners) -> Self {
+        Self {
+            relay_peer_id,
+            relay_listeners,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for CancelRelayCommand {
+    type Result = bool;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        // 条目由 EventLoop 在 ListenerClosed 时移除，取消标记使其不上报 RelayReservationFailed
+        let mut removed = false;
+        for listener_id in self.relay_listeners.cancel(&self.relay_peer_id) {
+            removed |= swarm.remove_listener(listener_id);
+        }
+        handle.finish(Ok(removed));
+    }
+}
+
+/// RelayReservations 命令 - 列出当前经各中继的预约（含仍在申请中的）
+pub struct RelayReservationsCommand {
+    relay_listeners: RelayListeners,
+}
+
+impl RelayReservationsCommand {
+    pub fn new(relay_listeners: RelayListeners) -> Self {
+        Self { relay_listeners }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for RelayReservationsCommand {
+    type Result = Vec<RelayReservationInfo>;
+
+    async fn run(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        handle.finish(Ok(self.relay_listeners.reservations()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circuit_addr_appends_relay_peer_once() {
+        let relay = PeerId::random();
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/4001".parse().unwrap();
+        let expected: Multiaddr = format!("/ip4/1.2.3.4/tcp/4001/p2p/{relay}/p2p-circuit")
+            .parse()
+            .unwrap();
+        assert_eq!(relay_circuit_addr(relay, &addr), expected);
+        let with_peer = addr.with(Protocol::P2p(relay));
+        assert_eq!(relay_circuit_addr(relay, &with_peer), expected);
+    }
+
+    #[test]
+    fn dial_addr_targets_peer_through_relay() {
+        let (relay, target) = (PeerId::random(), PeerId::random());
+        let relay_addr: Multiaddr = format!("/ip4/1.2.3.4/tcp/4001/p2p/{relay}")
+            .parse()
+            .unwrap();
+        let expected: Multiaddr = format!("{relay_addr}/p2p-circuit/p2p/{target}")
+            .parse()
+            .unwrap();
+        assert_eq!(relay_dial_addr(&relay_addr, target), Some(expected.clone()));
+        let circuit = relay_addr.clone().with(Protocol::P2pCircuit);
+        assert_eq!(relay_dial_addr(&circuit, target), Some(expected.clone()));
+        assert_eq!(relay_dial_addr(&expected, target), Some(expected));
+
+        // 缺少中继 PeerId，或中继即目标本身
+        let bare: Multiaddr = "/ip4/1.2.3.4/tcp/4001".parse().unwrap();
+        assert_eq!(relay_dial_addr(&bare, target), None);
+        assert_eq!(relay_dial_addr(&relay_addr, relay), None);
+    }
+}
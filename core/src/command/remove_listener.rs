@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+use libp2p::core::transport::ListenerId;
+
+use crate::runtime::CborMessage;
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// RemoveListener 命令 - 关闭指定监听器
+///
+/// 返回 `false` 表示该监听器不存在（可能已关闭）。
+pub struct RemoveListenerCommand {
+    listener_id: ListenerId,
+}
+
+impl RemoveListenerCommand {
+    pub fn new(listener_id: ListenerId) -> Self {
+        Self { listener_id }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for RemoveListenerCommand {
+    type Result = bool;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        handle.finish(Ok(swarm.remove_listener(self.listener_id)));
+    }
+}
@@ -1,5 +1,15 @@
+mod notify;
 mod send_request;
+mod send_request_streaming;
 mod send_response;
+mod send_response_many;
+mod send_stream_frame;
+mod stream;
 
+pub use notify::*;
 pub use send_request::*;
+pub use send_request_streaming::*;
 pub use send_response::*;
+pub use send_response_many::*;
+pub use send_stream_frame::*;
+pub use stream::*;
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use libp2p::PeerId;
+use libp2p::request_response::{Event, Message, OutboundRequestId};
+use libp2p::swarm::SwarmEvent;
+use tokio::sync::oneshot;
+use tracing::debug;
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent};
+
+use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+
+/// Notify 命令 - 发送请求但不等待响应
+///
+/// 请求交给 swarm 后经 `accepted` 通知调用方，调用方不等待其结果；命令本身一直留到
+/// 请求收到响应或失败，以便占用该 peer 的出站请求并发名额。响应内容和失败原因均被丢弃。
+pub struct NotifyCommand<Req>
+where
+    Req: CborMessage,
+{
+    peer_id: PeerId,
+    request: Option<Req>,
+    request_id: Option<OutboundRequestId>,
+    accepted: Option<oneshot::Sender<()>>,
+}
+
+impl<Req: CborMessage> NotifyCommand<Req> {
+    pub fn new(peer_id: PeerId, request: Req, accepted: oneshot::Sender<()>) -> Self {
+        Self {
+            peer_id,
+            request: Some(request),
+            request_id: None,
+            accepted: Some(accepted),
+        }
+    }
+}
+
+#[async_trait]
+impl<Req, Resp> CommandHandler<Req, Resp> for NotifyCommand<Req>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let Some(request) = self.request.take() else {
+            handle.finish(Err(Error::RequestResponse(
+                "Notify: run called twice".into(),
+            )));
+            return;
+        };
+        let request_id = swarm
+            .behaviour_mut()
+            .req_resp
+            .send_request(&self.peer_id, request);
+        self.request_id = Some(request_id);
+        if let Some(accepted) = self.accepted.take() {
+            let _ = accepted.send(());
+        }
+        debug!(
+            "Sent notification to {}, request_id: {:?}",
+            self.peer_id, request_id
+        );
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::ReqResp(Event::Message {
+                peer,
+                message: Message::Response { request_id, .. },
+                ..
+            })) if self.request_id.as_ref() == Some(&request_id) && peer == self.peer_id => {
+                handle.finish(Ok(()));
+                (false, None)
+            }
+            SwarmEvent::Behaviour(CoreBehaviourEvent::ReqResp(Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+                ..
+            })) if self.request_id.as_ref() == Some(&request_id) && peer == self.peer_id => {
+                debug!("Notification to {} failed: {:?}", peer, error);
+                handle.finish(Err(Error::RequestResponse(format!(
+                    "Notification to {} failed: {:?}",
+                    peer, error
+                ))));
+                (false, None)
+            }
+            other => (true, Some(other)),
+        }
+    }
+
+    fn request_peer(&self) -> Option<PeerId> {
+        Some(self.peer_id)
+    }
+}
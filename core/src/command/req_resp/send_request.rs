@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
 use libp2p::PeerId;
 use libp2p::request_response::{Event, Message, OutboundRequestId};
@@ -16,6 +18,8 @@ where
     peer_id: PeerId,
     request: Option<Req>,
     request_id: Option<OutboundRequestId>,
+    /// 覆盖 `req_resp_timeout` 的单次超时
+    timeout: Option<Duration>,
 }
 
 impl<Req: CborMessage> SendRequestCommand<Req> {
@@ -24,6 +28,15 @@ impl<Req: CborMessage> SendRequestCommand<Req> {
             peer_id,
             request: Some(request),
             request_id: None,
+            timeout: None,
+        }
+    }
+
+    /// 指定本次请求的超时，到期未收到响应以 `Error::RequestTimeout` 结束
+    pub fn with_timeout(peer_id: PeerId, request: Req, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..Self::new(peer_id, request)
         }
     }
 }
@@ -56,6 +69,7 @@ where
 
     async fn on_event(
         &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
         event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
         handle: &ResultHandle<Self::Result>,
     ) -> OnEventResult<Req, Resp> {
@@ -91,4 +105,21 @@ where
             other => (true, Some(other)), // 继续等待
         }
     }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn request_peer(&self) -> Option<PeerId> {
+        Some(self.peer_id)
+    }
+
+    async fn on_timeout(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        error!("Request to {} timed out", self.peer_id);
+        handle.finish(Err(Error::RequestTimeout));
+    }
 }
@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use libp2p::PeerId;
+use libp2p::request_response::{Event, Message, OutboundRequestId};
+use libp2p::swarm::SwarmEvent;
+use tracing::{debug, error};
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent};
+
+use super::super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+use super::{StreamFrame, StreamRequest};
+
+/// SendRequestStreaming 命令 - 发送一次流式拉取请求（`Open` 或 `Next`），
+/// 等待响应方回复一个 `StreamFrame`
+///
+/// 完整的流由 `NetClient::send_request_streaming` 逐帧驱动本命令组成。
+pub struct SendRequestStreamingCommand<Req>
+where
+    Req: CborMessage,
+{
+    peer_id: PeerId,
+    request: Option<StreamRequest<Req>>,
+    request_id: Option<OutboundRequestId>,
+}
+
+impl<Req: CborMessage> SendRequestStreamingCommand<Req> {
+    pub fn new(peer_id: PeerId, request: StreamRequest<Req>) -> Self {
+        Self {
+            peer_id,
+            request: Some(request),
+            request_id: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req, Resp> CommandHandler<Req, Resp> for SendRequestStreamingCommand<Req>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    type Result = StreamFrame<Resp>;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let Some(request) = self.request.take() else {
+            handle.finish(Err(Error::RequestResponse(
+                "SendRequestStreaming: run called twice".into(),
+            )));
+            return;
+        };
+        let request_id = swarm
+            .behaviour_mut()
+            .req_stream
+            .send_request(&self.peer_id, request);
+        self.request_id = Some(request_id);
+        debug!(
+            "Sent stream pull to {}, request_id: {:?}",
+            self.peer_id, request_id
+        );
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match event {
+            // 收到一个分块
+            SwarmEvent::Behaviour(CoreBehaviourEvent::ReqStream(Event::Message {
+                peer,
+                message:
+                    Message::Response {
+                        request_id,
+                        response,
+                    },
+                ..
+            })) if self.request_id.as_ref() == Some(&request_id) && peer == self.peer_id => {
+                handle.finish(Ok(response));
+                (false, None) // 消费，完成
+            }
+            // 拉取失败
+            SwarmEvent::Behaviour(CoreBehaviourEvent::ReqStream(Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+                ..
+            })) if self.request_id.as_ref() == Some(&request_id) && peer == self.peer_id => {
+                error!("Stream pull to {} failed: {:?}", peer, error);
+                handle.finish(Err(Error::RequestResponse(format!(
+                    "Stream request to {} failed: {:?}",
+                    peer, error
+                ))));
+                (false, None) // 消费，完成
+            }
+            other => (true, Some(other)), // 继续等待
+        }
+    }
+}
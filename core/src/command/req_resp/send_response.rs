@@ -1,22 +1,29 @@
 use async_trait::async_trait;
+use libp2p::PeerId;
 use libp2p::request_response::ResponseChannel;
 
+use crate::Result;
 use crate::error::Error;
 use crate::runtime::CborMessage;
 
 use super::super::{CommandHandler, CoreSwarm, ResultHandle};
 
+/// SendResponse 命令 - 回复一个 inbound request
+///
+/// 回复失败时区分请求方已断开（`Error::PeerDisconnected`）与其他原因。
 pub struct SendResponseCommand<Resp>
 where
     Resp: CborMessage,
 {
+    peer_id: PeerId,
     channel: Option<ResponseChannel<Resp>>,
     response: Option<Resp>,
 }
 
 impl<Resp: CborMessage> SendResponseCommand<Resp> {
-    pub fn new(channel: ResponseChannel<Resp>, response: Resp) -> Self {
+    pub fn new(peer_id: PeerId, channel: ResponseChannel<Resp>, response: Resp) -> Self {
         Self {
+            peer_id,
             channel: Some(channel),
             response: Some(response),
         }
@@ -31,26 +38,38 @@ where
 {
     type Result = ();
 
-    async fn run(
-        &mut self,
-        swarm: &mut CoreSwarm<Req, Resp>,
-        handle: &ResultHandle<Self::Result>,
-    ) {
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
         let (Some(channel), Some(response)) = (self.channel.take(), self.response.take()) else {
             handle.finish(Err(Error::RequestResponse(
                 "SendResponse: run called twice".into(),
             )));
             return;
         };
-        match swarm
-            .behaviour_mut()
-            .req_resp
-            .send_response(channel, response)
-        {
-            Ok(()) => handle.finish(Ok(())),
-            Err(_) => handle.finish(Err(Error::RequestResponse(
-                "Failed to send response: channel closed".into(),
-            ))),
-        }
+        handle.finish(respond(swarm, self.peer_id, channel, response));
+    }
+}
+
+/// 经 `channel` 回复 `peer_id` 的请求
+pub(super) fn respond<Req, Resp>(
+    swarm: &mut CoreSwarm<Req, Resp>,
+    peer_id: PeerId,
+    channel: ResponseChannel<Resp>,
+    response: Resp,
+) -> Result<()>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    match swarm
+        .behaviour_mut()
+        .req_resp
+        .send_response(channel, response)
+    {
+        Ok(()) => Ok(()),
+        // 请求方在应用回复前断开，连接关闭导致 channel 失效
+        Err(_) if !swarm.is_connected(&peer_id) => Err(Error::PeerDisconnected(peer_id)),
+        Err(_) => Err(Error::RequestResponse(
+            "Failed to send response: channel closed".into(),
+        )),
     }
 }
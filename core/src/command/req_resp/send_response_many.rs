@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use libp2p::PeerId;
+use libp2p::request_response::ResponseChannel;
+
+use crate::Result;
+use crate::error::Error;
+use crate::runtime::CborMessage;
+
+use super::super::{CommandHandler, CoreSwarm, ResultHandle};
+use super::send_response::respond;
+
+/// SendResponseMany 命令 - 用同一个响应回复多个 inbound request
+///
+/// 在一次命令中依次回复，结果按 `channels` 的顺序逐个返回，各自的失败互不影响。
+pub struct SendResponseManyCommand<Resp>
+where
+    Resp: CborMessage,
+{
+    channels: Vec<(PeerId, ResponseChannel<Resp>)>,
+    response: Option<Resp>,
+}
+
+impl<Resp: CborMessage> SendResponseManyCommand<Resp> {
+    pub fn new(channels: Vec<(PeerId, ResponseChannel<Resp>)>, response: Resp) -> Self {
+        Self {
+            channels,
+            response: Some(response),
+        }
+    }
+}
+
+#[async_trait]
+impl<Req, Resp> CommandHandler<Req, Resp> for SendResponseManyCommand<Resp>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    type Result = Vec<Result<()>>;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let Some(response) = self.response.take() else {
+            handle.finish(Err(Error::RequestResponse(
+                "SendResponseMany: run called twice".into(),
+            )));
+            return;
+        };
+        let results = self
+            .channels
+            .drain(..)
+            .map(|(peer_id, channel)| respond(swarm, peer_id, channel, response.clone()))
+            .collect();
+        handle.finish(Ok(results));
+    }
+}
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use libp2p::request_response::ResponseChannel;
+
+use crate::error::Error;
+use crate::runtime::CborMessage;
+
+use super::super::{CommandHandler, CoreSwarm, ResultHandle};
+use super::StreamFrame;
+
+/// SendStreamFrame 命令 - 用已到达的拉取请求回复一个流式分块
+pub struct SendStreamFrameCommand<Resp>
+where
+    Resp: CborMessage,
+{
+    channel: Option<ResponseChannel<StreamFrame<Resp>>>,
+    frame: Option<StreamFrame<Resp>>,
+}
+
+impl<Resp: CborMessage> SendStreamFrameCommand<Resp> {
+    pub fn new(channel: ResponseChannel<StreamFrame<Resp>>, frame: StreamFrame<Resp>) -> Self {
+        Self {
+            channel: Some(channel),
+            frame: Some(frame),
+        }
+    }
+}
+
+#[async_trait]
+impl<Req, Resp> CommandHandler<Req, Resp> for SendStreamFrameCommand<Resp>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let (Some(channel), Some(frame)) = (self.channel.take(), self.frame.take()) else {
+            handle.finish(Err(Error::RequestResponse(
+                "SendStreamFrame: run called twice".into(),
+            )));
+            return;
+        };
+        match swarm
+            .behaviour_mut()
+            .req_stream
+            .send_response(channel, frame)
+        {
+            Ok(()) => handle.finish(Ok(())),
+            Err(_) => handle.finish(Err(Error::RequestResponse(
+                "Failed to send stream frame: channel closed".into(),
+            ))),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use libp2p::request_response::ResponseChannel;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// 流式请求的线上消息（请求方 → 响应方）
+///
+/// 流式响应基于拉取模型：请求方先发送 `Open`，之后每收到一个分块
+/// 再发送一次 `Next`，响应方每次回复一个 `StreamFrame`。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StreamRequest<Req> {
+    /// 打开流，携带原始请求
+    Open(Req),
+    /// 拉取下一个分块，只接受打开该流的 peer 发来的拉取
+    Next { stream_id: u64 },
+}
+
+/// 流式响应的线上消息（响应方 → 请求方）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StreamFrame<Resp> {
+    /// 一个数据分块，`stream_id` 用于后续 `Next` 请求
+    Chunk { stream_id: u64, data: Resp },
+    /// 显式结束标记
+    End,
+    /// 响应方主动终止，携带原因
+    Error(String),
+}
+
+impl<Resp> StreamFrame<Resp> {
+    /// 是否为终止帧（`End` / `Error`）
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, StreamFrame::Chunk { .. })
+    }
+}
+
+/// 响应方每条流的暂存状态（EventLoop 与 NetClient 共享）
+///
+/// 同一时刻至多有一个拉取请求或一个待发送分块，
+/// 从而把生产速度限制在消费速度之内。
+pub enum StreamSlot<Resp> {
+    /// 无待处理的拉取请求，也无待发送的分块
+    Idle,
+    /// 对端的拉取请求已到达，等待应用提供分块
+    Waiting(ResponseChannel<StreamFrame<Resp>>),
+    /// 应用已提供分块，等待对端拉取；发送后通过 oneshot 通知生产者
+    Queued(StreamFrame<Resp>, oneshot::Sender<crate::Result<()>>),
+}
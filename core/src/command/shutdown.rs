@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::runtime::CborMessage;
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// Shutdown 命令 - 优雅关闭事件循环
+///
+/// EventLoop 收到后不再接收新命令，等待进行中的命令完成（最多 `timeout`），
+/// 随后关闭所有监听、发出 `NodeEvent::Shutdown` 并退出。
+/// 到期仍未完成的命令以 `Error::CommandTimeout` 结束。
+pub struct ShutdownCommand {
+    timeout: Duration,
+}
+
+impl ShutdownCommand {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for ShutdownCommand {
+    type Result = ();
+
+    async fn run(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        _handle: &ResultHandle<Self::Result>,
+    ) {
+        // 由 EventLoop 负责排空与收尾
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        Some(self.timeout)
+    }
+
+    /// 排空完成（或到期）后由 EventLoop 调用
+    async fn on_timeout(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        handle: &ResultHandle<Self::Result>,
+    ) {
+        handle.finish(Ok(()));
+    }
+
+    fn is_shutdown(&self) -> bool {
+        true
+    }
+}
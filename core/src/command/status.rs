@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+
+use crate::event::KadMode;
+use crate::runtime::CborMessage;
+
+use super::{CommandHandler, CoreSwarm, ResultHandle};
+
+/// 节点状态快照，各字段取自同一时刻的 swarm 状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatus {
+    /// 已连接的 peer 数
+    pub connected_peers: usize,
+    /// 当前 Kad 模式，未启用 Kad 时为 `None`
+    pub kad_mode: Option<KadMode>,
+    /// 已确认的外部地址
+    pub external_addrs: Vec<Multiaddr>,
+    /// 是否至少连着一个引导节点（未配置引导节点时为 `false`）
+    pub bootstrap_connected: bool,
+}
+
+/// Status 命令 - 在一次事件循环中读取节点状态，用于健康检查
+pub struct StatusCommand {
+    bootstrap_peers: Arc<[(PeerId, Multiaddr)]>,
+}
+
+impl StatusCommand {
+    pub fn new(bootstrap_peers: Arc<[(PeerId, Multiaddr)]>) -> Self {
+        Self { bootstrap_peers }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for StatusCommand {
+    type Result = NodeStatus;
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        let bootstrap_connected = self
+            .bootstrap_peers
+            .iter()
+            .any(|(peer_id, _)| swarm.is_connected(peer_id));
+        handle.finish(Ok(NodeStatus {
+            connected_peers: swarm.connected_peers().count(),
+            kad_mode: swarm.behaviour().kad.as_ref().map(|kad| kad.mode().into()),
+            external_addrs: swarm.external_addresses().cloned().collect(),
+            bootstrap_connected,
+        }));
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
+use libp2p::swarm::{ConnectionId, SwarmEvent};
+use libp2p::{PeerId, dcutr};
+use tracing::info;
+
+use crate::error::Error;
+use crate::runtime::{CborMessage, CoreBehaviourEvent, PeerConnections};
+
+use super::{CommandHandler, CoreSwarm, OnEventResult, ResultHandle};
+
+/// 等待打洞结果的上限（DCUtR 自身最多重试 3 次）
+const UPGRADE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// UpgradeConnection 命令 - 对中继连接重新发起 DCUtR 打洞
+///
+/// DCUtR 只在中继连接建立时自动协商，因此经同一中继再建一条电路来触发新一轮打洞，
+/// 以打洞结果（或出现直连）结束。已有直连时直接返回。
+pub struct UpgradeConnectionCommand {
+    peer_id: PeerId,
+    connections: PeerConnections,
+    /// 经中继重新拨号的连接
+    circuit_dial: Option<ConnectionId>,
+}
+
+impl UpgradeConnectionCommand {
+    pub fn new(peer_id: PeerId, connections: PeerConnections) -> Self {
+        Self {
+            peer_id,
+            connections,
+            circuit_dial: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<Req: CborMessage, Resp: CborMessage> CommandHandler<Req, Resp> for UpgradeConnectionCommand {
+    type Result = ();
+
+    async fn run(&mut self, swarm: &mut CoreSwarm<Req, Resp>, handle: &ResultHandle<Self::Result>) {
+        if !swarm.is_connected(&self.peer_id) {
+            handle.finish(Err(Error::PeerDisconnected(self.peer_id)));
+            return;
+        }
+        if self.connections.has_direct(&self.peer_id) {
+            handle.finish(Ok(()));
+            return;
+        }
+        let Some(relayed) = self.connections.relayed(&self.peer_id).into_iter().next() else {
+            handle.finish(Err(Error::HolePunch(format!(
+                "no relayed connection to {}",
+                self.peer_id
+            ))));
+            return;
+        };
+
+        info!(
+            "Requesting connection upgrade with {} via relay {}",
+            self.peer_id, relayed.relay_peer_id
+        );
+        let opts = DialOpts::peer_id(self.peer_id)
+            .addresses(vec![relayed.circuit_addr])
+            .condition(PeerCondition::Always)
+            .build();
+        self.circuit_dial = Some(opts.connection_id());
+        if let Err(e) = swarm.dial(opts) {
+            handle.finish(Err(Error::Dial(e.to_string())));
+        }
+    }
+
+    async fn on_event(
+        &mut self,
+        _swarm: &mut CoreSwarm<Req, Resp>,
+        event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+        handle: &ResultHandle<Self::Result>,
+    ) -> OnEventResult<Req, Resp> {
+        match &event {
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Dcutr(dcutr::Event {
+                remote_peer_id,
+                result,
+            })) if *remote_peer_id == self.peer_id => {
+                match result {
+                    Ok(_) => handle.finish(Ok(())),
+                    Err(e) => handle.finish(Err(Error::HolePunch(e.to_string()))),
+                }
+                (false, Some(event)) // 不消费，前端需要 HolePunchSucceeded / HolePunchFailed
+            }
+            // 经其他途径建立的直连（如对端打洞时作为拨号方连入）同样视为升级完成
+            SwarmEvent::ConnectionEstablished {
+                peer_id, endpoint, ..
+            } if *peer_id == self.peer_id && !endpoint.is_relayed() => {
+                handle.finish(Ok(()));
+                (false, Some(event))
+            }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                error,
+                ..
+            } if Some(*connection_id) == self.circuit_dial => {
+                handle.finish(Err(Error::Dial(error.to_string())));
+                (false, Some(event))
+            }
+            _ => (true, Some(event)),
+        }
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        Some(UPGRADE_TIMEOUT)
+    }
+}
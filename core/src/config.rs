@@ -1,9 +1,287 @@
+use std::fmt;
+use std::io;
+use std::sync::Arc;
 use std::time::Duration;
 
-use libp2p::{Multiaddr, PeerId};
+use libp2p::kad::Record;
+use libp2p::kad::store::{MemoryStoreConfig, RecordStore};
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId, yamux};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::store::BoxedStore;
+
+/// 入站 Kad 记录校验器
+///
+/// 开启 `kad_record_filtering` 后，对端 PUT 过来的记录先经过校验，
+/// 返回 `false` 的记录不会写入本地存储。
+#[derive(Clone)]
+pub struct RecordValidator(Arc<dyn Fn(&Record) -> bool + Send + Sync>);
+
+impl RecordValidator {
+    pub fn new(f: impl Fn(&Record) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub fn validate(&self, record: &Record) -> bool {
+        (self.0)(record)
+    }
+}
+
+impl fmt::Debug for RecordValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RecordValidator(..)")
+    }
+}
+
+/// Kad 记录存储工厂
+///
+/// 在 `start()` 时以本节点 PeerId 调用一次，创建 Kad 使用的存储。
+#[derive(Clone)]
+pub struct KadStoreFactory(Arc<dyn Fn(PeerId) -> io::Result<BoxedStore> + Send + Sync>);
+
+impl KadStoreFactory {
+    pub fn new<S>(f: impl Fn(PeerId) -> io::Result<S> + Send + Sync + 'static) -> Self
+    where
+        S: RecordStore + Send + 'static,
+    {
+        Self(Arc::new(move |peer_id| f(peer_id).map(BoxedStore::new)))
+    }
+
+    pub fn create(&self, local_id: PeerId) -> io::Result<BoxedStore> {
+        (self.0)(local_id)
+    }
+}
+
+impl fmt::Debug for KadStoreFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("KadStoreFactory(..)")
+    }
+}
+
+/// 应用层握手信息
+///
+/// 连接建立后双方交换，用于协商应用版本和能力（比 identify 的版本字符串更丰富）。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandshakeInfo {
+    /// 应用 API 版本（如 "2.1"）
+    pub version: String,
+    /// 支持的能力列表
+    pub features: Vec<String>,
+}
+
+impl HandshakeInfo {
+    pub fn new(version: impl Into<String>) -> Self {
+        Self {
+            version: version.into(),
+            features: Vec::new(),
+        }
+    }
+
+    pub fn with_features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.features = features.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 是否支持某项能力
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+}
+
+/// 底层传输组合
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransportConfig {
+    /// TCP + Noise + Yamux 与 QUIC 同时启用
+    #[default]
+    TcpAndQuic,
+    /// 仅 QUIC（移动端省电，避免 TCP 回退风暴）
+    QuicOnly,
+    /// 仅 TCP + Noise + Yamux
+    TcpOnly,
+    /// 进程内内存传输（`/memory/<n>` 地址，不占用系统端口），用于确定性的测试
+    ///
+    /// 仍经过安全握手与 Yamux，只能连接同一进程内的节点。
+    Memory,
+}
+
+impl TransportConfig {
+    pub fn tcp_enabled(&self) -> bool {
+        matches!(self, TransportConfig::TcpAndQuic | TransportConfig::TcpOnly)
+    }
+
+    pub fn quic_enabled(&self) -> bool {
+        matches!(
+            self,
+            TransportConfig::TcpAndQuic | TransportConfig::QuicOnly
+        )
+    }
+
+    pub fn memory_enabled(&self) -> bool {
+        matches!(self, TransportConfig::Memory)
+    }
+
+    /// 地址是否能由当前 transport 组合监听/拨号（relay 电路地址总是支持）
+    pub fn supports(&self, addr: &Multiaddr) -> bool {
+        // 内存传输只接受 `/memory` 地址
+        let mut supported = !self.memory_enabled();
+        for protocol in addr.iter() {
+            match protocol {
+                Protocol::P2pCircuit => return true,
+                Protocol::QuicV1 | Protocol::Quic => supported = self.quic_enabled(),
+                Protocol::Tcp(_) => supported = self.tcp_enabled(),
+                Protocol::Memory(_) => supported = self.memory_enabled(),
+                _ => {}
+            }
+        }
+        supported
+    }
+}
+
+/// TCP 与 relay 电路上的安全握手协议（QUIC 自带 TLS 1.3，不受影响）
+///
+/// 双方须至少有一种共同协议，否则握手失败；`swarm-bootstrap` 引导节点目前只支持 Noise，
+/// 仅 TLS 的节点无法经 TCP 连上它。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SecurityUpgrade {
+    /// 仅 Noise
+    #[default]
+    Noise,
+    /// 仅 TLS 1.3（只放行类 TLS 流量的企业网络、与要求 TLS 的其他 libp2p 实现互通）
+    Tls,
+    /// 同时支持两者，协商时优先 Noise
+    NoiseThenTls,
+}
+
+/// `/dns4` / `/dns6` / `/dnsaddr` 地址的解析器（需开启 `dns` feature）
+#[cfg(feature = "dns")]
+#[derive(Debug, Clone, Default)]
+pub enum DnsConfig {
+    /// 读取系统配置（如 `/etc/resolv.conf`），读取失败时不启用 DNS 解析，`start()` 照常成功
+    #[default]
+    System,
+    /// 指定上游服务器，如 `ResolverConfig::cloudflare()`，适合没有系统配置的容器或移动端
+    Custom(libp2p::dns::ResolverConfig),
+}
+
+/// 拨号时候选地址的尝试顺序
+///
+/// 对端同时公布 IPv4 与 IPv6 地址时，在某一协议族路径不通的双栈网络上，
+/// 优先尝试可用的协议族可避免先耗尽拨号超时。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AddressOrder {
+    /// 保持地址来源（Kad 路由表、identify、mDNS 等）给出的顺序
+    #[default]
+    Default,
+    /// IPv4 地址优先
+    PreferIpv4,
+    /// IPv6 地址优先
+    PreferIpv6,
+}
+
+impl AddressOrder {
+    /// 稳定排序：偏好的协议族在前，无法判断协议族的地址居中，另一协议族最后
+    pub fn sort(&self, addrs: &mut [Multiaddr]) {
+        let preferred_ipv4 = match self {
+            AddressOrder::Default => return,
+            AddressOrder::PreferIpv4 => true,
+            AddressOrder::PreferIpv6 => false,
+        };
+        addrs.sort_by_key(|addr| match addr.iter().next() {
+            Some(Protocol::Ip4(_) | Protocol::Dns4(_)) => u8::from(!preferred_ipv4) * 2,
+            Some(Protocol::Ip6(_) | Protocol::Dns6(_)) => u8::from(preferred_ipv4) * 2,
+            _ => 1,
+        });
+    }
+}
+
+/// 事件通道满（前端消费过慢）时的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EventOverflow {
+    /// 等待前端消费，期间事件循环暂停（不丢事件，但会拖慢网络处理）
+    #[default]
+    Block,
+    /// 暂存到等长的环形缓冲区，缓冲区满时丢弃最旧的事件
+    DropOldest,
+    /// 直接丢弃新事件
+    DropNewest,
+}
+
+/// 引导节点断线重连的退避策略
+///
+/// 第 n 次重试前等待 `min(base * 2^n, max)`，再按 `jitter` 比例随机上下浮动，
+/// 避免大量节点在引导节点恢复时同时重连。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BootstrapRetry {
+    /// 首次重试的等待时间
+    #[serde(with = "humantime_serde")]
+    pub base: Duration,
+    /// 等待时间上限
+    #[serde(with = "humantime_serde")]
+    pub max: Duration,
+    /// 随机浮动比例（0.0 ~ 1.0），如 0.2 表示 ±20%
+    ///
+    /// 超出范围的值在计算等待时间时截断到 [0, 1]，NaN / 无穷视为 0
+    pub jitter: f64,
+}
+
+impl Default for BootstrapRetry {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(300),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl BootstrapRetry {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = clamp_jitter(jitter);
+        self
+    }
+
+    /// 第 `attempt` 次重试（从 0 开始）前的等待时间，`random` 为 [0, 1) 的随机数
+    pub(crate) fn delay(&self, attempt: u32, random: f64) -> Duration {
+        let exp = self.base.saturating_mul(2u32.saturating_pow(attempt));
+        let delay = exp.min(self.max);
+        // 字段公开且可从配置文件加载，这里再截断一次，避免负数 / NaN 让 mul_f64 panic
+        delay.mul_f64(1.0 + clamp_jitter(self.jitter) * (random * 2.0 - 1.0))
+    }
+}
+
+/// 把 jitter 截断到 [0, 1]，`f64::clamp` 会原样放过 NaN，需单独处理
+fn clamp_jitter(jitter: f64) -> f64 {
+    if jitter.is_finite() {
+        jitter.clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
 
 /// 节点配置
-#[derive(Debug, Clone)]
+///
+/// 可通过 `from_toml_str` / `from_json_str` 从配置文件加载，字段名为 camelCase，缺省字段取默认值。
+/// 时长写作 `"30s"`、`"5m"` 这类字符串；引导节点与种子节点写作带 `/p2p/<peer_id>` 后缀的地址，
+/// 没有地址的种子节点写作 `/p2p/<peer_id>`。`record_validator` 与 `kad_store` 不参与序列化。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
 pub struct NodeConfig {
     /// identify 协议版本（如 "/myapp/1.0.0"）
     pub protocol_version: String,
@@ -15,45 +293,275 @@ pub struct NodeConfig {
     pub listen_addrs: Vec<Multiaddr>,
 
     /// Kademlia DHT 引导节点
+    #[serde(with = "peer_addrs")]
     pub bootstrap_peers: Vec<(PeerId, Multiaddr)>,
 
+    /// 引导节点拨号失败或断开后的重连策略
+    ///
+    /// 默认开启（1 秒起，指数退避至 5 分钟）：连接成功即停止重试，再次断开时重新开始。
+    /// 设为 `None` 则只在启动时拨号一次。
+    pub bootstrap_retry: Option<BootstrapRetry>,
+
+    /// 启动时预先导入 Kad 路由表的节点（如上次 `export_routing_table` 的快照）
+    ///
+    /// 与 `bootstrap_peers` 不同，这些节点只登记地址、不主动拨号，也不会被视为基础设施节点。
+    #[serde(with = "seed_peers")]
+    pub seed_peers: Vec<(PeerId, Vec<Multiaddr>)>,
+
+    /// 底层传输组合，默认 TCP + QUIC
+    ///
+    /// 切换为单一传输时，`listen_addrs` 需与之匹配（如 QuicOnly 使用 `/udp/0/quic-v1`，
+    /// Memory 使用 `/memory/0`），否则 `start()` 会报错。
+    pub transport: TransportConfig,
+
+    /// TCP 与 relay 电路的安全握手协议，默认仅 Noise
+    pub security: SecurityUpgrade,
+
+    /// Yamux 每个子流的接收窗口（字节），默认 `None`
+    ///
+    /// 默认的 Yamux 实现会按 RTT 与吞吐自动调大子流窗口（起始 256 KiB，单连接合计上限 1 GiB），
+    /// 通常无需设置。设置本项或 `yamux_max_streams` 后改用 libp2p 保留的旧版 Yamux 实现，
+    /// 子流窗口固定为该值，适合在高延迟链路上手动调优单连接的大文件吞吐；
+    /// 每个子流最多缓冲这么多未读数据，调大会相应增加内存占用。
+    /// 作用于 TCP、内存传输与 relay 电路，QUIC 自带多路复用，不受影响。
+    pub yamux_max_stream_receive_window: Option<u32>,
+
+    /// 单个连接的最大并发 Yamux 子流数，默认 `None`（沿用 Yamux 默认值 512）
+    ///
+    /// 设置后同样改用旧版 Yamux 实现，见 `yamux_max_stream_receive_window`。
+    pub yamux_max_streams: Option<usize>,
+
+    /// DNS 解析器，默认读取系统配置（需开启 `dns` feature，不参与序列化）
+    #[cfg(feature = "dns")]
+    #[serde(skip)]
+    pub dns: DnsConfig,
+
+    /// 按 PeerId 拨号时候选地址的顺序，默认保持地址来源的顺序
+    ///
+    /// 作用于 `dial` / `dial_many`、引导节点、mDNS 发现与重连时的拨号；
+    /// `dial_with_opts` 使用调用方给定的 `DialOpts`，不受影响。
+    pub dial_address_order: AddressOrder,
+
+    /// 单次拨号的截止时间，默认 `None`（只受 `command_timeout` 兜底）
+    ///
+    /// 作用于 `dial` / `dial_with_opts` / `dial_with_condition`：到期仍未建立连接时
+    /// 返回 `Error::DialTimeout`，并中止该 peer 仍在进行的拨号。`dial_many` 以此为整体超时
+    /// （未配置时为 30 秒）。与 `idle_connection_timeout` 无关，后者只管已建立连接的空闲关闭。
+    #[serde(with = "humantime_serde")]
+    pub dial_timeout: Option<Duration>,
+
     /// 启用 mDNS 局域网发现
     pub enable_mdns: bool,
 
     /// 启用 relay 中继客户端（NAT 穿透）
     pub enable_relay_client: bool,
 
+    /// 自动经 `bootstrap_peers` 申请 relay 预约
+    ///
+    /// 默认 `true`：与引导节点建立连接后即在其电路地址上监听。
+    /// 关闭后只能通过 `NetClient::reserve_relay` 显式选择中继。
+    pub auto_relay_reservations: bool,
+
+    /// 为其他节点提供 circuit relay v2 中继服务，默认 `false`
+    ///
+    /// 开启后本节点接受其他 peer 的预约并转发经本节点的电路（如桌面端为手机中继），
+    /// 预约的建立与结束通过 `NodeEvent::RelayServerReservationAccepted` /
+    /// `RelayServerReservationClosed` 上报。
+    ///
+    /// 中继流量占用本节点的带宽和连接：最多同时保持 128 个预约、16 条电路，
+    /// 每条电路受 `relay_server_max_circuit_duration` / `relay_server_max_circuit_bytes` 限制，
+    /// 移动端或按流量计费的网络不宜开启。预约响应携带本节点的外部地址，
+    /// 需先经 AutoNAT 确认或通过 `NetClient::add_external_address` 登记，否则预约方无法使用。
+    pub enable_relay_server: bool,
+
+    /// 单条中继电路的最长持续时间，默认 2 分钟
+    #[serde(with = "humantime_serde")]
+    pub relay_server_max_circuit_duration: Duration,
+
+    /// 单条中继电路可转发的最大字节数，默认 128 KiB
+    ///
+    /// 超出后电路被关闭；需要经中继传输文件时调大，理想情况下 DCUtR 打洞成功后会改走直连。
+    pub relay_server_max_circuit_bytes: u64,
+
     /// 启用 DCUtR 打洞
     pub enable_dcutr: bool,
 
     /// 启用 AutoNAT 检测
     pub enable_autonat: bool,
 
+    /// 判定为 `NatStatus::Private` 所需的探测失败服务器数
+    ///
+    /// 尚无地址被确认可达时，累计这么多个不同服务器探测失败后发出
+    /// `NatStatusChanged { status: Private }`。设为 0 则始终停留在 `Unknown`。
+    pub autonat_failure_threshold: usize,
+
+    /// 启用 UPnP 自动端口映射，默认 `false`
+    ///
+    /// 开启后向局域网内支持 IGD 的路由器申请把监听端口映射到公网，
+    /// 映射成功的地址直接登记为外部地址（`NodeEvent::ExternalAddrConfirmed`），
+    /// 映射结果和网关状态通过 `NodeEvent::UpnpMappingResult` 上报。
+    /// IGD 只支持 IPv4，仅映射私网 IPv4 上的 TCP / QUIC 监听端口；
+    /// 多层 NAT 或路由器未开启 UPnP 时无效，此时仍依赖 AutoNAT / relay。
+    pub enable_upnp: bool,
+
+    /// 启用 Gossipsub 发布/订阅
+    ///
+    /// 默认 `false`（关闭，不协商该协议）。开启后可通过 `NetClient::subscribe` / `publish`
+    /// 收发主题消息，消息以本节点密钥签名，收到的消息通过 `NodeEvent::GossipMessage` 上报。
+    pub enable_gossipsub: bool,
+
     /// 空闲连接超时时间
+    ///
+    /// 对所有连接生效；`NetClient::keep_alive` / `set_keep_alive` 登记的 peer 例外，
+    /// 事件循环每隔该时间的一半向其发起 Identify push，使连接始终有活动。
+    #[serde(with = "humantime_serde")]
     pub idle_connection_timeout: Duration,
 
     /// Ping 间隔
+    #[serde(with = "humantime_serde")]
     pub ping_interval: Duration,
 
     /// Ping 超时
+    #[serde(with = "humantime_serde")]
     pub ping_timeout: Duration,
 
+    /// identify 缓存的 peer 地址数，默认 100
+    ///
+    /// 缓存用于向 swarm 补充对端的监听地址，跟踪大量 peer 的枢纽节点可调大。
+    pub identify_cache_size: usize,
+
+    /// 本地监听地址变化时主动推送给已连接的 peer，默认 `true`
+    ///
+    /// 关闭后对端只能在下次 identify 时得知新地址，适合对流量和耗电敏感的客户端。
+    pub identify_push_updates: bool,
+
+    /// 启用 Kademlia DHT，默认 `true`
+    ///
+    /// 只靠 mDNS + 请求/响应的纯局域网应用可关闭，此时不协商 Kad 协议、不创建记录存储，
+    /// `bootstrap` / `put_record` 等 Kad 操作返回 `Error::Config("kad disabled")`。
+    pub enable_kad: bool,
+
     /// Kademlia 查询超时
+    #[serde(with = "humantime_serde")]
     pub kad_query_timeout: Duration,
 
+    /// 自动 Kad bootstrap 间隔
+    ///
+    /// 默认 `None`（只在调用 `NetClient::bootstrap` 时执行，外加 libp2p 内置的低频刷新）。
+    /// 设置后事件循环按此间隔发起 bootstrap 保持路由表新鲜，上一轮未结束时跳过，
+    /// 每轮结束发出 `NodeEvent::KadBootstrapCompleted`。设为 0 时视为 `None`。
+    #[serde(with = "humantime_serde")]
+    pub kad_auto_bootstrap_interval: Option<Duration>,
+
     /// 强制 Kad 以 Server 模式运行
     ///
     /// 默认 `false`（自动模式，由 AutoNAT 决定）。
     /// 设为 `true` 后节点始终响应 DHT 查询，适用于确认公网可达或测试场景。
     pub kad_server_mode: bool,
 
+    /// 入站 Kad 记录过滤
+    ///
+    /// 默认 `false`（对端 PUT 的记录和 Provider 直接写入本地存储）。
+    /// 开启后由事件循环接管写入：记录先经过 `record_validator`（若设置）再存储。
+    pub kad_record_filtering: bool,
+
+    /// 入站 Kad 记录校验器，仅在 `kad_record_filtering` 开启时生效
+    #[serde(skip)]
+    pub record_validator: Option<RecordValidator>,
+
+    /// Kad 记录存储，默认 `None`（使用内存存储 `MemoryStore`，重启即丢失）
+    #[serde(skip)]
+    pub kad_store: Option<KadStoreFactory>,
+
+    /// 默认内存存储最多保存的记录数，默认 1024
+    ///
+    /// 以下四项只作用于默认的 `MemoryStore`；通过 `kad_store` 自定义存储时由其自行配置。
+    /// 本地写入超出上限时 `put_record` / `start_provide` 返回 `Error::KadStore`。
+    pub kad_max_records: usize,
+
+    /// 默认内存存储中单条记录值的最大字节数，默认 65 KiB
+    pub kad_max_record_size: usize,
+
+    /// 默认内存存储中每个 key 最多保存的 Provider 数，默认 20（K 值）
+    pub kad_max_providers_per_key: usize,
+
+    /// 默认内存存储中本节点最多提供的 key 数，默认 1024
+    pub kad_max_provided_keys: usize,
+
     /// Request-Response 协议名称（如 "/myapp/req/1.0.0"）
     pub req_resp_protocol: String,
 
+    /// 单个 request-response 请求的最大字节数（CBOR 编码后），默认 1 MiB
+    ///
+    /// 入站请求超出时读取失败，不会分配超出上限的内存；出站请求超出时不发送，
+    /// `send_request` 返回的错误中写明上限。流式请求同样适用。
+    pub max_request_size: usize,
+
+    /// 单个 request-response 响应的最大字节数（CBOR 编码后），默认 10 MiB
+    ///
+    /// 流式请求按单个分块计算。回复超出时 inbound request 以 `InboundRequestFailed` 结束。
+    pub max_response_size: usize,
+
     /// Request-Response 请求超时时间
     ///
     /// 配对等需要用户交互的场景，默认 10 秒太短，建议 120 秒。
+    #[serde(with = "humantime_serde")]
     pub req_resp_timeout: Duration,
+
+    /// 应用层握手信息
+    ///
+    /// 默认 `None`（关闭）。设置后在与 peer 建立首个连接时自动交换，
+    /// 对端的信息通过 `NetClient::peer_info` 读取，并发出 `NodeEvent::HandshakeCompleted`。
+    /// 双方都需开启（协议名为 `{req_resp_protocol}/handshake`）。
+    pub handshake: Option<HandshakeInfo>,
+
+    /// 周期性自检事件间隔
+    ///
+    /// 默认 `None`（关闭）。设置后每个周期发出一次 `NodeEvent::Diagnostics`，
+    /// 首次在启动一个周期后发出。设为 0 时视为关闭。
+    #[serde(with = "humantime_serde")]
+    pub diagnostics_interval: Option<Duration>,
+
+    /// 连接数软上限
+    ///
+    /// 默认 `None`（不限制）。超过后事件循环按 Ping 延迟从高到低断开 peer，
+    /// 直到回到上限以内，并发出 `NodeEvent::ConnectionPruned`。
+    /// 保活中的 peer、引导节点和 relay 中继节点不会被断开；尚未测得延迟的新连接也会跳过。
+    pub target_connection_count: Option<usize>,
+
+    /// 同时等待回复的 inbound request 上限（含流式请求）
+    ///
+    /// 超过后新的 inbound request 会被直接拒绝，并发出 `NodeEvent::InboundRequestRejected`，
+    /// 防止请求洪泛把 `ResponseChannel` 堆满内存。
+    pub max_pending_responses: usize,
+
+    /// 对同一 peer 同时进行的出站请求（`send_request` / `notify`）上限
+    ///
+    /// 默认 `None`（不限制）。超出的请求在事件循环中按提交顺序排队，
+    /// 等前面的请求收到响应、失败或超时后再发出，避免压垮资源受限的 peer 或耗尽子流。
+    /// 设为 0 时按 1 处理。
+    pub max_concurrent_requests_per_peer: Option<usize>,
+
+    /// 命令通道容量（NetClient → EventLoop）
+    ///
+    /// 通道满时命令立即返回 `Error::CommandChannelFull`，可稍后重试。
+    pub command_channel_size: usize,
+
+    /// 命令的兜底超时，默认 5 分钟
+    ///
+    /// 只作用于未自带超时的命令（如 `dial`），到期返回 `Error::CommandTimeout`，
+    /// 防止完成事件始终未到达时调用方永远等待。Kad 查询另受 `kad_query_timeout` 限制。
+    /// `None` 关闭。
+    #[serde(with = "humantime_serde")]
+    pub command_timeout: Option<Duration>,
+
+    /// 事件通道容量（EventLoop → EventReceiver）
+    pub event_channel_size: usize,
+
+    /// 事件通道满时的处理策略，默认 `Block`
+    ///
+    /// 丢弃的事件数可通过 `NetClient::dropped_event_count` 读取。
+    pub event_overflow: EventOverflow,
 }
 
 impl Default for NodeConfig {
@@ -66,17 +574,56 @@ impl Default for NodeConfig {
                 "/ip6/::/tcp/0".parse().unwrap(),
             ],
             bootstrap_peers: vec![],
+            bootstrap_retry: Some(BootstrapRetry::default()),
+            seed_peers: vec![],
+            transport: TransportConfig::default(),
+            security: SecurityUpgrade::default(),
+            yamux_max_stream_receive_window: None,
+            yamux_max_streams: None,
+            #[cfg(feature = "dns")]
+            dns: DnsConfig::default(),
+            dial_address_order: AddressOrder::default(),
+            dial_timeout: None,
             enable_mdns: true,
             enable_relay_client: true,
+            auto_relay_reservations: true,
+            enable_relay_server: false,
+            relay_server_max_circuit_duration: Duration::from_secs(2 * 60),
+            relay_server_max_circuit_bytes: 128 * 1024,
             enable_dcutr: true,
             enable_autonat: true,
+            autonat_failure_threshold: 3,
+            enable_upnp: false,
+            enable_gossipsub: false,
             idle_connection_timeout: Duration::from_secs(60),
             ping_interval: Duration::from_secs(15),
             ping_timeout: Duration::from_secs(10),
+            identify_cache_size: 100,
+            identify_push_updates: true,
+            enable_kad: true,
             kad_query_timeout: Duration::from_secs(60),
+            kad_auto_bootstrap_interval: None,
             kad_server_mode: false,
+            kad_record_filtering: false,
+            record_validator: None,
+            kad_store: None,
+            kad_max_records: 1024,
+            kad_max_record_size: 65 * 1024,
+            kad_max_providers_per_key: 20,
+            kad_max_provided_keys: 1024,
             req_resp_protocol: "/swarm-p2p/req/1.0.0".into(),
             req_resp_timeout: Duration::from_secs(120),
+            max_request_size: 1024 * 1024,
+            max_response_size: 10 * 1024 * 1024,
+            handshake: None,
+            diagnostics_interval: None,
+            target_connection_count: None,
+            max_pending_responses: 1024,
+            max_concurrent_requests_per_peer: None,
+            command_channel_size: 32,
+            command_timeout: Some(Duration::from_secs(300)),
+            event_channel_size: 64,
+            event_overflow: EventOverflow::Block,
         }
     }
 }
@@ -90,6 +637,16 @@ impl NodeConfig {
         }
     }
 
+    /// 从 TOML 配置加载，缺省字段取默认值
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).map_err(|e| Error::Config(e.to_string()))
+    }
+
+    /// 从 JSON 配置加载，缺省字段取默认值
+    pub fn from_json_str(s: &str) -> Result<Self> {
+        serde_json::from_str(s).map_err(|e| Error::Config(e.to_string()))
+    }
+
     pub fn with_listen_addrs(mut self, addrs: Vec<Multiaddr>) -> Self {
         self.listen_addrs = addrs;
         self
@@ -100,6 +657,68 @@ impl NodeConfig {
         self
     }
 
+    pub fn with_bootstrap_retry(mut self, retry: Option<BootstrapRetry>) -> Self {
+        self.bootstrap_retry = retry;
+        self
+    }
+
+    pub fn with_seed_peers(mut self, peers: Vec<(PeerId, Vec<Multiaddr>)>) -> Self {
+        self.seed_peers = peers;
+        self
+    }
+
+    pub fn with_transport(mut self, transport: TransportConfig) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn with_security(mut self, security: SecurityUpgrade) -> Self {
+        self.security = security;
+        self
+    }
+
+    pub fn with_yamux_max_stream_receive_window(mut self, bytes: u32) -> Self {
+        self.yamux_max_stream_receive_window = Some(bytes);
+        self
+    }
+
+    pub fn with_yamux_max_streams(mut self, streams: usize) -> Self {
+        self.yamux_max_streams = Some(streams);
+        self
+    }
+
+    /// TCP / 内存传输 / relay 电路使用的 Yamux 配置
+    pub(crate) fn yamux_config(&self) -> yamux::Config {
+        let mut config = yamux::Config::default();
+        if let Some(window) = self.yamux_max_stream_receive_window {
+            // 旧版实现的子流缓冲默认 1 MiB，需容纳整个窗口
+            #[allow(deprecated)]
+            config
+                .set_receive_window_size(window)
+                .set_max_buffer_size((window as usize).max(1024 * 1024));
+        }
+        if let Some(streams) = self.yamux_max_streams {
+            config.set_max_num_streams(streams);
+        }
+        config
+    }
+
+    #[cfg(feature = "dns")]
+    pub fn with_dns(mut self, dns: DnsConfig) -> Self {
+        self.dns = dns;
+        self
+    }
+
+    pub fn with_dial_address_order(mut self, order: AddressOrder) -> Self {
+        self.dial_address_order = order;
+        self
+    }
+
+    pub fn with_dial_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.dial_timeout = timeout;
+        self
+    }
+
     pub fn with_mdns(mut self, enable: bool) -> Self {
         self.enable_mdns = enable;
         self
@@ -110,6 +729,26 @@ impl NodeConfig {
         self
     }
 
+    pub fn with_auto_relay_reservations(mut self, enable: bool) -> Self {
+        self.auto_relay_reservations = enable;
+        self
+    }
+
+    pub fn with_relay_server(mut self, enable: bool) -> Self {
+        self.enable_relay_server = enable;
+        self
+    }
+
+    pub fn with_relay_server_max_circuit_duration(mut self, duration: Duration) -> Self {
+        self.relay_server_max_circuit_duration = duration;
+        self
+    }
+
+    pub fn with_relay_server_max_circuit_bytes(mut self, bytes: u64) -> Self {
+        self.relay_server_max_circuit_bytes = bytes;
+        self
+    }
+
     pub fn with_dcutr(mut self, enable: bool) -> Self {
         self.enable_dcutr = enable;
         self
@@ -120,11 +759,107 @@ impl NodeConfig {
         self
     }
 
+    pub fn with_autonat_failure_threshold(mut self, threshold: usize) -> Self {
+        self.autonat_failure_threshold = threshold;
+        self
+    }
+
+    pub fn with_upnp(mut self, enable: bool) -> Self {
+        self.enable_upnp = enable;
+        self
+    }
+
+    pub fn with_gossipsub(mut self, enable: bool) -> Self {
+        self.enable_gossipsub = enable;
+        self
+    }
+
+    pub fn with_identify_cache_size(mut self, size: usize) -> Self {
+        self.identify_cache_size = size;
+        self
+    }
+
+    pub fn with_identify_push_updates(mut self, enable: bool) -> Self {
+        self.identify_push_updates = enable;
+        self
+    }
+
+    pub fn with_kad(mut self, enable: bool) -> Self {
+        self.enable_kad = enable;
+        self
+    }
+
+    pub fn with_kad_auto_bootstrap_interval(mut self, interval: Duration) -> Self {
+        self.kad_auto_bootstrap_interval = Some(interval);
+        self
+    }
+
     pub fn with_kad_server_mode(mut self, enable: bool) -> Self {
         self.kad_server_mode = enable;
         self
     }
 
+    pub fn with_kad_record_filtering(mut self, enable: bool) -> Self {
+        self.kad_record_filtering = enable;
+        self
+    }
+
+    /// 设置入站记录校验器，并开启 `kad_record_filtering`
+    pub fn with_record_validator(
+        mut self,
+        validator: impl Fn(&Record) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.record_validator = Some(RecordValidator::new(validator));
+        self.kad_record_filtering = true;
+        self
+    }
+
+    /// 自定义 Kad 记录存储（如 `FsStore` 持久化到磁盘）
+    ///
+    /// ```ignore
+    /// config.with_kad_store(|peer_id| FsStore::open("./kad", peer_id))
+    /// ```
+    pub fn with_kad_store<S>(
+        mut self,
+        factory: impl Fn(PeerId) -> io::Result<S> + Send + Sync + 'static,
+    ) -> Self
+    where
+        S: RecordStore + Send + 'static,
+    {
+        self.kad_store = Some(KadStoreFactory::new(factory));
+        self
+    }
+
+    pub fn with_kad_max_records(mut self, max: usize) -> Self {
+        self.kad_max_records = max;
+        self
+    }
+
+    pub fn with_kad_max_record_size(mut self, bytes: usize) -> Self {
+        self.kad_max_record_size = bytes;
+        self
+    }
+
+    pub fn with_kad_max_providers_per_key(mut self, max: usize) -> Self {
+        self.kad_max_providers_per_key = max;
+        self
+    }
+
+    pub fn with_kad_max_provided_keys(mut self, max: usize) -> Self {
+        self.kad_max_provided_keys = max;
+        self
+    }
+
+    /// 默认内存存储的容量配置
+    pub(crate) fn memory_store_config(&self) -> MemoryStoreConfig {
+        MemoryStoreConfig {
+            max_records: self.kad_max_records,
+            max_value_bytes: self.kad_max_record_size,
+            max_providers_per_key: self.kad_max_providers_per_key,
+            max_provided_keys: self.kad_max_provided_keys,
+        }
+    }
+
     pub fn with_req_resp_protocol(mut self, protocol: impl Into<String>) -> Self {
         self.req_resp_protocol = protocol.into();
         self
@@ -134,6 +869,148 @@ impl NodeConfig {
         self.req_resp_timeout = timeout;
         self
     }
+
+    pub fn with_max_request_size(mut self, bytes: usize) -> Self {
+        self.max_request_size = bytes;
+        self
+    }
+
+    pub fn with_max_response_size(mut self, bytes: usize) -> Self {
+        self.max_response_size = bytes;
+        self
+    }
+
+    pub fn with_handshake(mut self, info: HandshakeInfo) -> Self {
+        self.handshake = Some(info);
+        self
+    }
+
+    pub fn with_diagnostics_interval(mut self, interval: Duration) -> Self {
+        self.diagnostics_interval = Some(interval);
+        self
+    }
+
+    pub fn with_target_connection_count(mut self, count: usize) -> Self {
+        self.target_connection_count = Some(count);
+        self
+    }
+
+    pub fn with_max_pending_responses(mut self, max: usize) -> Self {
+        self.max_pending_responses = max;
+        self
+    }
+
+    pub fn with_max_concurrent_requests_per_peer(mut self, max: usize) -> Self {
+        self.max_concurrent_requests_per_peer = Some(max);
+        self
+    }
+
+    pub fn with_command_channel_size(mut self, size: usize) -> Self {
+        self.command_channel_size = size;
+        self
+    }
+
+    pub fn with_command_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.command_timeout = timeout;
+        self
+    }
+
+    pub fn with_event_channel_size(mut self, size: usize) -> Self {
+        self.event_channel_size = size;
+        self
+    }
+
+    pub fn with_event_overflow(mut self, overflow: EventOverflow) -> Self {
+        self.event_overflow = overflow;
+        self
+    }
+}
+
+/// 地址末尾附上 `/p2p/<peer_id>`（已带时原样返回）
+fn with_peer_suffix(addr: &Multiaddr, peer_id: PeerId) -> Multiaddr {
+    match addr.iter().last() {
+        Some(Protocol::P2p(_)) => addr.clone(),
+        _ => addr.clone().with(Protocol::P2p(peer_id)),
+    }
+}
+
+/// 拆出地址末尾的 `/p2p/<peer_id>`
+fn split_peer_suffix(s: &str) -> std::result::Result<(PeerId, Multiaddr), String> {
+    let mut addr: Multiaddr = s
+        .parse()
+        .map_err(|e| format!("invalid address {s:?}: {e}"))?;
+    match addr.pop() {
+        Some(Protocol::P2p(peer_id)) => Ok((peer_id, addr)),
+        _ => Err(format!("address {s:?} must end with /p2p/<peer_id>")),
+    }
+}
+
+/// `bootstrap_peers` 序列化为 `"<addr>/p2p/<peer_id>"` 字符串列表
+mod peer_addrs {
+    use libp2p::{Multiaddr, PeerId};
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    pub fn serialize<S: Serializer>(
+        peers: &[(PeerId, Multiaddr)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            peers
+                .iter()
+                .map(|(peer_id, addr)| super::with_peer_suffix(addr, *peer_id).to_string()),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(PeerId, Multiaddr)>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| super::split_peer_suffix(s).map_err(de::Error::custom))
+            .collect()
+    }
+}
+
+/// `seed_peers` 展开为每个地址一项的字符串列表，反序列化时按 peer 归并（保持首次出现的顺序）
+mod seed_peers {
+    use libp2p::{Multiaddr, PeerId};
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    pub fn serialize<S: Serializer>(
+        peers: &[(PeerId, Vec<Multiaddr>)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(peers.iter().flat_map(|(peer_id, addrs)| {
+            let addrs = if addrs.is_empty() {
+                vec![Multiaddr::empty()]
+            } else {
+                addrs.clone()
+            };
+            addrs
+                .into_iter()
+                .map(|addr| super::with_peer_suffix(&addr, *peer_id).to_string())
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(PeerId, Vec<Multiaddr>)>, D::Error> {
+        let mut peers: Vec<(PeerId, Vec<Multiaddr>)> = Vec::new();
+        for s in Vec::<String>::deserialize(deserializer)? {
+            let (peer_id, addr) = super::split_peer_suffix(&s).map_err(de::Error::custom)?;
+            let index = match peers.iter().position(|(p, _)| *p == peer_id) {
+                Some(index) => index,
+                None => {
+                    peers.push((peer_id, Vec::new()));
+                    peers.len() - 1
+                }
+            };
+            if !addr.is_empty() {
+                peers[index].1.push(addr);
+            }
+        }
+        Ok(peers)
+    }
 }
 
 #[cfg(test)]
@@ -147,16 +1024,54 @@ mod tests {
         assert!(config.agent_version.starts_with("swarm-p2p/"));
         assert_eq!(config.listen_addrs.len(), 2);
         assert!(config.bootstrap_peers.is_empty());
+        assert_eq!(config.bootstrap_retry, Some(BootstrapRetry::default()));
+        assert!(config.seed_peers.is_empty());
+        assert_eq!(config.transport, TransportConfig::TcpAndQuic);
+        assert_eq!(config.security, SecurityUpgrade::Noise);
+        #[cfg(feature = "dns")]
+        assert!(matches!(config.dns, DnsConfig::System));
+        assert_eq!(config.dial_address_order, AddressOrder::Default);
+        assert_eq!(config.dial_timeout, None);
         assert!(config.enable_mdns);
         assert!(config.enable_relay_client);
+        assert!(config.auto_relay_reservations);
         assert!(config.enable_dcutr);
         assert!(config.enable_autonat);
+        assert_eq!(config.autonat_failure_threshold, 3);
+        assert!(!config.enable_upnp);
+        assert!(!config.enable_gossipsub);
         assert_eq!(config.idle_connection_timeout, Duration::from_secs(60));
         assert_eq!(config.ping_interval, Duration::from_secs(15));
         assert_eq!(config.ping_timeout, Duration::from_secs(10));
+        assert_eq!(config.identify_cache_size, 100);
+        assert!(config.identify_push_updates);
         assert_eq!(config.kad_query_timeout, Duration::from_secs(60));
+        assert!(config.kad_auto_bootstrap_interval.is_none());
+        assert!(!config.kad_record_filtering);
+        assert!(config.record_validator.is_none());
+        assert!(config.kad_store.is_none());
+        let store = config.memory_store_config();
+        let libp2p_default = MemoryStoreConfig::default();
+        assert_eq!(store.max_records, libp2p_default.max_records);
+        assert_eq!(store.max_value_bytes, libp2p_default.max_value_bytes);
+        assert_eq!(
+            store.max_providers_per_key,
+            libp2p_default.max_providers_per_key
+        );
+        assert_eq!(store.max_provided_keys, libp2p_default.max_provided_keys);
         assert_eq!(config.req_resp_protocol, "/swarm-p2p/req/1.0.0");
         assert_eq!(config.req_resp_timeout, Duration::from_secs(120));
+        assert_eq!(config.max_request_size, 1024 * 1024);
+        assert_eq!(config.max_response_size, 10 * 1024 * 1024);
+        assert!(config.handshake.is_none());
+        assert!(config.diagnostics_interval.is_none());
+        assert!(config.target_connection_count.is_none());
+        assert_eq!(config.max_pending_responses, 1024);
+        assert!(config.max_concurrent_requests_per_peer.is_none());
+        assert_eq!(config.command_channel_size, 32);
+        assert_eq!(config.command_timeout, Some(Duration::from_secs(300)));
+        assert_eq!(config.event_channel_size, 64);
+        assert_eq!(config.event_overflow, EventOverflow::Block);
     }
 
     #[test]
@@ -187,6 +1102,129 @@ mod tests {
         assert_eq!(config.req_resp_protocol, "/test/req/1.0.0");
     }
 
+    #[test]
+    fn address_order_sorts_by_family() {
+        let addrs: Vec<Multiaddr> = [
+            "/ip6/::1/tcp/1",
+            "/dns/example.com/tcp/1",
+            "/ip4/127.0.0.1/tcp/1",
+            "/dns6/example.com/tcp/1",
+            "/ip4/127.0.0.1/udp/1/quic-v1",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        let sorted = |order: AddressOrder| {
+            let mut addrs = addrs.clone();
+            order.sort(&mut addrs);
+            addrs.iter().map(|a| a.to_string()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sorted(AddressOrder::Default),
+            addrs.iter().map(|a| a.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sorted(AddressOrder::PreferIpv4),
+            [
+                "/ip4/127.0.0.1/tcp/1",
+                "/ip4/127.0.0.1/udp/1/quic-v1",
+                "/dns/example.com/tcp/1",
+                "/ip6/::1/tcp/1",
+                "/dns6/example.com/tcp/1",
+            ]
+        );
+        assert_eq!(
+            sorted(AddressOrder::PreferIpv6),
+            [
+                "/ip6/::1/tcp/1",
+                "/dns6/example.com/tcp/1",
+                "/dns/example.com/tcp/1",
+                "/ip4/127.0.0.1/tcp/1",
+                "/ip4/127.0.0.1/udp/1/quic-v1",
+            ]
+        );
+    }
+
+    #[test]
+    fn transport_supports_addrs() {
+        let tcp: Multiaddr = "/ip4/0.0.0.0/tcp/0".parse().unwrap();
+        let quic: Multiaddr = "/ip4/0.0.0.0/udp/0/quic-v1".parse().unwrap();
+        let circuit: Multiaddr = "/ip4/1.2.3.4/tcp/4001/p2p-circuit".parse().unwrap();
+
+        assert!(TransportConfig::TcpAndQuic.supports(&tcp));
+        assert!(TransportConfig::TcpAndQuic.supports(&quic));
+        assert!(!TransportConfig::QuicOnly.supports(&tcp));
+        assert!(TransportConfig::QuicOnly.supports(&quic));
+        assert!(TransportConfig::TcpOnly.supports(&tcp));
+        assert!(!TransportConfig::TcpOnly.supports(&quic));
+        assert!(TransportConfig::QuicOnly.supports(&circuit));
+
+        let memory: Multiaddr = "/memory/0".parse().unwrap();
+        assert!(TransportConfig::Memory.supports(&memory));
+        assert!(!TransportConfig::Memory.supports(&tcp));
+        assert!(!TransportConfig::Memory.supports(&quic));
+        assert!(!TransportConfig::TcpAndQuic.supports(&memory));
+    }
+
+    #[test]
+    fn handshake_info_features() {
+        let info = HandshakeInfo::new("2.1").with_features(["sync", "stream"]);
+        assert!(info.supports("sync"));
+        assert!(!info.supports("video"));
+
+        let config = NodeConfig::default().with_handshake(info.clone());
+        assert_eq!(config.handshake, Some(info));
+    }
+
+    #[test]
+    fn record_validator_enables_filtering() {
+        let config = NodeConfig::default().with_record_validator(|r| !r.value.is_empty());
+        assert!(config.kad_record_filtering);
+
+        let validator = config.record_validator.unwrap();
+        let key = libp2p::kad::RecordKey::new(&b"k");
+        assert!(validator.validate(&Record::new(key.clone(), b"v".to_vec())));
+        assert!(!validator.validate(&Record::new(key, Vec::new())));
+    }
+
+    #[test]
+    fn bootstrap_retry_backoff() {
+        let retry =
+            BootstrapRetry::new(Duration::from_secs(1), Duration::from_secs(10)).with_jitter(0.0);
+        assert_eq!(retry.delay(0, 0.5), Duration::from_secs(1));
+        assert_eq!(retry.delay(3, 0.5), Duration::from_secs(8));
+        assert_eq!(retry.delay(4, 0.5), Duration::from_secs(10));
+        assert_eq!(retry.delay(u32::MAX, 0.5), Duration::from_secs(10));
+
+        let retry = retry.with_jitter(0.5);
+        assert_eq!(retry.delay(0, 0.0), Duration::from_millis(500));
+        assert_eq!(retry.delay(0, 0.5), Duration::from_secs(1));
+        assert!(retry.delay(0, 0.999) < Duration::from_millis(1500));
+
+        let retry = retry.with_jitter(f64::NAN);
+        assert_eq!(retry.jitter, 0.0);
+        assert_eq!(retry.delay(0, 0.0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn out_of_range_jitter_from_config_is_clamped() {
+        let config =
+            NodeConfig::from_json_str(r#"{"bootstrapRetry": {"base": "1s", "jitter": 1.5}}"#)
+                .unwrap();
+        let retry = config.bootstrap_retry.unwrap();
+        // 按 1.0 计算：下限为 0，上限不超过 2 倍
+        assert_eq!(retry.delay(0, 0.0), Duration::ZERO);
+        assert!(retry.delay(0, 0.999) < Duration::from_secs(2));
+
+        let config =
+            NodeConfig::from_json_str(r#"{"bootstrapRetry": {"base": "1s", "jitter": -3.0}}"#)
+                .unwrap();
+        let retry = config.bootstrap_retry.unwrap();
+        assert_eq!(retry.delay(0, 0.0), Duration::from_secs(1));
+        assert_eq!(retry.delay(0, 0.999), Duration::from_secs(1));
+    }
+
     #[test]
     fn clone_is_independent() {
         let config = NodeConfig::default();
@@ -195,4 +1233,68 @@ mod tests {
         assert!(config.enable_mdns);
         assert!(!config2.enable_mdns);
     }
+
+    #[test]
+    fn loads_from_toml() {
+        let peer = PeerId::random();
+        let config = NodeConfig::from_toml_str(&format!(
+            r#"
+            protocolVersion = "/myapp/1.0.0"
+            listenAddrs = ["/ip4/0.0.0.0/udp/0/quic-v1"]
+            bootstrapPeers = ["/ip4/1.2.3.4/tcp/4001/p2p/{peer}"]
+            seedPeers = ["/ip4/1.2.3.4/tcp/1/p2p/{peer}", "/ip4/5.6.7.8/tcp/1/p2p/{peer}"]
+            transport = "quicOnly"
+            idleConnectionTimeout = "2m"
+            commandTimeout = "30s"
+
+            [bootstrapRetry]
+            base = "500ms"
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(config.protocol_version, "/myapp/1.0.0");
+        assert_eq!(config.transport, TransportConfig::QuicOnly);
+        assert_eq!(
+            config.bootstrap_peers,
+            vec![(peer, "/ip4/1.2.3.4/tcp/4001".parse().unwrap())]
+        );
+        assert_eq!(config.seed_peers.len(), 1);
+        assert_eq!(config.seed_peers[0].1.len(), 2);
+        assert_eq!(config.idle_connection_timeout, Duration::from_secs(120));
+        assert_eq!(config.command_timeout, Some(Duration::from_secs(30)));
+        let retry = config.bootstrap_retry.unwrap();
+        assert_eq!(retry.base, Duration::from_millis(500));
+        assert_eq!(retry.max, BootstrapRetry::default().max);
+        // 缺省字段取默认值
+        assert!(config.enable_mdns);
+        assert_eq!(config.req_resp_protocol, "/swarm-p2p/req/1.0.0");
+
+        let err =
+            NodeConfig::from_toml_str(r#"bootstrapPeers = ["/ip4/1.2.3.4/tcp/4001"]"#).unwrap_err();
+        assert!(matches!(err, Error::Config(_)), "{err}");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let config = NodeConfig::new("/myapp/1.0.0", "myapp/1.0.0")
+            .with_bootstrap_peers(vec![(a, "/ip4/1.2.3.4/tcp/4001".parse().unwrap())])
+            .with_seed_peers(vec![
+                (b, vec![]),
+                (a, vec!["/ip6/::1/tcp/1".parse().unwrap()]),
+            ])
+            .with_bootstrap_retry(None)
+            .with_handshake(HandshakeInfo::new("1.0").with_features(["files"]))
+            .with_event_overflow(EventOverflow::DropOldest);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded = NodeConfig::from_json_str(&json).unwrap();
+        assert_eq!(loaded.bootstrap_peers, config.bootstrap_peers);
+        assert_eq!(loaded.seed_peers, config.seed_peers);
+        assert_eq!(loaded.bootstrap_retry, None);
+        assert_eq!(loaded.handshake, config.handshake);
+        assert_eq!(loaded.event_overflow, EventOverflow::DropOldest);
+        assert_eq!(loaded.command_timeout, config.command_timeout);
+    }
 }
@@ -0,0 +1,223 @@
+//! 联系人名片：用于二维码 / 链接等带外方式交换节点信息
+//!
+//! 线上格式为 URL：`swarm://<peer_id>?v=1&addr=<multiaddr>&addr=...&name=<名称>`。
+//! - 参数值按百分号编码，`addr` 可重复
+//! - 未知参数直接忽略，便于后续版本增加字段
+//! - `v` 为格式主版本，缺省视为 1；高于当前支持的版本时解码失败
+
+use std::fmt;
+use std::str::FromStr;
+
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// 名片 URL 前缀
+pub const CONTACT_CARD_SCHEME: &str = "swarm://";
+
+/// 当前支持的格式版本
+pub const CONTACT_CARD_VERSION: u32 = 1;
+
+/// 联系人名片
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactCard {
+    pub peer_id: PeerId,
+    /// 可拨号的地址（可为空，此时需借助 DHT / mDNS 查找）
+    pub addrs: Vec<Multiaddr>,
+    /// 展示名称
+    pub name: Option<String>,
+}
+
+impl ContactCard {
+    pub fn new(peer_id: PeerId) -> Self {
+        Self {
+            peer_id,
+            addrs: Vec::new(),
+            name: None,
+        }
+    }
+
+    pub fn with_addrs(mut self, addrs: Vec<Multiaddr>) -> Self {
+        self.addrs = addrs;
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// 编码为 `swarm://` URL
+    pub fn encode(&self) -> String {
+        let mut url = format!(
+            "{}{}?v={}",
+            CONTACT_CARD_SCHEME, self.peer_id, CONTACT_CARD_VERSION
+        );
+        for addr in &self.addrs {
+            url.push_str("&addr=");
+            url.push_str(&percent_encode(&addr.to_string()));
+        }
+        if let Some(name) = &self.name {
+            url.push_str("&name=");
+            url.push_str(&percent_encode(name));
+        }
+        url
+    }
+
+    /// 从 `swarm://` URL 解码
+    pub fn decode(s: &str) -> Result<Self> {
+        let rest = s
+            .trim()
+            .strip_prefix(CONTACT_CARD_SCHEME)
+            .ok_or_else(|| invalid(format!("missing {} scheme", CONTACT_CARD_SCHEME)))?;
+        let (peer_id, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let peer_id = PeerId::from_str(peer_id.trim_end_matches('/'))
+            .map_err(|e| invalid(format!("invalid peer id: {}", e)))?;
+
+        let mut card = ContactCard::new(peer_id);
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            match key {
+                "v" => {
+                    let version: u32 = value
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid version: {}", value)))?;
+                    if version > CONTACT_CARD_VERSION {
+                        return Err(invalid(format!(
+                            "unsupported version {} (supported up to {})",
+                            version, CONTACT_CARD_VERSION
+                        )));
+                    }
+                }
+                "addr" => {
+                    let addr = percent_decode(value)?
+                        .parse()
+                        .map_err(|e| invalid(format!("invalid address: {}", e)))?;
+                    card.addrs.push(addr);
+                }
+                "name" => card.name = Some(percent_decode(value)?),
+                // 未知字段：来自更新的次版本，忽略
+                _ => {}
+            }
+        }
+        Ok(card)
+    }
+}
+
+impl fmt::Display for ContactCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for ContactCard {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::decode(s)
+    }
+}
+
+fn invalid(msg: String) -> Error {
+    Error::ContactCard(msg)
+}
+
+/// 除 URL 非保留字符和 `/`、`:` 外一律编码（multiaddr 保持可读）
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/:".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+fn percent_decode(s: &str) -> Result<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let byte = s
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| invalid(format!("invalid percent escape in {}", s)))?;
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| invalid("value is not valid UTF-8".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card() -> ContactCard {
+        ContactCard::new(PeerId::random())
+            .with_addrs(vec![
+                "/ip4/192.168.1.2/tcp/4001".parse().unwrap(),
+                "/dns4/example.com/udp/4001/quic-v1".parse().unwrap(),
+            ])
+            .with_name("小明 & Co?")
+    }
+
+    #[test]
+    fn roundtrip() {
+        let card = card();
+        let encoded = card.encode();
+        assert!(encoded.starts_with("swarm://"));
+        assert!(encoded.contains("addr=/ip4/192.168.1.2/tcp/4001"));
+        assert_eq!(ContactCard::decode(&encoded).unwrap(), card);
+        assert_eq!(encoded.parse::<ContactCard>().unwrap(), card);
+    }
+
+    #[test]
+    fn minimal_card_without_query() {
+        let peer_id = PeerId::random();
+        let card = ContactCard::decode(&format!("swarm://{}", peer_id)).unwrap();
+        assert_eq!(card, ContactCard::new(peer_id));
+    }
+
+    #[test]
+    fn ignores_unknown_fields() {
+        let card = card();
+        let encoded = format!("{}&avatar=abc&flag", card.encode());
+        assert_eq!(ContactCard::decode(&encoded).unwrap(), card);
+    }
+
+    #[test]
+    fn rejects_newer_version() {
+        let encoded = format!("swarm://{}?v=2", PeerId::random());
+        let err = ContactCard::decode(&encoded).unwrap_err();
+        assert!(err.to_string().contains("unsupported version"));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let peer_id = PeerId::random();
+        for input in [
+            String::new(),
+            "http://example.com".into(),
+            "swarm://not-a-peer-id".into(),
+            format!("swarm://{}?v=abc", peer_id),
+            format!("swarm://{}?addr=not-an-addr", peer_id),
+            format!("swarm://{}?name=%ZZ", peer_id),
+            format!("swarm://{}?name=%FF", peer_id),
+        ] {
+            assert!(
+                matches!(ContactCard::decode(&input), Err(Error::ContactCard(_))),
+                "should reject {:?}",
+                input
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use libp2p::noise;
+use libp2p::{PeerId, kad, noise};
 use std::io;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -20,15 +20,92 @@ pub enum Error {
     #[error("Dial error: {0}")]
     Dial(String),
 
+    /// 拨号在 `dial_timeout` 内未完成
+    #[error("Dial timed out")]
+    DialTimeout,
+
     #[error("Listen error: {0}")]
     Listen(String),
 
     #[error("Kad error: {0}")]
     Kad(String),
 
+    /// Kad 查询超时（可重试）
+    #[error("Kad query timed out")]
+    KadTimeout,
+
+    /// Kad 查询正常结束，但没有找到记录 / provider
+    #[error("Kad record not found")]
+    KadNotFound,
+
+    /// 路由表为空，没有可询问的节点
+    #[error("Kad routing table has no peers to query")]
+    KadNoPeers,
+
+    /// 路由表为空，配置的引导节点也都无法连上
+    #[error("No bootstrap peer reachable to populate the Kad routing table")]
+    KadBootstrapUnreachable,
+
+    /// 本地 Kad 存储拒绝写入（容量已满或记录过大）
+    #[error("Kad store error: {0}")]
+    KadStore(#[from] kad::store::Error),
+
+    #[error("Kad quorum failed: stored on {stored} of {quorum} required peers")]
+    KadQuorumFailed { stored: usize, quorum: usize },
+
+    /// 读取记录时返回相同取值的节点数不足 quorum（取值冲突或副本不够）
+    #[error("Kad record quorum failed: {agreed} of {quorum} required peers agree")]
+    KadRecordQuorumFailed { agreed: usize, quorum: usize },
+
     #[error("Request-response error: {0}")]
     RequestResponse(String),
 
+    #[error("Gossipsub error: {0}")]
+    Gossipsub(String),
+
+    #[error("Relay reservation error: {0}")]
+    Relay(String),
+
+    #[error("Hole punch failed: {0}")]
+    HolePunch(String),
+
     #[error("Behaviour error: {0}")]
     Behaviour(String),
+
+    #[error("Peer disconnected: {0}")]
+    PeerDisconnected(PeerId),
+
+    #[error("Command channel full")]
+    CommandChannelFull,
+
+    #[error("Command channel closed")]
+    CommandChannelClosed,
+
+    #[error("Command timed out")]
+    CommandTimeout,
+
+    #[error("Request timed out")]
+    RequestTimeout,
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    #[error("CBOR codec error: {0}")]
+    Cbor(String),
+
+    #[error("Invalid contact card: {0}")]
+    ContactCard(String),
+
+    #[error("Invalid identity key: {0}")]
+    Identity(String),
+
+    #[error("Invalid record key: {0}")]
+    RecordKey(String),
+
+    #[error("Invalid record signature: {0}")]
+    RecordSignature(String),
+
+    /// 配置无法解析，或所需功能未在配置中开启
+    #[error("Config error: {0}")]
+    Config(String),
 }
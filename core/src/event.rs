@@ -1,20 +1,127 @@
+use libp2p::swarm::ConnectionId;
 use libp2p::{Multiaddr, PeerId};
 use serde::{Deserialize, Serialize};
 
+use crate::config::HandshakeInfo;
+
 /// NAT 状态
 ///
-/// 仅区分 Public 和 Unknown：AutoNAT v2 按地址逐一探测，
-/// 单次失败无法断定节点在 NAT 后面，因此不设 Private 状态。
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// AutoNAT v2 按地址逐一探测，单次失败无法断定节点在 NAT 后面，
+/// 因此只有累计 `autonat_failure_threshold` 个不同服务器探测失败后才判定为 Private。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum NatStatus {
     /// 公网可达（至少一个地址通过 AutoNAT 验证）
     Public,
-    /// 未知（尚未探测或探测未成功）
+    /// 多个服务器均无法回拨，判定为 NAT 后不可达
+    Private,
+    /// 未知（尚未探测或失败次数不足以判定）
     #[default]
     Unknown,
 }
 
+/// Kad 运行模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KadMode {
+    /// 只发起查询，不响应对端的 DHT 请求
+    Client,
+    /// 响应对端的 DHT 请求，可作为记录副本的存放节点
+    Server,
+}
+
+impl From<libp2p::kad::Mode> for KadMode {
+    fn from(mode: libp2p::kad::Mode) -> Self {
+        match mode {
+            libp2p::kad::Mode::Client => KadMode::Client,
+            libp2p::kad::Mode::Server => KadMode::Server,
+        }
+    }
+}
+
+/// 对端发来的 Kad 请求类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KadRequestKind {
+    /// 查询离某个 key 最近的节点
+    FindNode,
+    /// 查询记录
+    GetRecord,
+    /// 写入记录
+    PutRecord,
+    /// 查询某个 key 的 Provider
+    GetProviders,
+    /// 登记 Provider
+    AddProvider,
+}
+
+impl From<&libp2p::kad::InboundRequest> for KadRequestKind {
+    fn from(request: &libp2p::kad::InboundRequest) -> Self {
+        use libp2p::kad::InboundRequest;
+        match request {
+            InboundRequest::FindNode { .. } => KadRequestKind::FindNode,
+            InboundRequest::GetRecord { .. } => KadRequestKind::GetRecord,
+            InboundRequest::PutRecord { .. } => KadRequestKind::PutRecord,
+            InboundRequest::GetProvider { .. } => KadRequestKind::GetProviders,
+            InboundRequest::AddProvider { .. } => KadRequestKind::AddProvider,
+        }
+    }
+}
+
+/// UPnP 端口映射状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpnpMappingStatus {
+    /// 端口映射成功，映射后的公网地址已登记为外部地址
+    Mapped,
+    /// 续期失败，映射地址已失效
+    Expired,
+    /// 局域网内没有找到支持 UPnP 的网关
+    GatewayNotFound,
+    /// 网关自身没有公网地址（多层 NAT），映射不会生效
+    NonRoutableGateway,
+}
+
+/// 连接（或中继电路）的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionDirection {
+    /// 对端连入本节点
+    Inbound,
+    /// 本节点发起
+    Outbound,
+}
+
+/// Ping 失败原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PingFailureKind {
+    /// 超时未收到回应（对端可能已失联）
+    Timeout,
+    /// 对端不支持 ping 协议
+    Unsupported,
+    /// 其他错误（如连接中断）
+    Other,
+}
+
+/// 传输层错误分类
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransportErrorKind {
+    /// 握手超时（TCP 连接、Noise/TLS 或 QUIC 握手未在时限内完成）
+    HandshakeTimeout,
+    /// 加密握手失败（Noise/TLS 认证错误等）
+    HandshakeFailed,
+    /// 协议协商失败（对端不支持所需的安全/多路复用协议）
+    ProtocolMismatch,
+    /// 连接被重置或意外中断
+    Reset,
+    /// 地址不被任何 transport 支持
+    UnsupportedAddress,
+    /// 其他 I/O 错误
+    Other,
+}
+
 /// 对外暴露的节点事件
 ///
 /// 泛型参数 `Req` 是 request-response 协议的请求类型，
@@ -28,14 +135,73 @@ pub enum NodeEvent<Req = ()> {
     /// 发现 peers（mDNS）
     PeersDiscovered { peers: Vec<(PeerId, Multiaddr)> },
 
+    /// mDNS 发现的地址过期（对端离开局域网或停止广播）
+    ///
+    /// 不代表连接已断开；未被连接使用的地址同时从 Kad 路由表移除。
+    PeersExpired { peers: Vec<(PeerId, Multiaddr)> },
+
+    /// 开始出站拨号（mDNS / 引导节点 / 固定 peer 的自动拨号，以及 `dial`、`dial_many`、`dial_addr`）
+    ///
+    /// 之后以 `PeerConnected` 或同一 `connection_id` 的 `ConnectionFailed` 结束。
+    /// 同一 peer 已有拨号在进行时不重复上报；`addrs` 为拨号时已知的候选地址：
+    /// 按地址拨号或设置了 `dial_address_order` 偏好时给出，默认顺序（由 swarm 自行收集地址）
+    /// 及使用自定义 `DialOpts` 拨号时为空。
+    #[serde(rename_all = "camelCase")]
+    DialStarted {
+        peer_id: Option<PeerId>,
+        #[serde(with = "connection_id")]
+        connection_id: ConnectionId,
+        addrs: Vec<Multiaddr>,
+    },
+
     /// peer 已连接
+    ///
+    /// 只在与该 peer 的第一条连接建立时上报，端点信息也来自这条连接；之后的连接
+    /// （如 DCUtR 打洞成功后的直连）不再上报，可用 `NetClient::connection_info` 查询全部连接。
     #[serde(rename_all = "camelCase")]
-    PeerConnected { peer_id: PeerId },
+    PeerConnected {
+        peer_id: PeerId,
+        /// 对端地址（出站为拨号地址，入站为对端的回连地址）
+        remote_addr: Multiaddr,
+        direction: ConnectionDirection,
+        /// 是否经中继
+        relayed: bool,
+    },
 
     /// peer 已断开
     #[serde(rename_all = "camelCase")]
     PeerDisconnected { peer_id: PeerId },
 
+    /// 连接数超过 `target_connection_count`，与该 peer 的连接被主动断开
+    ///
+    /// 随后还会收到该 peer 的 `PeerDisconnected`。
+    #[serde(rename_all = "camelCase")]
+    ConnectionPruned { peer_id: PeerId },
+
+    /// 连接建立失败（出站拨号或入站握手）
+    ///
+    /// 出站时 `peer_id` 为拨号目标（按地址拨号时可能未知），
+    /// `connection_id` 可与 `DialOpts::connection_id()` 对应。
+    #[serde(rename_all = "camelCase")]
+    ConnectionFailed {
+        peer_id: Option<PeerId>,
+        #[serde(with = "connection_id")]
+        connection_id: ConnectionId,
+        /// 是否为入站连接
+        inbound: bool,
+        error: String,
+    },
+
+    /// 传输层错误（握手超时、协议不匹配、连接重置等）
+    ///
+    /// 与 `ConnectionFailed` 同时发出，仅针对传输层失败；
+    /// 对端明确拒绝连接（connection refused）不在此列。
+    TransportError {
+        addr: Multiaddr,
+        kind: TransportErrorKind,
+        error: String,
+    },
+
     /// 收到 identify 信息
     #[serde(rename_all = "camelCase")]
     IdentifyReceived {
@@ -44,6 +210,20 @@ pub enum NodeEvent<Req = ()> {
         protocol_version: String,
     },
 
+    /// 对端通过 identify 告知它观察到的本节点地址
+    ///
+    /// 只是候选地址，不代表可达；AutoNAT 迟迟未确认时可据此排查 NAT 映射
+    /// （如不同 peer 观察到的端口是否一致）。
+    #[serde(rename_all = "camelCase")]
+    ObservedAddr { by_peer: PeerId, addr: Multiaddr },
+
+    /// 与 peer 完成应用层握手（需配置 `handshake`）
+    #[serde(rename_all = "camelCase")]
+    HandshakeCompleted {
+        peer_id: PeerId,
+        info: HandshakeInfo,
+    },
+
     /// Ping 成功，返回延迟
     #[serde(rename_all = "camelCase")]
     PingSuccess {
@@ -52,7 +232,19 @@ pub enum NodeEvent<Req = ()> {
         rtt_ms: u64,
     },
 
-    /// NAT 状态变化
+    /// Ping 失败（超时、协议不支持等）
+    ///
+    /// 可作为对端失联的提前信号，早于空闲超时关闭连接。
+    #[serde(rename_all = "camelCase")]
+    PingFailure {
+        peer_id: PeerId,
+        kind: PingFailureKind,
+        error: String,
+    },
+
+    /// NAT 状态变化（仅在状态实际切换时发出）
+    ///
+    /// 已确认的外部地址全部失效时从 `Public` 回到 `Unknown`，等待 AutoNAT 重新判定。
     #[serde(rename_all = "camelCase")]
     NatStatusChanged {
         /// 新的 NAT 状态
@@ -61,14 +253,53 @@ pub enum NodeEvent<Req = ()> {
         public_addr: Option<Multiaddr>,
     },
 
+    /// 新的外部地址被确认可达（如 AutoNAT 回拨成功）
+    ///
+    /// 每个地址只上报一次（地址失效后再次确认会重新上报），
+    /// 与 `NatStatusChanged` 相互独立：确认第二个地址（如 IPv6）时不会再发出 `Public`。
+    ExternalAddrConfirmed { addr: Multiaddr },
+
+    /// UPnP 端口映射结果（需开启 `enable_upnp`）
+    ///
+    /// 映射成功时还会以 `ExternalAddrConfirmed` 上报同一地址；
+    /// 网关拒绝首次映射时 libp2p 只记录日志，不会发出该事件。
+    #[serde(rename_all = "camelCase")]
+    UpnpMappingResult {
+        status: UpnpMappingStatus,
+        /// 映射后的公网地址，网关不可用时为 `None`
+        addr: Option<Multiaddr>,
+    },
+
+    /// Kad 模式切换（自动模式下随外部地址确认/失效而变化）
+    ///
+    /// 处于 Client 模式时 `put_record` 可能因 `QuorumFailed` 失败，
+    /// 可等待切换到 Server 后再发布。配置 `kad_server_mode` 强制 Server 时不会发出。
+    KadModeChanged { mode: KadMode },
+
+    /// 一轮自动 Kad bootstrap 结束（需配置 `kad_auto_bootstrap_interval`）
+    #[serde(rename_all = "camelCase")]
+    KadBootstrapCompleted {
+        /// 结束时仍待查询的 bucket 数，超时结束时为 0
+        num_remaining: u32,
+    },
+
+    /// 收到对端的 Kad 请求（仅 Server 模式下会被询问），用于观察 DHT 负载
+    KadInboundRequest { request: KadRequestKind },
+
     /// DCUtR 打洞成功，连接已升级为直连
     #[serde(rename_all = "camelCase")]
-    HolePunchSucceeded { peer_id: PeerId },
+    HolePunchSucceeded {
+        peer_id: PeerId,
+        /// 发起打洞所经的中继（中继连接已先行关闭时为 `None`）
+        relay_peer_id: Option<PeerId>,
+    },
 
-    /// DCUtR 打洞失败
+    /// DCUtR 打洞失败，可调用 `NetClient::upgrade_connection` 重试
     #[serde(rename_all = "camelCase")]
     HolePunchFailed {
         peer_id: PeerId,
+        /// 发起打洞所经的中继（中继连接已先行关闭时为 `None`）
+        relay_peer_id: Option<PeerId>,
         /// 失败原因
         error: String,
     },
@@ -81,6 +312,58 @@ pub enum NodeEvent<Req = ()> {
         renewal: bool,
     },
 
+    /// Relay 预约失败或失效（被中继拒绝、预约请求出错或与中继的连接断开），
+    /// 可据此回退到其他中继。`cancel_relay` 主动放弃的预约不会发出
+    #[serde(rename_all = "camelCase")]
+    RelayReservationFailed {
+        relay_peer_id: PeerId,
+        /// 失败原因
+        error: String,
+    },
+
+    /// 经中继的连接已建立（电路握手完成），可据此提示发起 DCUtR 升级或显示连接质量
+    #[serde(rename_all = "camelCase")]
+    RelayCircuitEstablished {
+        peer_id: PeerId,
+        relay_peer_id: PeerId,
+        direction: ConnectionDirection,
+    },
+
+    /// 经中继建立的入站电路已关闭
+    #[serde(rename_all = "camelCase")]
+    RelayCircuitClosed {
+        relay_peer_id: PeerId,
+        /// 通过该电路连入的 peer
+        src_peer_id: PeerId,
+    },
+
+    /// 本节点作为中继（`enable_relay_server`）接受了其他 peer 的预约
+    #[serde(rename_all = "camelCase")]
+    RelayServerReservationAccepted {
+        peer_id: PeerId,
+        /// 是否为续约（而非首次预约）
+        renewal: bool,
+    },
+
+    /// 其他 peer 在本节点上的中继预约已结束（对端断开或到期未续约）
+    #[serde(rename_all = "camelCase")]
+    RelayServerReservationClosed { peer_id: PeerId },
+
+    /// 周期性自检摘要（需配置 `diagnostics_interval`）
+    #[serde(rename_all = "camelCase")]
+    Diagnostics {
+        /// 当前连接数
+        connections: usize,
+        /// Kad 路由表中的节点数
+        routing_table_peers: usize,
+        /// 当前 NAT 状态
+        nat_status: NatStatus,
+        /// 已接受的 relay 预约数
+        relay_reservations: usize,
+        /// 等待回复的 inbound request 数
+        pending_channels: usize,
+    },
+
     /// 收到对端的 request-response 请求
     #[serde(rename_all = "camelCase")]
     InboundRequest {
@@ -90,4 +373,125 @@ pub enum NodeEvent<Req = ()> {
         /// 请求内容
         request: Req,
     },
+
+    /// `send_response` 的回复已发出，对应的 `pending_id` 就此完成
+    #[serde(rename_all = "camelCase")]
+    ResponseSent { peer_id: PeerId, pending_id: u64 },
+
+    /// inbound request 未能完成（如回复前对端断开、回复超时），
+    /// 对应的 `pending_id` 已失效，可取消为该请求启动的工作
+    #[serde(rename_all = "camelCase")]
+    InboundRequestFailed {
+        peer_id: PeerId,
+        pending_id: u64,
+        /// 失败原因
+        error: String,
+    },
+
+    /// 收到对端的流式请求
+    ///
+    /// 通过 `NetClient::send_stream_chunk` 逐块回复，
+    /// 最后调用 `NetClient::end_stream`（或 `fail_stream`）结束。
+    #[serde(rename_all = "camelCase")]
+    InboundRequestStream {
+        peer_id: PeerId,
+        /// 流的唯一标识（传回 `send_stream_chunk` / `end_stream` 时使用）
+        pending_id: u64,
+        /// 请求内容
+        request: Req,
+    },
+
+    /// 待回复的 inbound request 已达上限，新请求被拒绝
+    #[serde(rename_all = "camelCase")]
+    InboundRequestRejected { peer_id: PeerId, reason: String },
+
+    /// 收到已订阅主题的 Gossipsub 消息（需开启 `enable_gossipsub`）
+    #[serde(rename_all = "camelCase")]
+    GossipMessage {
+        /// 主题名
+        topic: String,
+        /// 消息发布者（签名校验通过的原始来源，而非转发者）
+        source: Option<PeerId>,
+        /// 消息内容
+        data: Vec<u8>,
+    },
+
+    /// 节点已优雅关闭，之后不会再有事件
+    Shutdown,
+}
+
+impl<Req> NodeEvent<Req> {
+    /// 事件涉及的单个 peer，`EventReceiver::filter_peer` 据此分发
+    ///
+    /// 中继预约事件为中继节点，`RelayCircuitClosed` 为经电路连入的 peer；
+    /// 不针对单个 peer 的事件（`Listening`、`PeersDiscovered`、`GossipMessage` 等）返回 `None`。
+    pub fn peer_id(&self) -> Option<PeerId> {
+        match self {
+            Self::PeerConnected { peer_id, .. }
+            | Self::PeerDisconnected { peer_id }
+            | Self::ConnectionPruned { peer_id }
+            | Self::IdentifyReceived { peer_id, .. }
+            | Self::ObservedAddr {
+                by_peer: peer_id, ..
+            }
+            | Self::HandshakeCompleted { peer_id, .. }
+            | Self::PingSuccess { peer_id, .. }
+            | Self::PingFailure { peer_id, .. }
+            | Self::HolePunchSucceeded { peer_id, .. }
+            | Self::HolePunchFailed { peer_id, .. }
+            | Self::RelayReservationAccepted {
+                relay_peer_id: peer_id,
+                ..
+            }
+            | Self::RelayReservationFailed {
+                relay_peer_id: peer_id,
+                ..
+            }
+            | Self::RelayCircuitEstablished { peer_id, .. }
+            | Self::RelayCircuitClosed {
+                src_peer_id: peer_id,
+                ..
+            }
+            | Self::RelayServerReservationAccepted { peer_id, .. }
+            | Self::RelayServerReservationClosed { peer_id }
+            | Self::InboundRequest { peer_id, .. }
+            | Self::ResponseSent { peer_id, .. }
+            | Self::InboundRequestFailed { peer_id, .. }
+            | Self::InboundRequestStream { peer_id, .. }
+            | Self::InboundRequestRejected { peer_id, .. } => Some(*peer_id),
+            Self::DialStarted { peer_id, .. } | Self::ConnectionFailed { peer_id, .. } => *peer_id,
+            Self::Listening { .. }
+            | Self::PeersDiscovered { .. }
+            | Self::PeersExpired { .. }
+            | Self::TransportError { .. }
+            | Self::NatStatusChanged { .. }
+            | Self::ExternalAddrConfirmed { .. }
+            | Self::UpnpMappingResult { .. }
+            | Self::KadModeChanged { .. }
+            | Self::KadBootstrapCompleted { .. }
+            | Self::KadInboundRequest { .. }
+            | Self::Diagnostics { .. }
+            | Self::GossipMessage { .. }
+            | Self::Shutdown => None,
+        }
+    }
+}
+
+/// `ConnectionId` 以数字形式序列化
+mod connection_id {
+    use libp2p::swarm::ConnectionId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &ConnectionId, serializer: S) -> Result<S::Ok, S::Error> {
+        // ConnectionId 只通过 Display 暴露内部数值
+        let id: usize = id.to_string().parse().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_u64(id as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ConnectionId, D::Error> {
+        let id = u64::deserialize(deserializer)?;
+        Ok(ConnectionId::new_unchecked(id as usize))
+    }
 }
@@ -0,0 +1,102 @@
+//! 节点身份（密钥对）的导入导出与持久化
+//!
+//! 密钥以 libp2p 的 protobuf 编码保存，与 bootstrap 节点的 `identity.key` 格式一致，
+//! 两边生成的密钥文件可以互换使用。
+
+use std::path::Path;
+
+use libp2p::identity::Keypair;
+use tracing::info;
+
+use crate::error::{Error, Result};
+
+/// 加载或生成 Ed25519 密钥对
+///
+/// 文件存在时按 protobuf 编码解析；否则生成新密钥并保存（PeerId 从此固定）。
+/// unix 下新文件权限为 0o600。
+pub fn load_or_generate_keypair(path: impl AsRef<Path>) -> Result<Keypair> {
+    let path = path.as_ref();
+    if path.exists() {
+        info!("Loading identity from {:?}", path);
+        let bytes = std::fs::read(path).map_err(Error::Io)?;
+        return Keypair::from_protobuf_encoding(&bytes).map_err(|e| Error::Identity(e.to_string()));
+    }
+
+    info!("Generating new Ed25519 identity, saving to {:?}", path);
+    let keypair = Keypair::generate_ed25519();
+    std::fs::write(path, export_keypair(&keypair)).map_err(Error::Io)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .map_err(Error::Io)?;
+    }
+    Ok(keypair)
+}
+
+/// 由 32 字节种子（Ed25519 私钥）确定性地构造密钥对
+///
+/// 同一种子总是得到同一 PeerId，适合从上层已有的密钥派生节点身份。
+pub fn keypair_from_seed(seed: &[u8; 32]) -> Keypair {
+    // ed25519_from_bytes 会清零传入的缓冲区，这里传副本
+    Keypair::ed25519_from_bytes(*seed).expect("32 bytes is a valid ed25519 secret key")
+}
+
+/// 按 protobuf 编码导出密钥对，可用 `Keypair::from_protobuf_encoding` 还原
+///
+/// # Panics
+///
+/// 密钥类型不支持 protobuf 编码（RSA）时 panic；本 crate 只生成 Ed25519 密钥。
+pub fn export_keypair(keypair: &Keypair) -> Vec<u8> {
+    keypair
+        .to_protobuf_encoding()
+        .expect("keypair type should support protobuf encoding")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_key_is_persisted_and_reloaded() {
+        let path =
+            std::env::temp_dir().join(format!("swarm-p2p-identity-{}.key", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let generated = load_or_generate_keypair(&path).unwrap();
+        let loaded = load_or_generate_keypair(&path).unwrap();
+        assert_eq!(generated.public(), loaded.public());
+        assert_eq!(std::fs::read(&path).unwrap(), export_keypair(&generated));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn seed_is_deterministic_and_roundtrips() {
+        let seed = [7u8; 32];
+        let keypair = keypair_from_seed(&seed);
+        assert_eq!(keypair.public(), keypair_from_seed(&seed).public());
+        assert_ne!(keypair.public(), keypair_from_seed(&[8u8; 32]).public());
+
+        let decoded = Keypair::from_protobuf_encoding(&export_keypair(&keypair)).unwrap();
+        assert_eq!(decoded.public(), keypair.public());
+    }
+
+    #[test]
+    fn rejects_corrupt_key_file() {
+        let path = std::env::temp_dir().join(format!(
+            "swarm-p2p-identity-corrupt-{}.key",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"not a key").unwrap();
+        let err = load_or_generate_keypair(&path).unwrap_err();
+        assert!(matches!(err, Error::Identity(_)), "{err}");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,16 +1,31 @@
 pub mod client;
 pub mod command;
 pub mod config;
+pub mod contact;
 pub mod error;
 pub mod event;
+pub mod identity;
 pub mod pending_map;
 pub mod runtime;
+pub mod store;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod util;
 
-pub use client::{EventReceiver, NetClient};
-pub use config::NodeConfig;
+pub use client::{BroadcastEventReceiver, CommandBatch, EventReceiver, KadQuery, NetClient};
+#[cfg(feature = "dns")]
+pub use config::DnsConfig;
+pub use config::{
+    AddressOrder, BootstrapRetry, EventOverflow, HandshakeInfo, KadStoreFactory, NodeConfig,
+    RecordValidator, SecurityUpgrade, TransportConfig,
+};
+pub use contact::ContactCard;
 pub use error::*;
 pub use event::NodeEvent;
+pub use identity::{export_keypair, keypair_from_seed, load_or_generate_keypair};
 pub use libp2p;
-pub use runtime::{CborMessage, start};
-pub use util::QueryStatsInfo;
+pub use runtime::{BandwidthStats, CborMessage, ConnectionInfo, RelayReservationInfo, start};
+pub use util::{
+    Namespace, NamespacedKey, QueryStatsInfo, SignedValue, cbor_record, decode_record, sign_record,
+    verify_record,
+};
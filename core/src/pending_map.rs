@@ -70,6 +70,25 @@ where
         self.inner.lock().remove(key).map(|v| v.value)
     }
 
+    /// 原子地更新条目
+    ///
+    /// `f` 接收取出的旧值，返回需要放回的新值（`None` 表示删除）及附带结果。
+    /// 放回的条目重新计时。
+    pub fn update<R>(&self, key: K, f: impl FnOnce(Option<V>) -> (Option<V>, R)) -> R {
+        let mut map = self.inner.lock();
+        let (value, result) = f(map.remove(&key).map(|v| v.value));
+        if let Some(value) = value {
+            map.insert(
+                key,
+                PendingEntry {
+                    value,
+                    created_at: Instant::now(),
+                },
+            );
+        }
+        result
+    }
+
     pub fn len(&self) -> usize {
         self.inner.lock().len()
     }
@@ -112,6 +131,22 @@ mod tests {
         assert!(map.is_empty());
     }
 
+    #[tokio::test]
+    async fn update_replaces_or_removes() {
+        let map = PendingMap::new(Duration::from_secs(60));
+        map.insert(1u64, 10);
+
+        // 取出旧值并放回新值
+        let old = map.update(1, |v| (v.map(|v| v + 1), v));
+        assert_eq!(old, Some(10));
+        assert_eq!(map.take(&1), Some(11));
+
+        // 返回 None 时删除条目
+        map.insert(2, 20);
+        map.update(2, |_| (None, ()));
+        assert!(map.is_empty());
+    }
+
     #[tokio::test]
     async fn ttl_expiry_cleans_up() {
         // TTL = 1ms，后台清理任务的首次 tick 立即执行
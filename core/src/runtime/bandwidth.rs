@@ -0,0 +1,261 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll};
+
+use dashmap::DashMap;
+use futures::{AsyncRead, AsyncWrite};
+use libp2p::PeerId;
+use libp2p::core::muxing::{StreamMuxer, StreamMuxerEvent};
+use serde::{Deserialize, Serialize};
+
+/// 累计流量（字节）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthStats {
+    /// 收到的字节数
+    pub inbound: u64,
+    /// 发出的字节数
+    pub outbound: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    inbound: AtomicU64,
+    outbound: AtomicU64,
+}
+
+impl Counters {
+    fn stats(&self) -> BandwidthStats {
+        BandwidthStats {
+            inbound: self.inbound.load(Ordering::Relaxed),
+            outbound: self.outbound.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 按 peer 统计的流量
+///
+/// transport 为每个连接的多路复用器包一层计数，NetClient 直接读取原子计数，
+/// 无需经过事件循环。统计的是多路复用层之上的应用数据（不含 Noise/TLS 开销），
+/// 断开后 peer 的累计值仍保留，便于做配额。
+#[derive(Clone, Default)]
+pub struct PeerBandwidth {
+    peers: Arc<DashMap<PeerId, Arc<Counters>>>,
+    total: Arc<Counters>,
+}
+
+impl PeerBandwidth {
+    /// 与 `peer_id` 之间的累计流量，未连接过时为 0
+    pub fn peer(&self, peer_id: &PeerId) -> BandwidthStats {
+        self.peers
+            .get(peer_id)
+            .map(|c| c.stats())
+            .unwrap_or_default()
+    }
+
+    /// 所有连接的累计流量
+    pub fn totals(&self) -> BandwidthStats {
+        self.total.stats()
+    }
+
+    /// 为一个连接的多路复用器加上计数
+    ///
+    /// 中继电路承载在与中继节点的连接之上，其流量已计入总量，`relayed` 时只计入对端 peer。
+    pub(crate) fn meter<M>(&self, peer_id: PeerId, muxer: M, relayed: bool) -> MeteredMuxer<M> {
+        let peer = self.peers.entry(peer_id).or_default().clone();
+        let total = (!relayed).then(|| self.total.clone());
+        MeteredMuxer {
+            inner: muxer,
+            sinks: Sinks { peer, total },
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Sinks {
+    peer: Arc<Counters>,
+    total: Option<Arc<Counters>>,
+}
+
+impl Sinks {
+    fn inbound(&self, n: usize) {
+        self.peer.inbound.fetch_add(n as u64, Ordering::Relaxed);
+        if let Some(total) = &self.total {
+            total.inbound.fetch_add(n as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn outbound(&self, n: usize) {
+        self.peer.outbound.fetch_add(n as u64, Ordering::Relaxed);
+        if let Some(total) = &self.total {
+            total.outbound.fetch_add(n as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 为每个子流计数的多路复用器
+pub(crate) struct MeteredMuxer<M> {
+    inner: M,
+    sinks: Sinks,
+}
+
+impl<M> StreamMuxer for MeteredMuxer<M>
+where
+    M: StreamMuxer + Unpin,
+    M::Substream: Unpin,
+{
+    type Substream = MeteredStream<M::Substream>;
+    type Error = M::Error;
+
+    fn poll_inbound(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let sinks = self.sinks.clone();
+        Pin::new(&mut self.inner)
+            .poll_inbound(cx)
+            .map_ok(|inner| MeteredStream { inner, sinks })
+    }
+
+    fn poll_outbound(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Self::Substream, Self::Error>> {
+        let sinks = self.sinks.clone();
+        Pin::new(&mut self.inner)
+            .poll_outbound(cx)
+            .map_ok(|inner| MeteredStream { inner, sinks })
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<StreamMuxerEvent, Self::Error>> {
+        Pin::new(&mut self.inner).poll(cx)
+    }
+}
+
+/// 计数子流
+pub(crate) struct MeteredStream<S> {
+    inner: S,
+    sinks: Sinks,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for MeteredStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            self.sinks.inbound(n);
+        }
+        poll
+    }
+
+    fn poll_read_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [io::IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_read_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = poll {
+            self.sinks.inbound(n);
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for MeteredStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            self.sinks.outbound(n);
+        }
+        poll
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(n)) = poll {
+            self.sinks.outbound(n);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::io::{AsyncReadExt, AsyncWriteExt, Cursor};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn stream_counts_per_peer_and_total() {
+        let bandwidth = PeerBandwidth::default();
+        let (direct, relayed) = (PeerId::random(), PeerId::random());
+
+        let sinks = bandwidth.meter(direct, (), false).sinks;
+        let mut stream = MeteredStream {
+            inner: Cursor::new(vec![0u8; 16]),
+            sinks,
+        };
+        let mut buf = [0u8; 10];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(b"hello").await.unwrap();
+
+        let sinks = bandwidth.meter(relayed, (), true).sinks;
+        let mut stream = MeteredStream {
+            inner: Cursor::new(Vec::new()),
+            sinks,
+        };
+        stream.write_all(b"via relay").await.unwrap();
+
+        assert_eq!(
+            bandwidth.peer(&direct),
+            BandwidthStats {
+                inbound: 10,
+                outbound: 5
+            }
+        );
+        assert_eq!(
+            bandwidth.peer(&relayed),
+            BandwidthStats {
+                inbound: 0,
+                outbound: 9
+            }
+        );
+        // 中继电路不重复计入总量
+        assert_eq!(
+            bandwidth.totals(),
+            BandwidthStats {
+                inbound: 10,
+                outbound: 5
+            }
+        );
+        assert_eq!(bandwidth.peer(&PeerId::random()), BandwidthStats::default());
+    }
+}
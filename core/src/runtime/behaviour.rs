@@ -2,12 +2,34 @@ use std::time::Duration;
 use std::{fmt::Debug, num::NonZeroUsize};
 
 use libp2p::{
-    StreamProtocol, autonat, dcutr, identify, identity::Keypair, kad, mdns, ping, relay,
-    request_response, swarm::NetworkBehaviour,
+    StreamProtocol, allow_block_list, autonat, dcutr, gossipsub, identify,
+    identity::{Keypair, PublicKey},
+    kad, mdns, ping, relay, request_response,
+    swarm::{NetworkBehaviour, behaviour::toggle::Toggle},
+    upnp,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::config::NodeConfig;
+use super::LimitedCborCodec;
+use crate::command::{StreamFrame, StreamRequest};
+use crate::config::{HandshakeInfo, NodeConfig};
+use crate::store::BoxedStore;
+
+/// Kad 复制因子：记录存储到离 key 最近的 N 个节点，读取时的 quorum 也以此为总数
+pub(crate) const KAD_REPLICATION_FACTOR: NonZeroUsize = NonZeroUsize::new(3).unwrap();
+
+/// Identify 配置，`set_agent_version` 以此为模板重建 identify behaviour
+///
+/// - protocol_version: 协议版本，用于兼容性检查
+/// - agent_version: 客户端版本，可包含设备信息
+/// - push_listen_addr_updates: 地址变化时主动推送给已连接节点
+/// - cache_size: 缓存最近 N 个节点的信息，避免重复请求
+pub(crate) fn identify_config(config: &NodeConfig, public_key: PublicKey) -> identify::Config {
+    identify::Config::new(config.protocol_version.clone(), public_key)
+        .with_agent_version(config.agent_version.clone())
+        .with_push_listen_addr_updates(config.identify_push_updates)
+        .with_cache_size(config.identify_cache_size)
+}
 
 /// CBOR 编码消息的 trait 约束
 ///
@@ -29,11 +51,17 @@ impl<T> CborMessage for T where
 /// 组合了 P2P 网络所需的各种协议：
 /// - `ping`: 心跳检测，保持连接活跃
 /// - `identify`: 节点信息交换，获取对方设备信息
-/// - `kad`: Kademlia DHT，分布式哈希表用于跨网络发现
-/// - `mdns`: 局域网发现，无需中心服务器
+/// - `kad`: Kademlia DHT，分布式哈希表用于跨网络发现（`enable_kad` 时启用）
+/// - `mdns`: 局域网发现，无需中心服务器（`enable_mdns` 时启用）
 /// - `relay_client`: 中继客户端，NAT 穿透备选方案
+/// - `relay_server`: 中继服务端，为其他节点转发电路（`enable_relay_server` 时启用）
 /// - `autonat`: AutoNAT v2 Client，检测外部地址是否可达
+/// - `upnp`: 经路由器自动映射监听端口（`enable_upnp` 时启用）
 /// - `dcutr`: 打洞协调，实现 NAT 穿透
+/// - `req_stream`: 基于拉取的流式请求-响应，用于分块传输大负载
+/// - `handshake`: 应用层版本/能力握手（配置 `handshake` 时启用）
+/// - `gossipsub`: 主题发布/订阅（`enable_gossipsub` 时启用）
+/// - `blocked_peers`: 黑名单，拒绝与被屏蔽 peer 的所有连接
 #[derive(NetworkBehaviour)]
 pub struct CoreBehaviour<Req, Resp>
 where
@@ -42,11 +70,18 @@ where
 {
     pub ping: ping::Behaviour,
     pub identify: identify::Behaviour,
-    pub kad: kad::Behaviour<kad::store::MemoryStore>,
-    pub req_resp: request_response::cbor::Behaviour<Req, Resp>,
-    pub mdns: mdns::tokio::Behaviour,
+    pub kad: Toggle<kad::Behaviour<BoxedStore>>,
+    pub req_resp: request_response::Behaviour<LimitedCborCodec<Req, Resp>>,
+    pub req_stream:
+        request_response::Behaviour<LimitedCborCodec<StreamRequest<Req>, StreamFrame<Resp>>>,
+    pub handshake: Toggle<request_response::cbor::Behaviour<HandshakeInfo, HandshakeInfo>>,
+    pub gossipsub: Toggle<gossipsub::Behaviour>,
+    pub blocked_peers: allow_block_list::Behaviour<allow_block_list::BlockedPeers>,
+    pub mdns: Toggle<mdns::tokio::Behaviour>,
     pub relay_client: relay::client::Behaviour,
+    pub relay_server: Toggle<relay::Behaviour>,
     pub autonat: autonat::v2::client::Behaviour,
+    pub upnp: Toggle<upnp::tokio::Behaviour>,
     pub dcutr: dcutr::Behaviour,
 }
 
@@ -60,13 +95,15 @@ where
     /// # 参数
     /// - `keypair`: 节点密钥对，用于身份认证
     /// - `relay_client`: 中继客户端行为（由 SwarmBuilder 自动创建）
+    /// - `kad_store`: Kad 记录存储，`None` 表示不启用 Kad
     /// - `config`: 节点配置
     ///
     /// # Panics
-    /// 如果 mDNS 初始化失败（极少见，通常表示系统级问题）
+    /// 如果 mDNS 或 Gossipsub 初始化失败（极少见，通常表示系统级问题）
     pub fn new(
         keypair: &Keypair,
         relay_client: relay::client::Behaviour,
+        kad_store: Option<BoxedStore>,
         config: &NodeConfig,
     ) -> Self {
         let peer_id = keypair.public().to_peer_id();
@@ -83,16 +120,7 @@ where
 
         // ===== Identify =====
         // 节点信息交换协议，连接建立后自动运行
-        // - protocol_version: 协议版本，用于兼容性检查
-        // - agent_version: 客户端版本，可包含设备信息
-        // - push_listen_addr_updates: 地址变化时主动推送给已连接节点
-        // - cache_size: 缓存最近 N 个节点的信息，避免重复请求
-        let identify = identify::Behaviour::new(
-            identify::Config::new(config.protocol_version.clone(), keypair.public())
-                .with_agent_version(config.agent_version.clone())
-                .with_push_listen_addr_updates(true)
-                .with_cache_size(100),
-        );
+        let identify = identify::Behaviour::new(identify_config(config, keypair.public()));
 
         // ===== Kademlia DHT =====
         // 分布式哈希表，用于：
@@ -105,30 +133,53 @@ where
         // - replication_factor: 复制因子，存储到 N 个最近节点
         // - publication_interval: 定期重新发布，保持记录有效
         // - provider_record_ttl: Provider 记录的 TTL
+        // - record_filtering: 入站记录是否需要应用层批准后再存储
         let mut kad_config = kad::Config::default();
         kad_config
             .set_query_timeout(config.kad_query_timeout)
             .set_record_ttl(Some(Duration::from_secs(3600))) // 1 小时
-            .set_replication_factor(NonZeroUsize::new(3).unwrap())
+            .set_replication_factor(KAD_REPLICATION_FACTOR)
             .set_publication_interval(Some(Duration::from_secs(3600)))
             .set_provider_record_ttl(Some(Duration::from_secs(3600)));
+        // 开启记录过滤后，入站 PUT 以 InboundRequest 事件交给事件循环校验并存储
+        if config.kad_record_filtering {
+            kad_config.set_record_filtering(kad::StoreInserts::FilterBoth);
+        }
 
-        let mut kad =
-            kad::Behaviour::with_config(peer_id, kad::store::MemoryStore::new(peer_id), kad_config);
+        let kad = kad_store.map(|store| {
+            let mut kad = kad::Behaviour::with_config(peer_id, store, kad_config);
 
-        // 默认 Kad 模式由 AutoNAT 自动判定（确认公网可达后才切 Server）。
-        // 若 AutoNAT 未确认或处于 NAT 后，节点会停留在 Client 模式，
-        // 不响应 DHT 查询，导致 put_record 等操作因 QuorumFailed 失败。
-        // 在已知可达的场景（如测试、引导节点）可强制设为 Server。
-        if config.kad_server_mode {
-            kad.set_mode(Some(kad::Mode::Server));
-        }
+            // 默认 Kad 模式由 AutoNAT 自动判定（确认公网可达后才切 Server）。
+            // 若 AutoNAT 未确认或处于 NAT 后，节点会停留在 Client 模式，
+            // 不响应 DHT 查询，导致 put_record 等操作因 QuorumFailed 失败。
+            // 在已知可达的场景（如测试、引导节点）可强制设为 Server。
+            if config.kad_server_mode {
+                kad.set_mode(Some(kad::Mode::Server));
+            }
+            kad
+        });
 
         // ===== mDNS =====
         // 局域网多播 DNS 发现
         // 自动发现同一局域网内的其他节点，无需引导节点
-        let mdns = mdns::tokio::Behaviour::new(mdns::Config::default(), peer_id)
-            .expect("mDNS initialization failed");
+        let mdns = config.enable_mdns.then(|| {
+            mdns::tokio::Behaviour::new(mdns::Config::default(), peer_id)
+                .expect("mDNS initialization failed")
+        });
+
+        // ===== Relay Server =====
+        // relay::Behaviour 是服务端，与 relay_client 共存：本节点既可经其他中继被连接，
+        // 也可为其他节点中继。预约 / 电路总数沿用 libp2p 默认值，只开放单条电路的限制
+        let relay_server = config.enable_relay_server.then(|| {
+            relay::Behaviour::new(
+                peer_id,
+                relay::Config {
+                    max_circuit_duration: config.relay_server_max_circuit_duration,
+                    max_circuit_bytes: config.relay_server_max_circuit_bytes,
+                    ..Default::default()
+                },
+            )
+        });
 
         // ===== AutoNAT v2 Client =====
         // 定期向已连接的 AutoNAT v2 Server（如引导节点）发送探测请求，
@@ -136,12 +187,19 @@ where
         // 成功确认的地址会自动注册为 ExternalAddr。
         let autonat = autonat::v2::client::Behaviour::default();
 
+        // ===== UPnP =====
+        // 请求局域网网关（IGD）把监听端口映射到公网，
+        // 映射成功的地址由 behaviour 直接确认为外部地址，到期前自动续期
+        let upnp = config.enable_upnp.then(upnp::tokio::Behaviour::default);
+
         // ===== DCUtR =====
         // Direct Connection Upgrade through Relay
         // 通过中继连接协调打洞，实现 NAT 穿透后的直连
         let dcutr = dcutr::Behaviour::new(peer_id);
 
-        let req_resp = request_response::cbor::Behaviour::new(
+        // 请求/响应帧大小上限，超出的帧在读写时直接报错，防止对端用超大帧耗尽内存
+        let req_resp = request_response::Behaviour::with_codec(
+            LimitedCborCodec::new(config.max_request_size, config.max_response_size),
             [(
                 StreamProtocol::try_from_owned(config.req_resp_protocol.clone())
                     .expect("invalid req_resp_protocol"),
@@ -150,15 +208,59 @@ where
             request_response::Config::default().with_request_timeout(config.req_resp_timeout),
         );
 
+        // 流式协议与 req_resp 共用超时：每次拉取都是一次独立请求，
+        // 超时意味着对端在该时间内未能产出下一个分块；大小上限按单个分块计
+        let req_stream = request_response::Behaviour::with_codec(
+            LimitedCborCodec::new(config.max_request_size, config.max_response_size),
+            [(
+                StreamProtocol::try_from_owned(format!("{}/stream", config.req_resp_protocol))
+                    .expect("invalid req_resp_protocol"),
+                request_response::ProtocolSupport::Full,
+            )],
+            request_response::Config::default().with_request_timeout(config.req_resp_timeout),
+        );
+
+        // 应用层握手：请求和响应都携带本节点的 HandshakeInfo
+        let handshake = config.handshake.as_ref().map(|_| {
+            request_response::cbor::Behaviour::new(
+                [(
+                    StreamProtocol::try_from_owned(format!(
+                        "{}/handshake",
+                        config.req_resp_protocol
+                    ))
+                    .expect("invalid req_resp_protocol"),
+                    request_response::ProtocolSupport::Full,
+                )],
+                request_response::Config::default(),
+            )
+        });
+
+        // ===== Gossipsub =====
+        // 主题发布/订阅，适合在线状态广播等轻量消息
+        // 消息以节点密钥签名，接收方据此校验来源
+        let gossipsub = config.enable_gossipsub.then(|| {
+            gossipsub::Behaviour::new(
+                gossipsub::MessageAuthenticity::Signed(keypair.clone()),
+                gossipsub::Config::default(),
+            )
+            .expect("Gossipsub initialization failed")
+        });
+
         Self {
             ping,
             identify,
-            kad,
-            mdns,
+            kad: kad.into(),
+            mdns: mdns.into(),
             relay_client,
+            relay_server: relay_server.into(),
             autonat,
+            upnp: upnp.into(),
             dcutr,
             req_resp,
+            req_stream,
+            handshake: handshake.into(),
+            gossipsub: gossipsub.into(),
+            blocked_peers: Default::default(),
         }
     }
 }
@@ -0,0 +1,180 @@
+use std::io;
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use futures::prelude::*;
+use libp2p::StreamProtocol;
+use libp2p::request_response;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// 带大小上限的 CBOR 编解码器
+///
+/// 与 `request_response::cbor::codec::Codec` 编码格式相同，区别在于超限时的行为：
+/// 读取多出一个字节以区分"恰好达到上限"和"超出上限"，超出时返回写明上限的错误，
+/// 而不是截断后报解码失败；发送前也会检查本地消息大小，避免把注定被拒的帧写出去。
+/// 错误以 `InboundFailure::Io` / `OutboundFailure::Io` 的形式上报。
+pub struct LimitedCborCodec<Req, Resp> {
+    max_request_size: u64,
+    max_response_size: u64,
+    phantom: PhantomData<(Req, Resp)>,
+}
+
+impl<Req, Resp> LimitedCborCodec<Req, Resp> {
+    pub fn new(max_request_size: usize, max_response_size: usize) -> Self {
+        Self {
+            max_request_size: max_request_size as u64,
+            max_response_size: max_response_size as u64,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<Req, Resp> Clone for LimitedCborCodec<Req, Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            max_request_size: self.max_request_size,
+            max_response_size: self.max_response_size,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// 读取一帧，超过 `max` 字节时报错
+async fn read_limited<T, M>(io: &mut T, max: u64, kind: &str) -> io::Result<M>
+where
+    T: AsyncRead + Unpin + Send,
+    M: DeserializeOwned,
+{
+    let mut buf = Vec::new();
+    io.take(max + 1).read_to_end(&mut buf).await?;
+    if buf.len() as u64 > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{kind} exceeds max_{kind}_size ({max} bytes)"),
+        ));
+    }
+    cbor4ii::serde::from_slice(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// 编码一帧，超过 `max` 字节时报错（不写出）
+fn encode_limited<M: Serialize>(msg: &M, max: u64, kind: &str) -> io::Result<Vec<u8>> {
+    let buf = cbor4ii::serde::to_vec(Vec::new(), msg).map_err(io::Error::other)?;
+    if buf.len() as u64 > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{kind} of {} bytes exceeds max_{kind}_size ({max} bytes)",
+                buf.len()
+            ),
+        ));
+    }
+    Ok(buf)
+}
+
+#[async_trait]
+impl<Req, Resp> request_response::Codec for LimitedCborCodec<Req, Resp>
+where
+    Req: Send + Serialize + DeserializeOwned,
+    Resp: Send + Serialize + DeserializeOwned,
+{
+    type Protocol = StreamProtocol;
+    type Request = Req;
+    type Response = Resp;
+
+    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Req>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_limited(io, self.max_request_size, "request").await
+    }
+
+    async fn read_response<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Resp>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        read_limited(io, self.max_response_size, "response").await
+    }
+
+    async fn write_request<T>(&mut self, _: &Self::Protocol, io: &mut T, req: Req) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let buf = encode_limited(&req, self.max_request_size, "request")?;
+        io.write_all(&buf).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &Self::Protocol,
+        io: &mut T,
+        resp: Resp,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let buf = encode_limited(&resp, self.max_response_size, "response")?;
+        io.write_all(&buf).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::io::Cursor;
+    use libp2p::request_response::Codec as _;
+
+    use super::*;
+
+    const PROTOCOL: StreamProtocol = StreamProtocol::new("/test/limited");
+
+    fn encoded(msg: &str) -> Vec<u8> {
+        cbor4ii::serde::to_vec(Vec::new(), &msg.to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trip_within_limit() {
+        let mut codec = LimitedCborCodec::<String, String>::new(64, 64);
+        let mut io = Cursor::new(Vec::new());
+        codec
+            .write_request(&PROTOCOL, &mut io, "hello".into())
+            .await
+            .unwrap();
+        io.set_position(0);
+        let req = codec.read_request(&PROTOCOL, &mut io).await.unwrap();
+        assert_eq!(req, "hello");
+    }
+
+    #[tokio::test]
+    async fn oversized_inbound_frame_is_rejected() {
+        let mut codec = LimitedCborCodec::<String, String>::new(8, 8);
+        let mut io = Cursor::new(encoded("a much longer payload"));
+        let err = codec.read_request(&PROTOCOL, &mut io).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(
+            err.to_string().contains("max_request_size (8 bytes)"),
+            "{err}"
+        );
+
+        let mut io = Cursor::new(encoded("a much longer payload"));
+        let err = codec.read_response(&PROTOCOL, &mut io).await.unwrap_err();
+        assert!(
+            err.to_string().contains("max_response_size (8 bytes)"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_outbound_frame_is_not_written() {
+        let mut codec = LimitedCborCodec::<String, String>::new(8, 8);
+        let mut io = Cursor::new(Vec::new());
+        let err = codec
+            .write_response(&PROTOCOL, &mut io, "a much longer payload".into())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("exceeds max_response_size"),
+            "{err}"
+        );
+        assert!(io.get_ref().is_empty());
+    }
+}
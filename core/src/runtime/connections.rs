@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use libp2p::core::ConnectedPoint;
+use libp2p::multiaddr::Protocol;
+use libp2p::swarm::ConnectionId;
+use libp2p::{Multiaddr, PeerId};
+use parking_lot::Mutex;
+
+use crate::event::ConnectionDirection;
+
+/// 已建立的连接及其端点
+///
+/// EventLoop 在连接建立 / 关闭时维护，并记录每个连接最近一次 Ping 的延迟；
+/// `upgrade_connection` 等命令据此区分直连与中继连接。
+#[derive(Clone, Default)]
+pub struct PeerConnections {
+    inner: Arc<Mutex<HashMap<PeerId, HashMap<ConnectionId, Connection>>>>,
+}
+
+struct Connection {
+    endpoint: ConnectedPoint,
+    rtt: Option<Duration>,
+}
+
+/// 与某个 peer 的一条连接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// 对端地址（出站为拨号地址，入站为对端的回连地址），可据此判断 TCP / QUIC / 中继
+    pub remote_addr: Multiaddr,
+    pub direction: ConnectionDirection,
+    /// 是否经中继
+    pub relayed: bool,
+    /// 最近一次 Ping 的往返延迟，尚未测得时为 `None`
+    pub rtt: Option<Duration>,
+}
+
+/// 经中继到某个 peer 的连接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayedConnection {
+    pub connection_id: ConnectionId,
+    pub relay_peer_id: PeerId,
+    /// 经同一中继再次拨号该 peer 所用的电路地址
+    pub circuit_addr: Multiaddr,
+}
+
+impl PeerConnections {
+    pub fn insert(&self, peer_id: PeerId, connection_id: ConnectionId, endpoint: ConnectedPoint) {
+        self.inner.lock().entry(peer_id).or_default().insert(
+            connection_id,
+            Connection {
+                endpoint,
+                rtt: None,
+            },
+        );
+    }
+
+    /// 记录 Ping 测得的延迟
+    pub fn set_rtt(&self, peer_id: &PeerId, connection_id: &ConnectionId, rtt: Duration) {
+        if let Some(connection) = self
+            .inner
+            .lock()
+            .get_mut(peer_id)
+            .and_then(|c| c.get_mut(connection_id))
+        {
+            connection.rtt = Some(rtt);
+        }
+    }
+
+    /// 与该 peer 的所有连接，按建立顺序排列
+    pub fn info(&self, peer_id: &PeerId) -> Vec<ConnectionInfo> {
+        let inner = self.inner.lock();
+        let Some(connections) = inner.get(peer_id) else {
+            return Vec::new();
+        };
+        let mut connections: Vec<_> = connections.iter().collect();
+        connections.sort_by_key(|(connection_id, _)| **connection_id);
+        connections
+            .into_iter()
+            .map(|(_, c)| {
+                let (remote_addr, direction) = remote_endpoint(&c.endpoint);
+                ConnectionInfo {
+                    remote_addr,
+                    direction,
+                    relayed: c.endpoint.is_relayed(),
+                    rtt: c.rtt,
+                }
+            })
+            .collect()
+    }
+
+    pub fn remove(&self, peer_id: &PeerId, connection_id: &ConnectionId) {
+        let mut inner = self.inner.lock();
+        if let Some(connections) = inner.get_mut(peer_id) {
+            connections.remove(connection_id);
+            if connections.is_empty() {
+                inner.remove(peer_id);
+            }
+        }
+    }
+
+    /// 所有连接及其端点，按建立顺序排列
+    pub fn established(&self) -> Vec<(PeerId, ConnectionId, ConnectedPoint)> {
+        let mut established: Vec<_> = self
+            .inner
+            .lock()
+            .iter()
+            .flat_map(|(peer_id, connections)| {
+                connections
+                    .iter()
+                    .map(|(id, c)| (*peer_id, *id, c.endpoint.clone()))
+            })
+            .collect();
+        established.sort_by_key(|(_, connection_id, _)| *connection_id);
+        established
+    }
+
+    /// 是否有连接正在使用该地址（出站为拨号地址，入站为回连地址，忽略末尾的 `/p2p`）
+    pub fn uses_addr(&self, peer_id: &PeerId, addr: &Multiaddr) -> bool {
+        let addr = without_p2p(addr);
+        self.inner.lock().get(peer_id).is_some_and(|c| {
+            c.values()
+                .any(|c| without_p2p(c.endpoint.get_remote_address()) == addr)
+        })
+    }
+
+    /// 与该 peer 是否存在非中继连接
+    pub fn has_direct(&self, peer_id: &PeerId) -> bool {
+        self.inner
+            .lock()
+            .get(peer_id)
+            .is_some_and(|c| c.values().any(|c| !c.endpoint.is_relayed()))
+    }
+
+    /// 与该 peer 的中继连接
+    pub fn relayed(&self, peer_id: &PeerId) -> Vec<RelayedConnection> {
+        let inner = self.inner.lock();
+        let Some(connections) = inner.get(peer_id) else {
+            return Vec::new();
+        };
+        connections
+            .iter()
+            .filter_map(|(connection_id, connection)| {
+                // 入站电路的 local_addr 是本节点在中继上的监听地址，出站电路直接复用拨号地址
+                let circuit_addr = match &connection.endpoint {
+                    ConnectedPoint::Dialer { address, .. } => address,
+                    ConnectedPoint::Listener { local_addr, .. } => local_addr,
+                };
+                Some(RelayedConnection {
+                    connection_id: *connection_id,
+                    relay_peer_id: circuit_relay(circuit_addr)?,
+                    circuit_addr: circuit_addr.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 经中继的连接所用的中继节点及电路方向，直连返回 `None`
+pub(crate) fn relayed_circuit(endpoint: &ConnectedPoint) -> Option<(PeerId, ConnectionDirection)> {
+    match endpoint {
+        ConnectedPoint::Dialer { address, .. } => {
+            Some((circuit_relay(address)?, ConnectionDirection::Outbound))
+        }
+        ConnectedPoint::Listener { local_addr, .. } => {
+            Some((circuit_relay(local_addr)?, ConnectionDirection::Inbound))
+        }
+    }
+}
+
+/// 连接的对端地址与方向：出站为拨号地址，入站为对端的回连地址
+pub(crate) fn remote_endpoint(endpoint: &ConnectedPoint) -> (Multiaddr, ConnectionDirection) {
+    match endpoint {
+        ConnectedPoint::Dialer { address, .. } => (address.clone(), ConnectionDirection::Outbound),
+        ConnectedPoint::Listener { send_back_addr, .. } => {
+            (send_back_addr.clone(), ConnectionDirection::Inbound)
+        }
+    }
+}
+
+/// 去掉末尾的 `/p2p/<peer_id>`
+fn without_p2p(addr: &Multiaddr) -> Multiaddr {
+    let mut addr = addr.clone();
+    if let Some(Protocol::P2p(_)) = addr.iter().last() {
+        addr.pop();
+    }
+    addr
+}
+
+/// relay 电路地址（`.../p2p/<relay>/p2p-circuit`）中的中继节点，非电路地址返回 `None`
+pub(crate) fn circuit_relay(addr: &Multiaddr) -> Option<PeerId> {
+    let mut relay = None;
+    for protocol in addr.iter() {
+        match protocol {
+            Protocol::P2p(peer_id) => relay = Some(peer_id),
+            Protocol::P2pCircuit => return relay,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::core::Endpoint;
+    use libp2p::core::transport::PortUse;
+
+    use super::*;
+
+    fn dialer(address: Multiaddr) -> ConnectedPoint {
+        ConnectedPoint::Dialer {
+            address,
+            role_override: Endpoint::Dialer,
+            port_use: PortUse::Reuse,
+        }
+    }
+
+    #[test]
+    fn distinguishes_direct_and_relayed() {
+        let (peer, relay) = (PeerId::random(), PeerId::random());
+        let connections = PeerConnections::default();
+        let circuit: Multiaddr = format!("/ip4/1.2.3.4/tcp/4001/p2p/{relay}/p2p-circuit")
+            .parse()
+            .unwrap();
+        let relayed_id = ConnectionId::new_unchecked(1);
+        let direct_id = ConnectionId::new_unchecked(2);
+
+        connections.insert(
+            peer,
+            relayed_id,
+            ConnectedPoint::Listener {
+                local_addr: circuit.clone(),
+                send_back_addr: format!("/p2p/{peer}").parse().unwrap(),
+            },
+        );
+        assert!(!connections.has_direct(&peer));
+        assert_eq!(
+            connections.relayed(&peer),
+            vec![RelayedConnection {
+                connection_id: relayed_id,
+                relay_peer_id: relay,
+                circuit_addr: circuit,
+            }]
+        );
+
+        connections.insert(
+            peer,
+            direct_id,
+            dialer("/ip4/5.6.7.8/tcp/1".parse().unwrap()),
+        );
+        assert!(connections.has_direct(&peer));
+        assert_eq!(connections.relayed(&peer).len(), 1);
+
+        connections.remove(&peer, &relayed_id);
+        connections.remove(&peer, &direct_id);
+        assert!(!connections.has_direct(&peer));
+        assert!(connections.relayed(&peer).is_empty());
+    }
+
+    #[test]
+    fn uses_addr_ignores_p2p_suffix() {
+        let peer = PeerId::random();
+        let connections = PeerConnections::default();
+        let addr: Multiaddr = "/ip4/192.168.1.2/tcp/4001".parse().unwrap();
+        assert!(!connections.uses_addr(&peer, &addr));
+
+        connections.insert(
+            peer,
+            ConnectionId::new_unchecked(1),
+            dialer(addr.clone().with(Protocol::P2p(peer))),
+        );
+        assert!(connections.uses_addr(&peer, &addr));
+        assert!(connections.uses_addr(&peer, &addr.clone().with(Protocol::P2p(peer))));
+        assert!(!connections.uses_addr(&peer, &"/ip4/192.168.1.2/tcp/4002".parse().unwrap()));
+        assert!(!connections.uses_addr(&PeerId::random(), &addr));
+    }
+
+    #[test]
+    fn info_reports_endpoint_and_rtt() {
+        let peer = PeerId::random();
+        let connections = PeerConnections::default();
+        let (first, second) = (
+            ConnectionId::new_unchecked(1),
+            ConnectionId::new_unchecked(2),
+        );
+        let remote: Multiaddr = "/ip4/5.6.7.8/udp/1/quic-v1".parse().unwrap();
+        connections.insert(peer, second, dialer(remote.clone()));
+        connections.insert(
+            peer,
+            first,
+            ConnectedPoint::Listener {
+                local_addr: "/ip4/0.0.0.0/tcp/4001".parse().unwrap(),
+                send_back_addr: "/ip4/1.2.3.4/tcp/5000".parse().unwrap(),
+            },
+        );
+        connections.set_rtt(&peer, &second, Duration::from_millis(20));
+        // 未知连接忽略
+        connections.set_rtt(&peer, &ConnectionId::new_unchecked(3), Duration::ZERO);
+
+        assert_eq!(
+            connections.info(&peer),
+            vec![
+                ConnectionInfo {
+                    remote_addr: "/ip4/1.2.3.4/tcp/5000".parse().unwrap(),
+                    direction: ConnectionDirection::Inbound,
+                    relayed: false,
+                    rtt: None,
+                },
+                ConnectionInfo {
+                    remote_addr: remote,
+                    direction: ConnectionDirection::Outbound,
+                    relayed: false,
+                    rtt: Some(Duration::from_millis(20)),
+                },
+            ]
+        );
+        assert!(connections.info(&PeerId::random()).is_empty());
+    }
+
+    #[test]
+    fn circuit_relay_is_last_peer_before_circuit() {
+        let relay = PeerId::random();
+        let addr: Multiaddr = format!(
+            "/ip4/1.2.3.4/tcp/1/p2p/{relay}/p2p-circuit/p2p/{}",
+            PeerId::random()
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(circuit_relay(&addr), Some(relay));
+        assert_eq!(circuit_relay(&"/ip4/1.2.3.4/tcp/1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn relayed_circuit_direction() {
+        let (peer, relay) = (PeerId::random(), PeerId::random());
+        let outbound = dialer(
+            format!("/ip4/1.2.3.4/tcp/1/p2p/{relay}/p2p-circuit/p2p/{peer}")
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            relayed_circuit(&outbound),
+            Some((relay, ConnectionDirection::Outbound))
+        );
+
+        let inbound = ConnectedPoint::Listener {
+            local_addr: format!("/ip4/1.2.3.4/tcp/1/p2p/{relay}/p2p-circuit")
+                .parse()
+                .unwrap(),
+            send_back_addr: format!("/p2p/{peer}").parse().unwrap(),
+        };
+        assert_eq!(
+            relayed_circuit(&inbound),
+            Some((relay, ConnectionDirection::Inbound))
+        );
+        assert_eq!(
+            relayed_circuit(&dialer("/ip4/1.2.3.4/tcp/1".parse().unwrap())),
+            None
+        );
+    }
+}
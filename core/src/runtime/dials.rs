@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use libp2p::swarm::ConnectionId;
+use libp2p::{Multiaddr, PeerId};
+use parking_lot::Mutex;
+
+/// 一次开始的出站拨号，EventLoop 据此上报 `NodeEvent::DialStarted`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialStart {
+    pub peer_id: Option<PeerId>,
+    pub connection_id: ConnectionId,
+    pub addrs: Vec<Multiaddr>,
+}
+
+/// 进行中的出站拨号
+///
+/// EventLoop 自动拨号（mDNS、引导节点、固定 peer）和 `dial` / `dial_many` / `dial_addr`
+/// 命令在 `swarm.dial` 成功后登记，EventLoop 在连接建立或拨号失败时移除。
+/// 同一 peer 已有拨号在进行时不再重复上报。其余拨号（其他命令、behaviour 发起的拨号）
+/// 由 EventLoop 在收到 `SwarmEvent::Dialing` 时补登记，只用于判断拨号归属，不上报。
+#[derive(Clone, Default)]
+pub struct PendingDials(Arc<Mutex<PendingDialsInner>>);
+
+#[derive(Default)]
+struct PendingDialsInner {
+    dials: HashMap<ConnectionId, Option<PeerId>>,
+    /// 尚未上报的拨号
+    started: Vec<DialStart>,
+}
+
+impl PendingDials {
+    /// 登记已交给 swarm 的拨号，`addrs` 为拨号时已知的候选地址
+    pub fn start(
+        &self,
+        peer_id: Option<PeerId>,
+        connection_id: ConnectionId,
+        addrs: Vec<Multiaddr>,
+    ) {
+        let mut inner = self.0.lock();
+        let dialing = peer_id.is_some() && inner.dials.values().any(|p| *p == peer_id);
+        inner.dials.insert(connection_id, peer_id);
+        if !dialing {
+            inner.started.push(DialStart {
+                peer_id,
+                connection_id,
+                addrs,
+            });
+        }
+    }
+
+    /// 登记 swarm 上报的拨号，已登记的保持不变
+    pub fn observe(&self, peer_id: Option<PeerId>, connection_id: ConnectionId) {
+        self.0.lock().dials.entry(connection_id).or_insert(peer_id);
+    }
+
+    /// 与 `peer_id` 之间是否还有 `connection_id` 以外的拨号在进行
+    pub fn has_other_dials(&self, peer_id: &PeerId, connection_id: &ConnectionId) -> bool {
+        self.0
+            .lock()
+            .dials
+            .iter()
+            .any(|(id, peer)| id != connection_id && *peer == Some(*peer_id))
+    }
+
+    /// 拨号结束（连接建立或失败）
+    pub fn finish(&self, connection_id: &ConnectionId) {
+        self.0.lock().dials.remove(connection_id);
+    }
+
+    /// 取出待上报的拨号
+    pub fn take_started(&self) -> Vec<DialStart> {
+        std::mem::take(&mut self.0.lock().started)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_first_dial_per_peer() {
+        let dials = PendingDials::default();
+        let peer = PeerId::random();
+        let addr: Multiaddr = "/ip4/1.2.3.4/tcp/1".parse().unwrap();
+        let (first, second, third) = (
+            ConnectionId::new_unchecked(1),
+            ConnectionId::new_unchecked(2),
+            ConnectionId::new_unchecked(3),
+        );
+
+        dials.start(Some(peer), first, vec![addr.clone()]);
+        dials.start(Some(peer), second, vec![addr.clone()]);
+        // 未知 peer 的拨号各自上报
+        dials.start(None, third, vec![addr.clone()]);
+        let started = dials.take_started();
+        assert_eq!(started.len(), 2);
+        assert_eq!(started[0].connection_id, first);
+        assert_eq!(started[1].peer_id, None);
+        assert!(dials.take_started().is_empty());
+
+        // 该 peer 的拨号全部结束后，再次拨号重新上报
+        dials.finish(&first);
+        dials.start(Some(peer), first, vec![addr.clone()]);
+        assert!(dials.take_started().is_empty());
+        dials.finish(&first);
+        dials.finish(&second);
+        dials.start(Some(peer), second, vec![addr]);
+        assert_eq!(dials.take_started().len(), 1);
+    }
+
+    #[test]
+    fn tracks_other_dials_per_peer() {
+        let dials = PendingDials::default();
+        let peer = PeerId::random();
+        let (own, other) = (
+            ConnectionId::new_unchecked(1),
+            ConnectionId::new_unchecked(2),
+        );
+
+        dials.start(Some(peer), own, Vec::new());
+        assert!(!dials.has_other_dials(&peer, &own));
+
+        // swarm 上报的拨号只登记不上报
+        dials.take_started();
+        dials.observe(Some(peer), other);
+        dials.observe(Some(peer), own);
+        assert!(dials.take_started().is_empty());
+        assert!(dials.has_other_dials(&peer, &own));
+
+        dials.finish(&other);
+        assert!(!dials.has_other_dials(&peer, &own));
+    }
+}
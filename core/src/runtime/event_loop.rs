@@ -1,18 +1,48 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::ErrorKind;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
+use either::Either;
 use futures::StreamExt;
-use libp2p::request_response::{Event as ReqRespEvent, Message};
-use libp2p::swarm::SwarmEvent;
-use libp2p::{autonat, dcutr, ping};
+use libp2p::core::ConnectedPoint;
+use libp2p::core::transport::{ListenerId, TransportError};
+use libp2p::core::upgrade::NegotiationError;
+use libp2p::kad::{self, store::RecordStore};
+use libp2p::request_response::{Event as ReqRespEvent, InboundRequestId, Message};
+use libp2p::swarm::{DialError, ListenError, SwarmEvent};
+use libp2p::{PeerId, autonat, dcutr, gossipsub, identify, noise, ping, quic, tls, upnp};
 use tokio::sync::mpsc;
-use tracing::{debug, info, warn};
+use tokio::time::{self, Instant, Interval};
+use tracing::{Instrument, Span, debug, info, info_span, warn};
 
-use super::{CborMessage, CoreBehaviourEvent};
-use crate::command::{Command, CoreSwarm};
-use crate::event::{NatStatus, NodeEvent};
+use super::connections::{circuit_relay, relayed_circuit, remote_endpoint};
+use super::reconnect::BootstrapReconnect;
+use super::{
+    CborMessage, CoreBehaviourEvent, EventSender, KeepAlivePeers, NatTracker, PeerConnections,
+    PeerHandshakes, PendingDials, RelayListeners, SharedState,
+};
+use crate::command::{
+    Command, CoreSwarm, StreamFrame, StreamRequest, StreamSlot, is_shared_event, ordered_dial_opts,
+};
+use crate::config::{AddressOrder, HandshakeInfo, NodeConfig, RecordValidator};
+use crate::event::{
+    KadRequestKind, NatStatus, NodeEvent, PingFailureKind, TransportErrorKind, UpnpMappingStatus,
+};
 use crate::pending_map::PendingMap;
 
+/// 活跃命令及其超时截止时间
+struct ActiveCommand<Req, Resp>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    command: Command<Req, Resp>,
+    deadline: Option<Instant>,
+    /// 命令的 span，`run` / `on_event` / `on_timeout` 中的日志都挂在其下
+    span: Span,
+}
+
 /// 事件循环
 pub struct EventLoop<Req, Resp>
 where
@@ -21,17 +51,78 @@ where
 {
     swarm: CoreSwarm<Req, Resp>,
     command_rx: mpsc::Receiver<Command<Req, Resp>>,
-    event_tx: mpsc::Sender<NodeEvent<Req>>,
-    active_commands: Vec<Command<Req, Resp>>,
+    event_tx: EventSender<Req>,
+    active_commands: Vec<ActiveCommand<Req, Resp>>,
+    /// 对同一 peer 同时进行的出站请求上限
+    max_concurrent_requests_per_peer: Option<usize>,
+    /// 超出并发上限、等待发出的请求命令，按 peer 先进先出
+    queued_requests: HashMap<PeerId, VecDeque<Command<Req, Resp>>>,
     /// 本机的协议版本，用于判断是否加入 Kad
     protocol_version: String,
     /// 暂存 inbound request 的 ResponseChannel，等待前端回复
-    pending_channels: PendingMap<u64, libp2p::request_response::ResponseChannel<Resp>>,
+    pending_channels: PendingMap<u64, (PeerId, libp2p::request_response::ResponseChannel<Resp>)>,
+    /// inbound request 的 request_id → pending_id，用于把失败事件关联到 pending_id
+    inbound_requests: HashMap<InboundRequestId, u64>,
+    /// 流式请求的暂存状态及打开该流的 peer，按 pending_id 索引
+    stream_slots: PendingMap<u64, (PeerId, StreamSlot<Resp>)>,
+    /// 待回复 inbound request 的上限
+    max_pending_responses: usize,
     /// pending_id 自增计数器
     pending_id_counter: AtomicU64,
+    /// 命令 id 自增计数器，用于关联同一命令的日志
+    command_id_counter: u64,
     /// Bootstrap 节点地址映射（peer_id → 地址列表），
     /// 用于在连接建立后申请 relay reservation
     bootstrap_peers: HashMap<libp2p::PeerId, Vec<libp2p::Multiaddr>>,
+    /// 引导节点拨号失败或断开后的退避重连
+    bootstrap_reconnect: BootstrapReconnect,
+    /// 当前 NAT 状态（由 AutoNAT 事件更新，与 NetClient 共享）
+    nat: NatTracker,
+    /// 已接受 relay 预约的中继节点
+    relay_reservations: HashSet<libp2p::PeerId>,
+    /// relay 电路监听器对应的中继节点，监听器关闭即预约结束
+    relay_listeners: RelayListeners,
+    /// 已建立的连接及其端点（与 NetClient 共享）
+    connections: PeerConnections,
+    /// 进行中的出站拨号（与拨号命令共享），据此上报 `DialStarted`
+    pending_dials: PendingDials,
+    /// 是否自动经引导节点申请 relay 预约
+    auto_relay_reservations: bool,
+    /// 周期性自检定时器
+    diagnostics_timer: Option<Interval>,
+    /// 自动 Kad bootstrap 定时器
+    auto_bootstrap_timer: Option<Interval>,
+    /// 进行中的自动 bootstrap 查询
+    auto_bootstrap_query: Option<kad::QueryId>,
+    /// 入站 Kad 记录校验器（记录过滤模式下使用）
+    record_validator: Option<RecordValidator>,
+    /// 按 PeerId 拨号时候选地址的顺序
+    dial_address_order: AddressOrder,
+    /// 需要保活的 peer（与 NetClient 共享）
+    keep_alive_peers: KeepAlivePeers,
+    /// 应用层握手状态（与 NetClient 共享）
+    peer_handshakes: PeerHandshakes,
+    /// 保活定时器，周期为全局空闲超时的一半
+    keep_alive_timer: Interval,
+    /// 连接数软上限，超过后按延迟断开 peer
+    target_connection_count: Option<usize>,
+    /// 每个已连接 peer 的连接数
+    connection_counts: HashMap<PeerId, usize>,
+    /// 每个已连接 peer 最近一次 Ping 延迟
+    peer_rtts: HashMap<PeerId, Duration>,
+    /// 引导节点，不参与连接裁剪
+    infrastructure_peers: HashSet<PeerId>,
+    /// 已发起断开、等待连接关闭的 peer
+    pruning: HashSet<PeerId>,
+    /// 当前活跃的监听器，关闭时逐个移除
+    listeners: HashSet<ListenerId>,
+    /// 收到的关闭命令，存在时进入排空阶段
+    shutdown: Option<ActiveCommand<Req, Resp>>,
+    /// 命令通道已关闭且缓冲区已读空
+    commands_drained: bool,
+    /// Prometheus 指标（与 NetClient 共享）
+    #[cfg(feature = "metrics")]
+    metrics: super::NodeMetrics,
 }
 
 impl<Req, Resp> EventLoop<Req, Resp>
@@ -42,47 +133,100 @@ where
     pub fn new(
         swarm: CoreSwarm<Req, Resp>,
         command_rx: mpsc::Receiver<Command<Req, Resp>>,
-        event_tx: mpsc::Sender<NodeEvent<Req>>,
-        pending_channels: PendingMap<u64, libp2p::request_response::ResponseChannel<Resp>>,
-        protocol_version: String,
+        event_tx: EventSender<Req>,
+        shared: SharedState<Resp>,
+        config: &NodeConfig,
     ) -> Self {
+        let keep_alive_period =
+            (config.idle_connection_timeout / 2).max(Duration::from_millis(100));
         Self {
             swarm,
             command_rx,
             event_tx,
             active_commands: Vec::new(),
-            protocol_version,
-            pending_channels,
+            max_concurrent_requests_per_peer: config.max_concurrent_requests_per_peer,
+            queued_requests: HashMap::new(),
+            protocol_version: config.protocol_version.clone(),
+            pending_channels: shared.pending_channels,
+            inbound_requests: HashMap::new(),
+            stream_slots: shared.stream_slots,
+            max_pending_responses: config.max_pending_responses,
             pending_id_counter: AtomicU64::new(0),
+            command_id_counter: 0,
             bootstrap_peers: HashMap::new(),
+            bootstrap_reconnect: BootstrapReconnect::new(config.bootstrap_retry),
+            nat: shared.nat,
+            relay_reservations: HashSet::new(),
+            relay_listeners: shared.relay_listeners,
+            connections: shared.connections,
+            pending_dials: shared.pending_dials,
+            auto_relay_reservations: config.auto_relay_reservations,
+            // 首次自检在一个周期后发出，而不是启动时立即发出
+            diagnostics_timer: config
+                .diagnostics_interval
+                .filter(|period| !period.is_zero())
+                .map(|period| time::interval_at(Instant::now() + period, period)),
+            auto_bootstrap_timer: config
+                .kad_auto_bootstrap_interval
+                .filter(|period| !period.is_zero())
+                .map(time::interval),
+            auto_bootstrap_query: None,
+            record_validator: config.record_validator.clone(),
+            dial_address_order: config.dial_address_order,
+            keep_alive_peers: shared.keep_alive_peers,
+            peer_handshakes: shared.peer_handshakes,
+            keep_alive_timer: time::interval(keep_alive_period),
+            target_connection_count: config.target_connection_count,
+            connection_counts: HashMap::new(),
+            peer_rtts: HashMap::new(),
+            infrastructure_peers: HashSet::new(),
+            pruning: HashSet::new(),
+            listeners: HashSet::new(),
+            shutdown: None,
+            commands_drained: false,
+            #[cfg(feature = "metrics")]
+            metrics: shared.metrics,
         }
     }
 
     /// 启动监听
     pub fn start_listen(&mut self, addrs: &[libp2p::Multiaddr]) -> crate::Result<()> {
         for addr in addrs {
-            self.swarm
+            let listener_id = self
+                .swarm
                 .listen_on(addr.clone())
                 .map_err(|e| crate::error::Error::Listen(e.to_string()))?;
+            self.listeners.insert(listener_id);
         }
         Ok(())
     }
 
+    /// 导入种子节点到 Kad 路由表（不拨号）
+    pub fn import_seed_peers(&mut self, peers: &[(PeerId, Vec<libp2p::Multiaddr>)]) {
+        crate::command::import_peers(&mut self.swarm, peers);
+        info!("Imported {} seed peers into Kad routing table", peers.len());
+    }
+
     /// 连接引导节点：注册地址到 Kad 路由表、dial，并记录 bootstrap 节点用于后续 relay reservation
     pub fn connect_bootstrap_peers(&mut self, peers: &[(libp2p::PeerId, libp2p::Multiaddr)]) {
         for (peer_id, addr) in peers {
-            self.swarm
-                .behaviour_mut()
-                .kad
-                .add_address(peer_id, addr.clone());
+            if let Some(kad) = self.swarm.behaviour_mut().kad.as_mut() {
+                kad.add_address(peer_id, addr.clone());
+            }
             self.swarm.add_peer_address(*peer_id, addr.clone());
-            if let Err(e) = self.swarm.dial(*peer_id) {
+            self.infrastructure_peers.insert(*peer_id);
+            self.bootstrap_reconnect.add_peer(*peer_id);
+            if let Err(e) = self.dial_peer(*peer_id) {
                 warn!("Failed to dial bootstrap peer {}: {}", peer_id, e);
+                self.bootstrap_reconnect.schedule(peer_id, Instant::now());
             } else {
                 info!("Dialing bootstrap peer {} at {}", peer_id, addr);
             }
 
             // 记录 bootstrap 节点地址，等连接建立后再申请 relay reservation
+            if !self.auto_relay_reservations {
+                continue;
+            }
             self.bootstrap_peers
                 .entry(*peer_id)
                 .or_default()
@@ -93,11 +237,34 @@ where
     /// 运行事件循环
     pub async fn run(mut self) {
         loop {
+            // 上一轮中完成的请求空出了并发名额
+            self.dispatch_queued_requests().await;
+            if self.shutdown.is_some() && self.is_drained() {
+                self.finish_shutdown().await;
+                return;
+            }
+            // 上一轮事件循环中（命令、自动拨号）开始的拨号
+            for dial in self.pending_dials.take_started() {
+                self.event_tx
+                    .send(NodeEvent::DialStarted {
+                        peer_id: dial.peer_id,
+                        connection_id: dial.connection_id,
+                        addrs: dial.addrs,
+                    })
+                    .await;
+            }
+            let deadline = self.next_deadline();
+            // 关闭阶段不再重连
+            let reconnect_at = self
+                .bootstrap_reconnect
+                .next_due()
+                .filter(|_| self.shutdown.is_none());
             tokio::select! {
-                // 处理外部命令
-                cmd = self.command_rx.recv() => {
+                // 处理外部命令（关闭阶段仍会读完通道中已缓冲的命令）
+                cmd = self.command_rx.recv(), if !self.commands_drained => {
                     match cmd {
                         Some(cmd) => self.handle_command(cmd).await,
+                        None if self.shutdown.is_some() => self.commands_drained = true,
                         None => {
                             info!("Command channel closed, shutting down");
                             return;
@@ -108,25 +275,490 @@ where
                 event = self.swarm.select_next_some() => {
                     self.handle_swarm_event(event).await;
                 }
+                // 周期性自检
+                _ = tick(&mut self.diagnostics_timer) => {
+                    let evt = self.diagnostics();
+                    self.event_tx.send(evt).await;
+                }
+                // 周期性 Kad bootstrap
+                _ = tick(&mut self.auto_bootstrap_timer) => {
+                    self.auto_bootstrap();
+                }
+                // DropOldest 模式下，前端腾出空位后投递积压的事件
+                _ = self.event_tx.writable(), if self.event_tx.has_backlog() => {
+                    self.event_tx.flush();
+                }
+                // 为保活 peer 制造连接活动，防止空闲关闭
+                _ = self.keep_alive_timer.tick() => {
+                    self.keep_alive();
+                }
+                // 处理命令超时
+                _ = time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    self.expire_commands().await;
+                }
+                // 重拨退避到期的引导节点
+                _ = time::sleep_until(reconnect_at.unwrap_or_else(Instant::now)), if reconnect_at.is_some() => {
+                    self.redial_bootstrap_peers();
+                }
+            }
+        }
+    }
+
+    async fn handle_command(&mut self, cmd: Command<Req, Resp>) {
+        // 超出并发上限的请求排队，同一 peer 已有排队时也排在其后以保持顺序
+        if let Some(peer_id) = cmd.request_peer()
+            && (self.queued_requests.contains_key(&peer_id) || !self.has_request_slot(&peer_id))
+        {
+            debug!("Request to {} queued, concurrency limit reached", peer_id);
+            self.queued_requests
+                .entry(peer_id)
+                .or_default()
+                .push_back(cmd);
+            return;
+        }
+        self.run_command(cmd).await;
+    }
+
+    /// 与该 peer 进行中的请求数是否低于并发上限
+    fn has_request_slot(&self, peer_id: &PeerId) -> bool {
+        let Some(max) = self.max_concurrent_requests_per_peer else {
+            return true;
+        };
+        let in_flight = self
+            .active_commands
+            .iter()
+            .filter(|c| !c.command.is_finished() && c.command.request_peer() == Some(*peer_id))
+            .count();
+        in_flight < max.max(1)
+    }
+
+    /// 为有空闲名额的 peer 发出排队中的请求
+    async fn dispatch_queued_requests(&mut self) {
+        let peers: Vec<PeerId> = self.queued_requests.keys().copied().collect();
+        for peer_id in peers {
+            while self.has_request_slot(&peer_id) {
+                let Some(queue) = self.queued_requests.get_mut(&peer_id) else {
+                    break;
+                };
+                let Some(cmd) = queue.pop_front() else {
+                    self.queued_requests.remove(&peer_id);
+                    break;
+                };
+                if queue.is_empty() {
+                    self.queued_requests.remove(&peer_id);
+                }
+                self.run_command(cmd).await;
+            }
+        }
+    }
+
+    async fn run_command(&mut self, mut cmd: Command<Req, Resp>) {
+        // 调用方已超时放弃的命令不再执行
+        if cmd.is_finished() {
+            return;
+        }
+        let id = self.command_id_counter;
+        self.command_id_counter += 1;
+        let span = info_span!("command", id, kind = cmd.name());
+        cmd.run_boxed(&mut self.swarm)
+            .instrument(span.clone())
+            .await;
+        // run 中已完成的命令不再接收事件，避免再次 finish 覆盖结果
+        if cmd.is_finished() && !cmd.is_shutdown() {
+            return;
+        }
+        let deadline = cmd.timeout().map(|t| Instant::now() + t);
+        let active = ActiveCommand {
+            command: cmd,
+            deadline,
+            span,
+        };
+        if active.command.is_shutdown() && self.shutdown.is_none() {
+            info!("Graceful shutdown requested, draining active commands");
+            // 拒绝新命令，已缓冲的命令仍会被处理
+            self.command_rx.close();
+            self.shutdown = Some(active);
+        } else {
+            self.active_commands.push(active);
+        }
+    }
+
+    /// 关闭阶段：命令通道已读空且没有未完成的命令
+    fn is_drained(&mut self) -> bool {
+        // 已完成但尚未收到下一个事件的命令不再等待
+        self.active_commands.retain(|c| !c.command.is_finished());
+        self.commands_drained && self.active_commands.is_empty() && self.queued_requests.is_empty()
+    }
+
+    /// 关闭收尾：关闭所有监听，通知前端，完成关闭命令
+    async fn finish_shutdown(&mut self) {
+        for listener_id in self.listeners.drain() {
+            self.swarm.remove_listener(listener_id);
+        }
+        self.event_tx.send(NodeEvent::Shutdown).await;
+        if let Some(mut shutdown) = self.shutdown.take() {
+            shutdown
+                .command
+                .on_timeout_boxed(&mut self.swarm)
+                .instrument(shutdown.span)
+                .await;
+        }
+        info!("Event loop shut down");
+    }
+
+    /// 重拨退避到期的引导节点，已连接的跳过，立即失败的重新排期
+    fn redial_bootstrap_peers(&mut self) {
+        let now = Instant::now();
+        for peer_id in self.bootstrap_reconnect.take_due(now) {
+            if self.swarm.is_connected(&peer_id) {
+                self.bootstrap_reconnect.connected(&peer_id);
+                continue;
+            }
+            info!("Redialing bootstrap peer {}", peer_id);
+            if let Err(e) = self.dial_peer(peer_id) {
+                warn!("Failed to redial bootstrap peer {}: {}", peer_id, e);
+                self.bootstrap_reconnect.schedule(&peer_id, now);
+            }
+        }
+    }
+
+    /// 按 `dial_address_order` 排列候选地址后拨号
+    fn dial_peer(&mut self, peer_id: PeerId) -> Result<(), DialError> {
+        let (opts, addrs) = ordered_dial_opts(&mut self.swarm, peer_id, self.dial_address_order);
+        let connection_id = opts.connection_id();
+        self.swarm.dial(opts)?;
+        self.pending_dials
+            .start(Some(peer_id), connection_id, addrs);
+        Ok(())
+    }
+
+    /// 发起一轮自动 bootstrap，上一轮未结束或路由表为空时跳过
+    fn auto_bootstrap(&mut self) {
+        if self.auto_bootstrap_query.is_some() {
+            debug!("Previous auto bootstrap still running, skipping");
+            return;
+        }
+        let Some(kad) = self.swarm.behaviour_mut().kad.as_mut() else {
+            return;
+        };
+        match kad.bootstrap() {
+            Ok(query_id) => {
+                debug!("Auto bootstrap started, query_id: {:?}", query_id);
+                self.auto_bootstrap_query = Some(query_id);
+            }
+            Err(e) => debug!("Auto bootstrap skipped: {:?}", e),
+        }
+    }
+
+    /// 汇总当前节点状态，生成自检事件
+    fn diagnostics(&mut self) -> NodeEvent<Req> {
+        let connections = self
+            .swarm
+            .network_info()
+            .connection_counters()
+            .num_established() as usize;
+        let routing_table_peers = self.swarm.behaviour_mut().kad.as_mut().map_or(0, |kad| {
+            kad.kbuckets().map(|bucket| bucket.num_entries()).sum()
+        });
+        NodeEvent::Diagnostics {
+            connections,
+            routing_table_peers,
+            nat_status: self.nat.status(),
+            relay_reservations: self.relay_reservations.len(),
+            pending_channels: self.pending_channels.len(),
+        }
+    }
+
+    /// 向仍在保活期内且已连接的 peer 发起 Identify push，并重拨已断开的固定 peer
+    fn keep_alive(&mut self) {
+        let peers: Vec<PeerId> = self
+            .keep_alive_peers
+            .active()
+            .into_iter()
+            .filter(|peer_id| self.swarm.is_connected(peer_id))
+            .collect();
+        if !peers.is_empty() {
+            debug!("Keep-alive push to {:?}", peers);
+            self.swarm.behaviour_mut().identify.push(peers);
+        }
+        for peer_id in self.keep_alive_peers.pinned() {
+            if !self.swarm.is_connected(&peer_id) {
+                self.redial_pinned(peer_id);
+            }
+        }
+    }
+
+    /// 重拨固定的 peer（已在拨号中时跳过，关闭过程中不重拨）
+    fn redial_pinned(&mut self, peer_id: PeerId) {
+        if self.shutdown.is_some() {
+            return;
+        }
+        info!("Redialing pinned peer {}", peer_id);
+        if let Err(e) = self.dial_peer(peer_id) {
+            debug!("Failed to redial pinned peer {}: {}", peer_id, e);
+        }
+    }
+
+    /// 与新连接的 peer 发起应用层握手（未开启握手时跳过）
+    fn start_handshake(&mut self, peer_id: PeerId) {
+        let Some(local) = self.peer_handshakes.local().cloned() else {
+            return;
+        };
+        if let Some(handshake) = self.swarm.behaviour_mut().handshake.as_mut() {
+            handshake.send_request(&peer_id, local);
+        }
+    }
+
+    /// 处理握手协议事件：回复对端请求，记录对端响应
+    fn handle_handshake(
+        &mut self,
+        event: ReqRespEvent<HandshakeInfo, HandshakeInfo>,
+    ) -> Option<NodeEvent<Req>> {
+        match event {
+            ReqRespEvent::Message {
+                peer,
+                message: Message::Request { channel, .. },
+                ..
+            } => {
+                let local = self.peer_handshakes.local().cloned()?;
+                if let Some(handshake) = self.swarm.behaviour_mut().handshake.as_mut()
+                    && handshake.send_response(channel, local).is_err()
+                {
+                    debug!("Handshake response to {} dropped: channel closed", peer);
+                }
+                None
+            }
+            ReqRespEvent::Message {
+                peer,
+                message: Message::Response { response, .. },
+                ..
+            } => {
+                info!("Handshake with {} completed: {:?}", peer, response);
+                self.peer_handshakes.insert(peer, response.clone());
+                Some(NodeEvent::HandshakeCompleted {
+                    peer_id: peer,
+                    info: response,
+                })
+            }
+            ReqRespEvent::OutboundFailure { peer, error, .. } => {
+                warn!("Handshake with {} failed: {}", peer, error);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// 处理记录过滤模式下的入站 Kad 写入
+    fn handle_kad_inbound(&mut self, request: kad::InboundRequest) {
+        let Some(kad) = self.swarm.behaviour_mut().kad.as_mut() else {
+            return;
+        };
+        match request {
+            kad::InboundRequest::PutRecord {
+                source,
+                record: Some(record),
+                ..
+            } => {
+                if let Some(validator) = &self.record_validator
+                    && !validator.validate(&record)
+                {
+                    warn!("Rejected Kad record from {}: validation failed", source);
+                    return;
+                }
+                if let Err(e) = kad.store_mut().put(record) {
+                    warn!("Failed to store Kad record from {}: {}", source, e);
+                }
+            }
+            kad::InboundRequest::AddProvider {
+                record: Some(provider),
+            } => {
+                if let Err(e) = kad.store_mut().add_provider(provider) {
+                    warn!("Failed to store provider record: {}", e);
+                }
             }
+            _ => {}
         }
     }
 
-    async fn handle_command(&mut self, mut cmd: Command<Req, Resp>) {
-        cmd.run_boxed(&mut self.swarm).await;
-        self.active_commands.push(cmd);
+    /// 最早到期的命令截止时间
+    fn next_deadline(&self) -> Option<Instant> {
+        self.active_commands
+            .iter()
+            .chain(&self.shutdown)
+            .filter_map(|c| c.deadline)
+            .min()
+    }
+
+    /// 对已到期的命令调用 on_timeout 并移除
+    async fn expire_commands(&mut self) {
+        let now = Instant::now();
+        let mut i = 0;
+        while i < self.active_commands.len() {
+            if self.active_commands[i]
+                .deadline
+                .is_some_and(|deadline| deadline <= now)
+            {
+                let mut active = self.active_commands.swap_remove(i);
+                active
+                    .command
+                    .on_timeout_boxed(&mut self.swarm)
+                    .instrument(active.span)
+                    .await;
+            } else {
+                i += 1;
+            }
+        }
+
+        // 关闭等待到期：读出剩余缓冲命令，连同未完成的命令一并超时结束
+        if self
+            .shutdown
+            .as_ref()
+            .and_then(|c| c.deadline)
+            .is_some_and(|deadline| deadline <= now)
+        {
+            warn!("Graceful shutdown timed out, aborting remaining commands");
+            while let Ok(cmd) = self.command_rx.try_recv() {
+                self.handle_command(cmd).await;
+            }
+            self.commands_drained = true;
+            for mut active in self.active_commands.drain(..) {
+                active
+                    .command
+                    .on_timeout_boxed(&mut self.swarm)
+                    .instrument(active.span)
+                    .await;
+            }
+            for (_, queue) in self.queued_requests.drain() {
+                for mut cmd in queue {
+                    cmd.on_timeout_boxed(&mut self.swarm).await;
+                }
+            }
+        }
     }
 
     async fn handle_swarm_event(&mut self, event: SwarmEvent<CoreBehaviourEvent<Req, Resp>>) {
-        // 命令链：依次传递 owned event，命令可选择消费或传递
+        #[cfg(feature = "metrics")]
+        self.metrics.record(&event);
+
+        if let SwarmEvent::ConnectionEstablished { connection_id, .. }
+        | SwarmEvent::OutgoingConnectionError { connection_id, .. } = &event
+        {
+            self.pending_dials.finish(connection_id);
+        }
+        if let SwarmEvent::Dialing {
+            peer_id,
+            connection_id,
+        } = &event
+        {
+            self.pending_dials.observe(*peer_id, *connection_id);
+        }
+        match &event {
+            SwarmEvent::NewListenAddr { listener_id, .. } => {
+                self.listeners.insert(*listener_id);
+            }
+            SwarmEvent::ListenerClosed { listener_id, .. } => {
+                self.listeners.remove(listener_id);
+            }
+            // 最后一个已确认地址失效时 NAT 状态回到 Unknown
+            SwarmEvent::ExternalAddrExpired { address } if self.nat.expire_addr(address) => {
+                info!("All confirmed external addresses expired, NAT status unknown");
+                self.event_tx
+                    .send(NodeEvent::NatStatusChanged {
+                        status: NatStatus::Unknown,
+                        public_addr: None,
+                    })
+                    .await;
+            }
+            SwarmEvent::ConnectionEstablished {
+                peer_id,
+                connection_id,
+                endpoint,
+                num_established,
+                ..
+            } => {
+                self.connections
+                    .insert(*peer_id, *connection_id, endpoint.clone());
+                self.connection_counts
+                    .insert(*peer_id, num_established.get() as usize);
+                self.bootstrap_reconnect.connected(peer_id);
+            }
+            SwarmEvent::ConnectionClosed {
+                peer_id,
+                connection_id,
+                num_established,
+                ..
+            } => {
+                self.connections.remove(peer_id, connection_id);
+                if *num_established == 0 {
+                    self.connection_counts.remove(peer_id);
+                    self.peer_rtts.remove(peer_id);
+                    self.pruning.remove(peer_id);
+                    if self.keep_alive_peers.is_pinned(peer_id) {
+                        self.redial_pinned(*peer_id);
+                    }
+                    if self.shutdown.is_none()
+                        && let Some(delay) =
+                            self.bootstrap_reconnect.schedule(peer_id, Instant::now())
+                    {
+                        info!(
+                            "Bootstrap peer {} disconnected, redialing in {:?}",
+                            peer_id, delay
+                        );
+                    }
+                } else {
+                    self.connection_counts
+                        .insert(*peer_id, *num_established as usize);
+                }
+            }
+            // 引导节点拨号失败（已有其他连接时不算）
+            SwarmEvent::OutgoingConnectionError {
+                peer_id: Some(peer_id),
+                ..
+            } if !self.swarm.is_connected(peer_id) => {
+                if let Some(delay) = self.bootstrap_reconnect.schedule(peer_id, Instant::now()) {
+                    debug!(
+                        "Bootstrap peer {} unreachable, redialing in {:?}",
+                        peer_id, delay
+                    );
+                }
+            }
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Ping(ping::Event {
+                peer,
+                connection,
+                result: Ok(rtt),
+            })) => {
+                self.peer_rtts.insert(*peer, *rtt);
+                self.connections.set_rtt(peer, connection, *rtt);
+            }
+            _ => {}
+        }
+        // 新连接或新的延迟数据可能使连接裁剪成为必要/可行
+        let check_prune = matches!(
+            event,
+            SwarmEvent::ConnectionEstablished { .. }
+                | SwarmEvent::Behaviour(CoreBehaviourEvent::Ping(_))
+        );
+
+        // 命令链：依次传递 owned event，命令可选择消费或传递；
+        // 连接事件须让每个命令都看到（多个命令可能在等同一个 peer 的连接）
+        let shared = is_shared_event(&event);
         let mut remaining = Some(event);
         let mut i = 0;
         while i < self.active_commands.len() {
             let Some(event) = remaining.take() else {
                 break; // 事件已被消费，后续命令不再处理
             };
-            let (keep, returned) = self.active_commands[i].on_event_boxed(event).await;
+            let active = &mut self.active_commands[i];
+            let (keep, returned) = active
+                .command
+                .on_event_boxed(&mut self.swarm, event)
+                .instrument(active.span.clone())
+                .await;
             remaining = returned;
+            debug_assert!(
+                !shared || remaining.is_some(),
+                "commands must not consume connection events"
+            );
             if keep {
                 i += 1;
             } else {
@@ -139,15 +771,208 @@ where
             return;
         };
 
+        for evt in transport_errors(&event) {
+            self.event_tx.send(evt).await;
+        }
+        if let Some(evt) = self.relay_event(&event) {
+            self.event_tx.send(evt).await;
+        }
+
+        // 跟在 IdentifyReceived 之后上报
+        let observed = observed_addr(&event);
         if let Some(evt) = self.convert_to_node_event(event) {
-            let _ = self.event_tx.send(evt).await;
+            self.event_tx.send(evt).await;
+        }
+        if let Some(evt) = observed {
+            self.event_tx.send(evt).await;
+        }
+
+        if check_prune {
+            for evt in self.prune_connections() {
+                self.event_tx.send(evt).await;
+            }
         }
     }
 
+    /// relay 预约失败（电路监听器非主动关闭）、电路连接建立与入站电路关闭
+    ///
+    /// relay client 的 `*CircuitEstablished` 事件只带电路的一端（入站缺中继、出站缺对端），
+    /// 因此在电路连接建立时从连接地址中取出对端、中继与方向。
+    fn relay_event(
+        &mut self,
+        event: &SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+    ) -> Option<NodeEvent<Req>> {
+        match event {
+            SwarmEvent::ListenerClosed {
+                listener_id,
+                reason,
+                ..
+            } => {
+                let (relay_peer_id, cancelled) = self.relay_listeners.remove(listener_id)?;
+                self.relay_reservations.remove(&relay_peer_id);
+                if cancelled {
+                    info!("Relay reservation via {} cancelled", relay_peer_id);
+                    return None;
+                }
+                // 正常关闭意味着与中继的连接已断开（或拨号失败），预约随之失效
+                let error = match reason {
+                    Ok(()) => "connection to relay closed".to_string(),
+                    Err(e) => error_chain(e),
+                };
+                warn!("Relay reservation via {} failed: {}", relay_peer_id, error);
+                Some(NodeEvent::RelayReservationFailed {
+                    relay_peer_id,
+                    error,
+                })
+            }
+            SwarmEvent::ConnectionEstablished {
+                peer_id, endpoint, ..
+            } => {
+                let (relay_peer_id, direction) = relayed_circuit(endpoint)?;
+                info!(
+                    "Relay circuit with {} via {} established ({:?})",
+                    peer_id, relay_peer_id, direction
+                );
+                Some(NodeEvent::RelayCircuitEstablished {
+                    peer_id: *peer_id,
+                    relay_peer_id,
+                    direction,
+                })
+            }
+            SwarmEvent::ConnectionClosed {
+                peer_id,
+                endpoint: ConnectedPoint::Listener { local_addr, .. },
+                ..
+            } => {
+                let relay_peer_id = circuit_relay(local_addr)?;
+                info!(
+                    "Relay circuit from {} via {} closed",
+                    peer_id, relay_peer_id
+                );
+                Some(NodeEvent::RelayCircuitClosed {
+                    relay_peer_id,
+                    src_peer_id: *peer_id,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// 连接数超过软上限时，按 Ping 延迟从高到低断开可裁剪的 peer
+    fn prune_connections(&mut self) -> Vec<NodeEvent<Req>> {
+        let Some(target) = self.target_connection_count else {
+            return Vec::new();
+        };
+        let mut total: usize = self
+            .connection_counts
+            .iter()
+            .filter(|(peer_id, _)| !self.pruning.contains(peer_id))
+            .map(|(_, count)| count)
+            .sum();
+        if total <= target {
+            return Vec::new();
+        }
+
+        let pinned: HashSet<PeerId> = self.keep_alive_peers.active().into_iter().collect();
+        let mut candidates: Vec<(PeerId, Duration)> = self
+            .peer_rtts
+            .iter()
+            .filter(|(peer_id, _)| {
+                self.connection_counts.contains_key(peer_id)
+                    && !self.pruning.contains(peer_id)
+                    && !pinned.contains(peer_id)
+                    && !self.infrastructure_peers.contains(peer_id)
+                    && !self.relay_reservations.contains(peer_id)
+            })
+            .map(|(peer_id, rtt)| (*peer_id, *rtt))
+            .collect();
+        candidates.sort_by_key(|(_, rtt)| std::cmp::Reverse(*rtt));
+
+        let mut events = Vec::new();
+        for (peer_id, rtt) in candidates {
+            if total <= target {
+                break;
+            }
+            if self.swarm.disconnect_peer_id(peer_id).is_ok() {
+                info!(
+                    "Pruning connection to {} (rtt {:?}, {} connections over target {})",
+                    peer_id,
+                    rtt,
+                    total - target,
+                    target
+                );
+                total -= self.connection_counts[&peer_id];
+                self.pruning.insert(peer_id);
+                events.push(NodeEvent::ConnectionPruned { peer_id });
+            }
+        }
+        events
+    }
+
     fn next_pending_id(&self) -> u64 {
         self.pending_id_counter.fetch_add(1, Ordering::Relaxed)
     }
 
+    /// 待回复的 inbound request 是否已达上限
+    fn pending_full(&self) -> bool {
+        self.pending_channels.len() + self.stream_slots.len() >= self.max_pending_responses
+    }
+
+    fn reject_inbound(&self, peer_id: PeerId) -> NodeEvent<Req> {
+        warn!(
+            "Rejecting inbound request from {}: too many pending (limit {})",
+            peer_id, self.max_pending_responses
+        );
+        NodeEvent::InboundRequestRejected {
+            peer_id,
+            reason: "too many pending".into(),
+        }
+    }
+
+    /// 处理流式拉取请求：有待发送分块则立即回复，否则暂存 channel 等待应用提供
+    ///
+    /// stream_id 是递增的 pending_id，容易猜到，只接受打开该流的 peer 拉取
+    fn handle_stream_pull(
+        &mut self,
+        peer: PeerId,
+        stream_id: u64,
+        channel: libp2p::request_response::ResponseChannel<StreamFrame<Resp>>,
+    ) {
+        let queued = self.stream_slots.update(stream_id, |slot| match slot {
+            Some((owner, StreamSlot::Queued(frame, tx))) if owner == peer => {
+                let next = (!frame.is_terminal()).then_some((owner, StreamSlot::Idle));
+                (next, Some((channel, frame, Some(tx))))
+            }
+            Some((owner, StreamSlot::Idle)) if owner == peer => {
+                (Some((owner, StreamSlot::Waiting(channel))), None)
+            }
+            // 已有未处理的拉取请求、流不存在/已过期，或拉取方不是打开该流的 peer
+            other => {
+                if other.as_ref().is_some_and(|(owner, _)| *owner != peer) {
+                    warn!("Rejecting pull of stream {} from {}", stream_id, peer);
+                }
+                let frame = StreamFrame::Error(format!("Unknown stream {}", stream_id));
+                (other, Some((channel, frame, None)))
+            }
+        });
+
+        if let Some((channel, frame, tx)) = queued {
+            let result = self
+                .swarm
+                .behaviour_mut()
+                .req_stream
+                .send_response(channel, frame)
+                .map_err(|_| {
+                    crate::error::Error::RequestResponse(
+                        "Failed to send stream frame: channel closed".into(),
+                    )
+                });
+            if let Some(tx) = tx {
+                let _ = tx.send(result);
+            }
+        }
+    }
+
     /// 将 swarm 事件转换为对外事件
     fn convert_to_node_event(
         &mut self,
@@ -158,13 +983,19 @@ where
                 libp2p::relay::client::Event::ReservationReqAccepted {
                     relay_peer_id,
                     renewal,
-                    ..
+                    limit,
                 } => {
                     info!(
                         "Relay reservation {} by {}",
                         if renewal { "renewed" } else { "accepted" },
                         relay_peer_id
                     );
+                    self.relay_reservations.insert(relay_peer_id);
+                    self.relay_listeners.accept(
+                        &relay_peer_id,
+                        limit.as_ref().and_then(|l| l.duration()),
+                        limit.as_ref().and_then(|l| l.data_in_bytes()),
+                    );
                     Some(NodeEvent::RelayReservationAccepted {
                         relay_peer_id,
                         renewal,
@@ -173,45 +1004,118 @@ where
                 libp2p::relay::client::Event::OutboundCircuitEstablished {
                     relay_peer_id, ..
                 } => {
-                    info!("Outbound circuit established via relay {}", relay_peer_id);
+                    debug!("Outbound circuit established via relay {}", relay_peer_id);
                     None
                 }
                 libp2p::relay::client::Event::InboundCircuitEstablished { src_peer_id, .. } => {
-                    info!("Inbound circuit established from {}", src_peer_id);
+                    debug!("Inbound circuit established from {}", src_peer_id);
+                    None
+                }
+            },
+            SwarmEvent::Behaviour(CoreBehaviourEvent::RelayServer(e)) => match e {
+                libp2p::relay::Event::ReservationReqAccepted {
+                    src_peer_id,
+                    renewed,
+                } => {
+                    info!(
+                        "Relay reservation from {} {}",
+                        src_peer_id,
+                        if renewed { "renewed" } else { "accepted" }
+                    );
+                    Some(NodeEvent::RelayServerReservationAccepted {
+                        peer_id: src_peer_id,
+                        renewal: renewed,
+                    })
+                }
+                libp2p::relay::Event::ReservationClosed { src_peer_id }
+                | libp2p::relay::Event::ReservationTimedOut { src_peer_id } => {
+                    info!("Relay reservation from {} closed", src_peer_id);
+                    Some(NodeEvent::RelayServerReservationClosed {
+                        peer_id: src_peer_id,
+                    })
+                }
+                libp2p::relay::Event::ReservationReqDenied {
+                    src_peer_id,
+                    status,
+                } => {
+                    warn!(
+                        "Denied relay reservation from {}: {:?}",
+                        src_peer_id, status
+                    );
+                    None
+                }
+                libp2p::relay::Event::CircuitReqAccepted {
+                    src_peer_id,
+                    dst_peer_id,
+                } => {
+                    debug!("Relaying circuit {} -> {}", src_peer_id, dst_peer_id);
                     None
                 }
+                libp2p::relay::Event::CircuitReqDenied {
+                    src_peer_id,
+                    dst_peer_id,
+                    status,
+                } => {
+                    debug!(
+                        "Denied relay circuit {} -> {}: {:?}",
+                        src_peer_id, dst_peer_id, status
+                    );
+                    None
+                }
+                libp2p::relay::Event::CircuitClosed {
+                    src_peer_id,
+                    dst_peer_id,
+                    ..
+                } => {
+                    debug!("Relayed circuit {} -> {} closed", src_peer_id, dst_peer_id);
+                    None
+                }
+                _ => None,
             },
             SwarmEvent::NewListenAddr { address, .. } => {
                 Some(NodeEvent::Listening { addr: address })
             }
+            // AutoNAT 周期性重测会重复确认同一地址，只上报首次确认
+            SwarmEvent::ExternalAddrConfirmed { address } => {
+                self.nat.confirm_addr(&address).then(|| {
+                    info!("External address confirmed: {}", address);
+                    NodeEvent::ExternalAddrConfirmed { addr: address }
+                })
+            }
             // 只在第一个连接建立时通知（peer 级别聚合）
             SwarmEvent::ConnectionEstablished {
                 peer_id,
+                endpoint,
                 num_established,
                 ..
             } if num_established.get() == 1 => {
                 // 如果是 bootstrap 节点，连接建立后申请 relay reservation
                 if let Some(addrs) = self.bootstrap_peers.remove(&peer_id) {
                     for addr in addrs {
-                        let base = if addr
-                            .iter()
-                            .any(|p| matches!(p, libp2p::multiaddr::Protocol::P2p(_)))
-                        {
-                            addr.clone()
-                        } else {
-                            addr.clone()
-                                .with(libp2p::multiaddr::Protocol::P2p(peer_id))
-                        };
-                        let relay_addr = base.with(libp2p::multiaddr::Protocol::P2pCircuit);
+                        let relay_addr = crate::command::relay_circuit_addr(peer_id, &addr);
                         match self.swarm.listen_on(relay_addr.clone()) {
-                            Ok(_) => info!("Requesting relay reservation via {}", relay_addr),
+                            Ok(listener_id) => {
+                                info!("Requesting relay reservation via {}", relay_addr);
+                                self.relay_listeners.insert(
+                                    listener_id,
+                                    peer_id,
+                                    relay_addr.clone(),
+                                );
+                            }
                             Err(e) => {
                                 warn!("Failed to listen on relay circuit {}: {}", relay_addr, e)
                             }
                         }
                     }
                 }
-                Some(NodeEvent::PeerConnected { peer_id })
+                self.start_handshake(peer_id);
+                let (remote_addr, direction) = remote_endpoint(&endpoint);
+                Some(NodeEvent::PeerConnected {
+                    peer_id,
+                    remote_addr,
+                    direction,
+                    relayed: endpoint.is_relayed(),
+                })
             }
             SwarmEvent::ConnectionEstablished { .. } => None,
             // 只在最后一个连接关闭时通知（peer 级别聚合）
@@ -219,46 +1123,156 @@ where
                 peer_id,
                 num_established: 0,
                 ..
-            } => Some(NodeEvent::PeerDisconnected { peer_id }),
+            } => {
+                self.relay_reservations.remove(&peer_id);
+                self.peer_handshakes.remove(&peer_id);
+                Some(NodeEvent::PeerDisconnected { peer_id })
+            }
             // Inbound request: 取出 ResponseChannel 暂存，通知前端
             SwarmEvent::Behaviour(CoreBehaviourEvent::ReqResp(ReqRespEvent::Message {
                 peer,
                 message:
                     Message::Request {
-                        request, channel, ..
+                        request_id,
+                        request,
+                        channel,
                     },
                 ..
             })) => {
+                if self.pending_full() {
+                    // 丢弃 channel，对端会收到请求失败
+                    drop(channel);
+                    return Some(self.reject_inbound(peer));
+                }
                 let pending_id = self.next_pending_id();
                 info!(
                     "Inbound request from {}, assigned pending_id={}",
                     peer, pending_id
                 );
-                self.pending_channels.insert(pending_id, channel);
+                self.pending_channels.insert(pending_id, (peer, channel));
+                self.inbound_requests.insert(request_id, pending_id);
                 Some(NodeEvent::InboundRequest {
                     peer_id: peer,
                     pending_id,
                     request,
                 })
             }
-            SwarmEvent::Behaviour(CoreBehaviourEvent::Dcutr(dcutr::Event {
-                remote_peer_id,
-                result,
-            })) => match result {
-                Ok(_connection_id) => {
-                    info!("DCUtR hole-punch succeeded with {}", remote_peer_id);
-                    Some(NodeEvent::HolePunchSucceeded {
-                        peer_id: remote_peer_id,
+            // 回复已发出：按 request_id 找回 pending_id 通知前端
+            SwarmEvent::Behaviour(CoreBehaviourEvent::ReqResp(ReqRespEvent::ResponseSent {
+                peer,
+                request_id,
+                ..
+            })) => {
+                let pending_id = self.inbound_requests.remove(&request_id)?;
+                debug!("Response {} sent to {}", pending_id, peer);
+                Some(NodeEvent::ResponseSent {
+                    peer_id: peer,
+                    pending_id,
+                })
+            }
+            // 回复前对端断开、回复超时或发送失败：清理暂存的 channel 并通知前端
+            SwarmEvent::Behaviour(CoreBehaviourEvent::ReqResp(ReqRespEvent::InboundFailure {
+                peer,
+                request_id,
+                error,
+                ..
+            })) => {
+                // 因待回复数达到上限而被拒绝、或读取阶段就失败（如超过 max_request_size）
+                // 的请求没有 pending_id
+                let Some(pending_id) = self.inbound_requests.remove(&request_id) else {
+                    warn!("Inbound request from {} failed: {}", peer, error);
+                    return None;
+                };
+                self.pending_channels.take(&pending_id);
+                warn!(
+                    "Inbound request {} from {} failed: {}",
+                    pending_id, peer, error
+                );
+                Some(NodeEvent::InboundRequestFailed {
+                    peer_id: peer,
+                    pending_id,
+                    error: error.to_string(),
+                })
+            }
+            // Inbound 流式请求：Open 分配 pending_id 并通知前端，Next 拉取下一个分块
+            SwarmEvent::Behaviour(CoreBehaviourEvent::ReqStream(ReqRespEvent::Message {
+                peer,
+                message:
+                    Message::Request {
+                        request, channel, ..
+                    },
+                ..
+            })) => match request {
+                StreamRequest::Open(request) => {
+                    if self.pending_full() {
+                        let _ = self.swarm.behaviour_mut().req_stream.send_response(
+                            channel,
+                            StreamFrame::Error("too many pending requests".into()),
+                        );
+                        return Some(self.reject_inbound(peer));
+                    }
+                    let pending_id = self.next_pending_id();
+                    info!(
+                        "Inbound stream request from {}, assigned pending_id={}",
+                        peer, pending_id
+                    );
+                    self.stream_slots
+                        .insert(pending_id, (peer, StreamSlot::Waiting(channel)));
+                    Some(NodeEvent::InboundRequestStream {
+                        peer_id: peer,
+                        pending_id,
+                        request,
                     })
                 }
-                Err(e) => {
-                    warn!("DCUtR hole-punch failed with {}: {}", remote_peer_id, e);
-                    Some(NodeEvent::HolePunchFailed {
-                        peer_id: remote_peer_id,
-                        error: e.to_string(),
-                    })
+                StreamRequest::Next { stream_id } => {
+                    self.handle_stream_pull(peer, stream_id, channel);
+                    None
                 }
             },
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Handshake(e)) => self.handle_handshake(e),
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Gossipsub(gossipsub::Event::Message {
+                propagation_source,
+                message,
+                ..
+            })) => {
+                debug!(
+                    "Gossipsub message on {} via {}",
+                    message.topic, propagation_source
+                );
+                Some(NodeEvent::GossipMessage {
+                    topic: message.topic.into_string(),
+                    source: message.source,
+                    data: message.data,
+                })
+            }
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Dcutr(dcutr::Event {
+                remote_peer_id,
+                result,
+            })) => {
+                // 打洞经由与该 peer 的中继连接协商
+                let relay_peer_id = self
+                    .connections
+                    .relayed(&remote_peer_id)
+                    .first()
+                    .map(|c| c.relay_peer_id);
+                match result {
+                    Ok(_connection_id) => {
+                        info!("DCUtR hole-punch succeeded with {}", remote_peer_id);
+                        Some(NodeEvent::HolePunchSucceeded {
+                            peer_id: remote_peer_id,
+                            relay_peer_id,
+                        })
+                    }
+                    Err(e) => {
+                        warn!("DCUtR hole-punch failed with {}: {}", remote_peer_id, e);
+                        Some(NodeEvent::HolePunchFailed {
+                            peer_id: remote_peer_id,
+                            relay_peer_id,
+                            error: e.to_string(),
+                        })
+                    }
+                }
+            }
             SwarmEvent::Behaviour(CoreBehaviourEvent::Mdns(libp2p::mdns::Event::Discovered(
                 peers,
             ))) => {
@@ -273,13 +1287,26 @@ where
                 for peer_id in &dialed {
                     if !self.swarm.is_connected(peer_id) {
                         info!("mDNS: dialing peer {}", peer_id);
-                        if let Err(e) = self.swarm.dial(*peer_id) {
+                        if let Err(e) = self.dial_peer(*peer_id) {
                             warn!("Failed to dial discovered peer {}: {}", peer_id, e);
                         }
                     }
                 }
                 Some(NodeEvent::PeersDiscovered { peers })
             }
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Mdns(libp2p::mdns::Event::Expired(
+                peers,
+            ))) => {
+                // 仍被连接使用的地址保留，其余从 Kad 路由表移除（最后一个地址移除后 peer 随之移出）
+                if let Some(kad) = self.swarm.behaviour_mut().kad.as_mut() {
+                    for (peer_id, addr) in &peers {
+                        if !self.connections.uses_addr(peer_id, addr) {
+                            kad.remove_address(peer_id, addr);
+                        }
+                    }
+                }
+                Some(NodeEvent::PeersExpired { peers })
+            }
             SwarmEvent::Behaviour(CoreBehaviourEvent::Ping(ping::Event {
                 peer,
                 result: Ok(rtt),
@@ -288,16 +1315,32 @@ where
                 peer_id: peer,
                 rtt_ms: rtt.as_millis() as u64,
             }),
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Ping(ping::Event {
+                peer,
+                result: Err(e),
+                ..
+            })) => {
+                let kind = match &e {
+                    ping::Failure::Timeout => PingFailureKind::Timeout,
+                    ping::Failure::Unsupported => PingFailureKind::Unsupported,
+                    ping::Failure::Other { .. } => PingFailureKind::Other,
+                };
+                debug!("Ping to {} failed: {}", peer, e);
+                Some(NodeEvent::PingFailure {
+                    peer_id: peer,
+                    kind,
+                    error: e.to_string(),
+                })
+            }
             SwarmEvent::Behaviour(CoreBehaviourEvent::Identify(
                 libp2p::identify::Event::Received { peer_id, info, .. },
             )) => {
                 // 如果协议版本匹配，自动加入 Kad 并注册地址到 Swarm
                 if info.protocol_version == self.protocol_version {
                     for addr in &info.listen_addrs {
-                        self.swarm
-                            .behaviour_mut()
-                            .kad
-                            .add_address(&peer_id, addr.clone());
+                        if let Some(kad) = self.swarm.behaviour_mut().kad.as_mut() {
+                            kad.add_address(&peer_id, addr.clone());
+                        }
                         self.swarm.add_peer_address(peer_id, addr.clone());
                     }
                     info!(
@@ -316,7 +1359,7 @@ where
                     protocol_version: info.protocol_version,
                 })
             }
-            // AutoNAT: 仅在探测成功时上报 Public 状态。
+            // AutoNAT: 仅在状态变化时上报；新确认的地址由 ExternalAddrConfirmed 单独上报。
             // 单次探测失败不代表节点在 NAT 后面（可能是探测服务器自身不可达），
             // 因此失败时保持 Unknown，避免误判为 Private。
             SwarmEvent::Behaviour(CoreBehaviourEvent::Autonat(autonat::v2::client::Event {
@@ -330,19 +1373,51 @@ where
                         "AutoNAT: address {} confirmed reachable by {}",
                         tested_addr, server
                     );
-                    Some(NodeEvent::NatStatusChanged {
-                        status: NatStatus::Public,
-                        public_addr: Some(tested_addr),
-                    })
+                    self.nat
+                        .on_success()
+                        .then_some(NodeEvent::NatStatusChanged {
+                            status: NatStatus::Public,
+                            public_addr: Some(tested_addr),
+                        })
                 }
                 Err(e) => {
                     debug!(
                         "AutoNAT: address {} not reachable via {}: {}",
                         tested_addr, server, e
                     );
-                    None
+                    // 多个不同服务器均探测失败才判定为 Private
+                    self.nat.on_failure(server).then(|| {
+                        info!("AutoNAT: no address confirmed reachable, assuming private");
+                        NodeEvent::NatStatusChanged {
+                            status: NatStatus::Private,
+                            public_addr: None,
+                        }
+                    })
                 }
             },
+            // UPnP: 映射地址的确认/失效由 behaviour 经 ExternalAddrConfirmed/Expired 交给 Swarm，
+            // 这里只上报映射结果，便于前端提示用户检查路由器设置
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Upnp(event)) => {
+                let (status, addr) = match event {
+                    upnp::Event::NewExternalAddr(addr) => {
+                        info!("UPnP: mapped external address {}", addr);
+                        (UpnpMappingStatus::Mapped, Some(addr))
+                    }
+                    upnp::Event::ExpiredExternalAddr(addr) => {
+                        warn!("UPnP: mapping for {} expired", addr);
+                        (UpnpMappingStatus::Expired, Some(addr))
+                    }
+                    upnp::Event::GatewayNotFound => {
+                        info!("UPnP: no gateway found");
+                        (UpnpMappingStatus::GatewayNotFound, None)
+                    }
+                    upnp::Event::NonRoutableGateway => {
+                        info!("UPnP: gateway is not exposed to the public network");
+                        (UpnpMappingStatus::NonRoutableGateway, None)
+                    }
+                };
+                Some(NodeEvent::UpnpMappingResult { status, addr })
+            }
             // Kad 路由表更新：将学到的地址同步到 Swarm 地址簿，
             // 确保后续 dial(peer_id) 能找到地址（跨网络 DHT 查询场景）
             SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
@@ -360,6 +1435,46 @@ where
                 );
                 None
             }
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(libp2p::kad::Event::ModeChanged {
+                new_mode,
+            })) => {
+                info!("Kad mode changed to {}", new_mode);
+                Some(NodeEvent::KadModeChanged {
+                    mode: new_mode.into(),
+                })
+            }
+            // 自动 bootstrap 的进度：最后一步结束本轮并通知前端
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
+                libp2p::kad::Event::OutboundQueryProgressed {
+                    id,
+                    result: libp2p::kad::QueryResult::Bootstrap(result),
+                    step,
+                    ..
+                },
+            )) if self.auto_bootstrap_query == Some(id) => {
+                if !step.last {
+                    return None;
+                }
+                self.auto_bootstrap_query = None;
+                let num_remaining = match result {
+                    Ok(ok) => ok.num_remaining,
+                    Err(e) => {
+                        warn!("Auto bootstrap ended with error: {:?}", e);
+                        0
+                    }
+                };
+                info!("Auto bootstrap completed, {} remaining", num_remaining);
+                Some(NodeEvent::KadBootstrapCompleted { num_remaining })
+            }
+            // 入站 Kad 请求上报给前端；记录过滤模式下，入站 PUT 由这里校验后写入本地存储
+            SwarmEvent::Behaviour(CoreBehaviourEvent::Kad(
+                libp2p::kad::Event::InboundRequest { request },
+            )) => {
+                let kind = KadRequestKind::from(&request);
+                debug!("Kad inbound request: {:?}", kind);
+                self.handle_kad_inbound(request);
+                Some(NodeEvent::KadInboundRequest { request: kind })
+            }
             SwarmEvent::ListenerClosed {
                 listener_id,
                 addresses,
@@ -375,19 +1490,284 @@ where
                 warn!("Listener {:?} error: {}", listener_id, error);
                 None
             }
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                peer_id,
+                error,
+            } => {
+                debug!(
+                    "Outgoing connection error: peer={:?}, err={}",
+                    peer_id, error
+                );
+                Some(NodeEvent::ConnectionFailed {
+                    peer_id,
+                    connection_id,
+                    inbound: false,
+                    error: error.to_string(),
+                })
+            }
             SwarmEvent::IncomingConnectionError {
+                connection_id,
                 local_addr,
                 send_back_addr,
                 error,
-                ..
+                peer_id,
             } => {
                 debug!(
                     "Incoming connection error: local={}, remote={}, err={}",
                     local_addr, send_back_addr, error
                 );
-                None
+                Some(NodeEvent::ConnectionFailed {
+                    peer_id,
+                    connection_id,
+                    inbound: true,
+                    error: error.to_string(),
+                })
             }
             _ => None,
         }
     }
 }
+
+/// 等待可选定时器的下一次 tick，未启用时永远挂起
+async fn tick(timer: &mut Option<Interval>) {
+    match timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// 从连接错误中提取传输层失败，每个失败地址一个事件
+fn transport_errors<Req, Resp>(
+    event: &SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+) -> Vec<NodeEvent<Req>>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    let errors: Vec<(&libp2p::Multiaddr, &TransportError<std::io::Error>)> = match event {
+        SwarmEvent::OutgoingConnectionError {
+            error: DialError::Transport(errors),
+            ..
+        } => errors.iter().map(|(addr, e)| (addr, e)).collect(),
+        SwarmEvent::IncomingConnectionError {
+            send_back_addr,
+            error: ListenError::Transport(e),
+            ..
+        } => vec![(send_back_addr, e)],
+        _ => return Vec::new(),
+    };
+
+    errors
+        .into_iter()
+        .filter_map(|(addr, error)| {
+            let kind = classify_transport_error(error)?;
+            Some(NodeEvent::TransportError {
+                addr: addr.clone(),
+                kind,
+                error: error_chain(error),
+            })
+        })
+        .collect()
+}
+
+/// 对端在 identify 中报告的本节点地址
+fn observed_addr<Req, Resp>(
+    event: &SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+) -> Option<NodeEvent<Req>>
+where
+    Req: CborMessage,
+    Resp: CborMessage,
+{
+    match event {
+        SwarmEvent::Behaviour(CoreBehaviourEvent::Identify(identify::Event::Received {
+            peer_id,
+            info,
+            ..
+        })) => {
+            debug!("Peer {} observed us at {}", peer_id, info.observed_addr);
+            Some(NodeEvent::ObservedAddr {
+                by_peer: *peer_id,
+                addr: info.observed_addr.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// 传输层错误分类，对端明确拒绝连接时返回 `None`
+///
+/// 沿错误链逐层按具体错误类型判断，整条链都无法识别时归为 `Other`
+fn classify_transport_error(error: &TransportError<std::io::Error>) -> Option<TransportErrorKind> {
+    let mut current: &(dyn std::error::Error + 'static) = match error {
+        TransportError::MultiaddrNotSupported(_) => {
+            return Some(TransportErrorKind::UnsupportedAddress);
+        }
+        TransportError::Other(e) => e,
+    };
+
+    loop {
+        if let Some(kind) = classify_error_layer(current) {
+            return kind;
+        }
+        match inner_error(current) {
+            Some(inner) => current = inner,
+            None => return Some(TransportErrorKind::Other),
+        }
+    }
+}
+
+/// 按单层错误的类型分类；返回 `None` 表示这一层无法判断，需继续查看内层
+fn classify_error_layer(
+    error: &(dyn std::error::Error + 'static),
+) -> Option<Option<TransportErrorKind>> {
+    if let Some(e) = error.downcast_ref::<std::io::Error>() {
+        return match e.kind() {
+            ErrorKind::ConnectionRefused => Some(None),
+            ErrorKind::TimedOut => Some(Some(TransportErrorKind::HandshakeTimeout)),
+            ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof => Some(Some(TransportErrorKind::Reset)),
+            _ => None,
+        };
+    }
+    if let Some(e) = error.downcast_ref::<NegotiationError>() {
+        return matches!(e, NegotiationError::Failed)
+            .then_some(Some(TransportErrorKind::ProtocolMismatch));
+    }
+    if let Some(e) = error.downcast_ref::<noise::Error>() {
+        return (!matches!(e, noise::Error::Io(_)))
+            .then_some(Some(TransportErrorKind::HandshakeFailed));
+    }
+    if error.is::<tls::UpgradeError>() {
+        return Some(Some(TransportErrorKind::HandshakeFailed));
+    }
+    if let Some(quic::Error::HandshakeTimedOut) = error.downcast_ref::<quic::Error>() {
+        return Some(Some(TransportErrorKind::HandshakeTimeout));
+    }
+    None
+}
+
+/// 错误链的下一层
+///
+/// `io::Error`、`Either` 以及透明包装 `io::Error` 的变体，其 `source()` 会跳过被包装的错误本身，需显式取出。
+fn inner_error<'a>(
+    error: &'a (dyn std::error::Error + 'static),
+) -> Option<&'a (dyn std::error::Error + 'static)> {
+    if let Some(e) = error.downcast_ref::<std::io::Error>() {
+        return e.get_ref().map(|e| e as _);
+    }
+    if let Some(e) = error.downcast_ref::<Either<std::io::Error, std::io::Error>>() {
+        return Some(either::for_both!(e, e => e as _));
+    }
+    if let Some(e) = error.downcast_ref::<Either<noise::Error, tls::UpgradeError>>() {
+        return Some(either::for_both!(e, e => e as _));
+    }
+    if let Some(noise::Error::Io(e)) = error.downcast_ref::<noise::Error>() {
+        return Some(e);
+    }
+    if let Some(quic::Error::Io(e)) = error.downcast_ref::<quic::Error>() {
+        return Some(e);
+    }
+    error.source()
+}
+
+/// 拼接错误及其 source 链
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(e) = source {
+        message.push_str(": ");
+        message.push_str(&e.to_string());
+        source = e.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::core::transport::upgrade::TransportUpgradeError;
+
+    use super::*;
+
+    /// 与 libp2p 内部的 `UpgradeError::Apply` 相同，`source()` 直接返回握手错误（该类型未公开）
+    #[derive(Debug)]
+    struct Apply<E>(E);
+
+    impl<E: std::fmt::Display> std::fmt::Display for Apply<E> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Handshake failed")
+        }
+    }
+
+    impl<E: std::error::Error + 'static> std::error::Error for Apply<E> {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    /// 模拟握手失败经升级层与 `Boxed` 传输包装后的错误
+    fn upgrade_failure<E>(error: E) -> TransportError<std::io::Error>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        TransportError::Other(std::io::Error::other(Apply(error)))
+    }
+
+    #[test]
+    fn classifies_wrapped_upgrade_errors_by_type() {
+        let negotiation = TransportUpgradeError::<std::io::Error, noise::Error>::Upgrade(
+            NegotiationError::Failed.into(),
+        );
+        let negotiation = TransportError::Other(std::io::Error::other(negotiation));
+        assert_eq!(
+            classify_transport_error(&negotiation),
+            Some(TransportErrorKind::ProtocolMismatch)
+        );
+
+        let noise = upgrade_failure(noise::Error::AuthenticationFailed);
+        assert_eq!(
+            classify_transport_error(&noise),
+            Some(TransportErrorKind::HandshakeFailed)
+        );
+
+        // TLS 错误经 `SelectSecurity` 的 `Either` 和 or 传输的 `Either` 包装
+        let select: Either<noise::Error, tls::UpgradeError> = Either::Right(
+            tls::UpgradeError::ClientUpgrade(std::io::Error::other("bad certificate")),
+        );
+        let TransportError::Other(error) = upgrade_failure(select) else {
+            unreachable!();
+        };
+        let or: Either<std::io::Error, std::io::Error> = Either::Left(error);
+        let tls = TransportError::Other(std::io::Error::other(or));
+        assert_eq!(
+            classify_transport_error(&tls),
+            Some(TransportErrorKind::HandshakeFailed)
+        );
+    }
+
+    #[test]
+    fn classifies_inner_io_errors_by_kind() {
+        let reset = upgrade_failure(noise::Error::Io(ErrorKind::ConnectionReset.into()));
+        assert_eq!(
+            classify_transport_error(&reset),
+            Some(TransportErrorKind::Reset)
+        );
+
+        let refused = TransportUpgradeError::<_, NegotiationError>::Transport(
+            std::io::Error::from(ErrorKind::ConnectionRefused),
+        );
+        let refused = TransportError::Other(std::io::Error::other(refused));
+        assert_eq!(classify_transport_error(&refused), None);
+
+        // 描述文本不再参与分类
+        let other = TransportError::Other(std::io::Error::other("handshake timed out"));
+        assert_eq!(
+            classify_transport_error(&other),
+            Some(TransportErrorKind::Other)
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+
+use libp2p::PeerId;
+use parking_lot::Mutex;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{broadcast, mpsc};
+use tracing::debug;
+
+use crate::config::EventOverflow;
+use crate::event::NodeEvent;
+
+/// 按 peer 分发事件的订阅表（`EventReceiver::filter_peer` 登记）
+pub type PeerRoutes<Req> = Mutex<HashMap<PeerId, Vec<mpsc::Sender<NodeEvent<Req>>>>>;
+
+/// inbound request 的订阅表（`NetClient::inbound_requests` 登记），产出 `(pending_id, peer_id, request)`
+pub type InboundRoutes<Req> = Mutex<Vec<mpsc::UnboundedSender<(u64, PeerId, Req)>>>;
+
+/// 事件发送端：同时投递给单消费者的 `EventReceiver`、按 peer 过滤的接收器和所有广播订阅者
+///
+/// 通道满时按 `EventOverflow` 处理：`Block` 等待消费，`DropNewest` 丢弃新事件，
+/// `DropOldest` 暂存到 `backlog`（容量同通道），满后丢弃最旧的事件。
+/// 按 peer 过滤的接收器不参与背压，通道满时直接丢弃新事件。
+/// inbound request 订阅者使用无界通道，不丢弃也不阻塞。
+pub struct EventSender<Req> {
+    event_tx: mpsc::Sender<NodeEvent<Req>>,
+    broadcast_tx: broadcast::Sender<NodeEvent<Req>>,
+    /// 只由发送端持有强引用，事件循环退出后过滤接收器随之结束
+    peer_routes: Arc<PeerRoutes<Req>>,
+    /// 同上，事件循环退出后 inbound request 流随之结束
+    inbound_routes: Arc<InboundRoutes<Req>>,
+    overflow: EventOverflow,
+    backlog: VecDeque<NodeEvent<Req>>,
+    backlog_capacity: usize,
+    /// 累计丢弃的事件数（与 NetClient 共享）
+    dropped: Arc<AtomicU64>,
+}
+
+impl<Req: Clone + Send + 'static> EventSender<Req> {
+    pub fn new(
+        event_tx: mpsc::Sender<NodeEvent<Req>>,
+        broadcast_tx: broadcast::Sender<NodeEvent<Req>>,
+        overflow: EventOverflow,
+        dropped: Arc<AtomicU64>,
+    ) -> Self {
+        let backlog_capacity = event_tx.max_capacity();
+        Self {
+            event_tx,
+            broadcast_tx,
+            peer_routes: Arc::default(),
+            inbound_routes: Arc::default(),
+            overflow,
+            backlog: VecDeque::new(),
+            backlog_capacity,
+            dropped,
+        }
+    }
+
+    /// 按 peer 过滤的订阅表的弱引用，交给 `EventReceiver` 登记接收器
+    pub fn peer_routes(&self) -> Weak<PeerRoutes<Req>> {
+        Arc::downgrade(&self.peer_routes)
+    }
+
+    /// inbound request 订阅表的弱引用，交给 `NetClient` 登记订阅者
+    pub fn inbound_routes(&self) -> Weak<InboundRoutes<Req>> {
+        Arc::downgrade(&self.inbound_routes)
+    }
+
+    /// 发送事件；`EventReceiver` 已丢弃或没有订阅者时静默忽略
+    pub async fn send(&mut self, event: NodeEvent<Req>) {
+        self.route_to_peer(&event);
+        self.route_inbound(&event);
+        if self.broadcast_tx.receiver_count() > 0 {
+            let _ = self.broadcast_tx.send(event.clone());
+        }
+        match self.overflow {
+            EventOverflow::Block => {
+                let _ = self.event_tx.send(event).await;
+            }
+            EventOverflow::DropNewest => {
+                if let Err(TrySendError::Full(_)) = self.event_tx.try_send(event) {
+                    self.drop_event();
+                }
+            }
+            EventOverflow::DropOldest => {
+                // 先投递积压的事件，保持顺序
+                self.flush();
+                if !self.backlog.is_empty() {
+                    self.push_backlog(event);
+                } else if let Err(TrySendError::Full(event)) = self.event_tx.try_send(event) {
+                    self.push_backlog(event);
+                }
+            }
+        }
+    }
+
+    /// 是否有积压的事件（仅 `DropOldest`）
+    pub fn has_backlog(&self) -> bool {
+        !self.backlog.is_empty()
+    }
+
+    /// 通道有空位时完成，供事件循环在有积压时等待后调用 `flush`
+    ///
+    /// 返回的 future 不借用 `self`，可与事件循环的其他分支一起 select。
+    pub fn writable(&self) -> impl Future<Output = ()> + Send + 'static {
+        let event_tx = self.event_tx.clone();
+        async move {
+            // 立即释放 permit：只用于等待空位，实际发送由 flush 完成
+            let _ = event_tx.reserve_owned().await;
+        }
+    }
+
+    /// 尽量把积压的事件投递到通道
+    pub fn flush(&mut self) {
+        while let Some(event) = self.backlog.pop_front() {
+            match self.event_tx.try_send(event) {
+                Ok(()) => {}
+                Err(TrySendError::Full(event)) => {
+                    self.backlog.push_front(event);
+                    return;
+                }
+                Err(TrySendError::Closed(_)) => {
+                    self.backlog.clear();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// 投递给过滤该事件所涉 peer 的接收器，顺带清理已丢弃的接收器
+    fn route_to_peer(&self, event: &NodeEvent<Req>) {
+        let Some(peer_id) = event.peer_id() else {
+            return;
+        };
+        let mut routes = self.peer_routes.lock();
+        let Some(senders) = routes.get_mut(&peer_id) else {
+            return;
+        };
+        senders.retain(|tx| match tx.try_send(event.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.drop_event();
+                true
+            }
+            Err(TrySendError::Closed(_)) => false,
+        });
+        if senders.is_empty() {
+            routes.remove(&peer_id);
+        }
+    }
+
+    /// 把 inbound request 投递给所有订阅者，顺带清理已丢弃的订阅者
+    fn route_inbound(&self, event: &NodeEvent<Req>) {
+        let NodeEvent::InboundRequest {
+            peer_id,
+            pending_id,
+            request,
+        } = event
+        else {
+            return;
+        };
+        self.inbound_routes
+            .lock()
+            .retain(|tx| tx.send((*pending_id, *peer_id, request.clone())).is_ok());
+    }
+
+    fn push_backlog(&mut self, event: NodeEvent<Req>) {
+        self.backlog.push_back(event);
+        if self.backlog.len() > self.backlog_capacity {
+            self.backlog.pop_front();
+            self.drop_event();
+        }
+    }
+
+    fn drop_event(&self) {
+        let total = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        debug!("Event channel full, dropped event (total {})", total);
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use libp2p::PeerId;
+use parking_lot::Mutex;
+
+use crate::config::HandshakeInfo;
+
+/// 应用层握手状态：本节点的握手信息及已完成握手的 peer
+///
+/// EventLoop 在握手响应到达时写入、最后一个连接关闭时移除，
+/// NetClient 通过 `peer_info` 同步读取。
+#[derive(Clone, Default)]
+pub struct PeerHandshakes {
+    local: Option<HandshakeInfo>,
+    peers: Arc<Mutex<HashMap<PeerId, HandshakeInfo>>>,
+}
+
+impl PeerHandshakes {
+    pub fn new(local: Option<HandshakeInfo>) -> Self {
+        Self {
+            local,
+            peers: Default::default(),
+        }
+    }
+
+    /// 本节点的握手信息，未开启握手时为 `None`
+    pub fn local(&self) -> Option<&HandshakeInfo> {
+        self.local.as_ref()
+    }
+
+    pub fn insert(&self, peer_id: PeerId, info: HandshakeInfo) {
+        self.peers.lock().insert(peer_id, info);
+    }
+
+    pub fn remove(&self, peer_id: &PeerId) {
+        self.peers.lock().remove(peer_id);
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<HandshakeInfo> {
+        self.peers.lock().get(peer_id).cloned()
+    }
+}
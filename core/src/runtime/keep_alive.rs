@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use libp2p::PeerId;
+use parking_lot::Mutex;
+
+/// 需要保活的 peer 及其保活截止时间
+///
+/// libp2p 的空闲超时是 swarm 全局的，这里由 NetClient 登记需要延长的 peer，
+/// EventLoop 周期性地向其中仍在保活期内的 peer 发起 Identify push，
+/// 使连接在截止时间前不会因空闲而关闭；其余连接照常按全局超时关闭。
+/// 截止时间为 `None` 的 peer 被固定（pinned）：无限期保活，断开后还会被重新拨号。
+#[derive(Clone, Default)]
+pub struct KeepAlivePeers {
+    inner: Arc<Mutex<HashMap<PeerId, Option<Instant>>>>,
+}
+
+impl KeepAlivePeers {
+    /// 保活 `peer_id` 至 `duration` 之后，`Duration::ZERO` 表示取消
+    pub fn set(&self, peer_id: PeerId, duration: Duration) {
+        let mut peers = self.inner.lock();
+        if duration.is_zero() {
+            peers.remove(&peer_id);
+        } else {
+            peers.insert(peer_id, Some(Instant::now() + duration));
+        }
+    }
+
+    /// 固定或取消固定 `peer_id`，取消时同时清除限时保活
+    pub fn pin(&self, peer_id: PeerId, keep: bool) {
+        let mut peers = self.inner.lock();
+        if keep {
+            peers.insert(peer_id, None);
+        } else {
+            peers.remove(&peer_id);
+        }
+    }
+
+    /// 清理已到期的条目，返回仍需保活的 peer（含固定的 peer）
+    pub fn active(&self) -> Vec<PeerId> {
+        let now = Instant::now();
+        let mut peers = self.inner.lock();
+        peers.retain(|_, until| until.is_none_or(|until| until > now));
+        peers.keys().copied().collect()
+    }
+
+    /// 是否为固定的 peer
+    pub fn is_pinned(&self, peer_id: &PeerId) -> bool {
+        matches!(self.inner.lock().get(peer_id), Some(None))
+    }
+
+    /// 所有固定的 peer
+    pub fn pinned(&self) -> Vec<PeerId> {
+        self.inner
+            .lock()
+            .iter()
+            .filter(|(_, until)| until.is_none())
+            .map(|(peer_id, _)| *peer_id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_active_and_cancel() {
+        let peers = KeepAlivePeers::default();
+        let a = PeerId::random();
+        let b = PeerId::random();
+
+        peers.set(a, Duration::from_secs(60));
+        peers.set(b, Duration::from_nanos(1));
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(peers.active(), vec![a]); // b 已到期被清理
+
+        peers.set(a, Duration::ZERO);
+        assert!(peers.active().is_empty());
+    }
+
+    #[test]
+    fn pinned_peers_never_expire() {
+        let peers = KeepAlivePeers::default();
+        let a = PeerId::random();
+        let b = PeerId::random();
+
+        peers.pin(a, true);
+        peers.set(b, Duration::from_nanos(1));
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(peers.active(), vec![a]);
+        assert_eq!(peers.pinned(), vec![a]);
+        assert!(peers.is_pinned(&a));
+
+        // 限时保活覆盖固定，反之亦然
+        peers.set(a, Duration::from_secs(60));
+        assert!(!peers.is_pinned(&a));
+        peers.pin(a, true);
+        peers.pin(a, false);
+        assert!(peers.active().is_empty());
+    }
+}
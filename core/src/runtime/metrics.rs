@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use libp2p::metrics::{Metrics, Recorder, Registry};
+use libp2p::request_response::{Event as ReqRespEvent, Message};
+use libp2p::swarm::SwarmEvent;
+use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue};
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+
+use super::{CborMessage, CoreBehaviourEvent};
+
+/// 请求方向
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelValue)]
+enum Direction {
+    Inbound,
+    Outbound,
+}
+
+/// 请求结果
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelValue)]
+enum Outcome {
+    Success,
+    Failure,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct RequestLabels {
+    direction: Direction,
+    outcome: Outcome,
+}
+
+/// Prometheus 指标（需开启 `metrics` feature）
+///
+/// - `libp2p_*`: libp2p-metrics 提供的连接、带宽、Ping RTT、Kad 查询、Identify 等指标
+/// - `swarm_p2p_req_resp_requests_total`: 已完成的 request-response 请求，
+///   按方向（inbound/outbound）和结果（success/failure）区分
+///
+/// 注册表在节点启动时一次性建好，之后只读；各指标内部为原子计数，clone 后共享。
+#[derive(Clone)]
+pub struct NodeMetrics {
+    registry: Arc<Registry>,
+    recorder: Arc<EventMetrics>,
+}
+
+/// 由事件驱动的指标
+struct EventMetrics {
+    libp2p: Metrics,
+    requests: Family<RequestLabels, Counter>,
+}
+
+impl NodeMetrics {
+    /// 在 `registry` 上注册全部指标（带宽指标由 SwarmBuilder 预先注册）
+    pub fn new(mut registry: Registry) -> Self {
+        let libp2p = Metrics::new(&mut registry);
+        let requests = Family::<RequestLabels, Counter>::default();
+        registry.sub_registry_with_prefix("swarm_p2p").register(
+            "req_resp_requests",
+            "Completed request-response requests by direction and outcome",
+            requests.clone(),
+        );
+        Self {
+            registry: Arc::new(registry),
+            recorder: Arc::new(EventMetrics { libp2p, requests }),
+        }
+    }
+
+    pub fn registry(&self) -> Arc<Registry> {
+        self.registry.clone()
+    }
+
+    /// 记录一个 swarm 事件
+    pub fn record<Req: CborMessage, Resp: CborMessage>(
+        &self,
+        event: &SwarmEvent<CoreBehaviourEvent<Req, Resp>>,
+    ) {
+        let recorder = &self.recorder;
+        recorder.libp2p.record(event);
+        let SwarmEvent::Behaviour(event) = event else {
+            return;
+        };
+        match event {
+            CoreBehaviourEvent::Ping(e) => recorder.libp2p.record(e),
+            CoreBehaviourEvent::Identify(e) => recorder.libp2p.record(e),
+            CoreBehaviourEvent::Kad(e) => recorder.libp2p.record(e),
+            CoreBehaviourEvent::Dcutr(e) => recorder.libp2p.record(e),
+            CoreBehaviourEvent::Gossipsub(e) => recorder.libp2p.record(e),
+            CoreBehaviourEvent::ReqResp(e) => {
+                let labels = match e {
+                    ReqRespEvent::Message {
+                        message: Message::Response { .. },
+                        ..
+                    } => (Direction::Outbound, Outcome::Success),
+                    ReqRespEvent::OutboundFailure { .. } => (Direction::Outbound, Outcome::Failure),
+                    ReqRespEvent::ResponseSent { .. } => (Direction::Inbound, Outcome::Success),
+                    ReqRespEvent::InboundFailure { .. } => (Direction::Inbound, Outcome::Failure),
+                    ReqRespEvent::Message { .. } => return,
+                };
+                recorder
+                    .requests
+                    .get_or_create(&RequestLabels {
+                        direction: labels.0,
+                        outcome: labels.1,
+                    })
+                    .inc();
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,7 +1,34 @@
+mod bandwidth;
 mod behaviour;
+mod codec;
+mod connections;
+mod dials;
 mod event_loop;
+mod event_sender;
+mod handshake;
+mod keep_alive;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod nat;
 mod node;
+mod reconnect;
+mod relay;
+mod shared;
+mod transport;
 
+pub use bandwidth::{BandwidthStats, PeerBandwidth};
+pub(crate) use behaviour::KAD_REPLICATION_FACTOR;
 pub use behaviour::{CborMessage, CoreBehaviour, CoreBehaviourEvent};
+pub use codec::LimitedCborCodec;
+pub use connections::{ConnectionInfo, PeerConnections, RelayedConnection};
+pub use dials::{DialStart, PendingDials};
 pub use event_loop::EventLoop;
+pub use event_sender::{EventSender, InboundRoutes, PeerRoutes};
+pub use handshake::PeerHandshakes;
+pub use keep_alive::KeepAlivePeers;
+#[cfg(feature = "metrics")]
+pub use metrics::NodeMetrics;
+pub use nat::NatTracker;
 pub use node::start;
+pub use relay::{RelayListeners, RelayReservationInfo};
+pub use shared::SharedState;
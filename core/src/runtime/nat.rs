@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use libp2p::{Multiaddr, PeerId};
+use parking_lot::Mutex;
+
+use crate::event::NatStatus;
+
+/// AutoNAT 探测结果汇总
+///
+/// EventLoop 根据每次探测结果更新，`NatStatusCommand` 读取。
+/// 任一地址被确认可达即为 `Public`；尚未确认时，累计有 `failure_threshold`
+/// 个不同的服务器探测失败才判定为 `Private`，避免单个服务器异常造成误判。
+///
+/// 同时记录已确认的外部地址，AutoNAT 周期性重测时重复确认同一地址不再上报；
+/// 已确认的地址全部失效（如切换到 NAT 后的网络）时，状态回到 `Unknown` 重新判定。
+#[derive(Clone)]
+pub struct NatTracker {
+    failure_threshold: usize,
+    state: Arc<Mutex<NatState>>,
+}
+
+#[derive(Default)]
+struct NatState {
+    status: NatStatus,
+    failed_servers: HashSet<PeerId>,
+    confirmed_addrs: HashSet<Multiaddr>,
+}
+
+impl NatTracker {
+    /// `failure_threshold` 为 0 时不判定 `Private`
+    pub fn new(failure_threshold: usize) -> Self {
+        Self {
+            failure_threshold,
+            state: Default::default(),
+        }
+    }
+
+    pub fn status(&self) -> NatStatus {
+        self.state.lock().status.clone()
+    }
+
+    /// 记录一次成功的探测，状态因此变为 `Public` 时返回 `true`
+    pub fn on_success(&self) -> bool {
+        let mut state = self.state.lock();
+        state.failed_servers.clear();
+        let changed = state.status != NatStatus::Public;
+        state.status = NatStatus::Public;
+        changed
+    }
+
+    /// 记录一个已确认的外部地址，首次确认时返回 `true`
+    pub fn confirm_addr(&self, addr: &Multiaddr) -> bool {
+        self.state.lock().confirmed_addrs.insert(addr.clone())
+    }
+
+    /// 外部地址失效，之后再次确认时会重新上报
+    ///
+    /// 最后一个已确认地址失效使 `Public` 回到 `Unknown` 时返回 `true`
+    pub fn expire_addr(&self, addr: &Multiaddr) -> bool {
+        let mut state = self.state.lock();
+        if !state.confirmed_addrs.remove(addr)
+            || !state.confirmed_addrs.is_empty()
+            || state.status != NatStatus::Public
+        {
+            return false;
+        }
+        state.status = NatStatus::Unknown;
+        state.failed_servers.clear();
+        true
+    }
+
+    /// 记录一次失败的探测，状态因此变为 `Private` 时返回 `true`
+    pub fn on_failure(&self, server: PeerId) -> bool {
+        let mut state = self.state.lock();
+        if self.failure_threshold == 0 || !matches!(state.status, NatStatus::Unknown) {
+            return false;
+        }
+        state.failed_servers.insert(server);
+        if state.failed_servers.len() >= self.failure_threshold {
+            state.status = NatStatus::Private;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_after_distinct_server_failures() {
+        let tracker = NatTracker::new(2);
+        let server = PeerId::random();
+        assert!(!tracker.on_failure(server));
+        // 同一服务器重复失败不累计
+        assert!(!tracker.on_failure(server));
+        assert_eq!(tracker.status(), NatStatus::Unknown);
+        assert!(tracker.on_failure(PeerId::random()));
+        assert_eq!(tracker.status(), NatStatus::Private);
+        // 已判定后不再重复上报
+        assert!(!tracker.on_failure(PeerId::random()));
+    }
+
+    #[test]
+    fn success_overrides_failures() {
+        let tracker = NatTracker::new(1);
+        assert!(tracker.on_failure(PeerId::random()));
+        assert!(tracker.on_success());
+        assert_eq!(tracker.status(), NatStatus::Public);
+        // 再次成功不算状态变化
+        assert!(!tracker.on_success());
+        // 已确认公网后，其他地址的失败不改变状态
+        assert!(!tracker.on_failure(PeerId::random()));
+        assert_eq!(tracker.status(), NatStatus::Public);
+    }
+
+    #[test]
+    fn public_reverts_to_unknown_when_all_addrs_expire() {
+        let tracker = NatTracker::new(1);
+        let v4: Multiaddr = "/ip4/203.0.113.1/tcp/4001".parse().unwrap();
+        let v6: Multiaddr = "/ip6/2001:db8::1/tcp/4001".parse().unwrap();
+        assert!(tracker.on_success());
+        tracker.confirm_addr(&v4);
+        tracker.confirm_addr(&v6);
+
+        // 仍有地址可达时保持 Public
+        assert!(!tracker.expire_addr(&v4));
+        assert_eq!(tracker.status(), NatStatus::Public);
+        assert!(tracker.expire_addr(&v6));
+        assert_eq!(tracker.status(), NatStatus::Unknown);
+        // 未确认过的地址失效不算状态变化
+        assert!(!tracker.expire_addr(&v6));
+
+        // 回到 Unknown 后可重新判定为 Private
+        assert!(tracker.on_failure(PeerId::random()));
+        assert_eq!(tracker.status(), NatStatus::Private);
+    }
+
+    #[test]
+    fn addr_confirmed_once_until_expired() {
+        let tracker = NatTracker::new(1);
+        let v4: Multiaddr = "/ip4/203.0.113.1/tcp/4001".parse().unwrap();
+        let v6: Multiaddr = "/ip6/2001:db8::1/tcp/4001".parse().unwrap();
+        assert!(tracker.confirm_addr(&v4));
+        assert!(!tracker.confirm_addr(&v4));
+        assert!(tracker.confirm_addr(&v6));
+        tracker.expire_addr(&v4);
+        assert!(tracker.confirm_addr(&v4));
+    }
+
+    #[test]
+    fn zero_threshold_never_private() {
+        let tracker = NatTracker::new(0);
+        assert!(!tracker.on_failure(PeerId::random()));
+        assert_eq!(tracker.status(), NatStatus::Unknown);
+    }
+}
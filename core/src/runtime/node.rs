@@ -1,15 +1,19 @@
 use anyhow::Result;
-use libp2p::{SwarmBuilder, noise, tcp, yamux};
-use tokio::sync::mpsc;
+use libp2p::SwarmBuilder;
+use libp2p::kad::store::MemoryStore;
+use tokio::sync::{broadcast, mpsc};
 
+use super::behaviour::identify_config;
 use super::event_loop::EventLoop;
-use super::{CborMessage, CoreBehaviour};
+use super::transport::build_transport;
+use super::{
+    CborMessage, CoreBehaviour, EventSender, KeepAlivePeers, NatTracker, PeerBandwidth,
+    PeerConnections, PeerHandshakes, PendingDials, RelayListeners, SharedState,
+};
 use crate::client::{EventReceiver, NetClient};
 use crate::config::NodeConfig;
 use crate::pending_map::PendingMap;
-
-const COMMAND_CHANNEL_SIZE: usize = 32;
-const EVENT_CHANNEL_SIZE: usize = 64;
+use crate::store::BoxedStore;
 
 /// 启动节点
 ///
@@ -17,11 +21,11 @@ const EVENT_CHANNEL_SIZE: usize = 64;
 /// - NetClient: 用于发送命令（dial, close 等）
 /// - EventReceiver: 用于接收事件（peer discovered, connected 等）
 ///
-/// Transport 层包含：
-/// - TCP + Noise + Yamux（稳定连接，防火墙友好）
+/// Transport 层包含（TCP / QUIC 由 `config.transport` 选择）：
+/// - TCP + Noise / TLS（由 `config.security` 选择）+ Yamux（稳定连接，防火墙友好）
 /// - QUIC（内置 TLS 1.3 加密和多路复用，NAT 穿透更优）
 /// - Relay client（无法直连时的兜底）
-/// - DNS 解析（支持 /dnsaddr/, /dns4/, /dns6/ multiaddr）
+/// - DNS 解析（需 `dns` feature，支持 /dnsaddr/, /dns4/, /dns6/ multiaddr，解析器由 `config.dns` 选择）
 pub fn start<Req, Resp>(
     keypair: libp2p::identity::Keypair,
     config: NodeConfig,
@@ -30,48 +34,97 @@ where
     Req: CborMessage,
     Resp: CborMessage,
 {
-    // 构建 swarm：TCP + QUIC + (可选 DNS) + Relay
-    // dns feature 由上层按平台决定是否启用（Android 上 /etc/resolv.conf 不存在）
+    let local_peer_id = keypair.public().to_peer_id();
+
+    for addr in &config.listen_addrs {
+        if !config.transport.supports(addr) {
+            anyhow::bail!(
+                "listen address {} is not supported by transport {:?}",
+                addr,
+                config.transport
+            );
+        }
+    }
+
+    // Kad 存储：未配置时使用内存存储，关闭 Kad 时不创建
+    let kad_store = match &config.kad_store {
+        _ if !config.enable_kad => None,
+        Some(factory) => Some(factory.create(local_peer_id)?),
+        None => Some(BoxedStore::new(MemoryStore::with_config(
+            local_peer_id,
+            config.memory_store_config(),
+        ))),
+    };
+
+    // 构建 swarm：按 transport 选择 TCP / QUIC + (可选 DNS) + Relay（逐连接计流量）
+    // dns feature 由上层按平台决定是否启用；系统解析器不可用时（如 Android 上没有
+    // /etc/resolv.conf）不加 DNS 层
+    let bandwidth = PeerBandwidth::default();
+    let (transport, relay_client) = build_transport(&keypair, &config, &bandwidth)?;
+    let identify_config = identify_config(&config, keypair.public());
     let builder = SwarmBuilder::with_existing_identity(keypair)
         .with_tokio()
-        .with_tcp(
-            tcp::Config::default(),
-            noise::Config::new,
-            yamux::Config::default,
-        )?
-        .with_quic();
+        .with_other_transport(|_| transport)?;
 
-    #[cfg(feature = "dns")]
-    let builder = builder.with_dns()?;
+    // 带宽指标需包装 transport，只能在构建阶段注册
+    #[cfg(feature = "metrics")]
+    let mut registry = libp2p::metrics::Registry::default();
+    #[cfg(feature = "metrics")]
+    let builder = builder.with_bandwidth_metrics(&mut registry);
 
     let swarm = builder
-        .with_relay_client(noise::Config::new, yamux::Config::default)?
-        .with_behaviour(|key, relay_client| {
-            CoreBehaviour::<Req, Resp>::new(key, relay_client, &config)
+        .with_behaviour(|key| {
+            CoreBehaviour::<Req, Resp>::new(key, relay_client, kad_store, &config)
         })?
         .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(config.idle_connection_timeout))
         .build();
 
     // 创建 channels
-    let (command_tx, command_rx) = mpsc::channel(COMMAND_CHANNEL_SIZE);
-    let (event_tx, event_rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
-
-    // 创建共享的 PendingMap（EventLoop 存入，NetClient 取出）
-    // TTL 与 req_resp_timeout 保持一致，避免 channel 被提前清理
-    let pending_channels = PendingMap::new(config.req_resp_timeout);
+    let (command_tx, command_rx) = mpsc::channel(config.command_channel_size);
+    let (event_tx, event_rx) = mpsc::channel(config.event_channel_size);
+    // 广播订阅（subscribe_events），NetClient 只持有弱引用，事件循环退出后订阅者收到 None
+    let (broadcast_tx, _) = broadcast::channel(config.event_channel_size);
+    let broadcast_weak = broadcast_tx.downgrade();
 
-    // 创建 event loop
-    let mut event_loop = EventLoop::new(
-        swarm,
-        command_rx,
+    // EventLoop 与 NetClient 共享的状态
+    let shared = SharedState {
+        // 暂存 inbound request 的 ResponseChannel（EventLoop 存入，NetClient 取出）
+        // TTL 与 req_resp_timeout 保持一致，避免 channel 被提前清理
+        pending_channels: PendingMap::new(config.req_resp_timeout),
+        // 流式请求的暂存状态，对端超过 req_resp_timeout 未拉取即视为放弃
+        stream_slots: PendingMap::new(config.req_resp_timeout),
+        keep_alive_peers: KeepAlivePeers::default(),
+        peer_handshakes: PeerHandshakes::new(config.handshake.clone()),
+        nat: NatTracker::new(config.autonat_failure_threshold),
+        relay_listeners: RelayListeners::default(),
+        connections: PeerConnections::default(),
+        pending_dials: PendingDials::default(),
+        dropped_events: Default::default(),
+        bandwidth,
+        #[cfg(feature = "metrics")]
+        metrics: super::NodeMetrics::new(registry),
+    };
+    let event_tx = EventSender::new(
         event_tx,
-        pending_channels.clone(),
-        config.protocol_version.clone(),
+        broadcast_tx,
+        config.event_overflow,
+        shared.dropped_events.clone(),
     );
 
+    let peer_routes = event_tx.peer_routes();
+    let inbound_routes = event_tx.inbound_routes();
+
+    // 创建 event loop
+    let mut event_loop = EventLoop::new(swarm, command_rx, event_tx, shared.clone(), &config);
+
     // 启动监听
     event_loop.start_listen(&config.listen_addrs)?;
 
+    // 预热路由表，需在连接引导节点之前完成
+    if !config.seed_peers.is_empty() {
+        event_loop.import_seed_peers(&config.seed_peers);
+    }
+
     // 连接引导节点
     if !config.bootstrap_peers.is_empty() {
         event_loop.connect_bootstrap_peers(&config.bootstrap_peers);
@@ -81,8 +134,16 @@ where
     tokio::spawn(event_loop.run());
 
     // 返回 client 和 event receiver
-    let client = NetClient::new(command_tx, pending_channels);
-    let event_receiver = EventReceiver::new(event_rx);
+    let client = NetClient::new(
+        local_peer_id,
+        command_tx,
+        broadcast_weak,
+        inbound_routes,
+        shared,
+        &config,
+        identify_config,
+    );
+    let event_receiver = EventReceiver::new(event_rx, peer_routes);
 
     Ok((client, event_receiver))
 }
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use libp2p::PeerId;
+use tokio::time::Instant;
+
+use crate::config::BootstrapRetry;
+
+/// 引导节点重连调度
+///
+/// EventLoop 在引导节点拨号失败或断开时调用 `schedule` 排期，
+/// 到期后重新拨号；连接建立时 `connected` 清零退避计数。
+pub struct BootstrapReconnect {
+    retry: Option<BootstrapRetry>,
+    peers: HashMap<PeerId, Backoff>,
+}
+
+#[derive(Default)]
+struct Backoff {
+    /// 连续失败次数
+    attempt: u32,
+    /// 下次重拨时间，`None` 表示未排期
+    due: Option<Instant>,
+}
+
+impl BootstrapReconnect {
+    /// `retry` 为 `None` 时不重连
+    pub fn new(retry: Option<BootstrapRetry>) -> Self {
+        Self {
+            retry,
+            peers: HashMap::new(),
+        }
+    }
+
+    pub fn add_peer(&mut self, peer_id: PeerId) {
+        self.peers.entry(peer_id).or_default();
+    }
+
+    /// 连接成功，停止重试并清零退避计数
+    pub fn connected(&mut self, peer_id: &PeerId) {
+        if let Some(backoff) = self.peers.get_mut(peer_id) {
+            *backoff = Backoff::default();
+        }
+    }
+
+    /// 为引导节点排期一次重拨，已排期或不是引导节点时忽略，返回等待时间
+    pub fn schedule(&mut self, peer_id: &PeerId, now: Instant) -> Option<Duration> {
+        let retry = self.retry?;
+        let backoff = self.peers.get_mut(peer_id)?;
+        if backoff.due.is_some() {
+            return None;
+        }
+        let delay = retry.delay(backoff.attempt, rand::random());
+        backoff.attempt = backoff.attempt.saturating_add(1);
+        backoff.due = Some(now + delay);
+        Some(delay)
+    }
+
+    /// 最早的重拨时间
+    pub fn next_due(&self) -> Option<Instant> {
+        self.peers.values().filter_map(|b| b.due).min()
+    }
+
+    /// 取出已到期的引导节点（随后由调用方重拨）
+    pub fn take_due(&mut self, now: Instant) -> Vec<PeerId> {
+        self.peers
+            .iter_mut()
+            .filter(|(_, b)| b.due.is_some_and(|due| due <= now))
+            .map(|(peer_id, b)| {
+                b.due = None;
+                *peer_id
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry() -> BootstrapRetry {
+        BootstrapRetry::new(Duration::from_secs(1), Duration::from_secs(4)).with_jitter(0.0)
+    }
+
+    #[test]
+    fn backoff_grows_until_connected() {
+        let peer = PeerId::random();
+        let mut reconnect = BootstrapReconnect::new(Some(retry()));
+        reconnect.add_peer(peer);
+        let now = Instant::now();
+
+        let delays: Vec<Duration> = (0..4)
+            .map(|_| {
+                let delay = reconnect.schedule(&peer, now).unwrap();
+                // 已排期时重复调用不改变到期时间
+                assert_eq!(reconnect.schedule(&peer, now), None);
+                assert_eq!(reconnect.take_due(now + delay), vec![peer]);
+                delay
+            })
+            .collect();
+        assert_eq!(delays, [1, 2, 4, 4].map(Duration::from_secs));
+
+        reconnect.connected(&peer);
+        assert_eq!(reconnect.schedule(&peer, now), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn ignores_unknown_peers_and_disabled_policy() {
+        let peer = PeerId::random();
+        let mut reconnect = BootstrapReconnect::new(Some(retry()));
+        assert_eq!(reconnect.schedule(&peer, Instant::now()), None);
+        assert_eq!(reconnect.next_due(), None);
+
+        let mut disabled = BootstrapReconnect::new(None);
+        disabled.add_peer(peer);
+        assert_eq!(disabled.schedule(&peer, Instant::now()), None);
+    }
+
+    #[test]
+    fn take_due_only_returns_expired() {
+        let (a, b) = (PeerId::random(), PeerId::random());
+        let mut reconnect = BootstrapReconnect::new(Some(retry()));
+        reconnect.add_peer(a);
+        reconnect.add_peer(b);
+        let now = Instant::now();
+        // b 已失败过一次，第二次退避 2 秒
+        reconnect.schedule(&b, now);
+        assert_eq!(reconnect.take_due(now + Duration::from_secs(1)), vec![b]);
+        reconnect.schedule(&b, now);
+        reconnect.schedule(&a, now);
+
+        assert_eq!(reconnect.next_due(), Some(now + Duration::from_secs(1)));
+        assert_eq!(reconnect.take_due(now + Duration::from_secs(1)), vec![a]);
+        assert_eq!(reconnect.next_due(), Some(now + Duration::from_secs(2)));
+    }
+}
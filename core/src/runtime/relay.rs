@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use libp2p::core::transport::ListenerId;
+use libp2p::{Multiaddr, PeerId};
+use parking_lot::Mutex;
+
+/// 经某个中继的预约，由 `NetClient::relay_reservations` 返回
+///
+/// relay client 会在预约到期前自动续约且不公开到期时间，因此只给出最近一次接受 / 续约的时间。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayReservationInfo {
+    pub relay_peer_id: PeerId,
+    /// 经该中继的电路监听地址（`<relay_addr>/p2p/<relay>/p2p-circuit`）
+    pub circuit_addrs: Vec<Multiaddr>,
+    /// 最近一次被中继接受或续约的时间，`None` 表示仍在申请中
+    pub accepted_at: Option<Instant>,
+    /// 中继限定的单条电路时长
+    pub max_circuit_duration: Option<Duration>,
+    /// 中继限定的单条电路流量
+    pub max_circuit_bytes: Option<u64>,
+}
+
+/// relay 电路监听器 → 中继节点
+///
+/// 每个电路监听器对应一次预约：监听器存活期间预约由 relay client 自动续约，
+/// 关闭监听器即放弃预约。EventLoop 在监听器关闭时移除条目，
+/// `reserve_relay` / `cancel_relay` 命令登记和取消监听器。
+#[derive(Clone, Default)]
+pub struct RelayListeners {
+    inner: Arc<Mutex<HashMap<ListenerId, RelayListener>>>,
+}
+
+struct RelayListener {
+    relay_peer_id: PeerId,
+    circuit_addr: Multiaddr,
+    /// 由 `cancel_relay` 主动关闭，关闭时不视为预约失败
+    cancelled: bool,
+    accepted_at: Option<Instant>,
+    max_circuit_duration: Option<Duration>,
+    max_circuit_bytes: Option<u64>,
+}
+
+impl RelayListeners {
+    pub fn insert(&self, listener_id: ListenerId, relay_peer_id: PeerId, circuit_addr: Multiaddr) {
+        self.inner.lock().insert(
+            listener_id,
+            RelayListener {
+                relay_peer_id,
+                circuit_addr,
+                cancelled: false,
+                accepted_at: None,
+                max_circuit_duration: None,
+                max_circuit_bytes: None,
+            },
+        );
+    }
+
+    /// 中继接受（或续约）了预约，记录时间与中继给出的电路限制
+    pub fn accept(
+        &self,
+        relay_peer_id: &PeerId,
+        max_circuit_duration: Option<Duration>,
+        max_circuit_bytes: Option<u64>,
+    ) {
+        let now = Instant::now();
+        for listener in self
+            .inner
+            .lock()
+            .values_mut()
+            .filter(|l| l.relay_peer_id == *relay_peer_id)
+        {
+            listener.accepted_at = Some(now);
+            listener.max_circuit_duration = max_circuit_duration;
+            listener.max_circuit_bytes = max_circuit_bytes;
+        }
+    }
+
+    /// 按中继汇总当前的预约（已被 `cancel_relay` 取消的不计入）
+    pub fn reservations(&self) -> Vec<RelayReservationInfo> {
+        let mut by_relay: HashMap<PeerId, RelayReservationInfo> = HashMap::new();
+        for listener in self.inner.lock().values().filter(|l| !l.cancelled) {
+            let info =
+                by_relay
+                    .entry(listener.relay_peer_id)
+                    .or_insert_with(|| RelayReservationInfo {
+                        relay_peer_id: listener.relay_peer_id,
+                        circuit_addrs: Vec::new(),
+                        accepted_at: None,
+                        max_circuit_duration: None,
+                        max_circuit_bytes: None,
+                    });
+            info.circuit_addrs.push(listener.circuit_addr.clone());
+            if listener.accepted_at > info.accepted_at {
+                info.accepted_at = listener.accepted_at;
+                info.max_circuit_duration = listener.max_circuit_duration;
+                info.max_circuit_bytes = listener.max_circuit_bytes;
+            }
+        }
+        by_relay.into_values().collect()
+    }
+
+    /// 监听器已关闭，返回其对应的中继节点及是否为主动取消
+    pub fn remove(&self, listener_id: &ListenerId) -> Option<(PeerId, bool)> {
+        self.inner
+            .lock()
+            .remove(listener_id)
+            .map(|l| (l.relay_peer_id, l.cancelled))
+    }
+
+    /// 将经 `relay_peer_id` 预约的监听器标记为取消，返回这些监听器
+    pub fn cancel(&self, relay_peer_id: &PeerId) -> Vec<ListenerId> {
+        self.inner
+            .lock()
+            .iter_mut()
+            .filter(|(_, l)| l.relay_peer_id == *relay_peer_id)
+            .map(|(id, l)| {
+                l.cancelled = true;
+                *id
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_marks_only_listeners_of_relay() {
+        let listeners = RelayListeners::default();
+        let (relay_a, relay_b) = (PeerId::random(), PeerId::random());
+        let (id_a, id_b) = (ListenerId::next(), ListenerId::next());
+        listeners.insert(id_a, relay_a, Multiaddr::empty());
+        listeners.insert(id_b, relay_b, Multiaddr::empty());
+
+        assert_eq!(listeners.cancel(&relay_a), vec![id_a]);
+        assert_eq!(listeners.remove(&id_a), Some((relay_a, true)));
+        assert_eq!(listeners.remove(&id_b), Some((relay_b, false)));
+        assert_eq!(listeners.remove(&id_b), None);
+        assert!(listeners.cancel(&relay_a).is_empty());
+    }
+
+    #[test]
+    fn reservations_group_listeners_by_relay() {
+        let listeners = RelayListeners::default();
+        let (relay_a, relay_b) = (PeerId::random(), PeerId::random());
+        let addr_1: Multiaddr = "/ip4/1.2.3.4/tcp/1/p2p-circuit".parse().unwrap();
+        let addr_2: Multiaddr = "/ip4/1.2.3.4/udp/1/quic-v1/p2p-circuit".parse().unwrap();
+        listeners.insert(ListenerId::next(), relay_a, addr_1.clone());
+        listeners.insert(ListenerId::next(), relay_a, addr_2.clone());
+        listeners.insert(ListenerId::next(), relay_b, addr_1.clone());
+        listeners.accept(&relay_a, Some(Duration::from_secs(120)), None);
+        listeners.cancel(&relay_b);
+
+        let reservations = listeners.reservations();
+        assert_eq!(reservations.len(), 1);
+        let info = &reservations[0];
+        assert_eq!(info.relay_peer_id, relay_a);
+        let mut addrs = info.circuit_addrs.clone();
+        addrs.sort();
+        let mut expected = vec![addr_1, addr_2];
+        expected.sort();
+        assert_eq!(addrs, expected);
+        assert!(info.accepted_at.is_some());
+        assert_eq!(info.max_circuit_duration, Some(Duration::from_secs(120)));
+        assert_eq!(info.max_circuit_bytes, None);
+    }
+}
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+
+use libp2p::PeerId;
+use libp2p::request_response::ResponseChannel;
+
+use super::{
+    KeepAlivePeers, NatTracker, PeerBandwidth, PeerConnections, PeerHandshakes, PendingDials,
+    RelayListeners,
+};
+use crate::command::StreamSlot;
+use crate::pending_map::PendingMap;
+
+/// EventLoop 与 NetClient 共享的状态
+///
+/// 各字段内部均为 `Arc`，clone 后指向同一份数据。
+pub struct SharedState<Resp> {
+    /// 暂存 inbound request 的 ResponseChannel（EventLoop 存入，NetClient 取出）
+    pub pending_channels: PendingMap<u64, (PeerId, ResponseChannel<Resp>)>,
+    /// 流式请求的暂存状态及打开该流的 peer
+    pub stream_slots: PendingMap<u64, (PeerId, StreamSlot<Resp>)>,
+    /// 需要保活的 peer（NetClient 登记，EventLoop 周期性制造连接活动）
+    pub keep_alive_peers: KeepAlivePeers,
+    /// 应用层握手状态（EventLoop 记录对端信息，NetClient 查询）
+    pub peer_handshakes: PeerHandshakes,
+    /// NAT 状态（EventLoop 根据 AutoNAT 探测更新，`nat_status` 命令读取）
+    pub nat: NatTracker,
+    /// relay 电路监听器（EventLoop 与 `reserve_relay` / `cancel_relay` 命令共用）
+    pub relay_listeners: RelayListeners,
+    /// 已建立的连接及其端点（EventLoop 维护，`upgrade_connection` 命令读取）
+    pub connections: PeerConnections,
+    /// 进行中的出站拨号（EventLoop 与拨号命令登记，EventLoop 上报 `DialStarted`）
+    pub pending_dials: PendingDials,
+    /// 因事件通道满被丢弃的事件数（`EventOverflow` 非 `Block` 时）
+    pub dropped_events: Arc<AtomicU64>,
+    /// 按 peer 统计的流量（transport 写入，NetClient 直接读取）
+    pub bandwidth: PeerBandwidth,
+    /// Prometheus 指标（EventLoop 记录事件，NetClient 暴露注册表）
+    #[cfg(feature = "metrics")]
+    pub metrics: super::NodeMetrics,
+}
+
+impl<Resp> Clone for SharedState<Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            pending_channels: self.pending_channels.clone(),
+            stream_slots: self.stream_slots.clone(),
+            keep_alive_peers: self.keep_alive_peers.clone(),
+            peer_handshakes: self.peer_handshakes.clone(),
+            nat: self.nat.clone(),
+            relay_listeners: self.relay_listeners.clone(),
+            connections: self.connections.clone(),
+            pending_dials: self.pending_dials.clone(),
+            dropped_events: self.dropped_events.clone(),
+            bandwidth: self.bandwidth.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics.clone(),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::iter::{Chain, Map};
+
+use anyhow::Result;
+use either::Either;
+use futures::future::{self, MapOk};
+use futures::{AsyncRead, AsyncWrite, TryFutureExt};
+use libp2p::core::either::EitherFuture;
+use libp2p::core::muxing::StreamMuxerBox;
+use libp2p::core::transport::{Boxed, MemoryTransport};
+use libp2p::core::upgrade::{
+    InboundConnectionUpgrade, OutboundConnectionUpgrade, UpgradeInfo, Version,
+};
+use libp2p::identity::Keypair;
+use libp2p::{PeerId, Transport, noise, quic, relay, tcp, tls, yamux};
+
+use super::PeerBandwidth;
+#[cfg(feature = "dns")]
+use crate::config::DnsConfig;
+use crate::config::{NodeConfig, SecurityUpgrade, TransportConfig};
+
+type BoxedTransport = Boxed<(PeerId, StreamMuxerBox)>;
+
+/// 构建底层传输：按 `TransportConfig` 组合 TCP / QUIC（或内存传输），加上 relay client 传输
+///
+/// 与 `SwarmBuilder` 内置链路相同（TCP + 安全握手 + Yamux、QUIC、DNS、relay 电路 + 安全握手 + Yamux），
+/// 安全握手按 `SecurityUpgrade` 选择 Noise / TLS，区别在于每个连接都经过 `PeerBandwidth` 计数。
+/// 返回的 relay client 行为需交给 `CoreBehaviour`。
+pub(crate) fn build_transport(
+    keypair: &Keypair,
+    config: &NodeConfig,
+    bandwidth: &PeerBandwidth,
+) -> Result<(BoxedTransport, relay::client::Behaviour)> {
+    let security = config.security;
+    let yamux = config.yamux_config();
+    let tcp = || {
+        secured(
+            tcp::tokio::Transport::new(tcp::Config::default()),
+            keypair,
+            security,
+            &yamux,
+        )
+    };
+    let quic = || -> BoxedTransport {
+        quic::tokio::Transport::new(quic::Config::new(keypair))
+            .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)))
+            .boxed()
+    };
+    let direct = || -> Result<BoxedTransport> {
+        Ok(match config.transport {
+            TransportConfig::TcpAndQuic => or(tcp()?, quic()),
+            TransportConfig::QuicOnly => quic(),
+            TransportConfig::TcpOnly => tcp()?,
+            TransportConfig::Memory => {
+                secured(MemoryTransport::default(), keypair, security, &yamux)?
+            }
+        })
+    };
+    #[cfg(feature = "dns")]
+    let direct = resolved(direct, &config.dns)?;
+    #[cfg(not(feature = "dns"))]
+    let direct = direct()?;
+
+    // relay 电路与 TCP 使用同一种安全握手
+    let (relay_transport, relay_client) = relay::client::new(keypair.public().to_peer_id());
+    let relayed = secured(relay_transport, keypair, security, &yamux)?;
+
+    let transport = or(
+        metered(relayed, bandwidth, true),
+        metered(direct, bandwidth, false),
+    );
+    Ok((transport, relay_client))
+}
+
+/// 为流式传输加上安全握手与 Yamux 多路复用
+fn secured<T>(
+    transport: T,
+    keypair: &Keypair,
+    security: SecurityUpgrade,
+    yamux: &yamux::Config,
+) -> Result<BoxedTransport>
+where
+    T: Transport + Send + Unpin + 'static,
+    T::Output: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T::Error: Send + Sync + 'static,
+    T::Dial: Send + 'static,
+    T::ListenerUpgrade: Send + 'static,
+{
+    let builder = transport.upgrade(Version::V1Lazy);
+    let transport = match security {
+        SecurityUpgrade::Noise => builder
+            .authenticate(noise::Config::new(keypair)?)
+            .multiplex(yamux.clone())
+            .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)))
+            .boxed(),
+        SecurityUpgrade::Tls => builder
+            .authenticate(tls::Config::new(keypair)?)
+            .multiplex(yamux.clone())
+            .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)))
+            .boxed(),
+        SecurityUpgrade::NoiseThenTls => builder
+            .authenticate(SelectSecurity(
+                noise::Config::new(keypair)?,
+                tls::Config::new(keypair)?,
+            ))
+            .multiplex(yamux.clone())
+            .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)))
+            .boxed(),
+    };
+    Ok(transport)
+}
+
+/// 为直连传输加上 DNS 解析；系统解析器配置读取失败时告警并返回不带 DNS 的传输
+#[cfg(feature = "dns")]
+fn resolved(
+    direct: impl Fn() -> Result<BoxedTransport>,
+    dns: &DnsConfig,
+) -> Result<BoxedTransport> {
+    use libp2p::dns;
+
+    let transport = match dns {
+        DnsConfig::System => match dns::tokio::Transport::system(direct()?) {
+            Ok(transport) => transport,
+            Err(e) => {
+                tracing::warn!(
+                    "System DNS resolver unavailable, /dns addresses will not resolve: {}",
+                    e
+                );
+                return direct();
+            }
+        },
+        DnsConfig::Custom(resolver) => {
+            dns::tokio::Transport::custom(direct()?, resolver.clone(), dns::ResolverOpts::default())
+        }
+    };
+    Ok(transport.boxed())
+}
+
+fn or(a: BoxedTransport, b: BoxedTransport) -> BoxedTransport {
+    a.or_transport(b)
+        .map(|either, _| either.into_inner())
+        .boxed()
+}
+
+fn metered(transport: BoxedTransport, bandwidth: &PeerBandwidth, relayed: bool) -> BoxedTransport {
+    let bandwidth = bandwidth.clone();
+    transport
+        .map(move |(peer_id, conn), _| {
+            let conn = bandwidth.meter(peer_id, conn, relayed);
+            (peer_id, StreamMuxerBox::new(conn))
+        })
+        .boxed()
+}
+
+/// 同时提供两种安全握手，协商时优先第一种
+///
+/// 与 `SwarmBuilder` 内部的 `SelectSecurityUpgrade` 相同（该类型未公开）：
+/// 两侧输出都是 `(PeerId, 连接)`，合并后仍可交给 `authenticate`。
+#[derive(Debug, Clone)]
+struct SelectSecurity<A, B>(A, B);
+
+impl<A: UpgradeInfo, B: UpgradeInfo> UpgradeInfo for SelectSecurity<A, B> {
+    type Info = Either<A::Info, B::Info>;
+    type InfoIter = Chain<
+        Map<<A::InfoIter as IntoIterator>::IntoIter, fn(A::Info) -> Self::Info>,
+        Map<<B::InfoIter as IntoIterator>::IntoIter, fn(B::Info) -> Self::Info>,
+    >;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        let a = self.0.protocol_info().into_iter();
+        let b = self.1.protocol_info().into_iter();
+        a.map(Either::Left as fn(A::Info) -> _)
+            .chain(b.map(Either::Right as fn(B::Info) -> _))
+    }
+}
+
+type SelectFuture<FA, FB, TA, TB> = MapOk<
+    EitherFuture<FA, FB>,
+    fn(future::Either<(PeerId, TA), (PeerId, TB)>) -> (PeerId, future::Either<TA, TB>),
+>;
+
+impl<C, A, B, TA, TB> InboundConnectionUpgrade<C> for SelectSecurity<A, B>
+where
+    A: InboundConnectionUpgrade<C, Output = (PeerId, TA)>,
+    B: InboundConnectionUpgrade<C, Output = (PeerId, TB)>,
+{
+    type Output = (PeerId, future::Either<TA, TB>);
+    type Error = Either<A::Error, B::Error>;
+    type Future = SelectFuture<A::Future, B::Future, TA, TB>;
+
+    fn upgrade_inbound(self, socket: C, info: Self::Info) -> Self::Future {
+        match info {
+            Either::Left(info) => EitherFuture::First(self.0.upgrade_inbound(socket, info)),
+            Either::Right(info) => EitherFuture::Second(self.1.upgrade_inbound(socket, info)),
+        }
+        .map_ok(future::Either::factor_first)
+    }
+}
+
+impl<C, A, B, TA, TB> OutboundConnectionUpgrade<C> for SelectSecurity<A, B>
+where
+    A: OutboundConnectionUpgrade<C, Output = (PeerId, TA)>,
+    B: OutboundConnectionUpgrade<C, Output = (PeerId, TB)>,
+{
+    type Output = (PeerId, future::Either<TA, TB>);
+    type Error = Either<A::Error, B::Error>;
+    type Future = SelectFuture<A::Future, B::Future, TA, TB>;
+
+    fn upgrade_outbound(self, socket: C, info: Self::Info) -> Self::Future {
+        match info {
+            Either::Left(info) => EitherFuture::First(self.0.upgrade_outbound(socket, info)),
+            Either::Right(info) => EitherFuture::Second(self.1.upgrade_outbound(socket, info)),
+        }
+        .map_ok(future::Either::factor_first)
+    }
+}
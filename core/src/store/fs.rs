@@ -0,0 +1,368 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use libp2p::kad::store::{self, MemoryStore, MemoryStoreConfig, RecordStore};
+use libp2p::kad::{KBucketKey, ProviderRecord, Record, RecordKey};
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// 持久化到目录的 Kad 记录存储
+///
+/// 内存中以 `MemoryStore` 提供查询（容量限制与其一致），每次写入同步落盘：
+/// - `records/<key 的 SHA-256 十六进制>.json`：一条记录
+/// - `providers/<key 的 SHA-256 十六进制>.json`：该 key 的全部 Provider 记录
+///
+/// 文件名定长，不受 key 长度限制；key 本身保存在文件内容中。
+/// 打开时加载目录中未过期的条目，过期时间以系统时钟持久化，重启后继续生效。
+/// 落盘失败只记录日志，不影响内存中的数据。
+pub struct FsStore {
+    dir: PathBuf,
+    inner: MemoryStore,
+}
+
+/// 记录的磁盘格式
+#[derive(Serialize, Deserialize)]
+struct StoredRecord {
+    key: Vec<u8>,
+    value: Vec<u8>,
+    publisher: Option<PeerId>,
+    /// 过期时间（Unix 毫秒）
+    expires_at: Option<u64>,
+}
+
+/// 一个 key 的 Provider 记录文件的磁盘格式
+#[derive(Serialize, Deserialize)]
+struct StoredProviders {
+    key: Vec<u8>,
+    providers: Vec<StoredProvider>,
+}
+
+/// Provider 记录的磁盘格式
+#[derive(Serialize, Deserialize)]
+struct StoredProvider {
+    provider: PeerId,
+    /// 过期时间（Unix 毫秒）
+    expires_at: Option<u64>,
+    addresses: Vec<Multiaddr>,
+}
+
+impl FsStore {
+    /// 以默认容量限制打开（不存在则创建）目录
+    pub fn open(dir: impl Into<PathBuf>, local_id: PeerId) -> io::Result<Self> {
+        Self::with_config(dir, local_id, MemoryStoreConfig::default())
+    }
+
+    /// 以指定容量限制打开（不存在则创建）目录
+    pub fn with_config(
+        dir: impl Into<PathBuf>,
+        local_id: PeerId,
+        config: MemoryStoreConfig,
+    ) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(dir.join("records"))?;
+        fs::create_dir_all(dir.join("providers"))?;
+
+        let mut store = Self {
+            inner: MemoryStore::with_config(local_id, config),
+            dir,
+        };
+        store.load()?;
+        Ok(store)
+    }
+
+    fn load(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+
+        for path in json_files(&self.dir.join("records"))? {
+            let Some(stored) = read_json::<StoredRecord>(&path) else {
+                continue;
+            };
+            let record = Record {
+                key: RecordKey::new(&stored.key),
+                value: stored.value,
+                publisher: stored.publisher,
+                expires: stored.expires_at.map(to_instant),
+            };
+            if record.is_expired(now) || self.inner.put(record).is_err() {
+                let _ = fs::remove_file(&path);
+            }
+        }
+
+        for path in json_files(&self.dir.join("providers"))? {
+            let Some(stored) = read_json::<StoredProviders>(&path) else {
+                continue;
+            };
+            let key = RecordKey::new(&stored.key);
+            for stored in stored.providers {
+                let record = ProviderRecord {
+                    key: key.clone(),
+                    provider: stored.provider,
+                    expires: stored.expires_at.map(to_instant),
+                    addresses: stored.addresses,
+                };
+                if !record.is_expired(now) {
+                    let _ = self.inner.add_provider(record);
+                }
+            }
+            self.sync_providers(&key);
+        }
+        Ok(())
+    }
+
+    fn record_path(&self, key: &RecordKey) -> PathBuf {
+        self.dir.join("records").join(file_name(key))
+    }
+
+    fn providers_path(&self, key: &RecordKey) -> PathBuf {
+        self.dir.join("providers").join(file_name(key))
+    }
+
+    fn persist_record(&self, record: &Record) {
+        let stored = StoredRecord {
+            key: record.key.to_vec(),
+            value: record.value.clone(),
+            publisher: record.publisher,
+            expires_at: record.expires.map(to_unix_millis),
+        };
+        if let Err(e) = write_json(&self.record_path(&record.key), &stored) {
+            warn!("FsStore: failed to persist record: {}", e);
+        }
+    }
+
+    /// 以内存中的 Provider 列表覆盖磁盘文件（列表为空则删除）
+    fn sync_providers(&self, key: &RecordKey) {
+        let path = self.providers_path(key);
+        let providers: Vec<StoredProvider> = self
+            .inner
+            .providers(key)
+            .into_iter()
+            .map(|p| StoredProvider {
+                provider: p.provider,
+                expires_at: p.expires.map(to_unix_millis),
+                addresses: p.addresses,
+            })
+            .collect();
+        let result = if providers.is_empty() {
+            remove_if_exists(&path)
+        } else {
+            let stored = StoredProviders {
+                key: key.to_vec(),
+                providers,
+            };
+            write_json(&path, &stored)
+        };
+        if let Err(e) = result {
+            warn!("FsStore: failed to persist providers: {}", e);
+        }
+    }
+}
+
+impl RecordStore for FsStore {
+    type RecordsIter<'a> = <MemoryStore as RecordStore>::RecordsIter<'a>;
+    type ProvidedIter<'a> = <MemoryStore as RecordStore>::ProvidedIter<'a>;
+
+    fn get(&self, k: &RecordKey) -> Option<Cow<'_, Record>> {
+        self.inner.get(k)
+    }
+
+    fn put(&mut self, r: Record) -> store::Result<()> {
+        // 先由内存存储校验容量限制，被拒绝的记录不落盘，也不覆盖同 key 的旧记录
+        self.inner.put(r.clone())?;
+        self.persist_record(&r);
+        Ok(())
+    }
+
+    fn remove(&mut self, k: &RecordKey) {
+        self.inner.remove(k);
+        if let Err(e) = remove_if_exists(&self.record_path(k)) {
+            warn!("FsStore: failed to remove record: {}", e);
+        }
+    }
+
+    fn records(&self) -> Self::RecordsIter<'_> {
+        self.inner.records()
+    }
+
+    fn add_provider(&mut self, record: ProviderRecord) -> store::Result<()> {
+        let key = record.key.clone();
+        self.inner.add_provider(record)?;
+        self.sync_providers(&key);
+        Ok(())
+    }
+
+    fn providers(&self, key: &RecordKey) -> Vec<ProviderRecord> {
+        self.inner.providers(key)
+    }
+
+    fn provided(&self) -> Self::ProvidedIter<'_> {
+        self.inner.provided()
+    }
+
+    fn remove_provider(&mut self, k: &RecordKey, p: &PeerId) {
+        self.inner.remove_provider(k, p);
+        self.sync_providers(k);
+    }
+}
+
+fn json_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let data = fs::read(path).ok()?;
+    match serde_json::from_slice(&data) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("FsStore: skipping corrupt file {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// 先写临时文件再重命名，避免中途崩溃留下半个文件
+fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(value)?)?;
+    fs::rename(tmp, path)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn to_unix_millis(instant: Instant) -> u64 {
+    let remaining = instant.saturating_duration_since(Instant::now());
+    (SystemTime::now() + remaining)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn to_instant(unix_millis: u64) -> Instant {
+    let at = UNIX_EPOCH + Duration::from_millis(unix_millis);
+    let remaining = at.duration_since(SystemTime::now()).unwrap_or_default();
+    Instant::now() + remaining
+}
+
+/// 以 key 的 SHA-256 命名，长度固定，不会超过文件系统的文件名上限
+fn file_name(key: &RecordKey) -> String {
+    let hashed = KBucketKey::new(key.clone());
+    format!("{}.json", hex_encode(hashed.hashed_bytes()))
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "swarm-p2p-fs-store-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn records_and_providers_survive_reopen() {
+        let dir = temp_dir("reopen");
+        let local_id = PeerId::random();
+        let provider = PeerId::random();
+        let key = RecordKey::new(&b"/test/key");
+
+        {
+            let mut store = FsStore::open(&dir, local_id).unwrap();
+            store
+                .put(Record::new(key.clone(), b"value".to_vec()))
+                .unwrap();
+            store
+                .add_provider(ProviderRecord::new(key.clone(), provider, Vec::new()))
+                .unwrap();
+        }
+
+        let mut store = FsStore::open(&dir, local_id).unwrap();
+        assert_eq!(store.get(&key).unwrap().value, b"value".to_vec());
+        assert_eq!(store.providers(&key)[0].provider, provider);
+
+        store.remove(&key);
+        store.remove_provider(&key, &provider);
+        let store = FsStore::open(&dir, local_id).unwrap();
+        assert!(store.get(&key).is_none());
+        assert!(store.providers(&key).is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rejected_put_keeps_previous_record() {
+        let dir = temp_dir("rejected");
+        let local_id = PeerId::random();
+        let key = RecordKey::new(&b"/test/key");
+        let config = MemoryStoreConfig {
+            max_value_bytes: 8,
+            ..Default::default()
+        };
+
+        {
+            let mut store = FsStore::with_config(&dir, local_id, config.clone()).unwrap();
+            store
+                .put(Record::new(key.clone(), b"old".to_vec()))
+                .unwrap();
+            assert!(store.put(Record::new(key.clone(), vec![0; 64])).is_err());
+            assert_eq!(store.get(&key).unwrap().value, b"old".to_vec());
+        }
+
+        let store = FsStore::with_config(&dir, local_id, config).unwrap();
+        assert_eq!(store.get(&key).unwrap().value, b"old".to_vec());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn long_keys_survive_reopen() {
+        let dir = temp_dir("long-key");
+        let local_id = PeerId::random();
+        let provider = PeerId::random();
+        let key = RecordKey::new(&vec![0xab; 200]);
+
+        {
+            let mut store = FsStore::open(&dir, local_id).unwrap();
+            store
+                .put(Record::new(key.clone(), b"value".to_vec()))
+                .unwrap();
+            store
+                .add_provider(ProviderRecord::new(key.clone(), provider, Vec::new()))
+                .unwrap();
+        }
+
+        let store = FsStore::open(&dir, local_id).unwrap();
+        assert_eq!(store.get(&key).unwrap().value, b"value".to_vec());
+        assert_eq!(store.providers(&key)[0].provider, provider);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn hex_roundtrip() {
+        assert_eq!(hex_encode(b"\x00\x01/abc\xff"), "00012f616263ff");
+    }
+}
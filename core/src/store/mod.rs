@@ -0,0 +1,112 @@
+//! 可插拔的 Kad 记录存储
+//!
+//! `kad::store::RecordStore` 带有关联迭代器类型，无法直接做成 trait object。
+//! 这里用对象安全的 `KadStore` 包一层，`BoxedStore` 再实现回 `RecordStore`，
+//! 使 `CoreBehaviour` 的 Kad 存储可在运行时选择，而不必给整个 Swarm 加泛型参数。
+
+mod fs;
+
+use std::borrow::Cow;
+
+use libp2p::PeerId;
+use libp2p::kad::store::{self, RecordStore};
+use libp2p::kad::{ProviderRecord, Record, RecordKey};
+
+pub use fs::FsStore;
+
+/// 对象安全版的 `RecordStore`
+///
+/// 所有 `RecordStore + Send + 'static` 类型自动实现。
+pub trait KadStore: Send + 'static {
+    fn get(&self, k: &RecordKey) -> Option<Cow<'_, Record>>;
+    fn put(&mut self, r: Record) -> store::Result<()>;
+    fn remove(&mut self, k: &RecordKey);
+    fn records(&self) -> Box<dyn Iterator<Item = Cow<'_, Record>> + '_>;
+    fn add_provider(&mut self, record: ProviderRecord) -> store::Result<()>;
+    fn providers(&self, key: &RecordKey) -> Vec<ProviderRecord>;
+    fn provided(&self) -> Box<dyn Iterator<Item = Cow<'_, ProviderRecord>> + '_>;
+    fn remove_provider(&mut self, k: &RecordKey, p: &PeerId);
+}
+
+impl<T> KadStore for T
+where
+    T: RecordStore + Send + 'static,
+{
+    fn get(&self, k: &RecordKey) -> Option<Cow<'_, Record>> {
+        RecordStore::get(self, k)
+    }
+
+    fn put(&mut self, r: Record) -> store::Result<()> {
+        RecordStore::put(self, r)
+    }
+
+    fn remove(&mut self, k: &RecordKey) {
+        RecordStore::remove(self, k)
+    }
+
+    fn records(&self) -> Box<dyn Iterator<Item = Cow<'_, Record>> + '_> {
+        Box::new(RecordStore::records(self))
+    }
+
+    fn add_provider(&mut self, record: ProviderRecord) -> store::Result<()> {
+        RecordStore::add_provider(self, record)
+    }
+
+    fn providers(&self, key: &RecordKey) -> Vec<ProviderRecord> {
+        RecordStore::providers(self, key)
+    }
+
+    fn provided(&self) -> Box<dyn Iterator<Item = Cow<'_, ProviderRecord>> + '_> {
+        Box::new(RecordStore::provided(self))
+    }
+
+    fn remove_provider(&mut self, k: &RecordKey, p: &PeerId) {
+        RecordStore::remove_provider(self, k, p)
+    }
+}
+
+/// 类型擦除的 Kad 存储，`CoreBehaviour` 中 Kad 使用的存储类型
+pub struct BoxedStore(Box<dyn KadStore>);
+
+impl BoxedStore {
+    pub fn new(store: impl KadStore) -> Self {
+        Self(Box::new(store))
+    }
+}
+
+impl RecordStore for BoxedStore {
+    type RecordsIter<'a> = Box<dyn Iterator<Item = Cow<'a, Record>> + 'a>;
+    type ProvidedIter<'a> = Box<dyn Iterator<Item = Cow<'a, ProviderRecord>> + 'a>;
+
+    fn get(&self, k: &RecordKey) -> Option<Cow<'_, Record>> {
+        self.0.get(k)
+    }
+
+    fn put(&mut self, r: Record) -> store::Result<()> {
+        self.0.put(r)
+    }
+
+    fn remove(&mut self, k: &RecordKey) {
+        self.0.remove(k)
+    }
+
+    fn records(&self) -> Self::RecordsIter<'_> {
+        self.0.records()
+    }
+
+    fn add_provider(&mut self, record: ProviderRecord) -> store::Result<()> {
+        self.0.add_provider(record)
+    }
+
+    fn providers(&self, key: &RecordKey) -> Vec<ProviderRecord> {
+        self.0.providers(key)
+    }
+
+    fn provided(&self) -> Self::ProvidedIter<'_> {
+        self.0.provided()
+    }
+
+    fn remove_provider(&mut self, k: &RecordKey, p: &PeerId) {
+        self.0.remove_provider(k, p)
+    }
+}
@@ -0,0 +1,242 @@
+//! 集成测试脚手架（需开启 `testing` feature）
+//!
+//! 在 `127.0.0.1` 随机端口上启动节点，可选先启动一个引导节点并让其余节点连上它，
+//! 并提供等待事件的辅助方法，下游 crate 可直接用来编写针对 swarm-p2p 的集成测试：
+//!
+//! ```ignore
+//! let net = TestNetwork::<Req, Resp>::with_bootstrap(2).await?;
+//! let (a, b) = (&net.nodes[0], &net.nodes[1]);
+//! a.client.dial_addr(b.addr.clone()).await?;
+//! ```
+//!
+//! 等待类方法超时（默认 15 秒）时直接 panic，便于在测试中定位卡住的步骤。
+
+use std::time::Duration;
+
+use libp2p::identity::Keypair;
+use libp2p::{Multiaddr, PeerId};
+use tokio::time::timeout;
+use tracing::debug;
+
+use crate::client::{EventReceiver, NetClient};
+use crate::config::NodeConfig;
+use crate::error::Result;
+use crate::event::NodeEvent;
+use crate::runtime::{CborMessage, start};
+
+/// 等待类方法的默认超时
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 测试用配置：只监听 `127.0.0.1` 随机 TCP 端口，关闭 mDNS 与自动 relay 预约，
+/// Kad 固定为 Server 模式，节点之间只通过显式拨号或引导节点相连
+pub fn test_config() -> NodeConfig {
+    NodeConfig::new("/swarm-p2p-test/1.0.0", "swarm-p2p-test/1.0.0")
+        .with_listen_addrs(vec!["/ip4/127.0.0.1/tcp/0".parse().unwrap()])
+        .with_mdns(false)
+        .with_auto_relay_reservations(false)
+        .with_kad_server_mode(true)
+}
+
+/// 已启动并开始监听的测试节点
+pub struct TestNode<Req: CborMessage, Resp: CborMessage> {
+    pub client: NetClient<Req, Resp>,
+    pub events: EventReceiver<Req>,
+    pub peer_id: PeerId,
+    /// 首个监听地址
+    pub addr: Multiaddr,
+    /// 打印事件时的标签
+    pub label: String,
+    timeout: Duration,
+}
+
+impl<Req: CborMessage, Resp: CborMessage> TestNode<Req, Resp> {
+    /// 以 `test_config()` 启动一个节点，需要调整配置时使用 `TestNodeBuilder`
+    pub async fn start() -> Result<Self> {
+        TestNodeBuilder::new().start().await
+    }
+
+    /// 不带 `/p2p` 后缀的监听地址与 PeerId，可直接传给 `with_bootstrap_peers` / `add_peer_addrs`
+    pub fn peer_addr(&self) -> (PeerId, Multiaddr) {
+        (self.peer_id, self.addr.clone())
+    }
+
+    /// 等待 `f` 返回 `Some` 的事件，期间其他事件被丢弃；超时或事件通道关闭时 panic
+    pub async fn wait_for<T>(&mut self, mut f: impl FnMut(&NodeEvent<Req>) -> Option<T>) -> T {
+        let label = &self.label;
+        let events = &mut self.events;
+        timeout(self.timeout, async {
+            while let Some(event) = events.recv().await {
+                debug!("[{}] {:?}", label, event);
+                if let Some(value) = f(&event) {
+                    return value;
+                }
+            }
+            panic!("[{label}] event channel closed");
+        })
+        .await
+        .unwrap_or_else(|_| panic!("[{label}] timed out waiting for event"))
+    }
+
+    /// 等待与 `peer_id` 建立连接，已连接时立即返回
+    ///
+    /// 连接可能在 `start` 等待监听地址时就已建立（其 `PeerConnected` 随之被丢弃），故先查询连接状态。
+    pub async fn wait_connected(&mut self, peer_id: PeerId) {
+        if self.client.is_connected(peer_id).await.unwrap_or(false) {
+            return;
+        }
+        self.wait_for(|event| {
+            matches!(event, NodeEvent::PeerConnected { peer_id: p, .. } if *p == peer_id)
+                .then_some(())
+        })
+        .await
+    }
+
+    /// 等待收到 `peer_id` 的 identify 信息
+    pub async fn wait_identified(&mut self, peer_id: PeerId) {
+        self.wait_for(|event| {
+            matches!(event, NodeEvent::IdentifyReceived { peer_id: p, .. } if *p == peer_id)
+                .then_some(())
+        })
+        .await
+    }
+
+    /// 在后台持续消费（并以 debug 级别打印）事件，之后只通过 `client` 操作节点
+    pub fn drain_events(self) -> NetClient<Req, Resp> {
+        let Self {
+            client,
+            mut events,
+            label,
+            ..
+        } = self;
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                debug!("[{}] {:?}", label, event);
+            }
+        });
+        client
+    }
+}
+
+/// 测试节点构建器
+pub struct TestNodeBuilder {
+    config: NodeConfig,
+    keypair: Option<Keypair>,
+    label: Option<String>,
+    timeout: Duration,
+}
+
+impl Default for TestNodeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestNodeBuilder {
+    pub fn new() -> Self {
+        Self {
+            config: test_config(),
+            keypair: None,
+            label: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// 在 `test_config()` 基础上调整配置
+    pub fn with_config(mut self, f: impl FnOnce(NodeConfig) -> NodeConfig) -> Self {
+        self.config = f(self.config);
+        self
+    }
+
+    /// 使用固定密钥（默认随机生成）
+    pub fn with_keypair(mut self, keypair: Keypair) -> Self {
+        self.keypair = Some(keypair);
+        self
+    }
+
+    /// 打印事件时的标签，默认为 PeerId
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// 等待类方法的超时，默认 15 秒
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 以 `node` 为引导节点
+    pub fn with_bootstrap<Req: CborMessage, Resp: CborMessage>(
+        mut self,
+        node: &TestNode<Req, Resp>,
+    ) -> Self {
+        self.config.bootstrap_peers.push(node.peer_addr());
+        self
+    }
+
+    /// 启动节点并等待首个监听地址
+    pub async fn start<Req: CborMessage, Resp: CborMessage>(self) -> Result<TestNode<Req, Resp>> {
+        let keypair = self.keypair.unwrap_or_else(Keypair::generate_ed25519);
+        let peer_id = keypair.public().to_peer_id();
+        let (client, events) = start::<Req, Resp>(keypair, self.config)?;
+        let mut node = TestNode {
+            client,
+            events,
+            peer_id,
+            addr: Multiaddr::empty(),
+            label: self.label.unwrap_or_else(|| peer_id.to_string()),
+            timeout: self.timeout,
+        };
+        node.addr = node
+            .wait_for(|event| match event {
+                NodeEvent::Listening { addr } => Some(addr.clone()),
+                _ => None,
+            })
+            .await;
+        Ok(node)
+    }
+}
+
+/// 一组测试节点
+pub struct TestNetwork<Req: CborMessage, Resp: CborMessage> {
+    /// 引导节点（`with_bootstrap` 创建时存在）
+    pub bootstrap: Option<TestNode<Req, Resp>>,
+    pub nodes: Vec<TestNode<Req, Resp>>,
+}
+
+impl<Req: CborMessage, Resp: CborMessage> TestNetwork<Req, Resp> {
+    /// 启动 `n` 个互不相连的节点
+    pub async fn with_nodes(n: usize) -> Result<Self> {
+        let mut nodes = Vec::with_capacity(n);
+        for i in 0..n {
+            nodes.push(node_builder(i).start().await?);
+        }
+        Ok(Self {
+            bootstrap: None,
+            nodes,
+        })
+    }
+
+    /// 先启动一个引导节点，再启动 `n` 个以它为引导节点的节点，返回时均已连上引导节点
+    pub async fn with_bootstrap(n: usize) -> Result<Self> {
+        let mut bootstrap = TestNodeBuilder::new()
+            .with_label("bootstrap")
+            .start()
+            .await?;
+        let mut nodes = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut node = node_builder(i).with_bootstrap(&bootstrap).start().await?;
+            node.wait_connected(bootstrap.peer_id).await;
+            bootstrap.wait_connected(node.peer_id).await;
+            nodes.push(node);
+        }
+        Ok(Self {
+            bootstrap: Some(bootstrap),
+            nodes,
+        })
+    }
+}
+
+fn node_builder(index: usize) -> TestNodeBuilder {
+    TestNodeBuilder::new().with_label(format!("node-{index}"))
+}
@@ -1,8 +1,13 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use libp2p::kad;
+use libp2p::PeerId;
+use libp2p::identity::{Keypair, PublicKey};
+use libp2p::kad::{self, Record, RecordKey};
 use serde::{Deserialize, Serialize};
 
+use crate::error::{Error, Result};
+use crate::runtime::CborMessage;
+
 /// DHT 查询统计信息
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct QueryStatsInfo {
@@ -26,3 +31,331 @@ impl From<&kad::QueryStats> for QueryStatsInfo {
         }
     }
 }
+
+/// 以 CBOR 编码值构造 DHT 记录
+///
+/// `ttl` 为 `None` 时不设置过期时间，`put_record` 会使用 Kad 配置的默认 TTL。
+pub fn cbor_record<T: CborMessage>(
+    key: RecordKey,
+    value: &T,
+    ttl: Option<Duration>,
+) -> Result<Record> {
+    let value =
+        cbor4ii::serde::to_vec(Vec::new(), value).map_err(|e| Error::Cbor(e.to_string()))?;
+    let mut record = Record::new(key, value);
+    record.expires = ttl.map(|ttl| Instant::now() + ttl);
+    Ok(record)
+}
+
+/// 将 `cbor_record` 构造的记录解码为原始值
+pub fn decode_record<T: CborMessage>(record: &Record) -> Result<T> {
+    cbor4ii::serde::from_slice(&record.value).map_err(|e| Error::Cbor(e.to_string()))
+}
+
+/// 签名记录值的格式版本
+const SIGNED_RECORD_VERSION: u8 = 1;
+
+/// 签名内容的域分隔前缀，避免签名被挪用到其他协议
+const SIGNED_RECORD_DOMAIN: &[u8] = b"swarm-p2p-record:";
+
+/// `verify_record` 验签通过后的记录内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedValue {
+    /// 签名者
+    pub signer: PeerId,
+    /// 原始值
+    pub value: Vec<u8>,
+}
+
+/// 以节点密钥签名构造 DHT 记录，`publisher` 设为签名者
+///
+/// 记录值格式：`[版本][公钥长度 u16][公钥 protobuf][签名长度 u16][签名][原始值]`，
+/// 签名覆盖 key 与原始值。配合 `with_record_validator(|r| verify_record(r).is_ok())`
+/// 可拒绝未签名或被篡改的入站记录。
+pub fn sign_record(
+    keypair: &Keypair,
+    key: RecordKey,
+    value: Vec<u8>,
+    ttl: Option<Duration>,
+) -> Result<Record> {
+    let public_key = keypair.public().encode_protobuf();
+    let signature = keypair
+        .sign(&signing_payload(&key, &value))
+        .map_err(|e| Error::RecordSignature(e.to_string()))?;
+    let mut envelope = Vec::with_capacity(5 + public_key.len() + signature.len() + value.len());
+    envelope.push(SIGNED_RECORD_VERSION);
+    for field in [&public_key, &signature] {
+        let len = u16::try_from(field.len())
+            .map_err(|_| Error::RecordSignature("key or signature too long".into()))?;
+        envelope.extend_from_slice(&len.to_be_bytes());
+        envelope.extend_from_slice(field);
+    }
+    envelope.extend_from_slice(&value);
+
+    let mut record = Record::new(key, envelope);
+    record.publisher = Some(keypair.public().to_peer_id());
+    record.expires = ttl.map(|ttl| Instant::now() + ttl);
+    Ok(record)
+}
+
+/// 校验 `sign_record` 构造的记录，返回签名者与原始值
+///
+/// 格式错误、签名不匹配，或 `publisher` 与签名者不一致时返回 `Error::RecordSignature`。
+pub fn verify_record(record: &Record) -> Result<SignedValue> {
+    let invalid = |reason: &str| Error::RecordSignature(reason.into());
+    let (&version, rest) = record
+        .value
+        .split_first()
+        .ok_or_else(|| invalid("empty value"))?;
+    if version != SIGNED_RECORD_VERSION {
+        return Err(invalid(&format!("unsupported version {version}")));
+    }
+    let (public_key, rest) = split_field(rest).ok_or_else(|| invalid("truncated public key"))?;
+    let (signature, value) = split_field(rest).ok_or_else(|| invalid("truncated signature"))?;
+
+    let public_key =
+        PublicKey::try_decode_protobuf(public_key).map_err(|_| invalid("invalid public key"))?;
+    if !public_key.verify(&signing_payload(&record.key, value), signature) {
+        return Err(invalid("signature mismatch"));
+    }
+    let signer = public_key.to_peer_id();
+    if record
+        .publisher
+        .is_some_and(|publisher| publisher != signer)
+    {
+        return Err(invalid("publisher is not the signer"));
+    }
+    Ok(SignedValue {
+        signer,
+        value: value.to_vec(),
+    })
+}
+
+fn signing_payload(key: &RecordKey, value: &[u8]) -> Vec<u8> {
+    let key = key.as_ref();
+    let mut payload = Vec::with_capacity(SIGNED_RECORD_DOMAIN.len() + 4 + key.len() + value.len());
+    payload.extend_from_slice(SIGNED_RECORD_DOMAIN);
+    payload.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    payload.extend_from_slice(key);
+    payload.extend_from_slice(value);
+    payload
+}
+
+/// 读取 `u16` 长度前缀的字段，返回字段与剩余部分
+fn split_field(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (len, rest) = bytes.split_first_chunk::<2>()?;
+    let len = u16::from_be_bytes(*len) as usize;
+    (rest.len() >= len).then(|| rest.split_at(len))
+}
+
+/// DHT 记录 key 的命名空间
+///
+/// 生成 `<prefix>/<kind>/<peer_id>` 形式的 key，不同应用 / 用途各用一个前缀，避免 key 冲突。
+/// `kind` 中的 `/` 与 `%` 按百分号编码，因此可以包含任意字符，`parse` 能无歧义地还原。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Namespace {
+    prefix: String,
+}
+
+/// `Namespace::parse` 解析出的 key 组成部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespacedKey {
+    pub kind: String,
+    pub peer_id: PeerId,
+}
+
+impl Namespace {
+    /// 前缀统一为以 `/` 开头、不以 `/` 结尾，如 `"myapp/"` 与 `"/myapp"` 等价
+    pub fn new(prefix: impl AsRef<str>) -> Self {
+        let trimmed = prefix.as_ref().trim_matches('/');
+        Self {
+            prefix: format!("/{trimmed}"),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// 构造 `<prefix>/<kind>/<peer_id>`
+    pub fn record_key(&self, kind: &str, peer_id: PeerId) -> RecordKey {
+        RecordKey::new(&format!("{}/{}/{}", self.prefix, escape(kind), peer_id))
+    }
+
+    /// 解析 `record_key` 生成的 key；前缀不符或格式不对时返回 `Error::RecordKey`
+    pub fn parse(&self, key: &RecordKey) -> Result<NamespacedKey> {
+        let invalid = |reason: &str| Error::RecordKey(format!("{reason}: {key:?}"));
+        let key_str = std::str::from_utf8(key.as_ref()).map_err(|_| invalid("not UTF-8"))?;
+        let rest = key_str
+            .strip_prefix(self.prefix.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .ok_or_else(|| invalid(&format!("not under namespace {}", self.prefix)))?;
+        let (kind, peer_id) = rest
+            .split_once('/')
+            .ok_or_else(|| invalid("missing peer id"))?;
+        let peer_id = peer_id.parse().map_err(|_| invalid("invalid peer id"))?;
+        Ok(NamespacedKey {
+            kind: unescape(kind).ok_or_else(|| invalid("invalid escape in kind"))?,
+            peer_id,
+        })
+    }
+
+    /// 该命名空间下的 key（用于在本地存储中筛选记录）
+    pub fn contains(&self, key: &RecordKey) -> bool {
+        key.as_ref().starts_with(self.prefix.as_bytes())
+            && key.as_ref().get(self.prefix.len()) == Some(&b'/')
+    }
+}
+
+/// `%` → `%25`，`/` → `%2F`
+fn escape(segment: &str) -> String {
+    segment.replace('%', "%25").replace('/', "%2F")
+}
+
+/// `escape` 的逆操作，出现其他 `%xx` 或不完整的转义时返回 `None`
+fn unescape(segment: &str) -> Option<String> {
+    let mut out = String::with_capacity(segment.len());
+    let mut rest = segment;
+    while let Some(i) = rest.find('%') {
+        out.push_str(&rest[..i]);
+        let code = rest.get(i + 1..i + 3)?;
+        out.push(match code {
+            "25" => '%',
+            "2F" | "2f" => '/',
+            _ => return None,
+        });
+        rest = &rest[i + 3..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Presence {
+        name: String,
+        port: u16,
+    }
+
+    #[test]
+    fn cbor_record_roundtrip() {
+        let value = Presence {
+            name: "alice".into(),
+            port: 4001,
+        };
+        let record = cbor_record(RecordKey::new(&"presence"), &value, None).unwrap();
+        assert!(record.expires.is_none());
+        assert_eq!(decode_record::<Presence>(&record).unwrap(), value);
+    }
+
+    #[test]
+    fn cbor_record_sets_expiry() {
+        let record =
+            cbor_record(RecordKey::new(&"k"), &1u32, Some(Duration::from_secs(60))).unwrap();
+        let expires = record.expires.expect("expires should be set");
+        assert!(expires > Instant::now() + Duration::from_secs(50));
+    }
+
+    #[test]
+    fn decode_record_rejects_invalid_value() {
+        let record = Record::new(RecordKey::new(&"k"), vec![0xff]);
+        assert!(matches!(
+            decode_record::<Presence>(&record),
+            Err(Error::Cbor(_))
+        ));
+    }
+
+    #[test]
+    fn signed_record_roundtrip() {
+        let keypair = Keypair::generate_ed25519();
+        let key = RecordKey::new(&"signed");
+        let record = sign_record(&keypair, key, b"hello".to_vec(), None).unwrap();
+        assert_eq!(record.publisher, Some(keypair.public().to_peer_id()));
+        assert_eq!(
+            verify_record(&record).unwrap(),
+            SignedValue {
+                signer: keypair.public().to_peer_id(),
+                value: b"hello".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn verify_record_rejects_tampering() {
+        let keypair = Keypair::generate_ed25519();
+        let record = sign_record(&keypair, RecordKey::new(&"k"), b"v".to_vec(), None).unwrap();
+
+        let mut tampered = record.clone();
+        *tampered.value.last_mut().unwrap() ^= 1;
+        let mut moved = record.clone();
+        moved.key = RecordKey::new(&"other");
+        let mut impersonated = record.clone();
+        impersonated.publisher = Some(PeerId::random());
+        let mut truncated = record;
+        truncated.value.truncate(10);
+
+        for record in [
+            tampered,
+            moved,
+            impersonated,
+            truncated,
+            Record::new(RecordKey::new(&"k"), b"plain".to_vec()),
+        ] {
+            assert!(
+                matches!(verify_record(&record), Err(Error::RecordSignature(_))),
+                "{record:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn namespace_record_key_roundtrip() {
+        let ns = Namespace::new("myapp/");
+        assert_eq!(ns.prefix(), "/myapp");
+        let peer_id = PeerId::random();
+
+        let key = ns.record_key("users", peer_id);
+        assert_eq!(key.as_ref(), format!("/myapp/users/{peer_id}").as_bytes());
+        assert!(ns.contains(&key));
+        assert_eq!(
+            ns.parse(&key).unwrap(),
+            NamespacedKey {
+                kind: "users".into(),
+                peer_id
+            }
+        );
+
+        // kind 中的 `/` 与 `%` 被编码，仍可还原
+        let key = ns.record_key("files/100%", peer_id);
+        assert_eq!(
+            key.as_ref(),
+            format!("/myapp/files%2F100%25/{peer_id}").as_bytes()
+        );
+        assert_eq!(ns.parse(&key).unwrap().kind, "files/100%");
+    }
+
+    #[test]
+    fn namespace_rejects_malformed_keys() {
+        let ns = Namespace::new("/myapp");
+        let peer_id = PeerId::random();
+        let other = Namespace::new("/myapp2").record_key("users", peer_id);
+        assert!(!ns.contains(&other));
+
+        for key in [
+            other,
+            RecordKey::new(&"/myapp/users"),
+            RecordKey::new(&"/myapp/users/not-a-peer"),
+            RecordKey::new(&format!("/myapp/a%2/{peer_id}")),
+            RecordKey::new(&format!("/myapp/a%41/{peer_id}")),
+            RecordKey::new(&[0xffu8, 0xfe]),
+        ] {
+            assert!(
+                matches!(ns.parse(&key), Err(Error::RecordKey(_))),
+                "{key:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! 集成测试：按 peer 统计流量
+//!
+//! A 向 B 发送一次请求，验证：双方对彼此的计数非零 → 收发方向对称 →
+//! 总量不小于单个 peer 的计数 → 未连接过的 peer 计数为 0。
+
+mod common;
+
+use common::*;
+use swarm_p2p_core::NodeEvent;
+use swarm_p2p_core::libp2p::PeerId;
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn request_bytes_are_counted_per_peer() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_a = client_a.local_peer_id();
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    let responder = client_b.clone();
+    tokio::spawn(async move {
+        while let Some(event) = events_b.recv().await {
+            if let NodeEvent::InboundRequest { pending_id, .. } = event {
+                let _ = responder
+                    .send_response(pending_id, Pong { msg: "ok".into() })
+                    .await;
+            }
+        }
+    });
+
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    timeout(
+        TIMEOUT,
+        client_a.send_request(peer_b, Ping { msg: "hi".into() }),
+    )
+    .await
+    .expect("request timed out")
+    .expect("request failed");
+
+    let a_to_b = client_a.bandwidth_for(&peer_b);
+    let b_to_a = client_b.bandwidth_for(&peer_a);
+    assert!(a_to_b.outbound > 0 && a_to_b.inbound > 0, "{a_to_b:?}");
+    assert!(b_to_a.outbound > 0 && b_to_a.inbound > 0, "{b_to_a:?}");
+
+    let totals = client_a.bandwidth_totals();
+    assert!(totals.outbound >= a_to_b.outbound, "{totals:?}");
+    assert!(totals.inbound >= a_to_b.inbound, "{totals:?}");
+
+    assert_eq!(
+        client_a.bandwidth_for(&PeerId::random()),
+        Default::default()
+    );
+}
@@ -0,0 +1,183 @@
+//! 集成测试：引导节点断线重连
+//!
+//! A 启动时引导节点 B 尚未上线，验证：B 上线后 A 按退避策略重拨成功 →
+//! B 重启后 A 再次连上。另验证 `status` 反映与引导节点的连接，以及路由表为空时
+//! `bootstrap` 先重连引导节点再发起查询。
+
+mod common;
+
+use std::time::Duration;
+
+use common::*;
+use swarm_p2p_core::event::KadMode;
+use swarm_p2p_core::libp2p::identity::Keypair;
+use swarm_p2p_core::libp2p::{Multiaddr, PeerId};
+use swarm_p2p_core::{BootstrapRetry, Error, EventReceiver, NetClient, NodeEvent, start};
+use tokio::time::timeout;
+
+/// 以固定密钥和地址启动 B，等待监听就绪
+async fn start_b(
+    keypair: Keypair,
+    addr: Multiaddr,
+) -> (NetClient<Ping, Pong>, EventReceiver<Ping>) {
+    let config = test_config().with_mdns(false).with_listen_addrs(vec![addr]);
+    let (client, mut events) = start::<Ping, Pong>(keypair, config).expect("failed to start B");
+    timeout(TIMEOUT, async {
+        while let Some(event) = events.recv().await {
+            if matches!(event, NodeEvent::Listening { .. }) {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("B should start listening");
+    (client, events)
+}
+
+async fn wait_connected(events: &mut EventReceiver<Ping>, peer: PeerId) {
+    timeout(TIMEOUT, async {
+        while let Some(event) = events.recv().await {
+            if matches!(event, NodeEvent::PeerConnected { peer_id, .. } if peer_id == peer) {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("A should reconnect to bootstrap peer");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn redials_bootstrap_peer_with_backoff() {
+    let keypair_b = Keypair::generate_ed25519();
+    let peer_b = keypair_b.public().to_peer_id();
+    // 先占用再释放一个端口，作为 B 稍后监听的固定地址
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr_b: Multiaddr = format!("/ip4/127.0.0.1/tcp/{port}").parse().unwrap();
+
+    let retry = BootstrapRetry::new(Duration::from_millis(100), Duration::from_millis(500));
+    let config = test_config()
+        .with_mdns(false)
+        // A 不监听：B 无法经 mDNS 反向拨号 A，连接只能由 A 的重拨建立
+        .with_listen_addrs(vec![])
+        .with_bootstrap_peers(vec![(peer_b, addr_b.clone())])
+        .with_bootstrap_retry(Some(retry));
+    let (_client_a, mut events_a) =
+        start::<Ping, Pong>(Keypair::generate_ed25519(), config).expect("failed to start A");
+
+    // 首次拨号必然失败，B 上线后由重连循环连上
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let (client_b, _events_b) = start_b(keypair_b.clone(), addr_b.clone()).await;
+    wait_connected(&mut events_a, peer_b).await;
+
+    // B 重启：A 收到断开后重新开始重试
+    client_b
+        .shutdown_graceful(Duration::from_secs(5))
+        .await
+        .unwrap();
+    timeout(TIMEOUT, async {
+        while let Some(event) = events_a.recv().await {
+            if matches!(event, NodeEvent::PeerDisconnected { peer_id } if peer_id == peer_b) {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("A should observe B going away");
+    let (_client_b, _events_b) = start_b(keypair_b, addr_b).await;
+    wait_connected(&mut events_a, peer_b).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn status_reports_bootstrap_connection() {
+    let keypair_b = Keypair::generate_ed25519();
+    let peer_b = keypair_b.public().to_peer_id();
+    let (client_b, mut events_b) =
+        start_b(keypair_b, "/ip4/127.0.0.1/tcp/0".parse().unwrap()).await;
+    let addr_b = client_b.get_addrs().await.unwrap().remove(0);
+
+    let config = test_config()
+        .with_mdns(false)
+        .with_listen_addrs(vec![])
+        .with_bootstrap_peers(vec![(peer_b, addr_b)]);
+    let (client_a, mut events_a) =
+        start::<Ping, Pong>(Keypair::generate_ed25519(), config).expect("failed to start A");
+    wait_connected(&mut events_a, peer_b).await;
+
+    let status = client_a.status().await.unwrap();
+    assert_eq!(status.connected_peers, 1);
+    assert!(status.bootstrap_connected);
+    assert_eq!(status.kad_mode, Some(KadMode::Server));
+
+    // B 没有配置引导节点
+    wait_connected(&mut events_b, client_a.local_peer_id()).await;
+    let status = client_b.status().await.unwrap();
+    assert_eq!(status.connected_peers, 1);
+    assert!(!status.bootstrap_connected);
+}
+
+/// 屏蔽再解除屏蔽引导节点，清空 A 的路由表（block 会把它移出路由表）
+async fn clear_routing_table(client: &NetClient<Ping, Pong>, peer: PeerId) {
+    client.block_peer(peer).await.unwrap();
+    client.unblock_peer(peer).await.unwrap();
+    assert!(client.export_routing_table().await.unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bootstrap_reconnects_bootstrap_peers_when_table_empty() {
+    let keypair_b = Keypair::generate_ed25519();
+    let peer_b = keypair_b.public().to_peer_id();
+    let (client_b, _events_b) = start_b(keypair_b, "/ip4/127.0.0.1/tcp/0".parse().unwrap()).await;
+    let addr_b = client_b.get_addrs().await.unwrap().remove(0);
+
+    let config = test_config()
+        .with_mdns(false)
+        .with_listen_addrs(vec![])
+        .with_bootstrap_peers(vec![(peer_b, addr_b)])
+        .with_bootstrap_retry(None);
+    let (client_a, mut events_a) =
+        start::<Ping, Pong>(Keypair::generate_ed25519(), config).expect("failed to start A");
+    wait_connected(&mut events_a, peer_b).await;
+    clear_routing_table(&client_a, peer_b).await;
+
+    let result = timeout(TIMEOUT, client_a.bootstrap())
+        .await
+        .expect("bootstrap should finish");
+    assert!(result.is_ok(), "bootstrap should recover: {:?}", result);
+    assert!(client_a.is_connected(peer_b).await.unwrap());
+    let table = client_a.export_routing_table().await.unwrap();
+    assert!(table.iter().any(|(peer_id, _)| *peer_id == peer_b));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bootstrap_fails_when_bootstrap_peers_unreachable() {
+    let peer_b = Keypair::generate_ed25519().public().to_peer_id();
+    // 先占用再释放一个端口，保证无人监听
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr_b: Multiaddr = format!("/ip4/127.0.0.1/tcp/{port}").parse().unwrap();
+
+    let config = test_config()
+        .with_mdns(false)
+        .with_listen_addrs(vec![])
+        .with_bootstrap_peers(vec![(peer_b, addr_b)])
+        .with_bootstrap_retry(None);
+    let (client_a, _events_a) =
+        start::<Ping, Pong>(Keypair::generate_ed25519(), config).expect("failed to start A");
+    clear_routing_table(&client_a, peer_b).await;
+
+    let result = timeout(TIMEOUT, client_a.bootstrap())
+        .await
+        .expect("bootstrap should fail fast");
+    assert!(
+        matches!(result, Err(Error::KadBootstrapUnreachable)),
+        "expected KadBootstrapUnreachable, got: {:?}",
+        result
+    );
+}
@@ -1,8 +1,9 @@
 use std::time::Duration;
 
-use libp2p::PeerId;
+use libp2p::identity::Keypair;
+use libp2p::{Multiaddr, PeerId};
 use serde::{Deserialize, Serialize};
-use swarm_p2p_core::{NodeConfig, NodeEvent};
+use swarm_p2p_core::{EventReceiver, NetClient, NodeConfig, NodeEvent, start};
 use tokio::sync::oneshot;
 use tokio::time::timeout;
 
@@ -31,9 +32,47 @@ pub fn test_config() -> NodeConfig {
         .with_kad_server_mode(true)
 }
 
+/// 关闭 mDNS、仅监听 127.0.0.1 的测试配置，节点间通过地址显式相连
+#[allow(dead_code)]
+pub fn local_config() -> NodeConfig {
+    test_config()
+        .with_mdns(false)
+        .with_listen_addrs(vec!["/ip4/127.0.0.1/tcp/0".parse().unwrap()])
+}
+
 #[allow(dead_code)]
 pub const TIMEOUT: Duration = Duration::from_secs(15);
 
+/// 以随机身份启动节点
+#[allow(dead_code)]
+pub fn start_node(config: NodeConfig) -> (NetClient<Ping, Pong>, EventReceiver<Ping>) {
+    start::<Ping, Pong>(Keypair::generate_ed25519(), config).expect("failed to start node")
+}
+
+/// 以随机身份启动节点，并等待其第一个监听地址
+#[allow(dead_code)]
+pub async fn start_listening(
+    config: NodeConfig,
+) -> (NetClient<Ping, Pong>, EventReceiver<Ping>, Multiaddr) {
+    let (client, mut events) = start_node(config);
+    let addr = wait_for_listen_addr(&mut events).await;
+    (client, events, addr)
+}
+
+/// 从事件流中提取第一个 Listening 地址
+#[allow(dead_code)]
+pub async fn wait_for_listen_addr(events: &mut EventReceiver<Ping>) -> Multiaddr {
+    timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::Listening { addr }) = events.recv().await {
+                return addr;
+            }
+        }
+    })
+    .await
+    .expect("node should start listening")
+}
+
 /// A 侧：等待 mDNS 发现 + PeerConnected + IdentifyReceived
 #[allow(dead_code)]
 pub async fn wait_for_connection(
@@ -49,7 +88,7 @@ pub async fn wait_for_connection(
                 eprintln!("[A] {:?}", event);
                 match &event {
                     NodeEvent::PeersDiscovered { .. } => discovered = true,
-                    NodeEvent::PeerConnected { peer_id } => connected = Some(*peer_id),
+                    NodeEvent::PeerConnected { peer_id, .. } => connected = Some(*peer_id),
                     NodeEvent::IdentifyReceived {
                         protocol_version,
                         agent_version,
@@ -90,10 +129,10 @@ pub async fn event_printer(
         };
         eprintln!("[{}] {:?}", label, event);
 
-        if matches!(&event, NodeEvent::IdentifyReceived { .. }) {
-            if let Some(tx) = identify_tx.take() {
-                let _ = tx.send(());
-            }
+        if matches!(&event, NodeEvent::IdentifyReceived { .. })
+            && let Some(tx) = identify_tx.take()
+        {
+            let _ = tx.send(());
         }
     }
 }
@@ -0,0 +1,545 @@
+//! 集成测试：批量拨号与黑名单
+//!
+//! A 同时拨号一个可达的 B 和一个没有任何地址的随机 peer，验证：
+//! 结果按输入顺序返回 → B 成功 → 随机 peer 单独失败，不影响 B；
+//! 之后 `connected_peers` / `connection_count` 反映当前连接。
+//! 另验证 `block_peer` 断开并拒绝被屏蔽的 peer，`unblock_peer` 后恢复；
+//! `dial_addr` 按地址拨号并解析出对端 PeerId；identify 后上报对端观察到的本节点地址；
+//! 配置 `dial_address_order` 后仍能经排序后的候选地址连上对端；
+//! 同时发起的多个拨号（`dial` / `dial_many`）共享同一次连接结果，
+//! 加入的拨号失败时改用其余候选地址自行拨号；
+//! `connection_info` 报告每条连接的地址、方向与 Ping 延迟；
+//! 握手迟迟不完成的拨号在 `dial_timeout` 到期后返回 `Error::DialTimeout`，
+//! 且不中止其他命令发往同一 peer 的拨号；`dial_many` 同样以 `dial_timeout`
+//! 或调用方指定的超时为限。
+//! `batch` 一次提交多项操作，结果按添加顺序返回，效果与逐个调用相同。
+//! 拨号开始时先上报 `DialStarted`（设置地址偏好时带候选地址），之后以带端点信息的 `PeerConnected`
+//! 或同一 `connection_id` 的 `ConnectionFailed` 结束。
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::*;
+use swarm_p2p_core::command::BatchOutcome;
+use swarm_p2p_core::event::ConnectionDirection;
+use swarm_p2p_core::libp2p::PeerId;
+use swarm_p2p_core::libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
+use swarm_p2p_core::{AddressOrder, Error, NodeEvent};
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dial_many_reports_per_peer_results() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    let unknown = PeerId::random();
+
+    let results = timeout(TIMEOUT, client_a.dial_many(vec![peer_b, unknown, peer_b]))
+        .await
+        .expect("dial_many timed out")
+        .expect("dial_many failed");
+
+    assert_eq!(results.len(), 2, "duplicate peers should be dialed once");
+    assert_eq!(results[0].0, peer_b);
+    assert!(
+        results[0].1.is_ok(),
+        "B should be reachable: {:?}",
+        results[0].1
+    );
+    assert_eq!(results[1].0, unknown);
+    assert!(results[1].1.is_err(), "unknown peer has no addresses");
+    assert!(client_a.is_connected(peer_b).await.unwrap());
+    assert_eq!(client_a.connected_peers().await.unwrap(), vec![peer_b]);
+    assert!(client_a.connection_count().await.unwrap() >= 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocked_peer_is_disconnected_and_denied() {
+    let (client_a, mut events_a) = start_node(local_config());
+    let (client_b, events_b) = start_node(local_config());
+    let peer_a = client_a.local_peer_id();
+    tokio::spawn(event_printer(events_b, "B", None));
+
+    let addr_a = wait_for_listen_addr(&mut events_a).await;
+    tokio::spawn(async move { while events_a.recv().await.is_some() {} });
+
+    client_b.add_peer_addrs(peer_a, vec![addr_a]).await.unwrap();
+    let results = client_b.dial_many(vec![peer_a]).await.unwrap();
+    assert!(results[0].1.is_ok(), "{:?}", results[0].1);
+
+    // 屏蔽后连接被关闭，再次拨号直接被拒绝
+    assert!(client_b.block_peer(peer_a).await.unwrap());
+    assert!(!client_b.block_peer(peer_a).await.unwrap());
+    timeout(TIMEOUT, async {
+        while client_b.is_connected(peer_a).await.unwrap() {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("blocked peer should be disconnected");
+    let results = client_b.dial_many(vec![peer_a]).await.unwrap();
+    assert!(results[0].1.is_err(), "dial to blocked peer should fail");
+
+    // 解除屏蔽后恢复正常
+    assert!(client_b.unblock_peer(peer_a).await.unwrap());
+    let results = client_b.dial_many(vec![peer_a]).await.unwrap();
+    assert!(results[0].1.is_ok(), "{:?}", results[0].1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn batch_applies_ops_in_order() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    assert!(client_a.batch().submit().await.unwrap().is_empty());
+
+    let blocked = PeerId::random();
+    let external: swarm_p2p_core::libp2p::Multiaddr = "/ip4/203.0.113.7/tcp/4001".parse().unwrap();
+    let batch = client_a
+        .batch()
+        .add_peer_addrs(peer_b, vec![addr_b])
+        .add_external_address(external.clone())
+        .block_peer(blocked)
+        .block_peer(blocked);
+    assert_eq!(batch.len(), 4);
+    let outcomes = batch.submit().await.unwrap();
+    assert_eq!(
+        outcomes,
+        vec![
+            BatchOutcome::AddPeerAddrs,
+            BatchOutcome::AddExternalAddress,
+            BatchOutcome::BlockPeer(true),
+            BatchOutcome::BlockPeer(false),
+        ]
+    );
+
+    // 地址已登记，可直接按 PeerId 拨号；外部地址与黑名单均已生效
+    timeout(TIMEOUT, client_a.dial(peer_b))
+        .await
+        .expect("dial timed out")
+        .expect("dial failed");
+    assert!(client_a.get_addrs().await.unwrap().contains(&external));
+    assert!(client_a.unblock_peer(blocked).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dial_addr_resolves_peer_id() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    // 不带 /p2p：握手后才得知对端身份
+    let resolved = timeout(TIMEOUT, client_a.dial_addr(addr_b.clone()))
+        .await
+        .expect("dial_addr timed out")
+        .expect("dial_addr failed");
+    assert_eq!(resolved, peer_b);
+
+    // 地址已登记到 Kad 路由表
+    let routing = client_a.export_routing_table().await.unwrap();
+    assert!(
+        routing.iter().any(|(peer, addrs)| {
+            *peer == peer_b
+                && addrs
+                    .iter()
+                    .any(|a| a.to_string().starts_with(&addr_b.to_string()))
+        }),
+        "{routing:?}"
+    );
+
+    // 带 /p2p 且已连接：直接返回
+    let with_p2p = addr_b.clone().with_p2p(peer_b).unwrap();
+    assert_eq!(client_a.dial_addr(with_p2p).await.unwrap(), peer_b);
+
+    // 地址中的 PeerId 与实际不符时失败
+    let wrong = addr_b.with_p2p(PeerId::random()).unwrap();
+    let err = client_a.dial_addr(wrong).await.unwrap_err();
+    assert!(err.to_string().contains("peer ID"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn identify_reports_observed_addr() {
+    let (client_a, mut events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    client_a.dial_addr(addr_b).await.expect("dial_addr failed");
+    let observed = timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::ObservedAddr { by_peer, addr }) = events_a.recv().await {
+                return (by_peer, addr);
+            }
+        }
+    })
+    .await
+    .expect("A should learn its observed address from B");
+    assert_eq!(observed.0, peer_b);
+    assert!(
+        observed.1.to_string().starts_with("/ip4/127.0.0.1/tcp/"),
+        "{}",
+        observed.1
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dial_with_address_order() {
+    let (client_a, events_a) =
+        start_node(local_config().with_dial_address_order(AddressOrder::PreferIpv6));
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    // 排在前面的 IPv6 地址不可达，随后的 IPv4 地址仍会被尝试
+    let unreachable: swarm_p2p_core::libp2p::Multiaddr = "/ip6/::1/tcp/1".parse().unwrap();
+    client_a
+        .add_peer_addrs(peer_b, vec![addr_b, unreachable])
+        .await
+        .unwrap();
+    timeout(TIMEOUT, client_a.dial(peer_b))
+        .await
+        .expect("dial timed out")
+        .expect("dial failed");
+    // 已连接时两者都直接成功
+    client_a.dial(peer_b).await.expect("dial failed");
+    let results = client_a.dial_many(vec![peer_b]).await.unwrap();
+    assert!(results[0].1.is_ok(), "{:?}", results[0].1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_dials_share_connection() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+
+    // 后发起的拨号加入进行中的拨号，都等到同一个 ConnectionEstablished
+    let (first, second, many) = timeout(TIMEOUT, async {
+        tokio::join!(
+            client_a.dial(peer_b),
+            client_a.dial(peer_b),
+            client_a.dial_many(vec![peer_b])
+        )
+    })
+    .await
+    .expect("dials timed out");
+    first.expect("first dial failed");
+    second.expect("second dial failed");
+    let many = many.unwrap();
+    assert!(many[0].1.is_ok(), "{:?}", many[0].1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn joined_dial_falls_back_when_other_dial_fails() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    // 过期地址：accept 后不回应，由测试决定何时让这次拨号失败
+    let stale = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let stale_addr = format!("/ip4/127.0.0.1/tcp/{}", stale.local_addr().unwrap().port());
+    let stale_dial = {
+        let client_a = client_a.clone();
+        let opts = DialOpts::peer_id(peer_b)
+            .addresses(vec![stale_addr.parse().unwrap()])
+            .build();
+        tokio::spawn(async move { client_a.dial_with_opts(opts).await })
+    };
+    let (stream, _) = timeout(TIMEOUT, stale.accept())
+        .await
+        .expect("stale dial should connect")
+        .unwrap();
+
+    // 拨号进行中登记 B 的真实地址，dial 加入进行中的拨号
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    let dial = {
+        let client_a = client_a.clone();
+        tokio::spawn(async move { client_a.dial(peer_b).await })
+    };
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    drop(stream);
+
+    let stale_result = timeout(TIMEOUT, stale_dial).await.unwrap().unwrap();
+    assert!(stale_result.is_err(), "{stale_result:?}");
+    timeout(TIMEOUT, dial)
+        .await
+        .expect("dial timed out")
+        .unwrap()
+        .expect("joined dial should fall back to B's other address");
+    assert!(client_a.is_connected(peer_b).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn connection_info_reports_direction_and_rtt() {
+    let (client_a, mut events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    assert!(client_a.connection_info(peer_b).await.unwrap().is_empty());
+    client_a.dial_addr(addr_b.clone()).await.unwrap();
+
+    // 连接建立后立即发起首次 Ping
+    timeout(TIMEOUT, async {
+        while let Some(event) = events_a.recv().await {
+            if matches!(event, NodeEvent::PingSuccess { peer_id, .. } if peer_id == peer_b) {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("A should ping B");
+
+    let info = client_a.connection_info(peer_b).await.unwrap();
+    assert_eq!(info.len(), 1, "{info:?}");
+    assert_eq!(info[0].remote_addr, addr_b);
+    assert_eq!(info[0].direction, ConnectionDirection::Outbound);
+    assert!(!info[0].relayed);
+    assert!(info[0].rtt.is_some());
+
+    let info = client_b
+        .connection_info(client_a.local_peer_id())
+        .await
+        .unwrap();
+    assert_eq!(info.len(), 1, "{info:?}");
+    assert_eq!(info[0].direction, ConnectionDirection::Inbound);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dial_times_out_on_silent_peer() {
+    // 只监听不 accept：TCP 连上，但安全握手永远等不到回应
+    let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = silent.local_addr().unwrap().port();
+    let config = test_config()
+        .with_mdns(false)
+        .with_dial_timeout(Some(Duration::from_millis(500)));
+    let (client, events) = start_node(config);
+    tokio::spawn(event_printer(events, "A", None));
+
+    let peer = PeerId::random();
+    client
+        .add_peer_addrs(
+            peer,
+            vec![format!("/ip4/127.0.0.1/tcp/{port}").parse().unwrap()],
+        )
+        .await
+        .unwrap();
+    let started = Instant::now();
+    let result = timeout(TIMEOUT, client.dial(peer))
+        .await
+        .expect("dial should resolve at dial_timeout");
+    assert!(
+        matches!(result, Err(Error::DialTimeout)),
+        "expected DialTimeout, got: {:?}",
+        result
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(!client.is_connected(peer).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dial_many_times_out_on_silent_peer() {
+    let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = silent.local_addr().unwrap().port();
+    let start_node = |dial_timeout| {
+        let config = test_config()
+            .with_mdns(false)
+            .with_dial_timeout(dial_timeout);
+        let (client, events) = start_node(config);
+        tokio::spawn(event_printer(events, "A", None));
+        client
+    };
+    let silent_peer = |client: swarm_p2p_core::NetClient<Ping, Pong>| async move {
+        let peer = PeerId::random();
+        client
+            .add_peer_addrs(
+                peer,
+                vec![format!("/ip4/127.0.0.1/tcp/{port}").parse().unwrap()],
+            )
+            .await
+            .unwrap();
+        (client, peer)
+    };
+
+    // 整体超时取 dial_timeout
+    let (client, peer) = silent_peer(start_node(Some(Duration::from_millis(500)))).await;
+    let started = Instant::now();
+    let results = timeout(TIMEOUT, client.dial_many(vec![peer]))
+        .await
+        .expect("dial_many should resolve at dial_timeout")
+        .unwrap();
+    assert!(results[0].1.is_err(), "silent peer should time out");
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    // 调用方指定的超时
+    let (client, peer) = silent_peer(start_node(None)).await;
+    let started = Instant::now();
+    let results = timeout(
+        TIMEOUT,
+        client.dial_many_with_timeout(vec![peer], Duration::from_millis(500)),
+    )
+    .await
+    .expect("dial_many_with_timeout should resolve at its timeout")
+    .unwrap();
+    assert!(results[0].1.is_err(), "silent peer should time out");
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dial_timeout_keeps_other_pending_dials() {
+    let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr: swarm_p2p_core::libp2p::Multiaddr =
+        format!("/ip4/127.0.0.1/tcp/{}", silent.local_addr().unwrap().port())
+            .parse()
+            .unwrap();
+    let config = test_config()
+        .with_mdns(false)
+        .with_dial_timeout(Some(Duration::from_millis(500)));
+    let (client, events) = start_node(config);
+    tokio::spawn(event_printer(events, "A", None));
+
+    let peer = PeerId::random();
+    client
+        .add_peer_addrs(peer, vec![addr.clone()])
+        .await
+        .unwrap();
+    let many = tokio::spawn({
+        let client = client.clone();
+        async move { client.dial_many_with_timeout(vec![peer], TIMEOUT).await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // 按地址的拨号超时，不应中止 dial_many 仍在进行的拨号
+    let opts = DialOpts::peer_id(peer)
+        .condition(PeerCondition::Always)
+        .addresses(vec![addr])
+        .build();
+    let result = timeout(TIMEOUT, client.dial_with_opts(opts))
+        .await
+        .expect("dial should resolve at dial_timeout");
+    assert!(matches!(result, Err(Error::DialTimeout)), "{result:?}");
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!many.is_finished(), "dial_many's dial was aborted");
+    many.abort();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dial_started_precedes_outcome() {
+    // 设置地址偏好时 DialStarted 才带上收集到的候选地址
+    let (client_a, mut events_a) =
+        start_node(local_config().with_dial_address_order(AddressOrder::PreferIpv4));
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    client_a
+        .add_peer_addrs(peer_b, vec![addr_b.clone()])
+        .await
+        .unwrap();
+    timeout(TIMEOUT, client_a.dial(peer_b))
+        .await
+        .expect("dial timed out")
+        .expect("dial failed");
+    timeout(TIMEOUT, async {
+        let mut started = false;
+        loop {
+            match events_a.recv().await.expect("event stream closed") {
+                NodeEvent::DialStarted { peer_id, addrs, .. } => {
+                    assert_eq!(peer_id, Some(peer_b));
+                    assert!(
+                        addrs
+                            .iter()
+                            .any(|a| a.to_string().starts_with(&addr_b.to_string())),
+                        "{addrs:?}"
+                    );
+                    started = true;
+                }
+                NodeEvent::PeerConnected {
+                    peer_id,
+                    remote_addr,
+                    direction,
+                    relayed,
+                } if peer_id == peer_b => {
+                    assert!(started, "DialStarted should precede PeerConnected");
+                    // 端点信息来自本次出站直连
+                    assert!(remote_addr.to_string().starts_with(&addr_b.to_string()));
+                    assert_eq!(direction, ConnectionDirection::Outbound);
+                    assert!(!relayed);
+                    return;
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("A should report the dial and the connection");
+
+    // 无人监听的端口：DialStarted 与 ConnectionFailed 的 connection_id 一致
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_addr: swarm_p2p_core::libp2p::Multiaddr =
+        format!("/ip4/127.0.0.1/tcp/{}", closed.local_addr().unwrap().port())
+            .parse()
+            .unwrap();
+    drop(closed);
+    assert!(client_a.dial_addr(closed_addr.clone()).await.is_err());
+    timeout(TIMEOUT, async {
+        let mut started = None;
+        loop {
+            match events_a.recv().await.expect("event stream closed") {
+                NodeEvent::DialStarted {
+                    peer_id,
+                    connection_id,
+                    addrs,
+                } => {
+                    assert_eq!(peer_id, None);
+                    assert_eq!(addrs, vec![closed_addr.clone()]);
+                    started = Some(connection_id);
+                }
+                NodeEvent::ConnectionFailed {
+                    connection_id,
+                    inbound: false,
+                    ..
+                } => {
+                    assert_eq!(started, Some(connection_id));
+                    return;
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("A should report the failed dial");
+}
@@ -0,0 +1,85 @@
+//! 集成测试：Gossipsub 发布/订阅
+//!
+//! 两个开启 Gossipsub 的节点直连并订阅同一主题，验证：
+//! A 发布 → B 收到 `GossipMessage`（来源为 A）；未开启时命令返回错误。
+
+mod common;
+
+use std::time::Duration;
+
+use common::*;
+use swarm_p2p_core::{Error, NodeEvent};
+use tokio::time::timeout;
+
+const TOPIC: &str = "/test/presence";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_reaches_subscriber() {
+    let config = || local_config().with_gossipsub(true);
+
+    let (client_a, events_a) = start_node(config());
+    let (client_b, mut events_b) = start_node(config());
+    let peer_a = client_a.local_peer_id();
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+
+    assert!(client_a.subscribe(TOPIC).await.unwrap());
+    assert!(!client_a.subscribe(TOPIC).await.unwrap());
+    assert!(client_b.subscribe(TOPIC).await.unwrap());
+
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    client_a.dial(peer_b).await.unwrap();
+
+    // B 的订阅需要先同步到 A，之前发布会因没有订阅者而失败
+    timeout(TIMEOUT, async {
+        while let Err(e) = client_a.publish(TOPIC, b"online".to_vec()).await {
+            eprintln!("[A] publish not ready: {}", e);
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    })
+    .await
+    .expect("publish should succeed once B subscribed");
+
+    let (source, data) = timeout(TIMEOUT, async {
+        loop {
+            let event = events_b.recv().await.expect("event stream closed");
+            eprintln!("[B] {:?}", event);
+            if let NodeEvent::GossipMessage {
+                topic,
+                source,
+                data,
+            } = event
+            {
+                assert_eq!(topic, TOPIC);
+                return (source, data);
+            }
+        }
+    })
+    .await
+    .expect("B should receive the gossip message");
+    assert_eq!(source, Some(peer_a));
+    assert_eq!(data, b"online".to_vec());
+
+    assert!(client_b.unsubscribe(TOPIC).await.unwrap());
+    assert!(!client_b.unsubscribe(TOPIC).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn disabled_gossipsub_rejects_commands() {
+    let (client, _events) = start_node(test_config().with_mdns(false));
+
+    let result = client.subscribe(TOPIC).await;
+    assert!(
+        matches!(result, Err(Error::Behaviour(_))),
+        "got: {:?}",
+        result
+    );
+    let result = client.publish(TOPIC, b"x".to_vec()).await;
+    assert!(
+        matches!(result, Err(Error::Behaviour(_))),
+        "got: {:?}",
+        result
+    );
+}
@@ -0,0 +1,49 @@
+//! 集成测试：应用层握手
+//!
+//! 两个开启握手的节点直连后，验证：
+//! A 收到 `HandshakeCompleted` → `peer_info` 返回 B 的信息 → `version_handshake` 重新协商成功。
+
+mod common;
+
+use common::*;
+use swarm_p2p_core::{HandshakeInfo, NodeEvent};
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn handshake_on_connect() {
+    let info_a = HandshakeInfo::new("1.0").with_features(["chat"]);
+    let info_b = HandshakeInfo::new("2.0").with_features(["chat", "sync"]);
+    let config = |info: HandshakeInfo| local_config().with_handshake(info);
+
+    let (client_a, mut events_a) = start_node(config(info_a));
+    let (client_b, mut events_b) = start_node(config(info_b.clone()));
+    let peer_b = client_b.local_peer_id();
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    client_a.dial(peer_b).await.unwrap();
+
+    let received = timeout(TIMEOUT, async {
+        loop {
+            let event = events_a.recv().await.expect("event stream closed");
+            eprintln!("[A] {:?}", event);
+            if let NodeEvent::HandshakeCompleted { peer_id, info } = event {
+                assert_eq!(peer_id, peer_b);
+                return info;
+            }
+        }
+    })
+    .await
+    .expect("A should complete handshake");
+    assert_eq!(received, info_b);
+    assert_eq!(client_a.peer_info(&peer_b), Some(info_b.clone()));
+    assert!(received.supports("sync"));
+
+    let renegotiated = timeout(TIMEOUT, client_a.version_handshake(peer_b))
+        .await
+        .expect("version_handshake timed out")
+        .expect("version_handshake failed");
+    assert_eq!(renegotiated, info_b);
+}
@@ -2,16 +2,27 @@
 //!
 //! 三节点架构：引导节点(S) + A + B，关闭 mDNS。
 //! A 和 B 通过引导节点加入 DHT 网络，验证：
-//! bootstrap、put_record/get_record、start_provide/get_providers、
-//! get_closest_peers、stop_provide、remove_record。
+//! bootstrap、put_record/get_record/get_record_all/get_record_with_opts、start_provide/get_providers(_stream)、
+//! get_closest_peers、kad_routing_table、stop_provide、remove_record；
+//! 以及 seed_peers / import_peers 预热路由表、cancel_query 取消进行中的查询（命令超时后不可再取消）、丢弃 KadQuery 即取消查询、
+//! kad_auto_bootstrap_interval 周期性 bootstrap（为 0 时关闭）、get_providers_min 在 Kad 查询超时时返回部分结果、
+//! 默认内存存储的容量上限、
+//! 查询失败的错误分类、put_record_local 只写本地存储、Server 节点上报入站 Kad 请求、
+//! find_peer 按 PeerId 查找地址、republish_provider(s) 立即重新宣告；
+//! 关闭 Kad 后各操作返回 `Error::Config`；记录校验器拒绝被篡改的签名记录；
+//! publish_record 同时存储记录并宣告 Provider，存储失败时撤销宣告；
+//! find_and_connect_provider 在查询结束后仍以发现时记下的地址拨号其余 Provider。
 
 mod common;
 
 use std::time::Duration;
 
 use common::*;
-use libp2p::kad::{Record, RecordKey};
+use futures::StreamExt;
 use libp2p::PeerId;
+use libp2p::kad::{Record, RecordKey};
+use swarm_p2p_core::command::GetRecordOpts;
+use swarm_p2p_core::event::KadRequestKind;
 use swarm_p2p_core::{NodeConfig, NodeEvent, start};
 use tokio::sync::oneshot;
 use tokio::time::timeout;
@@ -37,17 +48,6 @@ fn kad_config_with_bootstrap(boot_peer: PeerId, boot_addr: libp2p::Multiaddr) ->
 
 const KAD_TIMEOUT: Duration = Duration::from_secs(15);
 
-/// 从事件流中提取第一个 Listening 地址
-async fn wait_for_listen_addr(
-    events: &mut swarm_p2p_core::EventReceiver<Ping>,
-) -> libp2p::Multiaddr {
-    loop {
-        if let Some(NodeEvent::Listening { addr }) = events.recv().await {
-            return addr;
-        }
-    }
-}
-
 /// 等待指定节点的 IdentifyReceived
 async fn wait_for_identify(
     events: &mut swarm_p2p_core::EventReceiver<Ping>,
@@ -77,9 +77,7 @@ async fn three_node_kad_flow() {
         start::<Ping, Pong>(keypair_s, kad_config()).expect("failed to start boot node S");
 
     // 获取 S 的监听地址
-    let boot_addr = timeout(KAD_TIMEOUT, wait_for_listen_addr(&mut events_s))
-        .await
-        .expect("boot node listen timed out");
+    let boot_addr = wait_for_listen_addr(&mut events_s).await;
     eprintln!("[S] listening at {}", boot_addr);
 
     // S 的事件后台消费（防止 channel 满阻塞）
@@ -136,6 +134,21 @@ async fn three_node_kad_flow() {
     let _ = timeout(KAD_TIMEOUT, b_identify_rx).await;
     eprintln!("[Kad] A and B discovered each other via DHT");
 
+    // 路由表中应包含引导节点及其地址
+    let buckets = client_a
+        .kad_routing_table()
+        .await
+        .expect("kad_routing_table failed");
+    assert!(
+        buckets
+            .iter()
+            .flat_map(|b| &b.peers)
+            .any(|(peer, addrs)| *peer == peer_s_id && !addrs.is_empty()),
+        "routing table should contain boot node, got: {:?}",
+        buckets
+    );
+    eprintln!("[Kad] kad_routing_table OK, {} buckets", buckets.len());
+
     // ===== 5. put_record (A) → get_record (B) =====
     let key = RecordKey::new(&b"/test/greeting");
     let record = Record::new(key.clone(), b"hello-kad".to_vec());
@@ -157,6 +170,101 @@ async fn three_node_kad_flow() {
         get_result.stats
     );
 
+    // get_record_all：收集所有副本，A 本地存储的副本来源为 None
+    let all_result = timeout(KAD_TIMEOUT, client_a.get_record_all(key.clone()))
+        .await
+        .expect("get_record_all timed out")
+        .expect("get_record_all failed");
+    assert!(
+        all_result
+            .records
+            .iter()
+            .all(|r| r.record.value == b"hello-kad".to_vec())
+    );
+    assert!(all_result.records.iter().any(|r| r.peer.is_none()));
+    eprintln!(
+        "[Kad] get_record_all OK, {} records",
+        all_result.records.len()
+    );
+
+    // put_record_with_progress：最终进度应达到 quorum
+    let progress_record = Record::new(RecordKey::new(&b"/test/progress"), b"p".to_vec());
+    let (progress, put_fut) =
+        client_a.put_record_with_progress(progress_record, libp2p::kad::Quorum::One);
+    let (updates, put_result) = timeout(KAD_TIMEOUT, async {
+        tokio::join!(progress.collect::<Vec<_>>(), put_fut)
+    })
+    .await
+    .expect("put_record_with_progress timed out");
+    put_result.expect("put_record_with_progress failed");
+    let last = updates.last().expect("no progress reported");
+    assert!(
+        last.stored >= last.quorum,
+        "final progress should reach quorum, got: {:?}",
+        updates
+    );
+    eprintln!("[Kad] put_record_with_progress OK, updates={:?}", updates);
+
+    // put_record_with_quorum：网络中只有 2 个远端节点，无法满足 replication_factor=3 的 All
+    let quorum_record = Record::new(RecordKey::new(&b"/test/quorum"), b"q".to_vec());
+    let quorum_result = timeout(
+        KAD_TIMEOUT,
+        client_a.put_record_with_quorum(quorum_record, libp2p::kad::Quorum::All),
+    )
+    .await
+    .expect("put_record_with_quorum timed out");
+    assert!(
+        matches!(
+            quorum_result,
+            Err(swarm_p2p_core::Error::KadQuorumFailed { quorum: 3, .. })
+        ),
+        "expected KadQuorumFailed, got: {:?}",
+        quorum_result
+    );
+    eprintln!("[Kad] put_record_with_quorum OK, {:?}", quorum_result);
+
+    // get_record_with_opts：B 本地改写取值后，A 以 Majority 读取仍得到多数一致的值；
+    // All 要求 3 个节点取值一致，冲突时失败
+    let conflict_key = RecordKey::new(&b"/test/conflict");
+    timeout(
+        KAD_TIMEOUT,
+        client_a.put_record(Record::new(conflict_key.clone(), b"v1".to_vec())),
+    )
+    .await
+    .expect("put_record timed out")
+    .expect("put_record failed");
+    client_b
+        .put_record_local(Record::new(conflict_key.clone(), b"v2".to_vec()))
+        .await
+        .expect("put_record_local failed");
+    let opts = |quorum| GetRecordOpts {
+        quorum,
+        cache: false,
+    };
+    let majority = timeout(
+        KAD_TIMEOUT,
+        client_a.get_record_with_opts(conflict_key.clone(), opts(libp2p::kad::Quorum::Majority)),
+    )
+    .await
+    .expect("get_record_with_opts timed out")
+    .expect("get_record_with_opts failed");
+    assert_eq!(majority.record.value, b"v1".to_vec());
+    let all = timeout(
+        KAD_TIMEOUT,
+        client_a.get_record_with_opts(conflict_key, opts(libp2p::kad::Quorum::All)),
+    )
+    .await
+    .expect("get_record_with_opts timed out");
+    assert!(
+        matches!(
+            all,
+            Err(swarm_p2p_core::Error::KadRecordQuorumFailed { quorum: 3, .. })
+        ),
+        "expected KadRecordQuorumFailed, got: {:?}",
+        all
+    );
+    eprintln!("[Kad] get_record_with_opts OK, {:?}", all);
+
     // ===== 6. start_provide (A) → get_providers (B) =====
     let provide_key = RecordKey::new(&b"/test/file/abc123");
 
@@ -171,15 +279,84 @@ async fn three_node_kad_flow() {
         .expect("get_providers timed out")
         .expect("get_providers failed");
     assert!(
-        providers_result.providers.contains(&peer_a_id),
+        providers_result.providers().contains(&peer_a_id),
         "A should be a provider, got: {:?}",
-        providers_result.providers
+        providers_result.provider_addrs
+    );
+    // B 与 A 相连，A 的地址随 Provider 一并返回
+    assert!(
+        providers_result
+            .provider_addrs
+            .iter()
+            .any(|(peer, addrs)| *peer == peer_a_id && !addrs.is_empty()),
+        "A's addresses should be returned, got: {:?}",
+        providers_result.provider_addrs
     );
     eprintln!(
         "[Kad] get_providers OK, providers={:?}, stats={:?}",
-        providers_result.providers, providers_result.stats
+        providers_result.provider_addrs, providers_result.stats
     );
 
+    let min_result = timeout(
+        KAD_TIMEOUT,
+        client_b.get_providers_min(provide_key.clone(), 1, Duration::from_secs(5)),
+    )
+    .await
+    .expect("get_providers_min timed out")
+    .expect("get_providers_min failed");
+    assert!(
+        min_result.providers().contains(&peer_a_id),
+        "A should be found by get_providers_min, got: {:?}",
+        min_result.provider_addrs
+    );
+    eprintln!(
+        "[Kad] get_providers_min OK, providers={:?}",
+        min_result.provider_addrs
+    );
+
+    // 流式查找：完整查询结束后 Stream 随之结束
+    let streamed: Vec<PeerId> = timeout(
+        KAD_TIMEOUT,
+        client_b.get_providers_stream(provide_key.clone()).collect(),
+    )
+    .await
+    .expect("get_providers_stream did not complete");
+    assert_eq!(streamed, vec![peer_a_id]);
+    eprintln!("[Kad] get_providers_stream OK, providers={:?}", streamed);
+
+    let connected_provider = timeout(
+        KAD_TIMEOUT,
+        client_b.find_and_connect_provider(provide_key.clone()),
+    )
+    .await
+    .expect("find_and_connect_provider timed out")
+    .expect("find_and_connect_provider failed");
+    assert_eq!(connected_provider, peer_a_id);
+    eprintln!(
+        "[Kad] find_and_connect_provider OK, peer={}",
+        connected_provider
+    );
+
+    // 立即重新宣告：单个 key 与全部 key，未提供的 key 报错
+    let republish_stats = timeout(
+        KAD_TIMEOUT,
+        client_a.republish_provider(provide_key.clone()),
+    )
+    .await
+    .expect("republish_provider timed out")
+    .expect("republish_provider failed");
+    assert!(republish_stats.num_requests > 0, "{:?}", republish_stats);
+    timeout(KAD_TIMEOUT, client_a.republish_all_providers())
+        .await
+        .expect("republish_all_providers timed out")
+        .expect("republish_all_providers failed");
+    let err = client_b
+        .republish_provider(provide_key.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, swarm_p2p_core::Error::Kad(_)), "{err}");
+    eprintln!("[Kad] republish_provider OK, stats={:?}", republish_stats);
+
     // ===== 7. get_closest_peers =====
     let closest_key = RecordKey::new(&b"/test/closest");
     let closest_result = timeout(KAD_TIMEOUT, client_a.get_closest_peers(closest_key))
@@ -204,7 +381,665 @@ async fn three_node_kad_flow() {
         .expect("remove_record failed");
     eprintln!("[Kad] remove_record OK");
 
+    // 从未写入过的 key：查询正常结束，以 KadNotFound 区别于超时
+    let missing_key = RecordKey::new(&"never-stored");
+    let missing = timeout(KAD_TIMEOUT, client_a.get_record(missing_key))
+        .await
+        .expect("get_record timed out")
+        .unwrap_err();
+    assert!(
+        matches!(missing, swarm_p2p_core::Error::KadNotFound),
+        "{missing}"
+    );
+
     a_task.abort();
     b_task.abort();
     s_task.abort();
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn seed_and_import_peers() {
+    let seed_peer = PeerId::random();
+    let seed_addr: libp2p::Multiaddr = "/ip4/127.0.0.1/tcp/40001".parse().unwrap();
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let config = kad_config().with_seed_peers(vec![(seed_peer, vec![seed_addr.clone()])]);
+    let (client, _events) = start::<Ping, Pong>(keypair, config).expect("failed to start node");
+
+    // 种子节点在启动时已写入路由表
+    let exported = client
+        .export_routing_table()
+        .await
+        .expect("export_routing_table failed");
+    // Kad 会为地址补上 /p2p 后缀
+    assert_eq!(exported.len(), 1);
+    assert_eq!(exported[0].0, seed_peer);
+    assert!(
+        exported[0].1[0]
+            .to_string()
+            .starts_with(&seed_addr.to_string())
+    );
+
+    // 运行时导入
+    let imported_peer = PeerId::random();
+    let imported_addr: libp2p::Multiaddr = "/ip4/127.0.0.1/tcp/40002".parse().unwrap();
+    client
+        .import_peers(vec![(imported_peer, vec![imported_addr.clone()])])
+        .await
+        .expect("import_peers failed");
+    let exported = client
+        .export_routing_table()
+        .await
+        .expect("export_routing_table failed");
+    assert_eq!(exported.len(), 2);
+    assert!(exported.iter().any(|(peer, addrs)| {
+        *peer == imported_peer && addrs[0].to_string().starts_with(&imported_addr.to_string())
+    }));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancel_in_flight_query() {
+    // 只接受 TCP 连接、从不回应握手的“节点”，让 Kad 查询一直挂起
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let _held: Vec<_> = listener.incoming().collect();
+    });
+    let stalled_addr: libp2p::Multiaddr = format!("/ip4/127.0.0.1/tcp/{port}").parse().unwrap();
+
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client, _events) =
+        start::<Ping, Pong>(keypair, kad_config()).expect("failed to start node");
+    client
+        .import_peers(vec![(PeerId::random(), vec![stalled_addr])])
+        .await
+        .expect("import_peers failed");
+
+    let mut query = client.get_closest_peers(RecordKey::new(&"cancel-me"));
+    let query_id = query.id().await.expect("query should start");
+    assert!(client.cancel_query(query_id).await.unwrap());
+
+    let err = timeout(Duration::from_secs(5), query)
+        .await
+        .expect("cancelled query should resolve promptly")
+        .unwrap_err();
+    assert!(matches!(err, swarm_p2p_core::Error::Cancelled), "{err}");
+
+    // 已结束的查询不能再取消
+    assert!(!client.cancel_query(query_id).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dropping_kad_query_cancels_it() {
+    // 只接受 TCP 连接、从不回应握手的“节点”，让 Kad 查询一直挂起
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let _held: Vec<_> = listener.incoming().collect();
+    });
+    let stalled_addr: libp2p::Multiaddr = format!("/ip4/127.0.0.1/tcp/{port}").parse().unwrap();
+
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client, _events) =
+        start::<Ping, Pong>(keypair, kad_config()).expect("failed to start node");
+    client
+        .import_peers(vec![(PeerId::random(), vec![stalled_addr])])
+        .await
+        .expect("import_peers failed");
+
+    let mut query = client.get_closest_peers(RecordKey::new(&"drop-me"));
+    let query_id = query.id().await.expect("query should start");
+    drop(query);
+
+    // 丢弃时已发出取消命令，查询不再归任何命令所有
+    assert!(!client.cancel_query(query_id).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancel_after_command_timeout_returns_false() {
+    // 只接受 TCP 连接、从不回应握手的“节点”，让 Kad 查询一直挂起
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let _held: Vec<_> = listener.incoming().collect();
+    });
+    let stalled_addr: libp2p::Multiaddr = format!("/ip4/127.0.0.1/tcp/{port}").parse().unwrap();
+
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let config = kad_config().with_command_timeout(Some(Duration::from_millis(500)));
+    let (client, _events) = start::<Ping, Pong>(keypair, config).expect("failed to start node");
+    client
+        .import_peers(vec![(PeerId::random(), vec![stalled_addr])])
+        .await
+        .expect("import_peers failed");
+
+    let mut query = client.get_record(RecordKey::new(&"time-me-out"));
+    let query_id = query.id().await.expect("query should start");
+    let err = timeout(Duration::from_secs(5), query)
+        .await
+        .expect("query should hit the command timeout")
+        .unwrap_err();
+    assert!(
+        matches!(err, swarm_p2p_core::Error::CommandTimeout),
+        "{err}"
+    );
+
+    // 超时的命令已结束其 Kad 查询，不能再取消
+    assert!(!client.cancel_query(query_id).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn auto_bootstrap_runs_periodically() {
+    let keypair_s = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_s_id = PeerId::from_public_key(&keypair_s.public());
+    let (_client_s, mut events_s) =
+        start::<Ping, Pong>(keypair_s, kad_config()).expect("failed to start boot node S");
+    let addr_s = wait_for_listen_addr(&mut events_s).await;
+
+    let keypair_a = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let config = kad_config_with_bootstrap(peer_s_id, addr_s)
+        .with_kad_auto_bootstrap_interval(Duration::from_millis(300));
+    let (_client_a, mut events_a) =
+        start::<Ping, Pong>(keypair_a, config).expect("failed to start node A");
+
+    // 无需手动调用 bootstrap()，按间隔重复完成
+    let completed = timeout(KAD_TIMEOUT, async {
+        let mut completed = 0;
+        while let Some(event) = events_a.recv().await {
+            if let NodeEvent::KadBootstrapCompleted { num_remaining } = event {
+                eprintln!("[A] auto bootstrap completed, {num_remaining} remaining");
+                completed += 1;
+                if completed == 2 {
+                    return completed;
+                }
+            }
+        }
+        completed
+    })
+    .await
+    .expect("auto bootstrap should complete twice");
+    assert_eq!(completed, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn zero_auto_bootstrap_interval_is_disabled() {
+    let keypair_s = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_s_id = PeerId::from_public_key(&keypair_s.public());
+    let (_client_s, mut events_s) =
+        start::<Ping, Pong>(keypair_s, kad_config()).expect("failed to start boot node S");
+    let addr_s = wait_for_listen_addr(&mut events_s).await;
+
+    // 0 视为关闭：启动不 panic，也不会自动 bootstrap
+    let keypair_a = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let config = kad_config_with_bootstrap(peer_s_id, addr_s)
+        .with_kad_auto_bootstrap_interval(Duration::ZERO);
+    let (client_a, mut events_a) =
+        start::<Ping, Pong>(keypair_a, config).expect("failed to start node A");
+    let completed = timeout(Duration::from_secs(1), async {
+        loop {
+            if let Some(NodeEvent::KadBootstrapCompleted { .. }) = events_a.recv().await {
+                return;
+            }
+        }
+    })
+    .await;
+    assert!(
+        completed.is_err(),
+        "zero interval should not auto bootstrap"
+    );
+    assert!(
+        client_a
+            .connected_peers()
+            .await
+            .unwrap()
+            .contains(&peer_s_id)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_providers_min_keeps_partial_result_on_kad_timeout() {
+    use libp2p::kad::ProviderRecord;
+    use libp2p::kad::store::{MemoryStore, RecordStore};
+
+    // 路由表里唯一的节点只接受 TCP 连接不握手，查询只能等到 kad_query_timeout
+    let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let silent_addr: libp2p::Multiaddr =
+        format!("/ip4/127.0.0.1/tcp/{}", silent.local_addr().unwrap().port())
+            .parse()
+            .unwrap();
+
+    // 本地存储里已有一个 Provider，查询开始时即被找到
+    let key = RecordKey::new(&"min-kad-timeout");
+    let provider = PeerId::random();
+    let record = ProviderRecord::new(key.clone(), provider, vec![]);
+    let mut config = kad_config()
+        .with_seed_peers(vec![(PeerId::random(), vec![silent_addr])])
+        .with_kad_store(move |local_id| {
+            let mut store = MemoryStore::new(local_id);
+            store
+                .add_provider(record.clone())
+                .map_err(std::io::Error::other)?;
+            Ok(store)
+        });
+    config.kad_query_timeout = Duration::from_secs(1);
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client, _events) = start::<Ping, Pong>(keypair, config).expect("failed to start node");
+
+    // Kad 只在 swarm 被 poll 时检查查询超时，空闲节点需要别的事件唤醒
+    let poke = tokio::spawn({
+        let client = client.clone();
+        async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                let _ = client.connected_peers().await;
+            }
+        }
+    });
+
+    // timeout 长于 kad_query_timeout（也长于测试等待时间）：只能由 Kad 查询超时返回
+    let result = timeout(
+        KAD_TIMEOUT,
+        client.get_providers_min(key, 5, Duration::from_secs(60)),
+    )
+    .await
+    .expect("get_providers_min should return when the Kad query times out")
+    .expect("partial result should be returned on Kad timeout");
+    assert_eq!(result.providers(), vec![provider]);
+    poke.abort();
+    drop(silent);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memory_store_limits_surface_errors() {
+    use libp2p::kad::store;
+    use swarm_p2p_core::Error;
+
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let config = kad_config()
+        .with_kad_max_records(1)
+        .with_kad_max_record_size(16)
+        .with_kad_max_provided_keys(1);
+    let (client, _events) = start::<Ping, Pong>(keypair, config).expect("failed to start node");
+    let record = |key: &str, len: usize| Record::new(RecordKey::new(&key), vec![0u8; len]);
+
+    // 没有其他节点，写入本地后查询以 quorum 失败结束，但不是存储错误
+    let err = client.put_record(record("big", 32)).await.unwrap_err();
+    assert!(
+        matches!(err, Error::KadStore(store::Error::ValueTooLarge)),
+        "{err}"
+    );
+    let result = timeout(KAD_TIMEOUT, client.put_record(record("first", 8)))
+        .await
+        .unwrap();
+    assert!(!matches!(result, Err(Error::KadStore(_))), "{result:?}");
+    let err = client.put_record(record("second", 8)).await.unwrap_err();
+    assert!(
+        matches!(err, Error::KadStore(store::Error::MaxRecords)),
+        "{err}"
+    );
+
+    let result = timeout(KAD_TIMEOUT, client.start_provide(RecordKey::new(&"p1")))
+        .await
+        .unwrap();
+    assert!(!matches!(result, Err(Error::KadStore(_))), "{result:?}");
+    let err = client
+        .start_provide(RecordKey::new(&"p2"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::KadStore(store::Error::MaxProvidedKeys)),
+        "{err}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn empty_routing_table_reports_no_peers() {
+    use swarm_p2p_core::Error;
+
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client, _events) =
+        start::<Ping, Pong>(keypair, kad_config()).expect("failed to start node");
+    let key = RecordKey::new(&"nobody-to-ask");
+
+    let err = client.bootstrap().await.unwrap_err();
+    assert!(matches!(err, Error::KadNoPeers), "{err}");
+    let err = timeout(KAD_TIMEOUT, client.get_record(key.clone()))
+        .await
+        .expect("get_record timed out")
+        .unwrap_err();
+    assert!(matches!(err, Error::KadNoPeers), "{err}");
+    let err = timeout(KAD_TIMEOUT, client.get_closest_peers(key))
+        .await
+        .expect("get_closest_peers timed out")
+        .unwrap_err();
+    assert!(matches!(err, Error::KadNoPeers), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn put_record_local_stores_without_replication() {
+    use libp2p::kad::store;
+    use swarm_p2p_core::Error;
+
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let config = kad_config().with_kad_max_record_size(16);
+    let (client, _events) = start::<Ping, Pong>(keypair, config).expect("failed to start node");
+    let key = RecordKey::new(&"local-only");
+
+    // 没有其他节点也立即成功，随后可从本地存储查到
+    client
+        .put_record_local(Record::new(key.clone(), b"hosted".to_vec()))
+        .await
+        .expect("put_record_local failed");
+    let result = timeout(KAD_TIMEOUT, client.get_record(key))
+        .await
+        .expect("get_record timed out")
+        .expect("local record should be found");
+    assert_eq!(result.record.value, b"hosted");
+
+    let err = client
+        .put_record_local(Record::new(RecordKey::new(&"big"), vec![0u8; 32]))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::KadStore(store::Error::ValueTooLarge)),
+        "{err}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn server_reports_inbound_requests() {
+    let keypair_s = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_s = keypair_s.public().to_peer_id();
+    let (_client_s, mut events_s) =
+        start::<Ping, Pong>(keypair_s, kad_config()).expect("failed to start S");
+    let addr_s = wait_for_listen_addr(&mut events_s).await;
+
+    let keypair_a = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client_a, mut events_a) =
+        start::<Ping, Pong>(keypair_a, kad_config_with_bootstrap(peer_s, addr_s))
+            .expect("failed to start A");
+    wait_for_identify(&mut events_a, "A").await;
+    tokio::spawn(async move { while events_a.recv().await.is_some() {} });
+
+    // S 只有 A 一个对端，查询一定会问到 S
+    let _ = timeout(KAD_TIMEOUT, client_a.get_record(RecordKey::new(&"asked")))
+        .await
+        .expect("get_record timed out");
+    timeout(KAD_TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::KadInboundRequest { request }) = events_s.recv().await
+                && request == KadRequestKind::GetRecord
+            {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("S should report the inbound GetRecord");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn validator_rejects_tampered_signed_records() {
+    use swarm_p2p_core::{sign_record, verify_record};
+
+    let keypair_s = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_s = keypair_s.public().to_peer_id();
+    let config_s = kad_config().with_record_validator(|record| verify_record(record).is_ok());
+    let (client_s, mut events_s) =
+        start::<Ping, Pong>(keypair_s, config_s).expect("failed to start S");
+    let addr_s = wait_for_listen_addr(&mut events_s).await;
+    tokio::spawn(async move { while events_s.recv().await.is_some() {} });
+
+    let keypair_a = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_a = keypair_a.public().to_peer_id();
+    let signed_key = RecordKey::new(&"signed");
+    let forged_key = RecordKey::new(&"forged");
+    let signed = sign_record(&keypair_a, signed_key.clone(), b"genuine".to_vec(), None).unwrap();
+    let mut forged =
+        sign_record(&keypair_a, forged_key.clone(), b"genuine".to_vec(), None).unwrap();
+    *forged.value.last_mut().unwrap() ^= 1;
+
+    let (client_a, mut events_a) =
+        start::<Ping, Pong>(keypair_a, kad_config_with_bootstrap(peer_s, addr_s))
+            .expect("failed to start A");
+    wait_for_identify(&mut events_a, "A").await;
+    tokio::spawn(async move { while events_a.recv().await.is_some() {} });
+
+    // S 是 A 唯一的对端，两条记录都会 PUT 到 S；随后删掉 A 本地的副本
+    for record in [signed, forged] {
+        let key = record.key.clone();
+        timeout(KAD_TIMEOUT, client_a.put_record(record))
+            .await
+            .expect("put_record timed out")
+            .expect("put_record failed");
+        client_a.remove_record(key).await.unwrap();
+    }
+
+    let result = timeout(KAD_TIMEOUT, client_s.get_record(signed_key))
+        .await
+        .expect("get_record timed out")
+        .expect("signed record should be stored on S");
+    let value = verify_record(&result.record).unwrap();
+    assert_eq!(value.signer, peer_a);
+    assert_eq!(value.value, b"genuine");
+
+    let result = timeout(KAD_TIMEOUT, client_s.get_record(forged_key))
+        .await
+        .expect("get_record timed out");
+    assert!(
+        result.is_err(),
+        "forged record should be rejected: {:?}",
+        result
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_record_stores_and_provides() {
+    let keypair_s = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_s = keypair_s.public().to_peer_id();
+    let (client_s, mut events_s) =
+        start::<Ping, Pong>(keypair_s, kad_config()).expect("failed to start S");
+    let addr_s = wait_for_listen_addr(&mut events_s).await;
+    tokio::spawn(async move { while events_s.recv().await.is_some() {} });
+
+    let keypair_a = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_a = keypair_a.public().to_peer_id();
+    let (client_a, mut events_a) =
+        start::<Ping, Pong>(keypair_a, kad_config_with_bootstrap(peer_s, addr_s))
+            .expect("failed to start A");
+    wait_for_identify(&mut events_a, "A").await;
+    tokio::spawn(async move { while events_a.recv().await.is_some() {} });
+
+    let key = RecordKey::new(&"published");
+    let result = timeout(
+        KAD_TIMEOUT,
+        client_a.publish_record(Record::new(key.clone(), b"content".to_vec())),
+    )
+    .await
+    .expect("publish_record timed out")
+    .expect("publish_record failed");
+    assert!(result.put.num_successes >= 1, "{:?}", result.put);
+    assert!(result.provide.num_successes >= 1, "{:?}", result.provide);
+
+    let record = timeout(KAD_TIMEOUT, client_s.get_record(key.clone()))
+        .await
+        .expect("get_record timed out")
+        .expect("S should store the record");
+    assert_eq!(record.record.value, b"content");
+    let providers = timeout(KAD_TIMEOUT, client_s.get_providers(key))
+        .await
+        .expect("get_providers timed out")
+        .expect("get_providers failed");
+    assert!(providers.providers().contains(&peer_a));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn publish_record_rolls_back_provide_on_failure() {
+    use swarm_p2p_core::Error;
+
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_id = keypair.public().to_peer_id();
+    let (client, _events) =
+        start::<Ping, Pong>(keypair, kad_config()).expect("failed to start node");
+    let key = RecordKey::new(&"unpublished");
+
+    // 没有可存储记录的远端节点，quorum 达不到
+    let err = timeout(
+        KAD_TIMEOUT,
+        client.publish_record(Record::new(key.clone(), b"content".to_vec())),
+    )
+    .await
+    .expect("publish_record timed out")
+    .unwrap_err();
+    assert!(
+        matches!(err, Error::KadQuorumFailed { stored: 0, .. }),
+        "{err}"
+    );
+
+    let providers = timeout(KAD_TIMEOUT, client.get_providers(key))
+        .await
+        .expect("get_providers timed out")
+        .expect("get_providers failed");
+    assert!(!providers.providers().contains(&peer_id));
+
+    // 之前通过 start_provide 提供的 key，publish_record 失败后仍保留宣告
+    let provided = RecordKey::new(&"provided");
+    let _ = timeout(KAD_TIMEOUT, client.start_provide(provided.clone()))
+        .await
+        .expect("start_provide timed out");
+    let err = timeout(
+        KAD_TIMEOUT,
+        client.publish_record(Record::new(provided.clone(), b"content".to_vec())),
+    )
+    .await
+    .expect("publish_record timed out")
+    .unwrap_err();
+    assert!(
+        matches!(err, Error::KadQuorumFailed { stored: 0, .. }),
+        "{err}"
+    );
+
+    let providers = timeout(KAD_TIMEOUT, client.get_providers(provided))
+        .await
+        .expect("get_providers timed out")
+        .expect("get_providers failed");
+    assert!(providers.providers().contains(&peer_id));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn find_peer_resolves_addresses() {
+    use swarm_p2p_core::Error;
+
+    let keypair_s = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_s = keypair_s.public().to_peer_id();
+    let (_client_s, mut events_s) =
+        start::<Ping, Pong>(keypair_s, kad_config()).expect("failed to start S");
+    let addr_s = wait_for_listen_addr(&mut events_s).await;
+    tokio::spawn(async move { while events_s.recv().await.is_some() {} });
+
+    let mut nodes = Vec::new();
+    for label in ["A", "B"] {
+        let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+        let config = kad_config_with_bootstrap(peer_s, addr_s.clone());
+        let (client, mut events) = start::<Ping, Pong>(keypair, config).expect("failed to start");
+        wait_for_identify(&mut events, label).await;
+        tokio::spawn(async move { while events.recv().await.is_some() {} });
+        nodes.push(client);
+    }
+    let (client_a, client_b) = (&nodes[0], &nodes[1]);
+    let peer_b = client_b.local_peer_id();
+
+    let addrs = timeout(KAD_TIMEOUT, client_a.find_peer(peer_b))
+        .await
+        .expect("find_peer timed out")
+        .expect("B should be found via S");
+    assert!(!addrs.is_empty());
+    // 地址已登记，可直接按 PeerId 拨号
+    client_a.dial(peer_b).await.expect("dial B failed");
+
+    let err = timeout(KAD_TIMEOUT, client_a.find_peer(PeerId::random()))
+        .await
+        .expect("find_peer timed out")
+        .unwrap_err();
+    assert!(matches!(err, Error::KadNotFound), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn find_and_connect_provider_dials_next_after_query_ends() {
+    use libp2p::kad::ProviderRecord;
+    use libp2p::kad::store::{MemoryStore, RecordStore};
+
+    // P1 不可达：接受 TCP 连接后不握手，稍后关闭，拨号失败时查询早已结束
+    let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let silent_addr: libp2p::Multiaddr =
+        format!("/ip4/127.0.0.1/tcp/{}", silent.local_addr().unwrap().port())
+            .parse()
+            .unwrap();
+    std::thread::spawn(move || {
+        for stream in silent.incoming() {
+            std::thread::sleep(Duration::from_millis(500));
+            drop(stream);
+        }
+    });
+
+    let keypair_p2 = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_p2 = keypair_p2.public().to_peer_id();
+    let (_client_p2, mut events_p2) =
+        start::<Ping, Pong>(keypair_p2, kad_config()).expect("failed to start P2");
+    let addr_p2 = wait_for_listen_addr(&mut events_p2).await;
+    tokio::spawn(async move { while events_p2.recv().await.is_some() {} });
+
+    // A 路由表为空，Provider 只来自本地存储，首个 FoundProviders 之后查询随即结束；
+    // 之后 Kad 不再提供 P2 的地址，只能用发现时记下的地址。
+    // 同一批 Provider 的顺序不定，先拨 P1 时还覆盖了失败后改拨下一个的路径
+    let key = RecordKey::new(&"find-after-query");
+    let peer_p1 = PeerId::random();
+    let records = vec![
+        ProviderRecord::new(key.clone(), peer_p1, vec![silent_addr]),
+        ProviderRecord::new(key.clone(), peer_p2, vec![addr_p2]),
+    ];
+    let config = kad_config().with_kad_store(move |local_id| {
+        let mut store = MemoryStore::new(local_id);
+        for record in records.clone() {
+            store.add_provider(record).map_err(std::io::Error::other)?;
+        }
+        Ok(store)
+    });
+    let keypair_a = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client_a, events_a) = start::<Ping, Pong>(keypair_a, config).expect("failed to start A");
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let connected = timeout(KAD_TIMEOUT, client_a.find_and_connect_provider(key))
+        .await
+        .expect("find_and_connect_provider timed out")
+        .expect("P2 should be reachable");
+    assert_eq!(connected, peer_p2);
+    assert!(client_a.is_connected(peer_p2).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn disabled_kad_rejects_operations() {
+    use swarm_p2p_core::Error;
+
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client, _events) =
+        start::<Ping, Pong>(keypair, kad_config().with_kad(false)).expect("failed to start node");
+    let key = RecordKey::new(&"kad-off");
+    let is_disabled = |err: Error| matches!(&err, Error::Config(msg) if msg == "kad disabled");
+
+    assert!(is_disabled(client.bootstrap().await.unwrap_err()));
+    assert!(is_disabled(
+        client
+            .put_record(Record::new(key.clone(), b"v".to_vec()))
+            .await
+            .unwrap_err()
+    ));
+    assert!(is_disabled(
+        client.get_record(key.clone()).await.unwrap_err()
+    ));
+    assert!(is_disabled(
+        client.start_provide(key.clone()).await.unwrap_err()
+    ));
+    assert!(is_disabled(client.get_providers(key).await.unwrap_err()));
+    assert!(is_disabled(client.kad_routing_table().await.unwrap_err()));
+    assert_eq!(client.status().await.unwrap().kad_mode, None);
+}
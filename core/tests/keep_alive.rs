@@ -0,0 +1,66 @@
+//! 集成测试：固定 peer 的连接保活
+//!
+//! 空闲超时设为 500ms，A 固定 B 后验证：A 自动拨号 B → 连接超过空闲超时仍保持 →
+//! B 主动断开后 A 立即重拨 → 取消固定后连接按空闲超时关闭。
+
+mod common;
+
+use std::time::Duration;
+
+use common::*;
+use swarm_p2p_core::libp2p::PeerId;
+use swarm_p2p_core::{EventReceiver, NodeEvent};
+use tokio::time::timeout;
+
+const IDLE_TIMEOUT: Duration = Duration::from_millis(500);
+
+async fn wait_for(events: &mut EventReceiver<Ping>, peer: PeerId, connected: bool) {
+    timeout(TIMEOUT, async {
+        while let Some(event) = events.recv().await {
+            match event {
+                NodeEvent::PeerConnected { peer_id, .. } if peer_id == peer && connected => return,
+                NodeEvent::PeerDisconnected { peer_id } if peer_id == peer && !connected => return,
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("connection state should change");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pinned_peer_stays_connected_and_is_redialed() {
+    let mut config_b = local_config();
+    config_b.idle_connection_timeout = IDLE_TIMEOUT;
+    // A 不监听：连接只能由 A 发起
+    let mut config_a = test_config().with_mdns(false).with_listen_addrs(vec![]);
+    config_a.idle_connection_timeout = IDLE_TIMEOUT;
+
+    let (client_a, mut events_a) = start_node(config_a);
+    let (client_b, mut events_b) = start_node(config_b);
+    let peer_a = client_a.local_peer_id();
+    let peer_b = client_b.local_peer_id();
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    // 固定尚未连接的 peer：下个保活周期即拨号
+    client_a.set_keep_alive(peer_b, true);
+    wait_for(&mut events_a, peer_b, true).await;
+
+    // 远超空闲超时仍保持连接
+    tokio::time::sleep(IDLE_TIMEOUT * 4).await;
+    assert!(client_a.is_connected(peer_b).await.unwrap());
+
+    // 对端断开后立即重拨
+    client_b.disconnect(peer_a).await.unwrap();
+    wait_for(&mut events_a, peer_b, false).await;
+    wait_for(&mut events_a, peer_b, true).await;
+
+    // 取消固定后按空闲超时关闭，且不再重拨
+    client_a.set_keep_alive(peer_b, false);
+    wait_for(&mut events_a, peer_b, false).await;
+    tokio::time::sleep(IDLE_TIMEOUT * 2).await;
+    assert!(!client_a.is_connected(peer_b).await.unwrap());
+}
@@ -0,0 +1,308 @@
+//! 集成测试：节点生命周期
+//!
+//! 单节点启动后，验证：
+//! 运行时 listen_on / remove_listener；get_addrs 展开未指定地址；add / remove_external_address；未探测时 nat_status 为 Unknown；
+//! `shutdown_graceful` 成功返回 → 最后一个事件为 `Shutdown` → 事件流结束；
+//! `subscribe_events` 的多个订阅者同样收到 `Shutdown` 并结束。
+//! `filter_peer` 返回的接收器只收到对应 peer 的事件，节点关闭后随之结束。
+//! `set_agent_version` 后新建立的连接上报新的 agent_version，protocol_version 不变。
+//! `diagnostics_interval` 为 0 时视为关闭，否则首次 `Diagnostics` 在一个周期后发出。
+
+mod common;
+
+use std::time::Duration;
+
+use common::*;
+use swarm_p2p_core::event::NatStatus;
+use swarm_p2p_core::{EventOverflow, NodeEvent, start};
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn graceful_shutdown_emits_final_event() {
+    let config = test_config().with_mdns(false);
+    let (client, mut events) = start_node(config);
+    let mut sub_a = client.subscribe_events();
+    let mut sub_b = client.subscribe_events();
+    let late_client = client.clone();
+
+    // 运行时新增监听，随后关闭
+    let listener_id = timeout(
+        TIMEOUT,
+        client.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()),
+    )
+    .await
+    .expect("listen_on timed out")
+    .expect("listen_on failed");
+    assert!(client.remove_listener(listener_id).await.unwrap());
+    assert!(!client.remove_listener(listener_id).await.unwrap());
+
+    // 未指定的监听地址已展开为具体网卡地址；过滤回环后不含 127.0.0.1
+    let addrs = client.get_addrs().await.unwrap();
+    assert!(
+        addrs
+            .iter()
+            .all(|a| !a.to_string().starts_with("/ip4/0.0.0.0")),
+        "{addrs:?}"
+    );
+    let shareable = client.get_addrs_with_loopback(false).await.unwrap();
+    assert!(
+        shareable
+            .iter()
+            .all(|a| !a.to_string().starts_with("/ip4/127.")),
+        "{shareable:?}"
+    );
+
+    // 手动登记的外部地址出现在 get_addrs 中（排在最前），移除后消失
+    let external: swarm_p2p_core::libp2p::Multiaddr = "/ip4/203.0.113.7/tcp/4001".parse().unwrap();
+    client.add_external_address(external.clone()).await.unwrap();
+    assert_eq!(client.get_addrs().await.unwrap().first(), Some(&external));
+    client
+        .remove_external_address(external.clone())
+        .await
+        .unwrap();
+    assert!(!client.get_addrs().await.unwrap().contains(&external));
+
+    // 未开启 AutoNAT，没有任何探测结果
+    assert_eq!(client.nat_status().await.unwrap(), NatStatus::Unknown);
+
+    timeout(TIMEOUT, client.shutdown_graceful(Duration::from_secs(5)))
+        .await
+        .expect("shutdown_graceful timed out")
+        .expect("shutdown_graceful failed");
+
+    let rest = timeout(TIMEOUT, async {
+        let mut rest = Vec::new();
+        while let Some(event) = events.recv().await {
+            rest.push(event);
+        }
+        rest
+    })
+    .await
+    .expect("event stream did not end after shutdown");
+
+    assert!(
+        matches!(rest.last(), Some(NodeEvent::Shutdown)),
+        "last event should be Shutdown, got: {:?}",
+        rest
+    );
+
+    // 每个订阅者独立收到完整的事件序列
+    for sub in [&mut sub_a, &mut sub_b] {
+        let seen = timeout(TIMEOUT, async {
+            let mut seen = Vec::new();
+            while let Some(event) = sub.recv().await {
+                seen.push(event);
+            }
+            seen
+        })
+        .await
+        .expect("subscriber did not end after shutdown");
+        assert!(
+            seen.iter()
+                .any(|e| matches!(e, NodeEvent::Listening { .. })),
+            "subscriber should see Listening, got: {:?}",
+            seen
+        );
+        assert!(matches!(seen.last(), Some(NodeEvent::Shutdown)));
+        assert_eq!(sub.missed(), 0);
+    }
+
+    // 关闭后再订阅，立即结束
+    assert!(late_client.subscribe_events().recv().await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn lossy_event_delivery_does_not_block() {
+    for overflow in [EventOverflow::DropNewest, EventOverflow::DropOldest] {
+        let config = test_config()
+            .with_mdns(false)
+            .with_listen_addrs(vec![])
+            .with_event_channel_size(2)
+            .with_event_overflow(overflow);
+        let (client, mut events) = start_node(config);
+
+        // 不消费事件，每次 listen_on 都会产生 Listening 事件；阻塞模式下第 3 次起会卡住
+        for _ in 0..6 {
+            timeout(
+                TIMEOUT,
+                client.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap()),
+            )
+            .await
+            .expect("listen_on should not block on a full event channel")
+            .expect("listen_on failed");
+        }
+        assert!(client.dropped_event_count() > 0, "{:?}", overflow);
+
+        // 通道容量 2，DropOldest 额外暂存 2 个
+        let kept = match overflow {
+            EventOverflow::DropOldest => 4,
+            _ => 2,
+        };
+        let mut received = 0;
+        while let Ok(Some(_)) = timeout(Duration::from_millis(200), events.recv()).await {
+            received += 1;
+        }
+        // mDNS 等其他事件可能在排空期间补进来，也可能一并被丢弃
+        assert!(received >= kept, "{:?}: received {}", overflow, received);
+        assert!(
+            client.dropped_event_count() >= 6 - kept as u64,
+            "{:?}",
+            overflow
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn filter_peer_receives_only_that_peer() {
+    let (client_a, events_a) = start_node(local_config());
+
+    let mut peers = Vec::new();
+    for label in ["B", "C"] {
+        let (client, mut events) = start_node(local_config());
+        let addr = wait_for_listen_addr(&mut events).await;
+        tokio::spawn(event_printer(events, label, None));
+        peers.push((client, addr));
+    }
+    let peer_b = peers[0].0.local_peer_id();
+    let peer_c = peers[1].0.local_peer_id();
+
+    let mut filtered_b = events_a.filter_peer(peer_b);
+    let mut filtered_c = events_a.filter_peer(peer_c);
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    for (_, addr) in &peers {
+        timeout(TIMEOUT, client_a.dial_addr(addr.clone()))
+            .await
+            .expect("dial_addr timed out")
+            .expect("dial_addr failed");
+    }
+
+    // 各自收到对应 peer 的连接与 identify，且没有其他 peer 或全局事件
+    for (filtered, peer) in [(&mut filtered_b, peer_b), (&mut filtered_c, peer_c)] {
+        let seen = timeout(TIMEOUT, async {
+            let mut seen = Vec::new();
+            loop {
+                let event = filtered.recv().await.expect("filtered stream ended");
+                let identified = matches!(event, NodeEvent::IdentifyReceived { .. });
+                seen.push(event);
+                if identified {
+                    return seen;
+                }
+            }
+        })
+        .await
+        .expect("filtered receiver should see IdentifyReceived");
+        assert!(
+            seen.iter()
+                .any(|e| matches!(e, NodeEvent::PeerConnected { peer_id, .. } if *peer_id == peer)),
+            "{seen:?}"
+        );
+        assert!(seen.iter().all(|e| e.peer_id() == Some(peer)), "{seen:?}");
+    }
+
+    timeout(TIMEOUT, client_a.shutdown_graceful(Duration::from_secs(5)))
+        .await
+        .expect("shutdown_graceful timed out")
+        .expect("shutdown_graceful failed");
+
+    // 事件循环退出后过滤接收器结束，之后新建的也立即结束
+    timeout(TIMEOUT, async {
+        while filtered_b.recv().await.is_some() {}
+    })
+    .await
+    .expect("filtered stream did not end after shutdown");
+    assert!(filtered_b.filter_peer(peer_c).recv().await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn set_agent_version_applies_to_new_connections() {
+    let (client_a, mut events_a) = start_node(local_config());
+    let addr_a = wait_for_listen_addr(&mut events_a).await;
+
+    timeout(TIMEOUT, client_a.set_agent_version("plugin/2.0.0"))
+        .await
+        .expect("set_agent_version timed out")
+        .expect("set_agent_version failed");
+
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    timeout(TIMEOUT, client_b.dial_addr(addr_a))
+        .await
+        .expect("dial_addr timed out")
+        .expect("dial_addr failed");
+
+    // B 看到 A 的新 agent_version，A 重建后的 identify 同样能识别 B
+    let (agent_version, protocol_version) = timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::IdentifyReceived {
+                peer_id,
+                agent_version,
+                protocol_version,
+            }) = events_b.recv().await
+                && peer_id == client_a.local_peer_id()
+            {
+                return (agent_version, protocol_version);
+            }
+        }
+    })
+    .await
+    .expect("B should receive A's identify");
+    assert_eq!(agent_version, "plugin/2.0.0");
+    assert_eq!(protocol_version, "/test/1.0.0");
+
+    timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::IdentifyReceived { peer_id, .. }) = events_a.recv().await
+                && peer_id == peer_b
+            {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("A should receive B's identify");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn diagnostics_start_after_one_interval() {
+    // 0 视为关闭，不会在启动时 panic
+    let config = test_config()
+        .with_mdns(false)
+        .with_diagnostics_interval(Duration::ZERO);
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client, mut events) = start::<Ping, Pong>(keypair, config).unwrap();
+    let diagnostics = timeout(Duration::from_millis(500), async {
+        loop {
+            if let Some(NodeEvent::Diagnostics { .. }) = events.recv().await {
+                return;
+            }
+        }
+    })
+    .await;
+    assert!(
+        diagnostics.is_err(),
+        "zero interval should disable diagnostics"
+    );
+    client.shutdown();
+
+    let interval = Duration::from_millis(500);
+    let config = test_config()
+        .with_mdns(false)
+        .with_diagnostics_interval(interval);
+    let keypair = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let started = tokio::time::Instant::now();
+    let (_client, mut events) = start::<Ping, Pong>(keypair, config).unwrap();
+    timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::Diagnostics { .. }) = events.recv().await {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("diagnostics should be emitted");
+    assert!(
+        started.elapsed() >= interval,
+        "first diagnostics should wait one interval"
+    );
+}
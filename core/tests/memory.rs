@@ -0,0 +1,74 @@
+//! 集成测试：内存传输
+//!
+//! A、B 均使用 `TransportConfig::Memory` 监听 `/memory/0`，关闭 mDNS，验证：
+//! 按内存地址拨号 → 请求-响应 → Kad 记录写入后可被对端读到。
+
+mod common;
+
+use common::*;
+use libp2p::kad::{Record, RecordKey};
+use swarm_p2p_core::{NodeConfig, NodeEvent, TransportConfig, start};
+use tokio::time::timeout;
+
+fn memory_config() -> NodeConfig {
+    test_config()
+        .with_mdns(false)
+        .with_transport(TransportConfig::Memory)
+        .with_listen_addrs(vec!["/memory/0".parse().unwrap()])
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memory_transport_connects_in_process() {
+    let keypair_a = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let keypair_b = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let (client_a, mut events_a) = start::<Ping, Pong>(keypair_a, memory_config()).unwrap();
+    let (client_b, mut events_b) = start::<Ping, Pong>(keypair_b, memory_config()).unwrap();
+    let peer_a = client_a.local_peer_id();
+
+    wait_for_listen_addr(&mut events_a).await;
+    tokio::spawn(event_printer(events_a, "A", None));
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    assert!(addr_b.to_string().starts_with("/memory/"), "{addr_b}");
+
+    let responder_b = client_b.clone();
+    let responder = tokio::spawn(async move {
+        while let Some(event) = events_b.recv().await {
+            if let NodeEvent::InboundRequest {
+                peer_id,
+                pending_id,
+                request,
+            } = event
+            {
+                assert_eq!(peer_id, peer_a);
+                responder_b
+                    .send_response(pending_id, Pong { msg: request.msg })
+                    .await
+                    .unwrap();
+            }
+        }
+    });
+
+    let peer_b = client_a.dial_addr(addr_b).await.expect("dial failed");
+    assert_eq!(peer_b, client_b.local_peer_id());
+    let pong = client_a
+        .send_request(peer_b, Ping { msg: "hi".into() })
+        .await
+        .expect("request failed");
+    assert_eq!(pong.msg, "hi");
+
+    let key = RecordKey::new(&"memory");
+    timeout(
+        TIMEOUT,
+        client_a.put_record(Record::new(key.clone(), b"v".to_vec())),
+    )
+    .await
+    .expect("put_record timed out")
+    .expect("put_record failed");
+    let found = timeout(TIMEOUT, client_b.get_record(key))
+        .await
+        .expect("get_record timed out")
+        .expect("get_record failed");
+    assert_eq!(found.record.value, b"v");
+
+    responder.abort();
+}
@@ -0,0 +1,54 @@
+//! 集成测试：Prometheus 指标（需 `--features metrics`）
+//!
+//! A 向 B 发送一次请求，验证 A 的注册表中：
+//! 连接建立计数 → 出站请求成功计数 → 带宽指标均已导出。
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use common::*;
+use swarm_p2p_core::NodeEvent;
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn registry_records_connections_and_requests() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    let responder = client_b.clone();
+    tokio::spawn(async move {
+        while let Some(event) = events_b.recv().await {
+            if let NodeEvent::InboundRequest { pending_id, .. } = event {
+                let _ = responder
+                    .send_response(pending_id, Pong { msg: "ok".into() })
+                    .await;
+            }
+        }
+    });
+
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    timeout(
+        TIMEOUT,
+        client_a.send_request(peer_b, Ping { msg: "hi".into() }),
+    )
+    .await
+    .expect("request timed out")
+    .expect("request failed");
+
+    let mut text = String::new();
+    prometheus_client::encoding::text::encode(&mut text, &client_a.metrics_registry()).unwrap();
+    assert!(
+        text.contains("libp2p_swarm_connections_established_total"),
+        "{text}"
+    );
+    assert!(
+        text.contains(
+            r#"swarm_p2p_req_resp_requests_total{direction="Outbound",outcome="Success"} 1"#
+        ),
+        "{text}"
+    );
+    assert!(text.contains("libp2p_bandwidth_bytes_total"), "{text}");
+}
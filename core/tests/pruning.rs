@@ -0,0 +1,52 @@
+//! 集成测试：连接裁剪
+//!
+//! A 设置 `target_connection_count = 1` 后同时连接 B 和 C，验证：
+//! 测得延迟后 A 主动断开其中一个 peer → 收到 `ConnectionPruned` → 只剩一个连接。
+
+mod common;
+
+use common::*;
+use swarm_p2p_core::NodeEvent;
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prunes_connections_over_target() {
+    let (client_a, mut events_a, _) =
+        start_listening(local_config().with_target_connection_count(1)).await;
+    let (client_b, events_b, addr_b) = start_listening(local_config()).await;
+    let (client_c, events_c, addr_c) = start_listening(local_config()).await;
+    tokio::spawn(event_printer(events_b, "B", None));
+    tokio::spawn(event_printer(events_c, "C", None));
+
+    let peer_b = client_b.local_peer_id();
+    let peer_c = client_c.local_peer_id();
+    client_a
+        .add_peers_addrs(vec![(peer_b, vec![addr_b]), (peer_c, vec![addr_c])])
+        .await
+        .unwrap();
+    let results = client_a.dial_many(vec![peer_b, peer_c]).await.unwrap();
+    assert!(results.iter().all(|(_, r)| r.is_ok()), "{:?}", results);
+
+    let pruned = timeout(TIMEOUT, async {
+        loop {
+            let event = events_a.recv().await.expect("event stream closed");
+            eprintln!("[A] {:?}", event);
+            if let NodeEvent::ConnectionPruned { peer_id } = event {
+                return peer_id;
+            }
+        }
+    })
+    .await
+    .expect("A should prune a connection");
+    assert!(pruned == peer_b || pruned == peer_c);
+
+    let kept = if pruned == peer_b { peer_c } else { peer_b };
+    timeout(TIMEOUT, async {
+        while client_a.is_connected(pruned).await.unwrap() {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("pruned peer should be disconnected");
+    assert!(client_a.is_connected(kept).await.unwrap());
+}
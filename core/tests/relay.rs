@@ -0,0 +1,220 @@
+//! 集成测试：显式 relay 预约
+//!
+//! B 是普通节点（不提供中继服务），A 经 B 申请预约，验证：
+//! `reserve_relay` 返回 `Error::Relay` → A 收到 `RelayReservationFailed`；
+//! 失败的预约不会残留，`cancel_relay` 返回 false；
+//! `upgrade_connection` 对未连接的 peer 报错，对已直连的 peer 直接返回。
+//! 另验证开启 `enable_relay_server` 的普通节点可作为中继：接受预约、转发电路，
+//! 预约方断开后上报预约结束；`relay_reservations` 列出已接受的预约及中继给出的电路限制，
+//! `cancel_relay` 后不再列出。
+//! `dial_via_relay` 按中继地址与目标 PeerId 拼出电路地址，经中继连上目标。
+
+mod common;
+
+use std::time::Duration;
+
+use common::*;
+use swarm_p2p_core::{Error, NodeEvent};
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reservation_denied_by_non_relay_peer() {
+    let config = || local_config().with_auto_relay_reservations(false);
+    let (client_a, mut events_a) = start_node(config());
+    let (client_b, mut events_b) = start_node(config());
+    let peer_b = client_b.local_peer_id();
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    let result = timeout(TIMEOUT, client_a.reserve_relay(peer_b, addr_b))
+        .await
+        .expect("reserve_relay timed out");
+    assert!(
+        matches!(result, Err(Error::Relay(_))),
+        "B does not serve relay: {result:?}"
+    );
+
+    let error = timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::RelayReservationFailed {
+                relay_peer_id,
+                error,
+            }) = events_a.recv().await
+            {
+                assert_eq!(relay_peer_id, peer_b);
+                return error;
+            }
+        }
+    })
+    .await
+    .expect("should receive RelayReservationFailed");
+    assert!(!error.is_empty());
+
+    assert!(!client_a.cancel_relay(peer_b).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn upgrade_connection_requires_relayed_peer() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    tokio::spawn(async move { while events_b.recv().await.is_some() {} });
+
+    // 未连接
+    let result = client_a.upgrade_connection(peer_b).await;
+    assert!(
+        matches!(result, Err(Error::PeerDisconnected(peer)) if peer == peer_b),
+        "{result:?}"
+    );
+
+    // 已是直连，无需打洞
+    client_a.dial_addr(addr_b).await.unwrap();
+    timeout(TIMEOUT, client_a.upgrade_connection(peer_b))
+        .await
+        .expect("upgrade_connection timed out")
+        .expect("direct connection needs no upgrade");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn client_node_serves_relay() {
+    let config = || local_config().with_auto_relay_reservations(false);
+    // A 为中继，B 经 A 预约，C 经电路连接 B
+    let (client_a, mut events_a) = start_node(config().with_relay_server(true));
+    let (client_b, events_b) = start_node(config());
+    let (client_c, events_c) = start_node(config());
+    let (peer_a, peer_b) = (client_a.local_peer_id(), client_b.local_peer_id());
+    tokio::spawn(event_printer(events_b, "B", None));
+    tokio::spawn(event_printer(events_c, "C", None));
+
+    let addr_a = wait_for_listen_addr(&mut events_a).await;
+    // 预约响应需携带中继的外部地址
+    client_a.add_external_address(addr_a.clone()).await.unwrap();
+
+    timeout(TIMEOUT, client_b.reserve_relay(peer_a, addr_a.clone()))
+        .await
+        .expect("reserve_relay timed out")
+        .expect("A should accept the reservation");
+    timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::RelayServerReservationAccepted { peer_id, renewal }) =
+                events_a.recv().await
+            {
+                assert_eq!(peer_id, peer_b);
+                assert!(!renewal);
+                return;
+            }
+        }
+    })
+    .await
+    .expect("A should report the accepted reservation");
+
+    let circuit = addr_a
+        .with_p2p(peer_a)
+        .unwrap()
+        .with(swarm_p2p_core::libp2p::multiaddr::Protocol::P2pCircuit)
+        .with_p2p(peer_b)
+        .unwrap();
+    let resolved = timeout(TIMEOUT, client_c.dial_addr(circuit))
+        .await
+        .expect("dial via relay timed out")
+        .expect("C should reach B via A");
+    assert_eq!(resolved, peer_b);
+
+    client_b
+        .shutdown_graceful(Duration::from_secs(5))
+        .await
+        .unwrap();
+    timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::RelayServerReservationClosed { peer_id }) = events_a.recv().await
+            {
+                assert_eq!(peer_id, peer_b);
+                return;
+            }
+        }
+    })
+    .await
+    .expect("A should report the closed reservation");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn relay_reservations_lists_accepted_reservations() {
+    let config = || local_config().with_auto_relay_reservations(false);
+    let relay_config = config()
+        .with_relay_server(true)
+        .with_relay_server_max_circuit_duration(Duration::from_secs(60))
+        .with_relay_server_max_circuit_bytes(4096);
+    let (client_a, mut events_a) = start_node(relay_config);
+    let (client_b, events_b) = start_node(config());
+    let peer_a = client_a.local_peer_id();
+    tokio::spawn(event_printer(events_b, "B", None));
+
+    let addr_a = wait_for_listen_addr(&mut events_a).await;
+    tokio::spawn(async move { while events_a.recv().await.is_some() {} });
+    client_a.add_external_address(addr_a.clone()).await.unwrap();
+
+    assert!(client_b.relay_reservations().await.unwrap().is_empty());
+    timeout(TIMEOUT, client_b.reserve_relay(peer_a, addr_a.clone()))
+        .await
+        .expect("reserve_relay timed out")
+        .expect("A should accept the reservation");
+
+    let reservations = client_b.relay_reservations().await.unwrap();
+    assert_eq!(reservations.len(), 1);
+    let info = &reservations[0];
+    assert_eq!(info.relay_peer_id, peer_a);
+    let circuit = addr_a
+        .with_p2p(peer_a)
+        .unwrap()
+        .with(swarm_p2p_core::libp2p::multiaddr::Protocol::P2pCircuit);
+    assert_eq!(info.circuit_addrs, vec![circuit]);
+    assert!(info.accepted_at.is_some());
+    assert_eq!(info.max_circuit_duration, Some(Duration::from_secs(60)));
+    assert_eq!(info.max_circuit_bytes, Some(4096));
+
+    assert!(client_b.cancel_relay(peer_a).await.unwrap());
+    assert!(client_b.relay_reservations().await.unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dial_via_relay_connects_through_circuit() {
+    let config = || local_config().with_auto_relay_reservations(false);
+    // A 为中继，B 经 A 预约，C 只知道 A 的地址和 B 的 PeerId
+    let (client_a, mut events_a) = start_node(config().with_relay_server(true));
+    let (client_b, events_b) = start_node(config());
+    let (client_c, events_c) = start_node(config());
+    let (peer_a, peer_b) = (client_a.local_peer_id(), client_b.local_peer_id());
+    tokio::spawn(event_printer(events_b, "B", None));
+    tokio::spawn(event_printer(events_c, "C", None));
+
+    let addr_a = wait_for_listen_addr(&mut events_a).await;
+    tokio::spawn(async move { while events_a.recv().await.is_some() {} });
+    client_a.add_external_address(addr_a.clone()).await.unwrap();
+
+    timeout(TIMEOUT, client_b.reserve_relay(peer_a, addr_a.clone()))
+        .await
+        .expect("reserve_relay timed out")
+        .expect("A should accept the reservation");
+
+    // 中继地址缺少 /p2p/<relay> 时无法拼出电路地址
+    let err = client_c
+        .dial_via_relay(addr_a.clone(), peer_b)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Dial(_)), "{err}");
+
+    let relay_addr = addr_a.with_p2p(peer_a).unwrap();
+    timeout(TIMEOUT, client_c.dial_via_relay(relay_addr.clone(), peer_b))
+        .await
+        .expect("dial_via_relay timed out")
+        .expect("C should reach B via A");
+    let info = client_c.connection_info(peer_b).await.unwrap();
+    assert!(info.iter().any(|c| c.relayed), "{info:?}");
+
+    // 已连接时直接返回
+    client_c.dial_via_relay(relay_addr, peer_b).await.unwrap();
+}
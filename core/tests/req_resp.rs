@@ -1,11 +1,15 @@
 //! 集成测试：双节点 mDNS 发现 + Request-Response
 //!
 //! 在同一进程内启动两个 libp2p 节点（仅 TCP + mDNS），
-//! 并行监听双方事件，验证：发现 → 连接 → Identify → 请求-响应。
+//! 并行监听双方事件，验证：发现 → 连接 → Identify → 请求-响应；
+//! 以及超时覆盖、流式响应、send_response_many 批量回复、notify 不等待响应、
+//! inbound_requests 只产出请求、不影响主事件流且消费过慢时不跳过请求、调整 Yamux 窗口后大请求仍能完成、
+//! 单 peer 并发上限下请求与通知按顺序排队发出、第三方 peer 无法拉取他人的流等。
 
 mod common;
 
 use common::*;
+use futures::StreamExt;
 use swarm_p2p_core::{NetClient, NodeEvent, start};
 use tokio::sync::mpsc;
 use tokio::time::timeout;
@@ -24,9 +28,10 @@ async fn dual_node_full_flow() {
 
     // 用 channel 从 B 的事件监听 task 传回 inbound request 信息
     let (inbound_tx, mut inbound_rx) = mpsc::channel::<(u64, Ping)>(1);
+    let (sent_tx, mut sent_rx) = mpsc::channel::<u64>(1);
 
     // ===== B 事件监听（后台 task，打印所有事件，处理 inbound request） =====
-    let b_task = tokio::spawn(node_b_listener(events_b, client_b, inbound_tx));
+    let b_task = tokio::spawn(node_b_listener(events_b, client_b, inbound_tx, sent_tx));
 
     // ===== A 事件监听：等待发现 + 连接 + Identify =====
     let (a_discovered, peer_b_id, a_identified) = wait_for_connection(events_a).await;
@@ -59,6 +64,13 @@ async fn dual_node_full_flow() {
     assert_eq!(request.msg, "hello");
     eprintln!("[B] handled inbound request pending_id={pending_id}");
 
+    // 回复发出后 B 收到 ResponseSent，pending_id 与 InboundRequest 一致
+    let sent_id = timeout(TIMEOUT, sent_rx.recv())
+        .await
+        .expect("B should observe ResponseSent")
+        .expect("B listener stopped");
+    assert_eq!(sent_id, pending_id);
+
     b_task.abort(); // 测试完成，停止 B 的事件监听
 }
 
@@ -67,6 +79,7 @@ async fn node_b_listener(
     mut events: swarm_p2p_core::EventReceiver<Ping>,
     client: NetClient<Ping, Pong>,
     inbound_tx: mpsc::Sender<(u64, Ping)>,
+    sent_tx: mpsc::Sender<u64>,
 ) {
     loop {
         let Some(event) = events.recv().await else {
@@ -74,6 +87,10 @@ async fn node_b_listener(
         };
         eprintln!("[B] {:?}", event);
 
+        if let NodeEvent::ResponseSent { pending_id, .. } = event {
+            let _ = sent_tx.send(pending_id).await;
+            continue;
+        }
         if let NodeEvent::InboundRequest {
             pending_id,
             request,
@@ -95,3 +112,695 @@ async fn node_b_listener(
         }
     }
 }
+
+/// 流式请求：B 逐块回复 3 个分块后结束，A 按顺序收到全部分块
+#[tokio::test(flavor = "multi_thread")]
+async fn dual_node_streaming_response() {
+    let keypair_a = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let keypair_b = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    // 同进程内其他测试的节点也会被 mDNS 发现，因此按 PeerId 指定 B
+    let peer_b_id = keypair_b.public().to_peer_id();
+
+    let (client_a, events_a) =
+        start::<Ping, Pong>(keypair_a, test_config()).expect("failed to start node A");
+    let (client_b, mut events_b) =
+        start::<Ping, Pong>(keypair_b, test_config()).expect("failed to start node B");
+
+    // B 侧：收到流式请求后逐块回复
+    let b_task = tokio::spawn(async move {
+        while let Some(event) = events_b.recv().await {
+            if let NodeEvent::InboundRequestStream {
+                pending_id,
+                request,
+                ..
+            } = event
+            {
+                assert_eq!(request.msg, "stream");
+                for i in 0..3 {
+                    client_b
+                        .send_stream_chunk(pending_id, Pong { msg: i.to_string() })
+                        .await
+                        .expect("send_stream_chunk should succeed");
+                }
+                client_b
+                    .end_stream(pending_id)
+                    .await
+                    .expect("end_stream should succeed");
+            }
+        }
+    });
+
+    wait_for_connection(events_a).await;
+
+    let chunks: Vec<String> = timeout(TIMEOUT, async {
+        let stream = client_a.send_request_streaming(
+            peer_b_id,
+            Ping {
+                msg: "stream".into(),
+            },
+        );
+        futures::pin_mut!(stream);
+        let mut chunks = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            chunks.push(chunk.expect("stream chunk failed").msg);
+        }
+        chunks
+    })
+    .await
+    .expect("streaming timed out");
+
+    assert_eq!(chunks, vec!["0", "1", "2"]);
+
+    b_task.abort();
+}
+
+/// 流式请求：第三方 peer 猜中 stream_id 也无法拉取别人的流，原请求方不受影响
+#[tokio::test(flavor = "multi_thread")]
+async fn stream_pull_from_other_peer_is_rejected() {
+    use swarm_p2p_core::command::{StreamFrame, StreamRequest};
+    use swarm_p2p_core::libp2p::request_response::{self, ProtocolSupport, cbor};
+    use swarm_p2p_core::libp2p::swarm::SwarmEvent;
+    use swarm_p2p_core::libp2p::{StreamProtocol, SwarmBuilder, noise, tcp, yamux};
+
+    let keypair_b = swarm_p2p_core::libp2p::identity::Keypair::generate_ed25519();
+    let peer_b = keypair_b.public().to_peer_id();
+
+    let (client_a, _events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start::<Ping, Pong>(keypair_b, local_config()).unwrap();
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a
+        .add_peer_addrs(peer_b, vec![addr_b.clone()])
+        .await
+        .unwrap();
+
+    // B 侧：先回复一个分块，第二个分块等 A 拉取，第三方的拉取结束后再结束流
+    let (stream_tx, mut stream_rx) = mpsc::channel(1);
+    let (end_tx, mut end_rx) = mpsc::channel::<()>(1);
+    let b_task = tokio::spawn(async move {
+        while let Some(event) = events_b.recv().await {
+            if let NodeEvent::InboundRequestStream { pending_id, .. } = event {
+                client_b
+                    .send_stream_chunk(pending_id, Pong { msg: "one".into() })
+                    .await
+                    .unwrap();
+                stream_tx.send(pending_id).await.unwrap();
+                client_b
+                    .send_stream_chunk(pending_id, Pong { msg: "two".into() })
+                    .await
+                    .unwrap();
+                end_rx.recv().await;
+                client_b.end_stream(pending_id).await.unwrap();
+            }
+        }
+    });
+
+    let stream = client_a.send_request_streaming(
+        peer_b,
+        Ping {
+            msg: "stream".into(),
+        },
+    );
+    futures::pin_mut!(stream);
+    let first = timeout(TIMEOUT, stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.msg, "one");
+    let stream_id = stream_rx.recv().await.unwrap();
+
+    // C：只实现流式协议的裸 swarm，直接用 A 的 stream_id 发 Next
+    let mut swarm_c = SwarmBuilder::with_new_identity()
+        .with_tokio()
+        .with_tcp(
+            tcp::Config::default(),
+            noise::Config::new,
+            yamux::Config::default,
+        )
+        .unwrap()
+        .with_behaviour(|_| {
+            cbor::Behaviour::<StreamRequest<Ping>, StreamFrame<Pong>>::new(
+                [(
+                    StreamProtocol::new("/swarm-p2p/req/1.0.0/stream"),
+                    ProtocolSupport::Outbound,
+                )],
+                request_response::Config::default(),
+            )
+        })
+        .unwrap()
+        .build();
+    swarm_c.add_peer_address(peer_b, addr_b);
+    swarm_c
+        .behaviour_mut()
+        .send_request(&peer_b, StreamRequest::Next { stream_id });
+
+    let frame = timeout(TIMEOUT, async {
+        loop {
+            match swarm_c.select_next_some().await {
+                SwarmEvent::Behaviour(request_response::Event::Message {
+                    message: request_response::Message::Response { response, .. },
+                    ..
+                }) => return response,
+                SwarmEvent::Behaviour(request_response::Event::OutboundFailure {
+                    error, ..
+                }) => panic!("pull failed: {error}"),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("third peer should get a reply");
+    assert!(
+        matches!(frame, StreamFrame::Error(_)),
+        "third peer must not receive A's chunk: {frame:?}"
+    );
+
+    // A 仍能拉到剩余分块并正常结束
+    end_tx.send(()).await.unwrap();
+    let mut rest = Vec::new();
+    timeout(TIMEOUT, async {
+        while let Some(chunk) = stream.next().await {
+            rest.push(chunk.unwrap().msg);
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(rest, vec!["two"]);
+
+    b_task.abort();
+}
+
+/// 对端不回复时，`send_request_with_timeout` 按单次超时返回 `RequestTimeout`
+#[tokio::test(flavor = "multi_thread")]
+async fn request_timeout_override() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    // B 收到请求但从不回复
+    tokio::spawn(event_printer(events_b, "B", None));
+
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    let started = std::time::Instant::now();
+    let result = timeout(
+        TIMEOUT,
+        client_a.send_request_with_timeout(
+            peer_b,
+            Ping { msg: "slow".into() },
+            std::time::Duration::from_millis(500),
+        ),
+    )
+    .await
+    .expect("send_request_with_timeout should not hang");
+
+    assert!(
+        matches!(result, Err(swarm_p2p_core::Error::RequestTimeout)),
+        "expected RequestTimeout, got {:?}",
+        result
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inbound_failure_when_requester_disconnects() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_a = client_a.local_peer_id();
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+    let requester = client_a.clone();
+    tokio::spawn(async move {
+        let _ = requester
+            .send_request(
+                peer_b,
+                Ping {
+                    msg: "hang up".into(),
+                },
+            )
+            .await;
+    });
+
+    // B 收到请求后不回复，A 随即断开
+    let pending_id = timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::InboundRequest { pending_id, .. }) = events_b.recv().await {
+                return pending_id;
+            }
+        }
+    })
+    .await
+    .expect("B should receive the request");
+    client_a.disconnect(peer_b).await.unwrap();
+
+    let (peer_id, failed_id) = timeout(TIMEOUT, async {
+        loop {
+            let event = events_b.recv().await.expect("event stream closed");
+            eprintln!("[B] {:?}", event);
+            if let NodeEvent::InboundRequestFailed {
+                peer_id,
+                pending_id,
+                ..
+            } = event
+            {
+                return (peer_id, pending_id);
+            }
+        }
+    })
+    .await
+    .expect("B should observe InboundRequestFailed");
+    assert_eq!(peer_id, peer_a);
+    assert_eq!(failed_id, pending_id);
+
+    // channel 已被清理，回复直接失败
+    assert!(
+        client_b
+            .send_response(pending_id, Pong { msg: "late".into() })
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn oversized_messages_fail_with_limit_reason() {
+    let config = || {
+        local_config()
+            .with_max_request_size(256)
+            .with_max_response_size(256)
+    };
+    let (client_a, events_a) = start_node(config());
+    let (client_b, mut events_b) = start_node(config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+
+    // 请求超过上限：本地直接拒绝发送
+    let err = timeout(
+        TIMEOUT,
+        client_a.send_request(
+            peer_b,
+            Ping {
+                msg: "x".repeat(1024),
+            },
+        ),
+    )
+    .await
+    .expect("oversized request timed out")
+    .unwrap_err();
+    assert!(err.to_string().contains("max_request_size"), "{err}");
+
+    // 回复超过上限：B 侧以 InboundRequestFailed 结束，A 侧请求失败
+    let requester = client_a.clone();
+    let request = tokio::spawn(async move {
+        requester
+            .send_request(peer_b, Ping { msg: "big".into() })
+            .await
+    });
+    let pending_id = timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::InboundRequest { pending_id, .. }) = events_b.recv().await {
+                return pending_id;
+            }
+        }
+    })
+    .await
+    .expect("B should receive the request");
+    client_b
+        .send_response(
+            pending_id,
+            Pong {
+                msg: "y".repeat(1024),
+            },
+        )
+        .await
+        .unwrap();
+
+    let error = timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::InboundRequestFailed {
+                pending_id: failed_id,
+                error,
+                ..
+            }) = events_b.recv().await
+            {
+                assert_eq!(failed_id, pending_id);
+                return error;
+            }
+        }
+    })
+    .await
+    .expect("B should observe InboundRequestFailed");
+    assert!(error.contains("max_response_size"), "{error}");
+    assert!(request.await.unwrap().is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn send_response_many_fans_out() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+
+    let requests = futures::future::join(
+        client_a.send_request(peer_b, Ping { msg: "one".into() }),
+        client_a.send_request(peer_b, Ping { msg: "two".into() }),
+    );
+    let responder = async {
+        let mut pending_ids = Vec::new();
+        while pending_ids.len() < 2 {
+            if let Some(NodeEvent::InboundRequest { pending_id, .. }) = events_b.recv().await {
+                pending_ids.push(pending_id);
+            }
+        }
+        // 不存在的 id 只影响自身结果
+        pending_ids.push(u64::MAX);
+        client_b
+            .send_response_many(
+                pending_ids.clone(),
+                Pong {
+                    msg: "shared".into(),
+                },
+            )
+            .await
+            .map(|results| (pending_ids, results))
+    };
+    let ((first, second), responded) = timeout(TIMEOUT, futures::future::join(requests, responder))
+        .await
+        .expect("fan-out responses should arrive");
+
+    let (pending_ids, results) = responded.expect("send_response_many failed");
+    assert_eq!(
+        results.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        pending_ids
+    );
+    assert!(results[0].1.is_ok() && results[1].1.is_ok(), "{results:?}");
+    assert!(results[2].1.is_err());
+    assert_eq!(first.unwrap().msg, "shared");
+    assert_eq!(second.unwrap().msg, "shared");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requests_beyond_per_peer_limit_are_queued() {
+    let (client_a, events_a) = start_node(local_config().with_max_concurrent_requests_per_peer(1));
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+
+    let requests = futures::future::join_all(
+        ["one", "two", "three"].map(|msg| client_a.send_request(peer_b, Ping { msg: msg.into() })),
+    );
+    let responder = async {
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            let (pending_id, msg) = next_inbound(&mut events_b).await;
+            // 上一个请求回复前，下一个请求不会发出
+            assert!(
+                timeout(
+                    std::time::Duration::from_millis(300),
+                    next_inbound(&mut events_b)
+                )
+                .await
+                .is_err(),
+                "request sent while another one was in flight"
+            );
+            client_b
+                .send_response(pending_id, Pong { msg: msg.clone() })
+                .await
+                .expect("send_response failed");
+            received.push(msg);
+        }
+        received
+    };
+    let (responses, received) = timeout(TIMEOUT, futures::future::join(requests, responder))
+        .await
+        .expect("queued requests should all complete");
+
+    assert_eq!(received, ["one", "two", "three"]);
+    let responses: Vec<_> = responses
+        .into_iter()
+        .map(|r| r.expect("request failed").msg)
+        .collect();
+    assert_eq!(responses, ["one", "two", "three"]);
+}
+
+/// 读取下一个 inbound request
+async fn next_inbound(events: &mut swarm_p2p_core::EventReceiver<Ping>) -> (u64, String) {
+    loop {
+        if let Some(NodeEvent::InboundRequest {
+            pending_id,
+            request,
+            ..
+        }) = events.recv().await
+        {
+            return (pending_id, request.msg);
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn notify_does_not_wait_for_response() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_a = client_a.local_peer_id();
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+
+    // B 从不回复，notify 仍立即返回
+    client_a
+        .notify(
+            peer_b,
+            Ping {
+                msg: "presence".into(),
+            },
+        )
+        .await
+        .expect("notify failed");
+
+    let (from, request) = timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::InboundRequest {
+                peer_id, request, ..
+            }) = events_b.recv().await
+            {
+                return (peer_id, request);
+            }
+        }
+    })
+    .await
+    .expect("B should receive the notification");
+    assert_eq!(from, peer_a);
+    assert_eq!(request.msg, "presence");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn notifies_beyond_per_peer_limit_are_queued() {
+    let (client_a, events_a) = start_node(local_config().with_max_concurrent_requests_per_peer(1));
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+
+    // 同一时刻只有一条在途，排队的通知轮到它发出时才返回
+    let notifier = client_a.clone();
+    let sender = tokio::spawn(async move {
+        for msg in ["one", "two", "three"] {
+            notifier
+                .notify(peer_b, Ping { msg: msg.into() })
+                .await
+                .expect("notify failed");
+        }
+    });
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        let (pending_id, msg) = timeout(TIMEOUT, next_inbound(&mut events_b))
+            .await
+            .expect("B should receive the next notification");
+        // 上一条通知被回复前，下一条不会发出
+        assert!(
+            timeout(
+                std::time::Duration::from_millis(300),
+                next_inbound(&mut events_b)
+            )
+            .await
+            .is_err(),
+            "notification sent while another one was in flight"
+        );
+        client_b
+            .send_response(pending_id, Pong { msg: msg.clone() })
+            .await
+            .expect("send_response failed");
+        received.push(msg);
+    }
+    assert_eq!(received, ["one", "two", "three"]);
+    timeout(TIMEOUT, sender)
+        .await
+        .expect("every notify should return")
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inbound_requests_stream_alongside_events() {
+    let (client_a, events_a) = start_node(local_config());
+    let (client_b, mut events_b) = start_node(local_config());
+    let peer_a = client_a.local_peer_id();
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a.dial_addr(addr_b).await.unwrap();
+
+    // B 只经 inbound_requests 回复
+    let requests = client_b.inbound_requests();
+    let responder = {
+        let client_b = client_b.clone();
+        tokio::spawn(async move {
+            futures::pin_mut!(requests);
+            let (pending_id, peer_id, request) = requests.next().await.unwrap();
+            client_b
+                .send_response(pending_id, Pong { msg: request.msg })
+                .await
+                .unwrap();
+            (pending_id, peer_id)
+        })
+    };
+
+    let response = timeout(
+        TIMEOUT,
+        client_a.send_request(peer_b, Ping { msg: "echo".into() }),
+    )
+    .await
+    .expect("send_request timed out")
+    .expect("send_request failed");
+    assert_eq!(response.msg, "echo");
+    let (pending_id, from) = responder.await.unwrap();
+    assert_eq!(from, peer_a);
+
+    // 主事件流仍收到同一个请求
+    let seen = timeout(TIMEOUT, async {
+        loop {
+            if let Some(NodeEvent::InboundRequest { pending_id, .. }) = events_b.recv().await {
+                return pending_id;
+            }
+        }
+    })
+    .await
+    .expect("EventReceiver should still see the request");
+    assert_eq!(seen, pending_id);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inbound_requests_stream_does_not_skip_when_slow() {
+    const COUNT: usize = 10;
+    let (client_a, events_a) = start_node(local_config());
+    // 事件缓冲区远小于请求数
+    let (client_b, mut events_b) = start_node(local_config().with_event_channel_size(2));
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a.add_peer_addrs(peer_b, vec![addr_b]).await.unwrap();
+
+    // 订阅后先不消费，主事件流照常读取并等全部请求到达
+    let requests = client_b.inbound_requests();
+    let all_arrived = tokio::spawn(async move {
+        let mut seen = 0;
+        while seen < COUNT {
+            if let Some(NodeEvent::InboundRequest { .. }) = events_b.recv().await {
+                seen += 1;
+            }
+        }
+        events_b
+    });
+    for i in 0..COUNT {
+        client_a
+            .notify(peer_b, Ping { msg: i.to_string() })
+            .await
+            .expect("notify failed");
+    }
+    let _events_b = timeout(TIMEOUT, all_arrived)
+        .await
+        .expect("B should receive every request")
+        .unwrap();
+
+    let mut received: Vec<String> = timeout(
+        TIMEOUT,
+        requests
+            .take(COUNT)
+            .map(|(_, _, request)| request.msg)
+            .collect(),
+    )
+    .await
+    .expect("stream should yield every request");
+    // 各请求走独立子流，到达顺序不保证
+    received.sort();
+    let expected: Vec<String> = (0..COUNT).map(|i| i.to_string()).collect();
+    assert_eq!(received, expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn large_request_with_tuned_yamux() {
+    let config = || {
+        local_config()
+            .with_max_request_size(8 * 1024 * 1024)
+            .with_yamux_max_stream_receive_window(4 * 1024 * 1024)
+            .with_yamux_max_streams(64)
+    };
+    let (client_a, events_a) = start_node(config());
+    let (client_b, mut events_b) = start_node(config());
+    let peer_b = client_b.local_peer_id();
+    tokio::spawn(event_printer(events_a, "A", None));
+
+    let addr_b = wait_for_listen_addr(&mut events_b).await;
+    client_a.dial_addr(addr_b).await.unwrap();
+
+    let responder = {
+        let client_b = client_b.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Some(NodeEvent::InboundRequest {
+                    pending_id,
+                    request,
+                    ..
+                }) = events_b.recv().await
+                {
+                    let len = request.msg.len().to_string();
+                    client_b
+                        .send_response(pending_id, Pong { msg: len })
+                        .await
+                        .unwrap();
+                    return;
+                }
+            }
+        })
+    };
+
+    let payload = "x".repeat(6 * 1024 * 1024);
+    let response = timeout(
+        TIMEOUT,
+        client_a.send_request(peer_b, Ping { msg: payload }),
+    )
+    .await
+    .expect("send_request timed out")
+    .expect("send_request failed");
+    assert_eq!(response.msg, (6 * 1024 * 1024).to_string());
+    responder.await.unwrap();
+}
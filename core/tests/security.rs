@@ -0,0 +1,49 @@
+//! 集成测试：安全握手协议选择
+//!
+//! 关闭 mDNS、仅 TCP，按地址拨号验证：仅 TLS 的节点之间可以连通 →
+//! 同时支持 Noise / TLS 的节点能连上仅 TLS 与仅 Noise 的节点 →
+//! 仅 Noise 与仅 TLS 的节点没有共同协议，握手失败。
+
+mod common;
+
+use common::*;
+use swarm_p2p_core::libp2p::Multiaddr;
+use swarm_p2p_core::{NetClient, SecurityUpgrade, TransportConfig};
+use tokio::time::timeout;
+
+/// 以指定安全握手启动节点，返回客户端与监听地址
+async fn start_secured(security: SecurityUpgrade) -> (NetClient<Ping, Pong>, Multiaddr) {
+    let config = local_config()
+        .with_transport(TransportConfig::TcpOnly)
+        .with_security(security);
+    let (client, mut events, addr) = start_listening(config).await;
+    tokio::spawn(async move { while events.recv().await.is_some() {} });
+    (client, addr)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn security_upgrades_negotiate() {
+    let (tls, tls_addr) = start_secured(SecurityUpgrade::Tls).await;
+    let (noise, noise_addr) = start_secured(SecurityUpgrade::Noise).await;
+    let (both, _) = start_secured(SecurityUpgrade::NoiseThenTls).await;
+    let (tls_dialer, _) = start_secured(SecurityUpgrade::Tls).await;
+
+    let peer = timeout(TIMEOUT, tls_dialer.dial_addr(tls_addr.clone()))
+        .await
+        .expect("dial timed out")
+        .expect("TLS nodes should connect");
+    assert_eq!(peer, tls.local_peer_id());
+
+    for (addr, expected) in [(tls_addr.clone(), &tls), (noise_addr, &noise)] {
+        let peer = timeout(TIMEOUT, both.dial_addr(addr))
+            .await
+            .expect("dial timed out")
+            .expect("NoiseThenTls should connect");
+        assert_eq!(peer, expected.local_peer_id());
+    }
+
+    let result = timeout(TIMEOUT, noise.dial_addr(tls_addr))
+        .await
+        .expect("dial timed out");
+    assert!(result.is_err(), "Noise-only should not reach TLS-only");
+}
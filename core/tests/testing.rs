@@ -0,0 +1,62 @@
+//! 集成测试：测试脚手架（需 `--features testing`）
+//!
+//! 验证 `TestNetwork::with_bootstrap` 启动的节点均已连上引导节点，
+//! 节点之间可按监听地址拨号并收发请求；`with_nodes` 启动的节点互不相连。
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::*;
+use swarm_p2p_core::NodeEvent;
+use swarm_p2p_core::testing::TestNetwork;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bootstrap_network_connects_nodes() {
+    let net = TestNetwork::<Ping, Pong>::with_bootstrap(2).await.unwrap();
+    let bootstrap = net.bootstrap.unwrap();
+    assert_eq!(bootstrap.client.connected_peers().await.unwrap().len(), 2);
+
+    let mut nodes = net.nodes.into_iter();
+    let (a, mut b) = (nodes.next().unwrap(), nodes.next().unwrap());
+    let (peer_a, peer_b) = (a.peer_id, b.peer_id);
+    let addr_b = b.addr.clone();
+    let client_a = a.drain_events();
+    assert_eq!(client_a.dial_addr(addr_b).await.unwrap(), peer_b);
+
+    // 节点随最后一个 NetClient 释放而关闭，B 由任务返回以保持运行
+    let responder = tokio::spawn(async move {
+        let pending_id = b
+            .wait_for(|event| match event {
+                NodeEvent::InboundRequest {
+                    peer_id,
+                    pending_id,
+                    request,
+                } if *peer_id == peer_a => {
+                    assert_eq!(request.msg, "ping");
+                    Some(*pending_id)
+                }
+                _ => None,
+            })
+            .await;
+        b.client
+            .send_response(pending_id, Pong { msg: "pong".into() })
+            .await
+            .unwrap();
+        b
+    });
+    let pong = client_a
+        .send_request(peer_b, Ping { msg: "ping".into() })
+        .await
+        .unwrap();
+    assert_eq!(pong.msg, "pong");
+    responder.await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn standalone_nodes_are_not_connected() {
+    let net = TestNetwork::<Ping, Pong>::with_nodes(2).await.unwrap();
+    assert!(net.bootstrap.is_none());
+    for node in &net.nodes {
+        assert!(node.client.connected_peers().await.unwrap().is_empty());
+    }
+}